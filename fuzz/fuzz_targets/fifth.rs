@@ -0,0 +1,124 @@
+// Same as the sixth target, but fifth is a queue: pushes at the back (or the
+// front, with push_front) and pops from the front, whatever index or end the
+// shared script asked for. On top of those, the script runs the calls that
+// relink fifth's nodes by hand, which is where its unsafe code lives.
+#![no_main]
+// The elements are boxed on purpose, as in the sixth target
+#![allow(clippy::vec_box)]
+
+use std::collections::VecDeque;
+
+use libfuzzer_sys::arbitrary::Arbitrary;
+use libfuzzer_sys::arbitrary::Result;
+use libfuzzer_sys::arbitrary::Unstructured;
+use libfuzzer_sys::fuzz_target;
+use rust_linked_lists::fifth::List;
+use rust_linked_lists::ops::Op;
+
+#[derive(Clone, Debug)]
+enum Step {
+    Op(Op<Box<u8>>),
+    ExtendFromSlice(Vec<Box<u8>>),
+    Rotate,
+    // Takes the count modulo the length plus one
+    TakeBatch(usize),
+    // Extracts up to `count` elements below `below`
+    ExtractIf { below: u8, count: usize },
+    InsertSorted(Box<u8>),
+    RawParts,
+    Reserve(u8),
+    ShrinkToFit,
+}
+
+impl<'a> Arbitrary<'a> for Step {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=8)? {
+            0 => Step::Op(u.arbitrary()?),
+            1 => Step::ExtendFromSlice(u.arbitrary()?),
+            2 => Step::Rotate,
+            3 => Step::TakeBatch(u.arbitrary()?),
+            4 => Step::ExtractIf {
+                below: u.arbitrary()?,
+                count: u.arbitrary()?,
+            },
+            5 => Step::InsertSorted(u.arbitrary()?),
+            6 => Step::RawParts,
+            7 => Step::Reserve(u.arbitrary()?),
+            _ => Step::ShrinkToFit,
+        })
+    }
+}
+
+fuzz_target!(|input: (Vec<Box<u8>>, Vec<Step>)| {
+    let (start, steps) = input;
+    let mut model = VecDeque::from(start);
+    let mut list = List::from(model.clone());
+
+    for step in steps {
+        match step {
+            Step::Op(Op::PushFront(elem)) => {
+                list.push_front(elem.clone());
+                model.push_front(elem);
+            }
+            Step::Op(Op::PushBack(elem) | Op::Insert(_, elem)) => {
+                list.push(elem.clone());
+                model.push_back(elem);
+            }
+            Step::Op(Op::PopFront | Op::PopBack | Op::Remove(_)) => {
+                assert_eq!(list.pop(), model.pop_front());
+            }
+            Step::ExtendFromSlice(elems) => {
+                list.extend_from_slice(&elems);
+                model.extend(elems);
+            }
+            Step::Rotate => {
+                list.rotate();
+                model.rotate_left(usize::from(!model.is_empty()));
+            }
+            Step::TakeBatch(n) => {
+                let n = n % (model.len() + 1);
+                let batch = list.take_batch(n);
+                batch.assert_invariants();
+                assert!(batch.into_iter().eq(model.drain(..n)));
+            }
+            Step::ExtractIf { below, count } => {
+                let count = count % (model.len() + 1);
+                let extracted = list
+                    .extract_if(|elem| **elem < below)
+                    .take(count)
+                    .collect::<Vec<_>>();
+                let mut expected = Vec::new();
+                let mut i = 0;
+                while i < model.len() && expected.len() < count {
+                    if *model[i] < below {
+                        expected.extend(model.remove(i));
+                    } else {
+                        i += 1;
+                    }
+                }
+                assert_eq!(extracted, expected);
+            }
+            Step::InsertSorted(elem) => {
+                list.insert_sorted(elem.clone(), Ord::cmp);
+                let index = model.iter().position(|x| *x > elem);
+                model.insert(index.unwrap_or(model.len()), elem);
+            }
+            Step::RawParts => {
+                let (head, tail, len) = list.into_raw_parts();
+                list = unsafe { List::from_raw_parts(head, tail, len) };
+            }
+            Step::Reserve(additional) => {
+                list.reserve(usize::from(additional));
+                assert!(list.capacity() >= model.len() + usize::from(additional));
+            }
+            Step::ShrinkToFit => {
+                list.shrink_to_fit();
+                assert_eq!(list.capacity(), model.len());
+            }
+        }
+        list.assert_invariants();
+        assert_eq!(list.len(), model.len());
+        assert_eq!(list.peek(), model.front());
+        assert!(list.iter().eq(model.iter()));
+    }
+});
@@ -0,0 +1,414 @@
+// Runs a script of deque operations on sixth and on a VecDeque side by side,
+// checking after every step that they gave back the same thing and hold the
+// same elements. The elements are boxed so that a double free or a leak in
+// the list shows up in the sanitizer too, not just a wrong value.
+//
+// On top of the shared ops, the script drives what only sixth has, which is
+// where most of its pointer juggling is: cursors, splicing and splitting,
+// rotating, swapping, draining, merging, and NodeRefs. Every element is tagged
+// with a serial number no other element gets, so a NodeRef can be checked to
+// give back the very element it was made for, or nothing once that's gone.
+#![no_main]
+// The elements are boxed on purpose, see above
+#![allow(clippy::vec_box, clippy::boxed_local)]
+
+use std::collections::VecDeque;
+
+use libfuzzer_sys::arbitrary::Arbitrary;
+use libfuzzer_sys::arbitrary::Result;
+use libfuzzer_sys::arbitrary::Unstructured;
+use libfuzzer_sys::fuzz_target;
+use rust_linked_lists::ops::Op;
+use rust_linked_lists::sixth::LinkedList;
+use rust_linked_lists::sixth::NodeRef;
+
+// The value the script picked, and the element's serial number
+type Elem = Box<(u8, u64)>;
+
+// Indices and counts are taken modulo whatever makes them valid at the time
+// they're applied, same as in `ops`
+#[derive(Clone, Debug)]
+enum Step {
+    Op(Op<Box<u8>>),
+    PushRef { back: bool, elem: Box<u8> },
+    // The handle is picked modulo the number of handles made so far
+    RemoveNode(usize),
+    MoveNode { handle: usize, back: bool },
+    Cursor { back: bool, moves: Vec<Move> },
+    SplitOff(usize),
+    Append(Vec<Box<u8>>),
+    Prepend(Vec<Box<u8>>),
+    RotateLeft(usize),
+    RotateRight(usize),
+    Swap(usize, usize),
+    RemoveUnordered(usize),
+    // Drains the range between the two indices, but only takes `take` of the
+    // drained elements before dropping the Drain
+    Drain { from: usize, to: usize, take: usize },
+    Merge(Vec<Box<u8>>),
+    CloneFrom(Vec<Box<u8>>),
+    ShrinkToFit,
+}
+
+#[derive(Clone, Debug)]
+enum Move {
+    Next,
+    Prev,
+    InsertBefore(Box<u8>),
+    InsertAfter(Box<u8>),
+    RemoveCurrent,
+    RemoveCurrentUnordered,
+    ToFront,
+    ToBack,
+    SpliceBefore(Vec<Box<u8>>),
+    SpliceAfter(Vec<Box<u8>>),
+    SplitBefore,
+    SplitAfter,
+}
+
+impl<'a> Arbitrary<'a> for Step {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=15)? {
+            0 => Step::Op(u.arbitrary()?),
+            1 => Step::PushRef {
+                back: u.arbitrary()?,
+                elem: u.arbitrary()?,
+            },
+            2 => Step::RemoveNode(u.arbitrary()?),
+            3 => Step::MoveNode {
+                handle: u.arbitrary()?,
+                back: u.arbitrary()?,
+            },
+            4 => Step::Cursor {
+                back: u.arbitrary()?,
+                moves: u.arbitrary()?,
+            },
+            5 => Step::SplitOff(u.arbitrary()?),
+            6 => Step::Append(u.arbitrary()?),
+            7 => Step::Prepend(u.arbitrary()?),
+            8 => Step::RotateLeft(u.arbitrary()?),
+            9 => Step::RotateRight(u.arbitrary()?),
+            10 => Step::Swap(u.arbitrary()?, u.arbitrary()?),
+            11 => Step::RemoveUnordered(u.arbitrary()?),
+            12 => Step::Drain {
+                from: u.arbitrary()?,
+                to: u.arbitrary()?,
+                take: u.arbitrary()?,
+            },
+            13 => Step::Merge(u.arbitrary()?),
+            14 => Step::CloneFrom(u.arbitrary()?),
+            _ => Step::ShrinkToFit,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Move {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=11)? {
+            0 => Move::Next,
+            1 => Move::Prev,
+            2 => Move::InsertBefore(u.arbitrary()?),
+            3 => Move::InsertAfter(u.arbitrary()?),
+            4 => Move::RemoveCurrent,
+            5 => Move::RemoveCurrentUnordered,
+            6 => Move::ToFront,
+            7 => Move::ToBack,
+            8 => Move::SpliceBefore(u.arbitrary()?),
+            9 => Move::SpliceAfter(u.arbitrary()?),
+            10 => Move::SplitBefore,
+            _ => Move::SplitAfter,
+        })
+    }
+}
+
+// Hands out the serial numbers
+#[derive(Default)]
+struct Tagger(u64);
+
+impl Tagger {
+    fn tag(&mut self, elem: Box<u8>) -> Elem {
+        self.0 += 1;
+        Box::new((*elem, self.0))
+    }
+
+    fn tag_all(&mut self, elems: Vec<Box<u8>>) -> VecDeque<Elem> {
+        elems.into_iter().map(|elem| self.tag(elem)).collect()
+    }
+
+    fn tag_op(&mut self, op: Op<Box<u8>>) -> Op<Elem> {
+        match op {
+            Op::PushFront(elem) => Op::PushFront(self.tag(elem)),
+            Op::PushBack(elem) => Op::PushBack(self.tag(elem)),
+            Op::PopFront => Op::PopFront,
+            Op::PopBack => Op::PopBack,
+            Op::Insert(index, elem) => Op::Insert(index, self.tag(elem)),
+            Op::Remove(index) => Op::Remove(index),
+        }
+    }
+}
+
+fn list_from(elems: &VecDeque<Elem>) -> LinkedList<Elem> {
+    elems.iter().cloned().collect()
+}
+
+fn check(list: &LinkedList<Elem>, model: &VecDeque<Elem>) {
+    list.assert_invariants();
+    assert_eq!(list.len(), model.len());
+    assert!(list.iter().eq(model.iter()));
+    assert!(list.iter().rev().eq(model.iter().rev()));
+}
+
+// Runs the moves on a cursor, and on the model with the index the cursor
+// should be at (None being the ghost)
+fn run_cursor(
+    list: &mut LinkedList<Elem>,
+    model: &mut VecDeque<Elem>,
+    back: bool,
+    moves: Vec<Move>,
+    tagger: &mut Tagger,
+) {
+    let (mut cursor, mut index) = if back {
+        (list.cursor_back_mut(), model.len().checked_sub(1))
+    } else {
+        (list.cursor_front_mut(), (!model.is_empty()).then_some(0))
+    };
+
+    for step in moves {
+        match step {
+            Move::Next => {
+                cursor.move_next();
+                index = match index {
+                    Some(i) if i + 1 < model.len() => Some(i + 1),
+                    Some(_) => None,
+                    None => (!model.is_empty()).then_some(0),
+                };
+            }
+            Move::Prev => {
+                cursor.move_prev();
+                index = match index {
+                    Some(i) => i.checked_sub(1),
+                    None => model.len().checked_sub(1),
+                };
+            }
+            Move::InsertBefore(elem) => {
+                let elem = tagger.tag(elem);
+                cursor.insert_before(elem.clone());
+                match index {
+                    Some(i) => {
+                        model.insert(i, elem);
+                        index = Some(i + 1);
+                    }
+                    None => model.push_back(elem),
+                }
+            }
+            Move::InsertAfter(elem) => {
+                let elem = tagger.tag(elem);
+                cursor.insert_after(elem.clone());
+                match index {
+                    Some(i) => model.insert(i + 1, elem),
+                    None => model.push_front(elem),
+                }
+            }
+            Move::RemoveCurrent => {
+                let expected = index.and_then(|i| model.remove(i));
+                assert_eq!(cursor.remove_current(), expected);
+                index = index.filter(|&i| i < model.len());
+            }
+            Move::RemoveCurrentUnordered => {
+                let expected = index.and_then(|i| model.swap_remove_back(i));
+                assert_eq!(cursor.remove_current_unordered(), expected);
+                index = index.filter(|&i| i < model.len());
+            }
+            Move::ToFront => {
+                cursor.move_to_front();
+                if let Some(i) = index {
+                    let elem = model.remove(i).unwrap();
+                    model.push_front(elem);
+                    index = Some(0);
+                }
+            }
+            Move::ToBack => {
+                cursor.move_to_back();
+                if let Some(i) = index {
+                    let elem = model.remove(i).unwrap();
+                    model.push_back(elem);
+                    index = Some(model.len() - 1);
+                }
+            }
+            Move::SpliceBefore(elems) => {
+                let elems = tagger.tag_all(elems);
+                cursor.splice_before(list_from(&elems));
+                match index {
+                    Some(i) => {
+                        index = Some(i + elems.len());
+                        let rest = model.split_off(i);
+                        model.extend(elems);
+                        model.extend(rest);
+                    }
+                    None => model.extend(elems),
+                }
+            }
+            Move::SpliceAfter(elems) => {
+                let elems = tagger.tag_all(elems);
+                cursor.splice_after(list_from(&elems));
+                let at = index.map_or(0, |i| i + 1);
+                let rest = model.split_off(at);
+                model.extend(elems);
+                model.extend(rest);
+            }
+            Move::SplitBefore => {
+                let split = cursor.split_before();
+                let expected = match index {
+                    Some(i) => {
+                        index = Some(0);
+                        model.drain(..i).collect()
+                    }
+                    None => core::mem::take(model),
+                };
+                check(&split, &expected);
+            }
+            Move::SplitAfter => {
+                let split = cursor.split_after();
+                let at = index.map_or(0, |i| i + 1);
+                check(&split, &model.split_off(at));
+            }
+        }
+        assert_eq!(cursor.index(), index);
+        assert_eq!(
+            cursor.current().map(|elem| &**elem),
+            index.map(|i| &*model[i])
+        );
+    }
+}
+
+fuzz_target!(|input: (Vec<Box<u8>>, Vec<Step>)| {
+    let (start, steps) = input;
+    let mut tagger = Tagger::default();
+    let mut model = tagger.tag_all(start);
+    let mut list = list_from(&model);
+    // Each handle with the serial number of the element it was made for
+    let mut handles: Vec<(NodeRef<Elem>, u64)> = Vec::new();
+
+    for step in steps {
+        match step {
+            Step::Op(op) => {
+                let op = tagger.tag_op(op);
+                assert_eq!(op.clone().apply(&mut list), op.apply(&mut model));
+            }
+            Step::PushRef { back, elem } => {
+                let elem = tagger.tag(elem);
+                handles.push((
+                    if back {
+                        list.push_back_ref(elem.clone())
+                    } else {
+                        list.push_front_ref(elem.clone())
+                    },
+                    elem.1,
+                ));
+                if back {
+                    model.push_back(elem);
+                } else {
+                    model.push_front(elem);
+                }
+            }
+            Step::RemoveNode(handle) if !handles.is_empty() => {
+                let (node, serial) = handles[handle % handles.len()];
+                let index = model.iter().position(|elem| elem.1 == serial);
+                // A handle may stop working while its element is still around
+                // (see NodeRef), but it must never give back another element
+                if let Some(elem) = list.remove_node(node) {
+                    assert_eq!(Some(elem), model.remove(index.unwrap()));
+                }
+            }
+            Step::MoveNode { handle, back } if !handles.is_empty() => {
+                let (node, serial) = handles[handle % handles.len()];
+                let index = model.iter().position(|elem| elem.1 == serial);
+                let moved = if back {
+                    list.move_node_to_back(node)
+                } else {
+                    list.move_node_to_front(node)
+                };
+                if moved {
+                    let elem = model.remove(index.unwrap()).unwrap();
+                    if back {
+                        model.push_back(elem);
+                    } else {
+                        model.push_front(elem);
+                    }
+                }
+            }
+            Step::RemoveNode(_) | Step::MoveNode { .. } => {}
+            Step::Cursor { back, moves } => {
+                run_cursor(&mut list, &mut model, back, moves, &mut tagger);
+            }
+            Step::SplitOff(at) => {
+                let at = at % (model.len() + 1);
+                check(&list.split_off(at), &model.split_off(at));
+            }
+            Step::Append(elems) => {
+                let mut elems = tagger.tag_all(elems);
+                let mut other = list_from(&elems);
+                list.append(&mut other);
+                assert!(other.is_empty());
+                model.append(&mut elems);
+            }
+            Step::Prepend(elems) => {
+                let mut elems = tagger.tag_all(elems);
+                let mut other = list_from(&elems);
+                list.prepend(&mut other);
+                assert!(other.is_empty());
+                elems.append(&mut model);
+                model = elems;
+            }
+            Step::RotateLeft(k) => {
+                let k = k % (model.len() + 1);
+                list.rotate_left(k);
+                model.rotate_left(k);
+            }
+            Step::RotateRight(k) => {
+                let k = k % (model.len() + 1);
+                list.rotate_right(k);
+                model.rotate_right(k);
+            }
+            Step::Swap(i, j) if !model.is_empty() => {
+                let (i, j) = (i % model.len(), j % model.len());
+                list.swap(i, j);
+                model.swap(i, j);
+            }
+            Step::Swap(..) => {}
+            Step::RemoveUnordered(index) => {
+                // Out of range on purpose every now and then
+                let index = index % (model.len() + 1);
+                assert_eq!(list.remove_unordered(index), model.swap_remove_back(index));
+            }
+            Step::Drain { from, to, take } => {
+                let (from, to) = (from % (model.len() + 1), to % (model.len() + 1));
+                let range = from.min(to)..from.max(to);
+                let taken = list.drain(range.clone()).take(take).collect::<Vec<_>>();
+                let drained = model.drain(range).take(take).collect::<Vec<_>>();
+                assert_eq!(taken, drained);
+            }
+            Step::Merge(elems) => {
+                let other = tagger.tag_all(elems);
+                list = core::mem::take(&mut list).merge(list_from(&other), |a, b| a.0.cmp(&b.0));
+                // Taking from the list first on ties
+                let (mut ours, mut theirs) = (core::mem::take(&mut model), other);
+                while let (Some(a), Some(b)) = (ours.front(), theirs.front()) {
+                    if b.0 < a.0 {
+                        model.extend(theirs.pop_front());
+                    } else {
+                        model.extend(ours.pop_front());
+                    }
+                }
+                model.extend(ours);
+                model.extend(theirs);
+            }
+            Step::CloneFrom(elems) => {
+                model = tagger.tag_all(elems);
+                list.clone_from(&list_from(&model));
+            }
+            Step::ShrinkToFit => list.shrink_to_fit(),
+        }
+        check(&list, &model);
+    }
+});
@@ -0,0 +1,153 @@
+// Lists that manage their own nodes can place them in a custom allocator, e.g.
+// an arena or a bump allocator. The `Allocator` trait is still nightly-only
+// (`allocator_api`), so on stable we provide a stand-in trait that only the
+// global allocator implements. Lists are generic over `A: Allocator = Global`
+// either way, which keeps the parameter invisible to stable users.
+#![cfg_attr(
+    feature = "strict-provenance",
+    deny(fuzzy_provenance_casts, lossy_provenance_casts)
+)]
+
+use alloc::alloc::Layout;
+use alloc::boxed::Box;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+#[cfg(feature = "allocator_api")]
+pub use alloc::alloc::Allocator;
+#[cfg(feature = "allocator_api")]
+pub use alloc::alloc::Global;
+
+#[cfg(not(feature = "allocator_api"))]
+pub use self::stable::Allocator;
+#[cfg(not(feature = "allocator_api"))]
+pub use self::stable::Global;
+
+#[cfg(not(feature = "allocator_api"))]
+mod stable {
+    // Sealed, as the helpers below assume that `Global` is the only allocator
+    pub trait Allocator: private::Sealed {}
+
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct Global;
+
+    impl Allocator for Global {}
+
+    mod private {
+        pub trait Sealed {}
+
+        impl Sealed for super::Global {}
+    }
+}
+
+// Returned when a fallible push can't allocate its node, handing the element
+// back so the caller can decide what to do with it
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AllocError<T>(pub T);
+
+impl<T> fmt::Display for AllocError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "memory allocation failed")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: fmt::Debug> Error for AllocError<T> {}
+
+// The helpers below are the allocator-aware equivalents of the
+// `Box::into_raw(Box::new(x))` and `Box::from_raw(ptr)` dance we do for nodes.
+
+// Moves `value` into a fresh allocation from `alloc`
+#[cfg(feature = "allocator_api")]
+pub(crate) fn into_raw_in<T, A: Allocator>(value: T, alloc: &A) -> *mut T {
+    // `&A` is an allocator too, so we don't need to clone `alloc`
+    Box::into_raw_with_allocator(Box::new_in(value, alloc)).0
+}
+
+#[cfg(not(feature = "allocator_api"))]
+pub(crate) fn into_raw_in<T, A: Allocator>(value: T, _alloc: &A) -> *mut T {
+    Box::into_raw(Box::new(value))
+}
+
+// Same as `into_raw_in`, but gives `value` back if the allocation fails
+#[cfg(feature = "allocator_api")]
+pub(crate) fn try_into_raw_in<T, A: Allocator>(value: T, alloc: &A) -> Result<*mut T, T> {
+    match alloc.allocate(Layout::new::<T>()) {
+        Ok(ptr) => {
+            let ptr = ptr.cast::<T>().as_ptr();
+            unsafe { ptr.write(value) };
+            Ok(ptr)
+        }
+        Err(_) => Err(value),
+    }
+}
+
+#[cfg(not(feature = "allocator_api"))]
+pub(crate) fn try_into_raw_in<T, A: Allocator>(value: T, _alloc: &A) -> Result<*mut T, T> {
+    let layout = Layout::new::<T>();
+    if layout.size() == 0 {
+        // Nothing to allocate, and `alloc` doesn't accept zero-sized layouts
+        return Ok(Box::into_raw(Box::new(value)));
+    }
+
+    unsafe {
+        let ptr = alloc::alloc::alloc(layout).cast::<T>();
+        if ptr.is_null() {
+            return Err(value);
+        }
+
+        // Memory from the global allocator with the layout of a T is exactly
+        // what a Box would have produced, so `from_raw_in` can free it
+        ptr.write(value);
+        Ok(ptr)
+    }
+}
+
+// Moves the value out of `ptr` and frees its memory
+//
+// SAFETY: `ptr` must come from `into_raw_in` or `try_into_raw_in` with the
+// same allocator, and must not be used afterwards.
+#[cfg(feature = "allocator_api")]
+pub(crate) unsafe fn from_raw_in<T, A: Allocator>(ptr: *mut T, alloc: &A) -> T {
+    *Box::from_raw_in(ptr, alloc)
+}
+
+#[cfg(not(feature = "allocator_api"))]
+pub(crate) unsafe fn from_raw_in<T, A: Allocator>(ptr: *mut T, _alloc: &A) -> T {
+    *Box::from_raw(ptr)
+}
+
+// Raw memory for `layout` from `alloc`, for the few nodes that aren't boxed
+// one by one (fifth's blocks of nodes). Runs out of memory the way Box does.
+// `layout` must not be zero-sized.
+#[cfg(all(feature = "fifth", feature = "allocator_api"))]
+pub(crate) fn alloc_in<A: Allocator>(layout: Layout, alloc: &A) -> *mut u8 {
+    match alloc.allocate(layout) {
+        Ok(ptr) => ptr.cast::<u8>().as_ptr(),
+        Err(_) => alloc::alloc::handle_alloc_error(layout),
+    }
+}
+
+#[cfg(all(feature = "fifth", not(feature = "allocator_api")))]
+pub(crate) fn alloc_in<A: Allocator>(layout: Layout, _alloc: &A) -> *mut u8 {
+    let ptr = unsafe { alloc::alloc::alloc(layout) };
+    if ptr.is_null() {
+        alloc::alloc::handle_alloc_error(layout);
+    }
+    ptr
+}
+
+// Frees memory from `alloc_in`
+//
+// SAFETY: `ptr` must come from `alloc_in` with the same layout and allocator,
+// and must not be used afterwards.
+#[cfg(all(feature = "fifth", feature = "allocator_api"))]
+pub(crate) unsafe fn dealloc_in<A: Allocator>(ptr: *mut u8, layout: Layout, alloc: &A) {
+    alloc.deallocate(core::ptr::NonNull::new_unchecked(ptr), layout);
+}
+
+#[cfg(all(feature = "fifth", not(feature = "allocator_api")))]
+pub(crate) unsafe fn dealloc_in<A: Allocator>(ptr: *mut u8, layout: Layout, _alloc: &A) {
+    alloc::alloc::dealloc(ptr, layout);
+}
@@ -0,0 +1,404 @@
+// A test suite for anything implementing the traits in `traits`, so that
+// every list runs the same checks, including ones that don't exist yet (or
+// live in another crate). Each suite panics on the first thing that's off,
+// which makes them work as the body of a #[test]:
+//
+//     #[test]
+//     fn conformance() {
+//         rust_linked_lists::conformance::deque(MyDeque::new);
+//     }
+//
+// The suites compare the list against a Vec or VecDeque doing the same
+// operations, and fill it with `Tracked` elements that count how many of
+// them are alive, to catch leaks and double drops. They're also small enough
+// to run under Miri, where the shared borrows they hold (several peeks and
+// iterators at once, interleaved with pushes and pops) check the unsafe
+// lists' aliasing.
+
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::fmt;
+
+use crate::traits::DequeOps;
+use crate::traits::Iterable;
+use crate::traits::QueueOps;
+use crate::traits::StackOps;
+
+// How many operations each suite runs
+const OPS: usize = 500;
+
+// Hands out Tracked elements and counts the ones still alive
+#[derive(Default)]
+pub struct DropCounter {
+    alive: Rc<Cell<usize>>,
+}
+
+pub struct Tracked {
+    value: i32,
+    alive: Rc<Cell<usize>>,
+}
+
+impl DropCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn elem(&self, value: i32) -> Tracked {
+        self.alive.set(self.alive.get() + 1);
+        Tracked {
+            value,
+            alive: self.alive.clone(),
+        }
+    }
+
+    pub fn alive(&self) -> usize {
+        self.alive.get()
+    }
+
+    // Panics if any element handed out hasn't been dropped yet
+    pub fn assert_none_alive(&self) {
+        assert_eq!(self.alive(), 0, "elements were leaked");
+    }
+}
+
+impl Tracked {
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        let alive = self.alive.get().checked_sub(1);
+        self.alive.set(alive.expect("an element was dropped twice"));
+    }
+}
+
+impl PartialEq for Tracked {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl fmt::Debug for Tracked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.value, f)
+    }
+}
+
+// A fixed sequence of pseudo-random numbers, so that failures reproduce
+struct Ops(u32);
+
+impl Ops {
+    fn new() -> Self {
+        Ops(0x2545_f491)
+    }
+
+    fn next(&mut self, n: u32) -> u32 {
+        self.0 = self.0.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (self.0 >> 16) % n
+    }
+}
+
+// Runs the push/pop/peek checks on a list from `new`, used as a stack
+pub fn stack<S: StackOps<Tracked>>(mut new: impl FnMut() -> S) {
+    let counter = DropCounter::new();
+    let mut list = new();
+    let mut model = Vec::new();
+    let mut ops = Ops::new();
+
+    assert!(list.is_empty());
+    assert!(list.pop().is_none());
+    assert!(list.peek().is_none());
+
+    for i in 0..OPS as i32 {
+        // Pushing a bit more often than popping, so that the list grows
+        // and shrinks back to empty a few times along the way
+        match ops.next(5) {
+            0 | 1 => assert_eq!(list.pop().map(|x| x.value()), model.pop()),
+            _ => {
+                list.push(counter.elem(i));
+                model.push(i);
+            }
+        }
+        assert_eq!(list.peek().map(|x| x.value()), model.last().copied());
+        assert_eq!(list.is_empty(), model.is_empty());
+        assert_eq!(counter.alive(), model.len());
+
+        // Two peeks at once are two shared borrows of the same element
+        if let (Some(a), Some(b)) = (list.peek(), list.peek()) {
+            assert_eq!(a.value(), b.value());
+        }
+    }
+
+    while let Some(elem) = list.pop() {
+        assert_eq!(Some(elem.value()), model.pop());
+    }
+    assert!(model.is_empty());
+    counter.assert_none_alive();
+
+    // Dropping the list drops whatever was still in it
+    let mut list = new();
+    for i in 0..10 {
+        list.push(counter.elem(i));
+    }
+    drop(list);
+    counter.assert_none_alive();
+}
+
+// Runs the enqueue/dequeue/peek checks on a list from `new`
+pub fn queue<Q: QueueOps<Tracked>>(mut new: impl FnMut() -> Q) {
+    let counter = DropCounter::new();
+    let mut list = new();
+    let mut model = VecDeque::new();
+    let mut ops = Ops::new();
+
+    assert!(list.is_empty());
+    assert!(list.dequeue().is_none());
+    assert!(list.peek().is_none());
+
+    for i in 0..OPS as i32 {
+        match ops.next(5) {
+            0 | 1 => assert_eq!(list.dequeue().map(|x| x.value()), model.pop_front()),
+            _ => {
+                list.enqueue(counter.elem(i));
+                model.push_back(i);
+            }
+        }
+        assert_eq!(list.peek().map(|x| x.value()), model.front().copied());
+        assert_eq!(list.is_empty(), model.is_empty());
+        assert_eq!(counter.alive(), model.len());
+
+        if let (Some(a), Some(b)) = (list.peek(), list.peek()) {
+            assert_eq!(a.value(), b.value());
+        }
+    }
+
+    while let Some(elem) = list.dequeue() {
+        assert_eq!(Some(elem.value()), model.pop_front());
+    }
+    assert!(model.is_empty());
+    counter.assert_none_alive();
+
+    let mut list = new();
+    for i in 0..10 {
+        list.enqueue(counter.elem(i));
+    }
+    drop(list);
+    counter.assert_none_alive();
+}
+
+// Runs the checks for both ends of a list from `new`
+pub fn deque<D: DequeOps<Tracked>>(mut new: impl FnMut() -> D) {
+    let counter = DropCounter::new();
+    let mut list = new();
+    let mut model = VecDeque::new();
+    let mut ops = Ops::new();
+
+    assert!(list.is_empty());
+    assert!(list.pop_front().is_none());
+    assert!(list.pop_back().is_none());
+    assert!(list.peek_front().is_none());
+    assert!(list.peek_back().is_none());
+
+    for i in 0..OPS as i32 {
+        match ops.next(8) {
+            0 | 1 => assert_eq!(list.pop_front().map(|x| x.value()), model.pop_front()),
+            2 | 3 => assert_eq!(list.pop_back().map(|x| x.value()), model.pop_back()),
+            4 | 5 => {
+                list.push_front(counter.elem(i));
+                model.push_front(i);
+            }
+            _ => {
+                list.push_back(counter.elem(i));
+                model.push_back(i);
+            }
+        }
+        assert_eq!(list.peek_front().map(|x| x.value()), model.front().copied());
+        assert_eq!(list.peek_back().map(|x| x.value()), model.back().copied());
+        assert_eq!(list.is_empty(), model.is_empty());
+        assert_eq!(counter.alive(), model.len());
+
+        // Both ends at once, which are the same element when there's only
+        // one: the front and back links mustn't step on each other
+        if let (Some(front), Some(back)) = (list.peek_front(), list.peek_back()) {
+            assert_eq!(front.value(), *model.front().unwrap());
+            assert_eq!(back.value(), *model.back().unwrap());
+        }
+    }
+
+    // Emptying from both ends, so that they meet in the middle
+    let mut front = true;
+    while !model.is_empty() {
+        if front {
+            assert_eq!(list.pop_front().map(|x| x.value()), model.pop_front());
+        } else {
+            assert_eq!(list.pop_back().map(|x| x.value()), model.pop_back());
+        }
+        front = !front;
+    }
+    assert!(list.is_empty());
+    counter.assert_none_alive();
+
+    let mut list = new();
+    for i in 0..10 {
+        list.push_front(counter.elem(i));
+        list.push_back(counter.elem(i));
+    }
+    drop(list);
+    counter.assert_none_alive();
+}
+
+// Checks the iterator laws on `list`, whose elements should be `expected`
+// in that order
+pub fn iterable<L: Iterable<i32>>(list: &L, expected: &[i32]) {
+    let items: Vec<i32> = list.iter().copied().collect();
+    assert_eq!(items, expected);
+
+    // Iterating doesn't consume anything, and gives the same every time
+    assert_eq!(list.iter().copied().collect::<Vec<_>>(), expected);
+    assert_eq!(list.iter().count(), expected.len());
+
+    // The size hint is never wrong, all the way to the end
+    let mut iter = list.iter();
+    for left in (0..=expected.len()).rev() {
+        let (lower, upper) = iter.size_hint();
+        assert!(lower <= left, "size_hint lower bound {lower} > {left}");
+        assert!(upper.map_or(true, |upper| left <= upper));
+        assert_eq!(iter.next().is_some(), left > 0);
+    }
+
+    // Any number of iterators can be live at once, as they only share
+    let mut a = list.iter();
+    let mut b = list.iter();
+    let mut refs = Vec::new();
+    for x in expected {
+        let (from_a, from_b) = (a.next().unwrap(), b.next().unwrap());
+        assert_eq!((from_a, from_b), (x, x));
+        refs.push(from_a);
+    }
+    // And what they gave out stays valid after they're gone
+    drop((a, b));
+    assert!(refs.iter().copied().eq(expected));
+}
+
+#[cfg(test)]
+mod test {
+    use super::DropCounter;
+
+    #[test]
+    #[cfg(feature = "second")]
+    fn second() {
+        use crate::second::List;
+
+        let values = [1, 2, 3, 4];
+        super::stack(List::new);
+        super::iterable(&values.into_iter().collect::<List<_>>(), &values);
+    }
+
+    #[test]
+    #[cfg(feature = "third")]
+    fn third() {
+        use crate::third::List;
+
+        let values = [1, 2, 3, 4];
+        let list = values
+            .iter()
+            .rev()
+            .fold(List::new(), |list, &x| list.prepend(x));
+        super::iterable(&list, &values);
+    }
+
+    #[test]
+    #[cfg(feature = "fourth")]
+    fn fourth() {
+        super::deque(crate::fourth::List::new);
+    }
+
+    #[test]
+    #[cfg(feature = "fifth")]
+    fn fifth() {
+        use crate::fifth::List;
+
+        let values = [1, 2, 3, 4];
+        super::queue(List::new);
+        let mut queue = List::new();
+        queue.extend_from_slice(&values);
+        super::iterable(&queue, &values);
+    }
+
+    #[test]
+    #[cfg(feature = "sixth")]
+    fn sixth() {
+        use crate::sixth::LinkedList;
+
+        let values = [1, 2, 3, 4];
+        super::stack(LinkedList::new);
+        super::queue(LinkedList::new);
+        super::deque(LinkedList::new);
+        super::iterable(&LinkedList::from(values), &values);
+        super::iterable(&LinkedList::new(), &[]);
+    }
+
+    #[test]
+    #[cfg(feature = "tenth")]
+    fn tenth() {
+        use crate::tenth::Queue;
+
+        let values = [1, 2, 3, 4];
+        super::iterable(&values.into_iter().collect::<Queue<_>>(), &values);
+        super::iterable(&Queue::new(), &[]);
+        // Half in the rotated front, half still in the rear
+        let queue = Queue::new().snoc(0).snoc(1).snoc(2).snoc(3).snoc(4).tail();
+        super::iterable(&queue, &values);
+    }
+
+    #[test]
+    #[cfg(feature = "eleventh")]
+    fn eleventh() {
+        use crate::eleventh::Deque;
+
+        let values = [1, 2, 3, 4];
+        super::iterable(&values.into_iter().collect::<Deque<_>>(), &values);
+        super::iterable(&Deque::new(), &[]);
+        // Deep enough for the middle to hold nodes, and put back together
+        let long: Vec<i32> = (0..100).collect();
+        let deque: Deque<_> = long.iter().copied().collect();
+        let (front, back) = deque.split_at(37);
+        super::iterable(&front.concat(&back), &long);
+    }
+
+    #[test]
+    #[cfg(feature = "fourteenth")]
+    fn fourteenth() {
+        use crate::fourteenth::LinkedList;
+
+        let values = [1, 2, 3, 4];
+        super::deque(LinkedList::new);
+        super::iterable(&values.into_iter().collect::<LinkedList<_>>(), &values);
+        super::iterable(&LinkedList::new(), &[]);
+    }
+
+    #[test]
+    #[cfg(feature = "fifteenth")]
+    fn fifteenth() {
+        use crate::fifteenth::LinkedList;
+
+        let values = [1, 2, 3, 4];
+        super::deque(LinkedList::new);
+        super::iterable(&values.into_iter().collect::<LinkedList<_>>(), &values);
+        super::iterable(&LinkedList::new(), &[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "elements were leaked")]
+    fn leaks() {
+        let counter = DropCounter::new();
+        // Still alive when we check, and dropped as we unwind, so that Miri
+        // doesn't flag a real leak
+        let _elem = counter.elem(0);
+        counter.assert_none_alive();
+    }
+}
@@ -0,0 +1,242 @@
+// Conversions between the lists, so code can start out on a safe list and
+// move to an unsafe one (or back) by changing a type rather than every call
+// site. They all keep the iteration order: the first element iter() gives on
+// one list is the first one iter() gives on the other. For second, that means
+// the top of the stack maps to the front of the others.
+//
+// Each conversion moves the elements across in a single pass. The only one
+// that can't move them is third, whose nodes may be shared with other lists,
+// so we clone its elements out of a borrowed list instead.
+
+// Each pair of lists gets a module, built only when both lists are
+
+#[cfg(all(feature = "fifth", feature = "sixth"))]
+mod fifth_sixth {
+    use crate::allocator::Allocator;
+    use crate::fifth;
+    use crate::sixth;
+
+    // fifth only ever grows at the back, which is just what sixth's Extend
+    // does
+    impl<T, A: Allocator> From<fifth::List<T, A>> for sixth::LinkedList<T> {
+        fn from(list: fifth::List<T, A>) -> Self {
+            list.into_iter().collect()
+        }
+    }
+
+    impl<T, A: Allocator> From<sixth::LinkedList<T, A>> for fifth::List<T> {
+        fn from(list: sixth::LinkedList<T, A>) -> Self {
+            let mut queue = fifth::List::new();
+            for elem in list {
+                queue.push(elem);
+            }
+            queue
+        }
+    }
+}
+
+// Popping second gives us its elements top first, so they go in at the back
+// of the others. The other way around, second's FromIterator puts the first
+// element on top.
+
+#[cfg(all(feature = "second", feature = "fifth"))]
+mod second_fifth {
+    use crate::allocator::Allocator;
+    use crate::fifth;
+    use crate::second;
+
+    impl<T> From<second::List<T>> for fifth::List<T> {
+        fn from(list: second::List<T>) -> Self {
+            let mut queue = fifth::List::new();
+            for elem in list {
+                queue.push(elem);
+            }
+            queue
+        }
+    }
+
+    impl<T, A: Allocator> From<fifth::List<T, A>> for second::List<T> {
+        fn from(list: fifth::List<T, A>) -> Self {
+            list.into_iter().collect()
+        }
+    }
+}
+
+#[cfg(all(feature = "second", feature = "sixth"))]
+mod second_sixth {
+    use crate::allocator::Allocator;
+    use crate::second;
+    use crate::sixth;
+
+    impl<T> From<second::List<T>> for sixth::LinkedList<T> {
+        fn from(list: second::List<T>) -> Self {
+            list.into_iter().collect()
+        }
+    }
+
+    impl<T, A: Allocator> From<sixth::LinkedList<T, A>> for second::List<T> {
+        fn from(list: sixth::LinkedList<T, A>) -> Self {
+            list.into_iter().collect()
+        }
+    }
+}
+
+#[cfg(all(feature = "fourth", feature = "sixth"))]
+mod fourth_sixth {
+    use crate::allocator::Allocator;
+    use crate::fourth;
+    use crate::sixth;
+
+    impl<T> From<fourth::List<T>> for sixth::LinkedList<T> {
+        fn from(list: fourth::List<T>) -> Self {
+            list.into_iter().collect()
+        }
+    }
+
+    impl<T, A: Allocator> From<sixth::LinkedList<T, A>> for fourth::List<T> {
+        fn from(list: sixth::LinkedList<T, A>) -> Self {
+            let mut deque = fourth::List::new();
+            for elem in list {
+                deque.push_back(elem);
+            }
+            deque
+        }
+    }
+}
+
+#[cfg(all(feature = "second", feature = "third"))]
+impl<T: Clone> From<&crate::third::List<T>> for crate::second::List<T> {
+    fn from(list: &crate::third::List<T>) -> Self {
+        list.iter().cloned().collect()
+    }
+}
+
+#[cfg(all(feature = "third", feature = "sixth"))]
+impl<T: Clone> From<&crate::third::List<T>> for crate::sixth::LinkedList<T> {
+    fn from(list: &crate::third::List<T>) -> Self {
+        list.iter().cloned().collect()
+    }
+}
+
+// Exercises every list, so it's only built when they all are
+#[cfg(all(
+    test,
+    feature = "second",
+    feature = "third",
+    feature = "fourth",
+    feature = "fifth",
+    feature = "sixth"
+))]
+mod test {
+    use crate::fifth;
+    use crate::fourth;
+    use crate::second;
+    use crate::sixth;
+    use crate::third;
+
+    fn second() -> second::List<i32> {
+        (1..=3).collect()
+    }
+
+    #[test]
+    fn second_fifth() {
+        let queue = fifth::List::from(second());
+        assert_eq!(queue.iter().copied().collect::<Vec<_>>(), &[1, 2, 3]);
+
+        let stack = second::List::from(queue);
+        assert_eq!(stack.iter().copied().collect::<Vec<_>>(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn second_sixth() {
+        let deque = sixth::LinkedList::from(second());
+        assert_eq!(deque, [1, 2, 3].into());
+
+        let mut stack = second::List::from(deque);
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.iter().copied().collect::<Vec<_>>(), &[2, 3]);
+    }
+
+    #[test]
+    fn fourth_sixth() {
+        let mut deque = fourth::List::new();
+        deque.push_back(2);
+        deque.push_front(1);
+        deque.push_back(3);
+
+        let list = sixth::LinkedList::from(deque);
+        assert_eq!(list, [1, 2, 3].into());
+
+        let mut deque = fourth::List::from(list);
+        assert_eq!(deque.pop_front(), Some(1));
+        assert_eq!(deque.pop_back(), Some(3));
+        assert_eq!(deque.pop_back(), Some(2));
+        assert_eq!(deque.pop_back(), None);
+    }
+
+    #[test]
+    fn fifth_sixth() {
+        let mut queue = fifth::List::new();
+        queue.extend_from_slice(&[1, 2, 3]);
+
+        let list = sixth::LinkedList::from(queue);
+        assert_eq!(list, [1, 2, 3].into());
+
+        let mut queue = fifth::List::from(list);
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.len(), 2);
+    }
+
+    #[test]
+    fn from_third() {
+        let list = third::List::new().prepend(3).prepend(2).prepend(1);
+        let tail = list.tail();
+
+        let stack = second::List::from(&list);
+        assert_eq!(stack.iter().copied().collect::<Vec<_>>(), &[1, 2, 3]);
+        assert_eq!(sixth::LinkedList::from(&tail), [2, 3].into());
+
+        // The persistent list is left as it was
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), &[1, 2, 3]);
+    }
+
+    // Moving, not cloning: the elements don't need to be Clone, and each
+    // one is dropped exactly once, by whichever list has it last
+    #[test]
+    fn moves() {
+        use std::rc::Rc;
+
+        let elem = Rc::new(0);
+        let stack: second::List<_> = (0..3).map(|_| elem.clone()).collect();
+        let queue = fifth::List::from(stack);
+        let list = sixth::LinkedList::from(queue);
+        let deque = fourth::List::from(list);
+        assert_eq!(Rc::strong_count(&elem), 4);
+        drop(deque);
+        assert_eq!(Rc::strong_count(&elem), 1);
+    }
+
+    // The lists all encode the same elements the same way, so what one
+    // serializes, any other can deserialize
+    #[cfg(feature = "serde")]
+    #[test]
+    fn same_encoding() {
+        use serde_test::assert_ser_tokens;
+        use serde_test::Token;
+
+        let tokens = [
+            Token::Seq { len: Some(3) },
+            Token::I32(1),
+            Token::I32(2),
+            Token::I32(3),
+            Token::SeqEnd,
+        ];
+        let list = sixth::LinkedList::from([1, 2, 3]);
+        assert_ser_tokens(&list, &tokens);
+        assert_ser_tokens(&second::List::from(list.clone()), &tokens);
+        assert_ser_tokens(&fifth::List::from(list.clone()), &tokens);
+        assert_ser_tokens(&fourth::List::from(list), &tokens);
+        let list = third::List::new().prepend(3).prepend(2).prepend(1);
+        assert_ser_tokens(&list, &tokens);
+    }
+}
@@ -0,0 +1,414 @@
+// fifth's queue, but for any number of threads enqueueing and dequeueing at
+// once without locks: the Michael-Scott queue, which is to queues what
+// seventh's Treiber stack is to stacks.
+//
+// Like fifth, we keep pointers to both ends. The difference is that the
+// queue always starts with a dummy node, whose element (if it ever had one)
+// has already been dequeued. The head points to the dummy and the tail to
+// the last node, which is the dummy again when the queue is empty. Thanks to
+// it, enqueueing only ever touches the tail end and dequeueing the head end,
+// and neither has to deal with the queue running empty as a special case.
+//
+// Enqueueing is two steps: link the new node after the last one (a compare
+// and swap on the last node's `next`), then swing the tail to it. Between
+// the two, the tail lags one node behind. Rather than wait for the enqueuer
+// to finish, anyone who sees the lag swings the tail forward themselves, and
+// carries on. That's what makes the queue lock-free: no thread stopping
+// halfway can hold up the others.
+//
+// Dequeueing swings the head from the dummy to the first real node, which
+// becomes the new dummy once we've taken its element out, and retires the
+// old dummy. Freeing it is as hard as in seventh, for the same reasons (a
+// slower thread may still be reading its `next`), and we solve it the same
+// way: every operation holds a guard from the queue's collector, which the
+// retired dummies are handed to (see `reclaim`).
+
+use alloc::boxed::Box;
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::mem::MaybeUninit;
+use core::ptr;
+
+use crate::reclaim::Collector;
+use crate::sync::atomic::AtomicPtr;
+use crate::sync::atomic::Ordering;
+use crate::traits::MemSize;
+
+pub struct Queue<T> {
+    // The dummy node
+    head: AtomicPtr<Node<T>>,
+    // The last node, or the one before it while an enqueue is halfway
+    tail: AtomicPtr<Node<T>>,
+    // Frees retired dummies once no other thread can be looking at them
+    collector: Collector,
+    // We own the elements in the nodes
+    _marker: PhantomData<T>,
+}
+
+struct Node<T> {
+    // Uninitialized in the first dummy node, and moved out when the node
+    // becomes the dummy, so it's never dropped in place
+    elem: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>,
+}
+
+impl<T> Node<T> {
+    fn new(elem: MaybeUninit<T>) -> *mut Self {
+        Box::into_raw(Box::new(Node {
+            elem,
+            next: AtomicPtr::new(ptr::null_mut()),
+        }))
+    }
+}
+
+// Sharing the queue lets threads hand elements to each other, hence Send
+// for both, like a Mutex
+unsafe impl<T: Send> Send for Queue<T> {}
+unsafe impl<T: Send> Sync for Queue<T> {}
+
+impl<T> Queue<T> {
+    pub fn new() -> Self {
+        let dummy = Node::new(MaybeUninit::uninit());
+        Queue {
+            head: AtomicPtr::new(dummy),
+            tail: AtomicPtr::new(dummy),
+            collector: Collector::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn enqueue(&self, elem: T) {
+        let node = Node::new(MaybeUninit::new(elem));
+
+        // Pinned, so that the nodes we look at can't be freed under us
+        let _guard = self.collector.pin();
+
+        loop {
+            let tail = self.tail.load(Ordering::Acquire);
+            let next = unsafe { (*tail).next.load(Ordering::Acquire) };
+
+            if next.is_null() {
+                // Release, so that whoever dequeues the node sees its element
+                let linked = unsafe {
+                    (*tail).next.compare_exchange(
+                        ptr::null_mut(),
+                        node,
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                    )
+                };
+                if linked.is_ok() {
+                    // If this fails, someone already helped us
+                    let _ = self.tail.compare_exchange(
+                        tail,
+                        node,
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                    );
+                    return;
+                }
+            } else {
+                // Another enqueue is halfway, so we finish it for them
+                let _ =
+                    self.tail
+                        .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn dequeue(&self) -> Option<T> {
+        // Same as in `enqueue`
+        let guard = self.collector.pin();
+
+        loop {
+            let head = self.head.load(Ordering::Acquire);
+            let tail = self.tail.load(Ordering::Acquire);
+            let next = unsafe { (*head).next.load(Ordering::Acquire) };
+
+            if head == tail {
+                if next.is_null() {
+                    return None;
+                }
+                // Not empty, just an enqueue halfway: the head mustn't get
+                // past the tail, so we finish the enqueue first
+                let _ =
+                    self.tail
+                        .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+            } else if self
+                .head
+                .compare_exchange(head, next, Ordering::Acquire, Ordering::Acquire)
+                .is_ok()
+            {
+                // `next` is the dummy now, and its element is ours alone.
+                // Nobody frees it before we're done, as we're still pinned.
+                // The old dummy is unlinked, and ours to retire.
+                let elem = unsafe { (*next).elem.as_ptr().read() };
+                unsafe { guard.defer_destroy(head) };
+                return Some(elem);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        // Only a snapshot, like any answer about a queue others are using
+        let _guard = self.collector.pin();
+        let head = self.head.load(Ordering::Acquire);
+        unsafe { (*head).next.load(Ordering::Acquire) }.is_null()
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Only a snapshot while other threads enqueue and dequeue, like `is_empty`.
+// The dummy counts, as it's allocated all the same, but the retired ones the
+// collector hasn't freed yet don't.
+impl<T> MemSize for Queue<T> {
+    fn node_count(&self) -> usize {
+        // Pinned, so that the nodes we walk can't be freed under us
+        let _guard = self.collector.pin();
+        let mut count = 0;
+        let mut node = self.head.load(Ordering::Acquire);
+        while !node.is_null() {
+            count += 1;
+            node = unsafe { (*node).next.load(Ordering::Acquire) };
+        }
+        count
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        size_of::<Node<T>>()
+    }
+}
+
+impl<T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        // Nobody else has the queue anymore, so we can just walk it. All but
+        // the dummy still have their element. The collector frees whatever
+        // retired dummies are still pending.
+        let dummy = self.head.load(Ordering::Relaxed);
+        let mut node = unsafe { Box::from_raw(dummy) }.next.load(Ordering::Relaxed);
+        while !node.is_null() {
+            let mut boxed = unsafe { Box::from_raw(node) };
+            unsafe { boxed.elem.assume_init_drop() };
+            node = boxed.next.load(Ordering::Relaxed);
+        }
+    }
+}
+
+pub struct IntoIter<T>(Queue<T>);
+
+impl<T> IntoIterator for Queue<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.dequeue()
+    }
+}
+
+impl<T> FromIterator<T> for Queue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let queue = Queue::new();
+        for elem in iter {
+            queue.enqueue(elem);
+        }
+        queue
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::mem::size_of;
+
+    use super::Queue;
+    use crate::sync::model;
+    use crate::sync::thread;
+    use crate::sync::Arc;
+    use crate::traits::MemSize;
+
+    #[test]
+    fn basics() {
+        model(|| {
+            let queue = Queue::new();
+            assert!(queue.is_empty());
+            assert_eq!(queue.dequeue(), None);
+
+            queue.enqueue(1);
+            queue.enqueue(2);
+            assert!(!queue.is_empty());
+            assert_eq!(queue.dequeue(), Some(1));
+            queue.enqueue(3);
+            assert_eq!(queue.dequeue(), Some(2));
+            assert_eq!(queue.dequeue(), Some(3));
+            assert_eq!(queue.dequeue(), None);
+            assert!(queue.is_empty());
+        });
+    }
+
+    #[test]
+    fn into_iter() {
+        model(|| {
+            let queue: Queue<_> = (0..4).map(Box::new).collect();
+            let elems: Vec<_> = queue.into_iter().map(|x| *x).collect();
+            assert_eq!(elems, [0, 1, 2, 3]);
+        });
+    }
+
+    #[test]
+    fn mem_size() {
+        model(|| {
+            let queue = Queue::new();
+            // The element and the link (which loom's atomics make bigger)
+            let node = queue.bytes_per_element();
+            #[cfg(not(loom))]
+            assert_eq!(node, 2 * size_of::<usize>());
+            assert_eq!(queue.node_count(), 1);
+            assert_eq!(queue.heap_bytes(), node);
+            for i in 0..4 {
+                queue.enqueue(i);
+            }
+            queue.dequeue();
+            assert_eq!(queue.node_count(), 4);
+            assert_eq!(queue.heap_bytes(), 4 * node);
+        });
+    }
+
+    // Whatever's left, queued or waiting to be freed, goes with the queue
+    #[test]
+    fn drop() {
+        model(|| {
+            let queue = Queue::new();
+            for i in 0..4 {
+                queue.enqueue(Box::new(i));
+            }
+            queue.dequeue();
+        });
+    }
+
+    // Enqueueing at one end while dequeueing at the other, which loom runs
+    // through every order, including the consumer finding an enqueue
+    // halfway and finishing it
+    #[test]
+    fn producer_consumer() {
+        model(|| {
+            let queue = Arc::new(Queue::new());
+
+            let producer = queue.clone();
+            let handle = thread::spawn(move || {
+                producer.enqueue(Box::new(1));
+                producer.enqueue(Box::new(2));
+            });
+
+            let mut seen = Vec::new();
+            seen.extend(queue.dequeue().map(|x| *x));
+            handle.join().unwrap();
+            while let Some(elem) = queue.dequeue() {
+                seen.push(*elem);
+            }
+            assert_eq!(seen, [1, 2]);
+        });
+    }
+
+    #[test]
+    fn racing_dequeues() {
+        model(|| {
+            let queue = Arc::new(Queue::new());
+            queue.enqueue(Box::new(1));
+            queue.enqueue(Box::new(2));
+
+            let other = queue.clone();
+            let handle = thread::spawn(move || other.dequeue().map(|x| *x));
+            let mine = queue.dequeue().map(|x| *x);
+            let theirs = handle.join().unwrap();
+
+            let mut dequeued = [mine.unwrap(), theirs.unwrap()];
+            dequeued.sort();
+            assert_eq!(dequeued, [1, 2]);
+            assert!(queue.is_empty());
+        });
+    }
+
+    #[test]
+    fn racing_enqueues() {
+        model(|| {
+            let queue = Arc::new(Queue::new());
+
+            let other = queue.clone();
+            let handle = thread::spawn(move || other.enqueue(Box::new(1)));
+            queue.enqueue(Box::new(2));
+            handle.join().unwrap();
+
+            let mut dequeued: Vec<_> = Arc::try_unwrap(queue)
+                .ok()
+                .unwrap()
+                .into_iter()
+                .map(|x| *x)
+                .collect();
+            dequeued.sort();
+            assert_eq!(dequeued, [1, 2]);
+        });
+    }
+
+    // Far too many interleavings for loom, but plenty of real contention.
+    // Elements from each producer must come out in the order it enqueued
+    // them, whichever consumer gets them.
+    #[cfg(not(loom))]
+    #[test]
+    fn threads() {
+        const PRODUCERS: usize = 3;
+        const CONSUMERS: usize = 3;
+        let per_producer = if cfg!(miri) { 50 } else { 10_000 };
+
+        let queue = Arc::new(Queue::new());
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|p| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    for i in 0..per_producer {
+                        queue.enqueue(Box::new((p, i)));
+                    }
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = (0..CONSUMERS)
+            .map(|_| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    let mut last = [None; PRODUCERS];
+                    let mut count = 0;
+                    for _ in 0..per_producer {
+                        if let Some(elem) = queue.dequeue() {
+                            let (p, i) = *elem;
+                            assert!(last[p] < Some(i), "out of order");
+                            last[p] = Some(i);
+                            count += 1;
+                        }
+                    }
+                    count
+                })
+            })
+            .collect();
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+        let dequeued: usize = consumers
+            .into_iter()
+            .map(|consumer| consumer.join().unwrap())
+            .sum();
+        let left = Arc::try_unwrap(queue).ok().unwrap().into_iter().count();
+        assert_eq!(dequeued + left, PRODUCERS * per_producer);
+    }
+}
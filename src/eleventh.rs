@@ -0,0 +1,897 @@
+// third and tenth are persistent, but they can only change at their ends,
+// and putting two of them together means copying one. This is the heavy
+// machinery for when that's not enough: a finger tree (Hinze and Paterson's
+// 2-3 finger tree, annotated with sizes), which gives a persistent sequence
+// with amortized O(1) pushes and pops at both ends, and O(log n) indexing,
+// concatenation and splitting.
+//
+// A tree is either empty, a single item, or "deep": a prefix and a suffix of
+// one to four items each (the fingers, which is where pushes and pops
+// happen), and in between, a tree of nodes of two or three items. Each level
+// down holds nodes of the items of the level above, so the middle shrinks
+// exponentially and the whole thing is O(log n) levels deep. Pushing onto a
+// full finger moves three of its items down a level as a node, which happens
+// rarely enough to be O(1) amortized, same as carrying in a binary counter.
+// The paper needs laziness for that to survive persistence, which unlike
+// tenth we do without: pushing again and again onto the same old version
+// that's about to carry costs O(log n) each time. The O(log n) bounds hold
+// regardless.
+//
+// In Haskell, each level is a tree of a different type (of elements, then of
+// nodes of elements, then of nodes of nodes...). Rust can't compile a type
+// that's generic over an unbounded nesting like that, so every level is
+// made of the same `Item`s instead, which are either elements (at the top)
+// or nodes (further down). Nothing but the code below checks that the
+// levels are right.
+//
+// Every item knows how many elements it holds, and every deep tree how many
+// elements are in it, which is what lets us find the i-th element without
+// walking everything before it.
+//
+// Everything is shared between versions behind Rcs, so no version ever
+// changes and nothing ever needs T: Clone. Each element gets an Rc of its
+// own, so that moving it between fingers and nodes doesn't copy it.
+
+use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use crate::traits::counted_size;
+use crate::traits::MemSize;
+
+pub struct Deque<T> {
+    tree: Tree<T>,
+}
+
+enum Tree<T> {
+    Empty,
+    Single(Item<T>),
+    Deep(Rc<Deep<T>>),
+}
+
+struct Deep<T> {
+    // How many elements are in the whole tree
+    size: usize,
+    // One to four items each
+    prefix: Vec<Item<T>>,
+    middle: Tree<T>,
+    suffix: Vec<Item<T>>,
+}
+
+enum Item<T> {
+    Elem(Rc<T>),
+    Node(Rc<Node<T>>),
+}
+
+struct Node<T> {
+    size: usize,
+    // Two or three, one level further down than the node itself
+    items: Vec<Item<T>>,
+}
+
+// Another handle to the same tree, like cloning an Rc
+impl<T> Clone for Deque<T> {
+    fn clone(&self) -> Self {
+        Deque {
+            tree: self.tree.clone(),
+        }
+    }
+}
+
+impl<T> Clone for Tree<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Tree::Empty => Tree::Empty,
+            Tree::Single(item) => Tree::Single(item.clone()),
+            Tree::Deep(deep) => Tree::Deep(deep.clone()),
+        }
+    }
+}
+
+impl<T> Clone for Item<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Item::Elem(elem) => Item::Elem(elem.clone()),
+            Item::Node(node) => Item::Node(node.clone()),
+        }
+    }
+}
+
+impl<T> Item<T> {
+    fn size(&self) -> usize {
+        match self {
+            Item::Elem(_) => 1,
+            Item::Node(node) => node.size,
+        }
+    }
+
+    // Only for items at the top level, which are all elements
+    fn elem(&self) -> &T {
+        match self {
+            Item::Elem(elem) => elem,
+            Item::Node(_) => unreachable!("node at the top level"),
+        }
+    }
+
+    // Only for items below the top level, which are all nodes
+    fn items(&self) -> &[Item<T>] {
+        match self {
+            Item::Elem(_) => unreachable!("element below the top level"),
+            Item::Node(node) => &node.items,
+        }
+    }
+
+    // The element at index `i` among the ones in the item, however deep
+    fn get(&self, mut i: usize) -> &T {
+        let mut item = self;
+        loop {
+            match item {
+                Item::Elem(elem) => return elem,
+                Item::Node(node) => {
+                    let (skipped, found) = find(&node.items, i);
+                    i -= skipped;
+                    item = found;
+                }
+            }
+        }
+    }
+}
+
+fn node<T>(items: Vec<Item<T>>) -> Item<T> {
+    Item::Node(Rc::new(Node {
+        size: size(&items),
+        items,
+    }))
+}
+
+fn size<T>(items: &[Item<T>]) -> usize {
+    items.iter().map(Item::size).sum()
+}
+
+// Which of `items` holds the element at index `i`, and how many elements
+// come before it
+fn find<T>(items: &[Item<T>], i: usize) -> (usize, &Item<T>) {
+    let mut skipped = 0;
+    for item in items {
+        if i < skipped + item.size() {
+            return (skipped, item);
+        }
+        skipped += item.size();
+    }
+    unreachable!("index out of the items' range")
+}
+
+// Splits `items` around the one holding the element at index `i`
+fn split_items<T>(items: &[Item<T>], i: usize) -> (Vec<Item<T>>, Item<T>, Vec<Item<T>>) {
+    let mut skipped = 0;
+    for (at, item) in items.iter().enumerate() {
+        if i < skipped + item.size() {
+            return (items[..at].to_vec(), item.clone(), items[at + 1..].to_vec());
+        }
+        skipped += item.size();
+    }
+    unreachable!("index out of the items' range")
+}
+
+// Groups two to twelve items into nodes of two or three
+fn nodes<T>(mut items: Vec<Item<T>>) -> Vec<Item<T>> {
+    let mut nodes = Vec::new();
+    while !items.is_empty() {
+        // Threes as long as that doesn't leave a single item behind
+        let take = match items.len() {
+            2 | 4 => 2,
+            _ => 3,
+        };
+        let rest = items.split_off(take);
+        nodes.push(node(items));
+        items = rest;
+    }
+    nodes
+}
+
+impl<T> Tree<T> {
+    fn deep(prefix: Vec<Item<T>>, middle: Tree<T>, suffix: Vec<Item<T>>) -> Self {
+        Tree::Deep(Rc::new(Deep {
+            size: size(&prefix) + middle.size() + size(&suffix),
+            prefix,
+            middle,
+            suffix,
+        }))
+    }
+
+    fn from_items(items: Vec<Item<T>>) -> Self {
+        items
+            .into_iter()
+            .fold(Tree::Empty, |tree, item| tree.push_back(item))
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            Tree::Empty => 0,
+            Tree::Single(item) => item.size(),
+            Tree::Deep(deep) => deep.size,
+        }
+    }
+
+    fn push_front(&self, item: Item<T>) -> Self {
+        match self {
+            Tree::Empty => Tree::Single(item),
+            Tree::Single(other) => Tree::deep(vec![item], Tree::Empty, vec![other.clone()]),
+            Tree::Deep(deep) if deep.prefix.len() == 4 => {
+                // The finger is full: keep the newest, and move the other
+                // three down a level
+                let down = node(deep.prefix[1..].to_vec());
+                Tree::deep(
+                    vec![item, deep.prefix[0].clone()],
+                    deep.middle.push_front(down),
+                    deep.suffix.clone(),
+                )
+            }
+            Tree::Deep(deep) => {
+                let mut prefix = Vec::with_capacity(deep.prefix.len() + 1);
+                prefix.push(item);
+                prefix.extend(deep.prefix.iter().cloned());
+                Tree::deep(prefix, deep.middle.clone(), deep.suffix.clone())
+            }
+        }
+    }
+
+    fn push_back(&self, item: Item<T>) -> Self {
+        match self {
+            Tree::Empty => Tree::Single(item),
+            Tree::Single(other) => Tree::deep(vec![other.clone()], Tree::Empty, vec![item]),
+            Tree::Deep(deep) if deep.suffix.len() == 4 => {
+                let down = node(deep.suffix[..3].to_vec());
+                Tree::deep(
+                    deep.prefix.clone(),
+                    deep.middle.push_back(down),
+                    vec![deep.suffix[3].clone(), item],
+                )
+            }
+            Tree::Deep(deep) => {
+                let mut suffix = deep.suffix.clone();
+                suffix.push(item);
+                Tree::deep(deep.prefix.clone(), deep.middle.clone(), suffix)
+            }
+        }
+    }
+
+    fn front(&self) -> Option<&Item<T>> {
+        match self {
+            Tree::Empty => None,
+            Tree::Single(item) => Some(item),
+            Tree::Deep(deep) => deep.prefix.first(),
+        }
+    }
+
+    fn back(&self) -> Option<&Item<T>> {
+        match self {
+            Tree::Empty => None,
+            Tree::Single(item) => Some(item),
+            Tree::Deep(deep) => deep.suffix.last(),
+        }
+    }
+
+    // The tree without its first item
+    fn pop_front(&self) -> Self {
+        match self {
+            Tree::Empty | Tree::Single(_) => Tree::Empty,
+            Tree::Deep(deep) => {
+                Tree::deep_left(deep.prefix[1..].to_vec(), &deep.middle, deep.suffix.clone())
+            }
+        }
+    }
+
+    fn pop_back(&self) -> Self {
+        match self {
+            Tree::Empty | Tree::Single(_) => Tree::Empty,
+            Tree::Deep(deep) => Tree::deep_right(
+                deep.prefix.clone(),
+                &deep.middle,
+                deep.suffix[..deep.suffix.len() - 1].to_vec(),
+            ),
+        }
+    }
+
+    // A deep tree whose prefix may have run out, in which case it borrows
+    // the first node from the middle (or the middle is empty, and the suffix
+    // is all there is)
+    fn deep_left(prefix: Vec<Item<T>>, middle: &Tree<T>, suffix: Vec<Item<T>>) -> Self {
+        if !prefix.is_empty() {
+            return Tree::deep(prefix, middle.clone(), suffix);
+        }
+        match middle.front() {
+            None => Tree::from_items(suffix),
+            Some(first) => Tree::deep(first.items().to_vec(), middle.pop_front(), suffix),
+        }
+    }
+
+    fn deep_right(prefix: Vec<Item<T>>, middle: &Tree<T>, suffix: Vec<Item<T>>) -> Self {
+        if !suffix.is_empty() {
+            return Tree::deep(prefix, middle.clone(), suffix);
+        }
+        match middle.back() {
+            None => Tree::from_items(prefix),
+            Some(last) => Tree::deep(prefix, middle.pop_back(), last.items().to_vec()),
+        }
+    }
+
+    // The element at index `i`, which has to be in range
+    fn get(&self, i: usize) -> &T {
+        match self {
+            Tree::Empty => unreachable!("index out of the tree's range"),
+            Tree::Single(item) => item.get(i),
+            Tree::Deep(deep) => {
+                let prefix = size(&deep.prefix);
+                let middle = deep.middle.size();
+                if i < prefix {
+                    let (skipped, item) = find(&deep.prefix, i);
+                    item.get(i - skipped)
+                } else if i < prefix + middle {
+                    deep.middle.get(i - prefix)
+                } else {
+                    let (skipped, item) = find(&deep.suffix, i - prefix - middle);
+                    item.get(i - prefix - middle - skipped)
+                }
+            }
+        }
+    }
+
+    // `self`, then `items`, then `other`
+    fn concat(&self, items: Vec<Item<T>>, other: &Tree<T>) -> Self {
+        match (self, other) {
+            (Tree::Empty, _) => items
+                .into_iter()
+                .rev()
+                .fold(other.clone(), |tree, item| tree.push_front(item)),
+            (_, Tree::Empty) => items
+                .into_iter()
+                .fold(self.clone(), |tree, item| tree.push_back(item)),
+            (Tree::Single(item), _) => Tree::Empty.concat(items, other).push_front(item.clone()),
+            (_, Tree::Single(item)) => self.concat(items, &Tree::Empty).push_back(item.clone()),
+            (Tree::Deep(left), Tree::Deep(right)) => {
+                // The inner fingers go down a level, between the middles
+                let mut inner = left.suffix.clone();
+                inner.extend(items);
+                inner.extend(right.prefix.iter().cloned());
+                Tree::deep(
+                    left.prefix.clone(),
+                    left.middle.concat(nodes(inner), &right.middle),
+                    right.suffix.clone(),
+                )
+            }
+        }
+    }
+
+    // Splits the tree around the item holding the element at index `i`,
+    // which has to be in range
+    fn split(&self, i: usize) -> (Tree<T>, Item<T>, Tree<T>) {
+        match self {
+            Tree::Empty => unreachable!("index out of the tree's range"),
+            Tree::Single(item) => (Tree::Empty, item.clone(), Tree::Empty),
+            Tree::Deep(deep) => {
+                let prefix = size(&deep.prefix);
+                let middle = deep.middle.size();
+                if i < prefix {
+                    let (before, item, after) = split_items(&deep.prefix, i);
+                    (
+                        Tree::from_items(before),
+                        item,
+                        Tree::deep_left(after, &deep.middle, deep.suffix.clone()),
+                    )
+                } else if i < prefix + middle {
+                    // The node holding it, split in turn
+                    let (left, node, right) = deep.middle.split(i - prefix);
+                    let (before, item, after) = split_items(node.items(), i - prefix - left.size());
+                    (
+                        Tree::deep_right(deep.prefix.clone(), &left, before),
+                        item,
+                        Tree::deep_left(after, &right, deep.suffix.clone()),
+                    )
+                } else {
+                    let (before, item, after) = split_items(&deep.suffix, i - prefix - middle);
+                    (
+                        Tree::deep_right(deep.prefix.clone(), &deep.middle, before),
+                        item,
+                        Tree::from_items(after),
+                    )
+                }
+            }
+        }
+    }
+}
+
+impl<T> Deque<T> {
+    pub fn new() -> Self {
+        Deque { tree: Tree::Empty }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self.tree, Tree::Empty)
+    }
+
+    pub fn push_front(&self, elem: T) -> Self {
+        Deque {
+            tree: self.tree.push_front(Item::Elem(Rc::new(elem))),
+        }
+    }
+
+    pub fn push_back(&self, elem: T) -> Self {
+        Deque {
+            tree: self.tree.push_back(Item::Elem(Rc::new(elem))),
+        }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.tree.front().map(Item::elem)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.tree.back().map(Item::elem)
+    }
+
+    pub fn pop_front(&self) -> Option<(&T, Self)> {
+        let rest = Deque {
+            tree: self.tree.pop_front(),
+        };
+        self.front().map(|elem| (elem, rest))
+    }
+
+    pub fn pop_back(&self) -> Option<(&T, Self)> {
+        let rest = Deque {
+            tree: self.tree.pop_back(),
+        };
+        self.back().map(|elem| (elem, rest))
+    }
+
+    pub fn get(&self, i: usize) -> Option<&T> {
+        (i < self.len()).then(|| self.tree.get(i))
+    }
+
+    // All of `self`, then all of `other`, neither of which changes
+    pub fn concat(&self, other: &Self) -> Self {
+        Deque {
+            tree: self.tree.concat(Vec::new(), &other.tree),
+        }
+    }
+
+    // The first `at` elements, and the rest
+    pub fn split_at(&self, at: usize) -> (Self, Self) {
+        if at >= self.len() {
+            return (self.clone(), Deque::new());
+        }
+        let (before, item, after) = self.tree.split(at);
+        (
+            Deque { tree: before },
+            Deque {
+                tree: after.push_front(item),
+            },
+        )
+    }
+}
+
+impl<T> Default for Deque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<T> for Deque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Deque {
+            tree: iter.into_iter().fold(Tree::Empty, |tree, elem| {
+                tree.push_back(Item::Elem(Rc::new(elem)))
+            }),
+        }
+    }
+}
+
+// An element costs its own Rc and the slot holding it, in a finger or a node.
+// On top of that come the nodes and the deep levels, which heap_bytes walks
+// the tree for. Versions sharing parts of a tree each count them, even though
+// they're only there once, as with third's.
+impl<T> MemSize for Deque<T> {
+    fn heap_bytes(&self) -> usize {
+        self.tree.heap_bytes()
+    }
+
+    fn node_count(&self) -> usize {
+        self.len()
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        counted_size::<T>() + size_of::<Item<T>>()
+    }
+}
+
+impl<T> Tree<T> {
+    // The Rcs and Vecs of the tree, elements included
+    fn heap_bytes(&self) -> usize {
+        match self {
+            Tree::Empty => 0,
+            Tree::Single(item) => item.heap_bytes(),
+            Tree::Deep(deep) => {
+                let fingers = deep.prefix.iter().chain(&deep.suffix);
+                counted_size::<Deep<T>>()
+                    + (deep.prefix.capacity() + deep.suffix.capacity()) * size_of::<Item<T>>()
+                    + fingers.map(Item::heap_bytes).sum::<usize>()
+                    + deep.middle.heap_bytes()
+            }
+        }
+    }
+}
+
+impl<T> Item<T> {
+    fn heap_bytes(&self) -> usize {
+        match self {
+            Item::Elem(_) => counted_size::<T>(),
+            Item::Node(node) => {
+                counted_size::<Node<T>>()
+                    + node.items.capacity() * size_of::<Item<T>>()
+                    + node.items.iter().map(Item::heap_bytes).sum::<usize>()
+            }
+        }
+    }
+}
+
+// Front to back. What's left to visit is kept on a stack, with whatever is
+// next on top, so each step only unpacks as much of the tree as it needs.
+pub struct Iter<'a, T> {
+    stack: Vec<Pending<'a, T>>,
+}
+
+enum Pending<'a, T> {
+    Tree(&'a Tree<T>),
+    Item(&'a Item<T>),
+}
+
+impl<T> Deque<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            stack: vec![Pending::Tree(&self.tree)],
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.pop()? {
+                Pending::Item(Item::Elem(elem)) => return Some(elem),
+                Pending::Item(Item::Node(node)) => {
+                    self.stack
+                        .extend(node.items.iter().rev().map(Pending::Item));
+                }
+                Pending::Tree(Tree::Empty) => {}
+                Pending::Tree(Tree::Single(item)) => self.stack.push(Pending::Item(item)),
+                Pending::Tree(Tree::Deep(deep)) => {
+                    self.stack
+                        .extend(deep.suffix.iter().rev().map(Pending::Item));
+                    self.stack.push(Pending::Tree(&deep.middle));
+                    self.stack
+                        .extend(deep.prefix.iter().rev().map(Pending::Item));
+                }
+            }
+        }
+    }
+}
+
+// Same encoding as the other lists: a sequence, front to back. Versions
+// that share nodes serialize them once each, and don't share them anymore
+// once they're deserialized.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::de::SeqAccess;
+    use serde::de::Visitor;
+    use serde::ser::SerializeSeq;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    use super::Deque;
+
+    impl<T: Serialize> Serialize for Deque<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(Some(self.len()))?;
+            for elem in self.iter() {
+                seq.serialize_element(elem)?;
+            }
+            seq.end()
+        }
+    }
+
+    impl<'de, T: Deserialize<'de>> Deserialize<'de> for Deque<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_seq(DequeVisitor(PhantomData))
+        }
+    }
+
+    struct DequeVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for DequeVisitor<T> {
+        type Value = Deque<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a sequence")
+        }
+
+        fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+            let mut deque = Deque::new();
+            while let Some(elem) = seq.next_element()? {
+                deque = deque.push_back(elem);
+            }
+            Ok(deque)
+        }
+    }
+}
+
+// From front to back
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl {
+    use arbitrary::Arbitrary;
+    use arbitrary::Result;
+    use arbitrary::Unstructured;
+
+    use super::Deque;
+
+    impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for Deque<T> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_iter()?.collect()
+        }
+
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_take_rest_iter()?.collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+
+    use rand::rngs::StdRng;
+    use rand::Rng;
+    use rand::SeedableRng;
+
+    use super::Deque;
+    use super::Item;
+    use super::Tree;
+
+    // Checks the shape of the tree: finger lengths, node arities, that every
+    // level has the items it should, and the sizes
+    fn check<T>(deque: &Deque<T>) {
+        fn check_item<T>(item: &Item<T>, depth: usize) -> usize {
+            match item {
+                Item::Elem(_) => {
+                    assert_eq!(depth, 0);
+                    1
+                }
+                Item::Node(node) => {
+                    assert!(depth > 0);
+                    assert!((2..=3).contains(&node.items.len()));
+                    let size = node.items.iter().map(|i| check_item(i, depth - 1)).sum();
+                    assert_eq!(node.size, size);
+                    size
+                }
+            }
+        }
+
+        fn check_tree<T>(tree: &Tree<T>, depth: usize) -> usize {
+            match tree {
+                Tree::Empty => 0,
+                Tree::Single(single) => check_item(single, depth),
+                Tree::Deep(deep) => {
+                    assert!((1..=4).contains(&deep.prefix.len()));
+                    assert!((1..=4).contains(&deep.suffix.len()));
+                    let size = deep
+                        .prefix
+                        .iter()
+                        .map(|i| check_item(i, depth))
+                        .sum::<usize>()
+                        + check_tree(&deep.middle, depth + 1)
+                        + deep
+                            .suffix
+                            .iter()
+                            .map(|i| check_item(i, depth))
+                            .sum::<usize>();
+                    assert_eq!(deep.size, size);
+                    size
+                }
+            }
+        }
+
+        assert_eq!(check_tree(&deque.tree, 0), deque.len());
+    }
+
+    #[test]
+    fn basics() {
+        let deque = Deque::new();
+        assert!(deque.is_empty());
+        assert_eq!(deque.front(), None);
+        assert!(deque.pop_back().is_none());
+
+        let deque = deque.push_back(2).push_front(1).push_back(3);
+        assert_eq!(deque.len(), 3);
+        assert_eq!(deque.front(), Some(&1));
+        assert_eq!(deque.back(), Some(&3));
+
+        let (elem, deque) = deque.pop_front().unwrap();
+        assert_eq!(*elem, 1);
+        let (elem, deque) = deque.pop_back().unwrap();
+        assert_eq!(*elem, 3);
+        let (elem, deque) = deque.pop_back().unwrap();
+        assert_eq!(*elem, 2);
+        assert!(deque.is_empty());
+        check(&deque);
+    }
+
+    // Enough elements for a few levels, taken apart from both ends
+    #[test]
+    fn ends() {
+        let mut deque: Deque<_> = (0..100).collect();
+        check(&deque);
+        assert!(deque.iter().copied().eq(0..100));
+
+        for i in 0..50 {
+            let (front, rest) = deque.pop_front().unwrap();
+            assert_eq!(*front, i);
+            let (back, rest) = rest.pop_back().unwrap();
+            assert_eq!(*back, 99 - i);
+            deque = rest;
+            check(&deque);
+        }
+        assert!(deque.is_empty());
+    }
+
+    #[test]
+    fn get() {
+        let deque: Deque<_> = (0..1000).collect();
+        for i in 0..1000 {
+            assert_eq!(deque.get(i), Some(&i));
+        }
+        assert_eq!(deque.get(1000), None);
+    }
+
+    #[test]
+    fn concat_and_split() {
+        for left in 0..40 {
+            for right in [0, 1, 5, 17, 40] {
+                let a: Deque<_> = (0..left).collect();
+                let b: Deque<_> = (left..left + right).collect();
+                let both = a.concat(&b);
+                check(&both);
+                assert!(both.iter().copied().eq(0..left + right));
+
+                for at in [0, left / 2, left, left + right] {
+                    let (before, after) = both.split_at(at);
+                    check(&before);
+                    check(&after);
+                    assert!(before.iter().copied().eq(0..at));
+                    assert!(after.iter().copied().eq(at..left + right));
+                }
+            }
+        }
+    }
+
+    // Every version stays as it was, whatever is done with the others
+    #[test]
+    fn persistence() {
+        let one: Deque<_> = (0..10).collect();
+        let two = one.push_front(-1).concat(&one);
+        let (three, _) = one.split_at(3);
+
+        assert!(one.iter().copied().eq(0..10));
+        assert!(two.iter().copied().eq((-1..10).chain(0..10)));
+        assert!(three.iter().copied().eq(0..3));
+    }
+
+    // Random operations against a VecDeque
+    #[test]
+    fn model() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut deque = Deque::new();
+        let mut model = VecDeque::new();
+        for i in 0..2000 {
+            match rng.random_range(0..6) {
+                0 => {
+                    deque = deque.push_front(i);
+                    model.push_front(i);
+                }
+                1 => {
+                    deque = deque.push_back(i);
+                    model.push_back(i);
+                }
+                2 => {
+                    let popped = deque.pop_front().map(|(elem, rest)| (*elem, rest));
+                    assert_eq!(popped.as_ref().map(|(elem, _)| *elem), model.pop_front());
+                    deque = popped.map_or_else(Deque::new, |(_, rest)| rest);
+                }
+                3 => {
+                    let popped = deque.pop_back().map(|(elem, rest)| (*elem, rest));
+                    assert_eq!(popped.as_ref().map(|(elem, _)| *elem), model.pop_back());
+                    deque = popped.map_or_else(Deque::new, |(_, rest)| rest);
+                }
+                4 => {
+                    // Cut somewhere and glue back the other way round
+                    let at = rng.random_range(0..=model.len());
+                    let (before, after) = deque.split_at(at);
+                    deque = after.concat(&before);
+                    model.rotate_left(at);
+                }
+                _ => {
+                    let doubled = deque.concat(&deque);
+                    let (half, _) = doubled.split_at(model.len());
+                    deque = half;
+                }
+            }
+            check(&deque);
+            assert_eq!(deque.len(), model.len());
+            if let Some(i) = (!model.is_empty()).then(|| rng.random_range(0..model.len())) {
+                assert_eq!(deque.get(i), model.get(i));
+            }
+        }
+        assert!(deque.iter().eq(model.iter()));
+    }
+
+    #[test]
+    fn mem_size() {
+        use std::mem::size_of;
+
+        use crate::traits::counted_size;
+        use crate::traits::MemSize;
+
+        let deque = Deque::new();
+        assert_eq!(deque.heap_bytes(), 0);
+
+        // A single element is only its Rc
+        let deque = deque.push_back(1usize);
+        let elem = counted_size::<usize>();
+        assert_eq!(deque.node_count(), 1);
+        assert_eq!(deque.bytes_per_element(), elem + size_of::<Item<usize>>());
+        assert_eq!(deque.heap_bytes(), elem);
+
+        // The nodes and levels only add to what the elements cost
+        let deque: Deque<_> = (0..100usize).collect();
+        assert!(deque.heap_bytes() > 100 * elem);
+        let (half, _) = deque.split_at(50);
+        assert!(half.heap_bytes() < deque.heap_bytes());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde::de::value::Error;
+        use serde::de::value::SeqDeserializer;
+        use serde::Deserialize;
+        use serde_test::assert_ser_tokens;
+        use serde_test::Token;
+
+        let deque: Deque<_> = [1, 2, 3].into_iter().collect();
+        assert_ser_tokens(
+            &deque,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::I32(1),
+                Token::I32(2),
+                Token::I32(3),
+                Token::SeqEnd,
+            ],
+        );
+
+        let seq = SeqDeserializer::<_, Error>::new(vec![1, 2, 3].into_iter());
+        let deque = Deque::<i32>::deserialize(seq).unwrap();
+        assert_eq!(deque.len(), 3);
+        assert!(deque.iter().copied().eq([1, 2, 3]));
+    }
+}
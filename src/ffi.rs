@@ -0,0 +1,349 @@
+// C bindings for the unsafe queue (fifth) and deque (sixth), so that C, or
+// Python through ctypes, can use them too. The elements are `void *`s the
+// lists never look into: what they point to is the caller's, and so is
+// freeing it, which `ll_*_free` can do for whatever is still in the list
+// through a callback.
+//
+// C only ever sees pointers to the opaque handles below, made by `ll_*_new`
+// and given back to `ll_*_free`. The names and types are plain C, so a tool
+// like cbindgen can write the header. To get a library C can link against,
+// build the crate as a cdylib (or staticlib) with this feature on, and std
+// too, as that's where the panic handler and the allocator come from:
+//
+//     cargo rustc --release --features ffi --crate-type cdylib
+//
+// Nothing here aborts on running out of memory: `ll_*_new` returns NULL, and
+// pushing returns false, with the element left out of the list. Popping and
+// peeking write the element to `out` and return true, or return false on an
+// empty list, as a NULL element is a perfectly valid one.
+
+use alloc::boxed::Box;
+use core::ffi::c_void;
+use core::ptr;
+
+use crate::allocator;
+use crate::allocator::Global;
+use crate::fifth;
+use crate::sixth;
+
+type Queue = fifth::List<*mut c_void>;
+type Deque = sixth::LinkedList<*mut c_void>;
+
+// Called by `ll_*_free` on every element still in the list
+pub type LlFreeFn = Option<unsafe extern "C" fn(*mut c_void)>;
+
+// The handles C holds. There's nothing to see in them: each one is really the
+// Rust list, which only we get to look into.
+#[repr(C)]
+pub struct LlQueue {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+pub struct LlDeque {
+    _private: [u8; 0],
+}
+
+// Back from a handle to the list behind it
+unsafe fn queue<'a>(handle: *mut LlQueue) -> &'a mut Queue {
+    debug_assert!(!handle.is_null(), "NULL queue handle");
+    &mut *handle.cast::<Queue>()
+}
+
+unsafe fn deque<'a>(handle: *mut LlDeque) -> &'a mut Deque {
+    debug_assert!(!handle.is_null(), "NULL deque handle");
+    &mut *handle.cast::<Deque>()
+}
+
+// Moves a new list to the heap, as the handle C holds, or gives NULL if there's
+// no memory for it. `try_into_raw_in` makes what a Box would on Global, so
+// `ll_*_free` can take it back with `Box::from_raw`.
+fn new_handle<L, H>(list: L) -> *mut H {
+    allocator::try_into_raw_in(list, &Global).map_or(ptr::null_mut(), <*mut L>::cast)
+}
+
+// Writes what we popped or peeked to `out`, if there's anything
+unsafe fn give(elem: Option<*mut c_void>, out: *mut *mut c_void) -> bool {
+    match elem {
+        Some(elem) => {
+            out.write(elem);
+            true
+        }
+        None => false,
+    }
+}
+
+// Drops the list, handing the elements left in it to `free` first
+fn destroy<I: IntoIterator<Item = *mut c_void>>(list: I, free: LlFreeFn) {
+    match free {
+        Some(free) => list.into_iter().for_each(|elem| unsafe { free(elem) }),
+        None => drop(list),
+    }
+}
+
+/// Makes an empty queue, to be freed with `ll_queue_free`, or returns NULL
+/// if there's no memory for it.
+///
+/// # Safety
+///
+/// Always safe to call, it's only unsafe for symmetry with the rest.
+#[no_mangle]
+pub unsafe extern "C" fn ll_queue_new() -> *mut LlQueue {
+    new_handle(Queue::new())
+}
+
+/// Frees the queue, calling `free` (unless NULL) on each element left in it,
+/// front to back. Does nothing on a NULL queue.
+///
+/// # Safety
+///
+/// `queue` must be NULL or come from `ll_queue_new`, and must not be used
+/// after this.
+#[no_mangle]
+pub unsafe extern "C" fn ll_queue_free(queue: *mut LlQueue, free: LlFreeFn) {
+    if !queue.is_null() {
+        destroy(*Box::from_raw(queue.cast::<Queue>()), free);
+    }
+}
+
+/// Adds `elem` at the back, returning false if there's no memory for it.
+///
+/// # Safety
+///
+/// `queue` must come from `ll_queue_new` and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ll_queue_push(queue: *mut LlQueue, elem: *mut c_void) -> bool {
+    self::queue(queue).try_push(elem).is_ok()
+}
+
+/// Takes the front element out into `out`, returning false if the queue is
+/// empty (leaving `out` alone).
+///
+/// # Safety
+///
+/// `queue` must come from `ll_queue_new` and not be freed yet, and `out`
+/// must be valid for writing a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn ll_queue_pop(queue: *mut LlQueue, out: *mut *mut c_void) -> bool {
+    give(self::queue(queue).pop(), out)
+}
+
+/// Same as `ll_queue_pop`, but leaves the element in the queue.
+///
+/// # Safety
+///
+/// Same as `ll_queue_pop`.
+#[no_mangle]
+pub unsafe extern "C" fn ll_queue_peek(queue: *mut LlQueue, out: *mut *mut c_void) -> bool {
+    give(self::queue(queue).peek().copied(), out)
+}
+
+/// The number of elements in the queue.
+///
+/// # Safety
+///
+/// `queue` must come from `ll_queue_new` and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ll_queue_len(queue: *mut LlQueue) -> usize {
+    self::queue(queue).len()
+}
+
+/// Makes an empty deque, to be freed with `ll_deque_free`, or returns NULL
+/// if there's no memory for it.
+///
+/// # Safety
+///
+/// Always safe to call, it's only unsafe for symmetry with the rest.
+#[no_mangle]
+pub unsafe extern "C" fn ll_deque_new() -> *mut LlDeque {
+    new_handle(Deque::new())
+}
+
+/// Frees the deque, calling `free` (unless NULL) on each element left in it,
+/// front to back. Does nothing on a NULL deque.
+///
+/// # Safety
+///
+/// `deque` must be NULL or come from `ll_deque_new`, and must not be used
+/// after this.
+#[no_mangle]
+pub unsafe extern "C" fn ll_deque_free(deque: *mut LlDeque, free: LlFreeFn) {
+    if !deque.is_null() {
+        destroy(*Box::from_raw(deque.cast::<Deque>()), free);
+    }
+}
+
+/// Adds `elem` at the front, returning false if there's no memory for it.
+///
+/// # Safety
+///
+/// `deque` must come from `ll_deque_new` and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ll_deque_push_front(deque: *mut LlDeque, elem: *mut c_void) -> bool {
+    self::deque(deque).try_push_front(elem).is_ok()
+}
+
+/// Adds `elem` at the back, returning false if there's no memory for it.
+///
+/// # Safety
+///
+/// `deque` must come from `ll_deque_new` and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ll_deque_push_back(deque: *mut LlDeque, elem: *mut c_void) -> bool {
+    self::deque(deque).try_push_back(elem).is_ok()
+}
+
+/// Takes the front element out into `out`, returning false if the deque is
+/// empty (leaving `out` alone).
+///
+/// # Safety
+///
+/// `deque` must come from `ll_deque_new` and not be freed yet, and `out`
+/// must be valid for writing a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn ll_deque_pop_front(deque: *mut LlDeque, out: *mut *mut c_void) -> bool {
+    give(self::deque(deque).pop_front(), out)
+}
+
+/// Same as `ll_deque_pop_front`, from the back.
+///
+/// # Safety
+///
+/// Same as `ll_deque_pop_front`.
+#[no_mangle]
+pub unsafe extern "C" fn ll_deque_pop_back(deque: *mut LlDeque, out: *mut *mut c_void) -> bool {
+    give(self::deque(deque).pop_back(), out)
+}
+
+/// Same as `ll_deque_pop_front`, but leaves the element in the deque.
+///
+/// # Safety
+///
+/// Same as `ll_deque_pop_front`.
+#[no_mangle]
+pub unsafe extern "C" fn ll_deque_front(deque: *mut LlDeque, out: *mut *mut c_void) -> bool {
+    give(self::deque(deque).front().copied(), out)
+}
+
+/// Same as `ll_deque_pop_back`, but leaves the element in the deque.
+///
+/// # Safety
+///
+/// Same as `ll_deque_pop_front`.
+#[no_mangle]
+pub unsafe extern "C" fn ll_deque_back(deque: *mut LlDeque, out: *mut *mut c_void) -> bool {
+    give(self::deque(deque).back().copied(), out)
+}
+
+/// The number of elements in the deque.
+///
+/// # Safety
+///
+/// `deque` must come from `ll_deque_new` and not be freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ll_deque_len(deque: *mut LlDeque) -> usize {
+    self::deque(deque).len()
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::c_void;
+    use std::ptr;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+
+    // What C would pass: pointers to boxed `usize`s, which the tests read and
+    // free
+    fn elem(x: usize) -> *mut c_void {
+        Box::into_raw(Box::new(x)).cast()
+    }
+
+    unsafe extern "C" fn free_elem(elem: *mut c_void) {
+        FREED.fetch_add(*Box::from_raw(elem.cast::<usize>()), Ordering::Relaxed);
+    }
+
+    static FREED: AtomicUsize = AtomicUsize::new(0);
+
+    unsafe fn value(elem: *mut c_void) -> usize {
+        *Box::from_raw(elem.cast::<usize>())
+    }
+
+    #[test]
+    fn queue() {
+        unsafe {
+            let queue = ll_queue_new();
+            let mut out = ptr::null_mut();
+            assert!(!ll_queue_pop(queue, &mut out));
+            assert!(!ll_queue_peek(queue, &mut out));
+            assert!(out.is_null());
+
+            assert!(ll_queue_push(queue, elem(1)));
+            assert!(ll_queue_push(queue, elem(2)));
+            // NULL is an element like any other
+            assert!(ll_queue_push(queue, ptr::null_mut()));
+            assert_eq!(ll_queue_len(queue), 3);
+
+            assert!(ll_queue_peek(queue, &mut out));
+            assert_eq!(*out.cast::<usize>(), 1);
+            assert!(ll_queue_pop(queue, &mut out));
+            assert_eq!(value(out), 1);
+            assert!(ll_queue_pop(queue, &mut out));
+            assert_eq!(value(out), 2);
+            assert!(ll_queue_pop(queue, &mut out));
+            assert!(out.is_null());
+            assert_eq!(ll_queue_len(queue), 0);
+
+            ll_queue_free(queue, None);
+            ll_queue_free(ptr::null_mut(), None);
+        }
+    }
+
+    #[test]
+    fn deque() {
+        unsafe {
+            let deque = ll_deque_new();
+            let mut out = ptr::null_mut();
+            assert!(!ll_deque_pop_back(deque, &mut out));
+
+            assert!(ll_deque_push_back(deque, elem(2)));
+            assert!(ll_deque_push_front(deque, elem(1)));
+            assert!(ll_deque_push_back(deque, elem(3)));
+            assert_eq!(ll_deque_len(deque), 3);
+
+            assert!(ll_deque_front(deque, &mut out));
+            assert_eq!(*out.cast::<usize>(), 1);
+            assert!(ll_deque_back(deque, &mut out));
+            assert_eq!(*out.cast::<usize>(), 3);
+            assert!(ll_deque_pop_back(deque, &mut out));
+            assert_eq!(value(out), 3);
+            assert!(ll_deque_pop_front(deque, &mut out));
+            assert_eq!(value(out), 1);
+
+            assert_eq!(ll_deque_len(deque), 1);
+            assert!(ll_deque_pop_front(deque, &mut out));
+            assert_eq!(value(out), 2);
+            ll_deque_free(deque, None);
+            ll_deque_free(ptr::null_mut(), None);
+        }
+    }
+
+    // Freeing hands whatever is left to the callback, so C doesn't have to
+    // pop everything first
+    #[test]
+    fn free() {
+        unsafe {
+            let queue = ll_queue_new();
+            ll_queue_push(queue, elem(1));
+            ll_queue_push(queue, elem(2));
+            let deque = ll_deque_new();
+            ll_deque_push_front(deque, elem(10));
+            ll_deque_push_back(deque, elem(20));
+
+            ll_queue_free(queue, Some(free_elem));
+            ll_deque_free(deque, Some(free_elem));
+            assert_eq!(FREED.load(Ordering::Relaxed), 33);
+        }
+    }
+}
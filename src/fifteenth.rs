@@ -0,0 +1,742 @@
+// sixth's NodeRefs find a node again in O(1), for the likes of LRU caches,
+// but they're addresses underneath: the list can only check one by making
+// sure the node hasn't been freed first, so splitting or shrinking it throws
+// all of its handles away. This list does the same without a line of unsafe
+// code, and its handles survive anything but removing their own element.
+//
+// The nodes live in a Vec, a slab, and link to each other by their index in
+// it rather than by address. A node that's removed leaves a free slot
+// behind, which goes on a free list (chained through the slots) for the next
+// push to reuse, so the indices of the others never change. A handle is an
+// index, then, plus a generation: every slot counts how many times it was
+// freed, and a handle only works while its slot is still on the generation
+// it was made in. A handle to a removed element finds a newer generation
+// there (whether or not the slot holds something else by then), and gets
+// None instead of somebody else's element.
+//
+// Like sixth, every list also gets an id of its own, which its handles
+// carry. One from another list (a clone included) would otherwise find
+// whatever is at that index there, so it gets None too.
+//
+// Having all the nodes in one buffer is also much kinder to the cache than
+// an allocation per node. A list that was only ever pushed to is laid out in
+// order, so iterating it is a walk along the buffer. The links are indices,
+// which Vec bounds-checks for us, and the Vec drops whatever is left in it,
+// so there's no Drop to write either.
+//
+// The one thing we can't do safely is hand out a &mut to every element in
+// list order, as that means many borrows into the Vec at once. So there's no
+// iter_mut: get_mut takes a handle instead.
+#![forbid(unsafe_code)]
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Debug;
+use core::iter::FusedIterator;
+use core::mem;
+use core::mem::size_of;
+
+use crate::traits::MemSize;
+
+pub struct LinkedList<T> {
+    // What our handles carry, so another list's can't pass for ours
+    id: usize,
+    slots: Vec<Slot<T>>,
+    front: Option<usize>,
+    back: Option<usize>,
+    len: usize,
+    // The first free slot
+    free: Option<usize>,
+}
+
+// Handles work with any list of any type, so deriving is fine
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Handle {
+    list: usize,
+    index: usize,
+    generation: u64,
+}
+
+#[derive(Clone)]
+struct Slot<T> {
+    // How many times the slot was freed, which no handle to it survives
+    generation: u64,
+    entry: Entry<T>,
+}
+
+#[derive(Clone)]
+enum Entry<T> {
+    Occupied(Node<T>),
+    // Holds the next free slot
+    Free(Option<usize>),
+}
+
+#[derive(Clone)]
+struct Node<T> {
+    elem: T,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+// Every list gets an id that no list had before it. We'd rather panic than
+// hand out an id twice.
+fn next_id() -> usize {
+    use core::sync::atomic::AtomicUsize;
+    use core::sync::atomic::Ordering;
+
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    NEXT.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |id| id.checked_add(1))
+        .expect("ran out of list ids")
+}
+
+impl<T> LinkedList<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        LinkedList {
+            id: next_id(),
+            slots: Vec::with_capacity(capacity),
+            front: None,
+            back: None,
+            len: 0,
+            free: None,
+        }
+    }
+
+    // How many elements fit before the slab has to grow
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Every slot gets freed the usual way, so no handle outlives this
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.front.map(|index| &self.node(index).elem)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.front.map(|index| &mut self.node_mut(index).elem)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.back.map(|index| &self.node(index).elem)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.back.map(|index| &mut self.node_mut(index).elem)
+    }
+
+    pub fn push_front(&mut self, elem: T) -> Handle {
+        let index = self.alloc(elem);
+        self.attach(index, None, self.front);
+        self.handle(index)
+    }
+
+    pub fn push_back(&mut self, elem: T) -> Handle {
+        let index = self.alloc(elem);
+        self.attach(index, self.back, None);
+        self.handle(index)
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let index = self.front?;
+        self.detach(index);
+        Some(self.release(index))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let index = self.back?;
+        self.detach(index);
+        Some(self.release(index))
+    }
+
+    // Whether the element behind `handle` is still in the list
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.lookup(handle).is_some()
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.lookup(handle).map(|index| &self.node(index).elem)
+    }
+
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        self.lookup(handle)
+            .map(|index| &mut self.node_mut(index).elem)
+    }
+
+    // O(1) wherever the element is. None if it was already removed.
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        let index = self.lookup(handle)?;
+        self.detach(index);
+        Some(self.release(index))
+    }
+
+    // Relinks the element behind `handle` at the front, without moving it
+    // in the slab, so the handle stays good. Returns whether it was there.
+    pub fn move_to_front(&mut self, handle: Handle) -> bool {
+        let Some(index) = self.lookup(handle) else {
+            return false;
+        };
+        self.detach(index);
+        self.attach(index, None, self.front);
+        true
+    }
+
+    // Same as move_to_front, but to the back
+    pub fn move_to_back(&mut self, handle: Handle) -> bool {
+        let Some(index) = self.lookup(handle) else {
+            return false;
+        };
+        self.detach(index);
+        self.attach(index, self.back, None);
+        true
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            slots: &self.slots,
+            front: self.front,
+            back: self.back,
+            len: self.len,
+        }
+    }
+
+    fn handle(&self, index: usize) -> Handle {
+        Handle {
+            list: self.id,
+            index,
+            generation: self.slots[index].generation,
+        }
+    }
+
+    // Where the element behind `handle` is, if it's still there
+    fn lookup(&self, handle: Handle) -> Option<usize> {
+        if handle.list != self.id {
+            return None;
+        }
+        let slot = self.slots.get(handle.index)?;
+        match slot.entry {
+            Entry::Occupied(_) if slot.generation == handle.generation => Some(handle.index),
+            _ => None,
+        }
+    }
+
+    // Only for indices we got from our own links, which only lead to
+    // occupied slots
+    fn node(&self, index: usize) -> &Node<T> {
+        match &self.slots[index].entry {
+            Entry::Occupied(node) => node,
+            Entry::Free(_) => unreachable!("linked to a free slot"),
+        }
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut Node<T> {
+        match &mut self.slots[index].entry {
+            Entry::Occupied(node) => node,
+            Entry::Free(_) => unreachable!("linked to a free slot"),
+        }
+    }
+
+    // Puts `elem` in a slot, preferably a free one, not linked to anything
+    // yet
+    fn alloc(&mut self, elem: T) -> usize {
+        let entry = Entry::Occupied(Node {
+            elem,
+            prev: None,
+            next: None,
+        });
+        match self.free {
+            Some(index) => {
+                let slot = &mut self.slots[index];
+                match mem::replace(&mut slot.entry, entry) {
+                    Entry::Free(next) => self.free = next,
+                    Entry::Occupied(_) => unreachable!("occupied slot on the free list"),
+                }
+                index
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    entry,
+                });
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    // Frees a detached slot, which is where its handles stop working
+    fn release(&mut self, index: usize) -> T {
+        let slot = &mut self.slots[index];
+        slot.generation += 1;
+        match mem::replace(&mut slot.entry, Entry::Free(self.free)) {
+            Entry::Occupied(node) => {
+                self.free = Some(index);
+                node.elem
+            }
+            Entry::Free(_) => unreachable!("freeing a free slot"),
+        }
+    }
+
+    // Links the node at `index` between `prev` and `next`, which are next to
+    // each other (or None, at the ends)
+    fn attach(&mut self, index: usize, prev: Option<usize>, next: Option<usize>) {
+        let node = self.node_mut(index);
+        node.prev = prev;
+        node.next = next;
+        match prev {
+            Some(prev) => self.node_mut(prev).next = Some(index),
+            None => self.front = Some(index),
+        }
+        match next {
+            Some(next) => self.node_mut(next).prev = Some(index),
+            None => self.back = Some(index),
+        }
+        self.len += 1;
+    }
+
+    // The other way around, leaving the node in its slot but out of the list
+    fn detach(&mut self, index: usize) {
+        let node = self.node_mut(index);
+        let prev = node.prev.take();
+        let next = node.next.take();
+        match prev {
+            Some(prev) => self.node_mut(prev).next = next,
+            None => self.front = next,
+        }
+        match next {
+            Some(next) => self.node_mut(next).prev = prev,
+            None => self.back = prev,
+        }
+        self.len -= 1;
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Copies the slab as it is, but under an id of its own, so the handles we
+// gave out only work on us
+impl<T: Clone> Clone for LinkedList<T> {
+    fn clone(&self) -> Self {
+        LinkedList {
+            id: next_id(),
+            slots: self.slots.clone(),
+            front: self.front,
+            back: self.back,
+            len: self.len,
+            free: self.free,
+        }
+    }
+}
+
+impl<T: Debug> Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = LinkedList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+// The whole slab counts, free slots and spare capacity included, as that's
+// what we asked the allocator for
+impl<T> MemSize for LinkedList<T> {
+    fn node_count(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        size_of::<Slot<T>>()
+    }
+}
+
+pub struct Iter<'a, T> {
+    slots: &'a [Slot<T>],
+    front: Option<usize>,
+    back: Option<usize>,
+    len: usize,
+}
+
+// Just the list, popping from either end
+pub struct IntoIter<T> {
+    list: LinkedList<T>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn node(&self, index: usize) -> &'a Node<T> {
+        match &self.slots[index].entry {
+            Entry::Occupied(node) => node,
+            Entry::Free(_) => unreachable!("linked to a free slot"),
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    // The length tells us when the two ends meet
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.node(self.front?);
+        self.front = node.next;
+        self.len -= 1;
+        Some(&node.elem)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.node(self.back?);
+        self.back = node.prev;
+        self.len -= 1;
+        Some(&node.elem)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// A plain sequence, front to back, like sixth's. Handles aren't part of it,
+// as they're only good for the list that handed them out.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::de::SeqAccess;
+    use serde::de::Visitor;
+    use serde::ser::SerializeSeq;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    use super::LinkedList;
+
+    impl<T: Serialize> Serialize for LinkedList<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(Some(self.len()))?;
+            for elem in self.iter() {
+                seq.serialize_element(elem)?;
+            }
+            seq.end()
+        }
+    }
+
+    impl<'de, T: Deserialize<'de>> Deserialize<'de> for LinkedList<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_seq(LinkedListVisitor(PhantomData))
+        }
+    }
+
+    struct LinkedListVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for LinkedListVisitor<T> {
+        type Value = LinkedList<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a sequence")
+        }
+
+        fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+            let mut list = LinkedList::new();
+            while let Some(elem) = seq.next_element()? {
+                list.push_back(elem);
+            }
+            Ok(list)
+        }
+    }
+}
+
+// From front to back
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl {
+    use arbitrary::Arbitrary;
+    use arbitrary::Result;
+    use arbitrary::Unstructured;
+
+    use super::LinkedList;
+
+    impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for LinkedList<T> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_iter()?.collect()
+        }
+
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_take_rest_iter()?.collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+
+    use rand::rngs::StdRng;
+    use rand::Rng;
+    use rand::SeedableRng;
+
+    use super::LinkedList;
+
+    #[test]
+    fn basics() {
+        let mut list = LinkedList::new();
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+        assert_eq!(list.front(), None);
+
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.front(), Some(&1));
+        assert_eq!(list.back(), Some(&3));
+
+        *list.front_mut().unwrap() = 10;
+        *list.back_mut().unwrap() = 30;
+        assert_eq!(format!("{list:?}"), "[10, 2, 30]");
+        assert_eq!(list.pop_back(), Some(30));
+        assert_eq!(list.pop_front(), Some(10));
+        assert_eq!(list.pop_front(), Some(2));
+        assert!(list.is_empty());
+        assert_eq!(list.back(), None);
+    }
+
+    #[test]
+    fn handles() {
+        let mut list = LinkedList::new();
+        let one = list.push_back(1);
+        let two = list.push_back(2);
+        let three = list.push_back(3);
+
+        // Handles survive the pushes after them
+        list.extend(4..10);
+        *list.get_mut(two).unwrap() = 20;
+        assert_eq!(list.get(two), Some(&20));
+
+        assert_eq!(list.remove(two), Some(20));
+        assert!(!list.contains(two));
+        assert_eq!(list.remove(two), None);
+        assert_eq!(list.get_mut(two), None);
+
+        // The slot gets reused, but the old handle doesn't see the new
+        // element
+        let four = list.push_front(4);
+        assert_eq!(four.index, two.index);
+        assert_eq!(list.get(two), None);
+        assert_eq!(list.get(four), Some(&4));
+
+        assert!(list.move_to_back(one));
+        assert!(list.move_to_front(three));
+        assert!(!list.move_to_front(two));
+        assert!(list.iter().copied().eq([3, 4, 4, 5, 6, 7, 8, 9, 1]));
+        assert_eq!(list.get(one), Some(&1));
+
+        // Popping and clearing free slots too
+        assert_eq!(list.pop_front(), Some(3));
+        assert_eq!(list.get(three), None);
+        list.clear();
+        assert_eq!(list.get(one), None);
+    }
+
+    #[test]
+    fn foreign_handles() {
+        let mut list = LinkedList::new();
+        let mut other = LinkedList::new();
+        let one = list.push_back(1);
+        let two = other.push_back(2);
+        assert_eq!(one.index, two.index);
+
+        assert_eq!(list.get(two), None);
+        assert_eq!(list.get_mut(two), None);
+        assert_eq!(list.remove(two), None);
+        assert!(!list.move_to_back(two));
+        assert_eq!(other.get(one), None);
+
+        // A clone is another list, with the same elements in the same slots
+        let mut clone = list.clone();
+        assert_eq!(clone.get(one), None);
+        assert_eq!(clone.remove(one), None);
+        assert_eq!(clone.len(), 1);
+        assert_eq!(list.get(one), Some(&1));
+    }
+
+    #[test]
+    fn iter() {
+        let list: LinkedList<_> = (0..6).collect();
+        assert!(list.iter().rev().copied().eq((0..6).rev()));
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 6);
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&4));
+        assert_eq!(iter.next_back(), Some(&3));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        let mut iter = list.clone().into_iter();
+        assert_eq!(iter.next_back(), Some(5));
+        assert!(iter.eq(0..5));
+    }
+
+    // Removed slots are pushed again before the slab grows
+    #[test]
+    fn reuse() {
+        let mut list = LinkedList::with_capacity(4);
+        list.extend(0..4);
+        for i in 4..100 {
+            list.pop_front();
+            list.push_back(i);
+        }
+        assert_eq!(list.capacity(), 4);
+        assert!(list.iter().copied().eq(96..100));
+    }
+
+    // Random pushes, pops, and removals and moves by handle, against a
+    // VecDeque of the elements with their handles
+    #[test]
+    fn model() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut list = LinkedList::new();
+        let mut model = VecDeque::new();
+        let mut removed = Vec::new();
+        for i in 0..5000 {
+            match rng.random_range(0..6) {
+                0 => model.push_front((list.push_front(i), i)),
+                1 => model.push_back((list.push_back(i), i)),
+                2 => assert_eq!(list.pop_front(), model.pop_front().map(|(_, elem)| elem)),
+                3 => assert_eq!(list.pop_back(), model.pop_back().map(|(_, elem)| elem)),
+                4 if !model.is_empty() => {
+                    let at = rng.random_range(0..model.len());
+                    let (handle, elem) = model.remove(at).unwrap();
+                    assert_eq!(list.remove(handle), Some(elem));
+                    removed.push(handle);
+                }
+                5 if !model.is_empty() => {
+                    let at = rng.random_range(0..model.len());
+                    let entry = model.remove(at).unwrap();
+                    assert!(list.move_to_front(entry.0));
+                    model.push_front(entry);
+                }
+                _ => {}
+            }
+            assert_eq!(list.len(), model.len());
+        }
+        assert!(list.iter().eq(model.iter().map(|(_, elem)| elem)));
+        for (handle, elem) in &model {
+            assert_eq!(list.get(*handle), Some(elem));
+        }
+        for handle in removed {
+            assert_eq!(list.get(handle), None);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde::de::value::Error;
+        use serde::de::value::SeqDeserializer;
+        use serde::Deserialize;
+        use serde_test::assert_ser_tokens;
+        use serde_test::Token;
+
+        let list: LinkedList<_> = [1, 2, 3].into_iter().collect();
+        assert_ser_tokens(
+            &list,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::I32(1),
+                Token::I32(2),
+                Token::I32(3),
+                Token::SeqEnd,
+            ],
+        );
+
+        let seq = SeqDeserializer::<_, Error>::new(vec![1, 2, 3].into_iter());
+        let list = LinkedList::<i32>::deserialize(seq).unwrap();
+        assert_eq!(list.len(), 3);
+        assert!(list.iter().copied().eq([1, 2, 3]));
+    }
+}
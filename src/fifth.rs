@@ -6,17 +6,73 @@
 // exception that this time our list behaves like a queue, so push and pop act
 // at the end of the list rather than the beginning.
 
-use std::mem;
-use std::ptr;
+// Everything here only needs `core` and `alloc` (through our allocator
+// helpers), so the queue is available in `no_std` builds of the crate. Only
+// the channel, which needs threads and locks, requires std.
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::cmp::Ordering;
+use core::iter::FusedIterator;
+use core::mem::size_of;
+use core::mem::MaybeUninit;
+use core::ops::Deref;
+use core::ops::DerefMut;
+use core::ptr;
+use core::ptr::NonNull;
+use core::sync::atomic;
+use core::sync::atomic::AtomicUsize;
+
+use crate::allocator;
+pub use crate::allocator::AllocError;
+use crate::allocator::Allocator;
+use crate::allocator::Global;
+use crate::traits::MemSize;
+
+#[cfg(feature = "std")]
+pub mod channel;
+
+#[cfg(feature = "std")]
+pub use self::channel::channel;
+
+#[cfg(feature = "rayon")]
+pub mod parallel;
 
 // This implementation uses mutable pointers in the interface, but they are
 // hidden from the users given that we define them in structs. Nonetheless,
 // we really don't want to expose
-pub struct List<T> {
+//
+// Nodes come from `A`, which is the global allocator unless the user picks
+// another one through `new_in` (only possible with the `allocator_api`
+// feature on nightly).
+pub struct List<T, A: Allocator = Global> {
     head: Link<T>,
     // We avoid the use of mutable references because
     // tail: Option<&mut Node<T>>,
     tail: *mut Node<T>,
+    // Keeping track of the length is cheap and lets iterators report exact
+    // sizes, so `collect` and friends can preallocate
+    len: usize,
+    alloc: A,
+    // Nodes `reserve` allocated that no element went into yet, chained
+    // through `next`. Pushing takes them before allocating. Popping frees its
+    // node rather than putting it here, so the pool only ever holds what was
+    // asked for up front, and `shrink_to_fit` hands it back.
+    free: Link<T>,
+    free_len: usize,
+    #[cfg(feature = "metrics")]
+    stats: Stats,
+}
+
+// Counters kept by the queue since it was created, with the `metrics` feature.
+// Moving nodes between queues (e.g. `take_batch`) counts as popping them from
+// one and pushing them onto the other.
+#[cfg(feature = "metrics")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub pushes: u64,
+    pub pops: u64,
+    pub high_water_mark: usize,
 }
 
 // We don't want to mix Box with mutable pointers, so we avoid:
@@ -26,28 +82,274 @@ type Link<T> = *mut Node<T>;
 // Note that Option above is not even that useful when using mutable pointers,
 // because we already have a null value (the null pointer)
 
-struct Node<T> {
-    elem: T,
-    next: Link<T>,
+// Nodes are only public for the raw parts API below, which lets advanced users
+// build chains themselves (or hand them over FFI, hence the C layout).
+#[repr(C)]
+pub struct Node<T> {
+    pub elem: T,
+    pub next: *mut Node<T>,
+    // The block `reserve` allocated the node in, or null if it was allocated
+    // on its own, which is what `Node::new` does
+    block: *mut Block,
+}
+
+impl<T> Node<T> {
+    pub fn new(elem: T, next: *mut Node<T>) -> Self {
+        Node {
+            elem,
+            next,
+            block: ptr::null_mut(),
+        }
+    }
+}
+
+// `reserve` allocates all the nodes it needs at once: this header followed by
+// `cap` nodes. The block can only be freed as a whole, so the header counts
+// the nodes that are still around (queued or pooled, here or in a list we
+// handed them to), and whoever frees the last one frees the block. Every node
+// points at its block, so freeing a node is O(1) either way. Lists sharing a
+// block can live on different threads (`take_batch`, or rayon splitting a
+// list), hence the atomic count.
+struct Block {
+    live: AtomicUsize,
+    cap: usize,
 }
 
+impl Block {
+    // The layout of a block of `cap` nodes, and the offset of the first node
+    fn layout<T>(cap: usize) -> (Layout, usize) {
+        let nodes = Layout::array::<Node<T>>(cap).expect("capacity overflow");
+        Layout::new::<Block>()
+            .extend(nodes)
+            .expect("capacity overflow")
+    }
+}
+
+// What the raw parts API hands out: a non-null node pointer, if any
+pub type RawLink<T> = Option<NonNull<Node<T>>>;
+
 impl<T> List<T> {
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    /// Same as `from_raw_parts_in`, for nodes from the global allocator.
+    ///
+    /// # Safety
+    ///
+    /// See `from_raw_parts_in`.
+    pub unsafe fn from_raw_parts(head: RawLink<T>, tail: RawLink<T>, len: usize) -> Self {
+        Self::from_raw_parts_in(head, tail, len, Global)
+    }
+}
+
+impl<T, A: Allocator> List<T, A> {
+    pub fn new_in(alloc: A) -> Self {
         List {
             head: ptr::null_mut(),
             tail: ptr::null_mut(),
+            len: 0,
+            alloc,
+            free: ptr::null_mut(),
+            free_len: 0,
+            #[cfg(feature = "metrics")]
+            stats: Stats::default(),
         }
     }
 
-    pub fn push(&mut self, elem: T) {
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    /// Builds a list out of a chain of nodes allocated elsewhere.
+    ///
+    /// # Safety
+    ///
+    /// Either `head` and `tail` are both `None` and `len` is zero, or:
+    ///
+    /// - Following `next` from `head` reaches `tail` after exactly `len - 1`
+    ///   hops, without cycles, and `tail.next` is null.
+    /// - Every node was either handed out by `into_raw_parts` of a list
+    ///   using `alloc`, or made with `Node::new` and allocated with `alloc`
+    ///   with the layout of a `Node<T>` (e.g. through `Box::new` for the
+    ///   global allocator).
+    /// - No one else owns or accesses the nodes afterwards, since the list
+    ///   will read, mutate, and free them.
+    pub unsafe fn from_raw_parts_in(
+        head: RawLink<T>,
+        tail: RawLink<T>,
+        len: usize,
+        alloc: A,
+    ) -> Self {
+        let mut list = List::new_in(alloc);
+        list.head = head.map_or(ptr::null_mut(), NonNull::as_ptr);
+        list.tail = tail.map_or(ptr::null_mut(), NonNull::as_ptr);
+        list.len = len;
+        list.record_pushes(len);
+        list.strict_check();
+        list
+    }
+
+    // Gives up ownership of the nodes, which must be given back to
+    // `from_raw_parts_in` to be freed (or leaked). The queued nodes are handed
+    // over as they are, without moving any element. The pooled nodes are
+    // freed and the allocator is dropped.
+    pub fn into_raw_parts(self) -> (RawLink<T>, RawLink<T>, usize) {
+        let parts = (self.raw_head(), self.raw_tail(), self.len);
+        let mut this = core::mem::ManuallyDrop::new(self);
+        this.shrink_to_fit();
+        // SAFETY: `this` is never used again, and the only field that owns
+        // resources besides the nodes is the allocator
+        unsafe { ptr::drop_in_place(&mut this.alloc) };
+        parts
+    }
+
+    // The raw pointers to both ends, for splicing chains by hand. Writing
+    // through them is as unsafe as it gets: the list's invariants (see
+    // `from_raw_parts_in`) must still hold by the next call on the list.
+    pub fn raw_head(&self) -> RawLink<T> {
+        NonNull::new(self.head)
+    }
+
+    pub fn raw_tail(&self) -> RawLink<T> {
+        NonNull::new(self.tail)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // How many elements fit before pushing has to allocate again
+    pub fn capacity(&self) -> usize {
+        self.len + self.free_len
+    }
+
+    // Makes sure the next `additional` pushes don't allocate. The nodes the
+    // pool is missing come from a single allocation (see `Block`).
+    pub fn reserve(&mut self, additional: usize) {
+        let missing = additional.saturating_sub(self.free_len);
+        if missing == 0 {
+            return;
+        }
+
+        let (layout, offset) = Block::layout::<T>(missing);
         unsafe {
+            let memory = allocator::alloc_in(layout, &self.alloc);
+            let block = memory.cast::<Block>();
+            block.write(Block {
+                live: AtomicUsize::new(missing),
+                cap: missing,
+            });
+
+            // Pooled backwards, so pushes fill the block from the start
+            let nodes = memory.add(offset).cast::<Node<T>>();
+            for i in (0..missing).rev() {
+                let node = nodes.add(i);
+                ptr::addr_of_mut!((*node).block).write(block);
+                self.pool(node);
+            }
+        }
+        self.strict_check();
+    }
+
+    // Gives all the pooled nodes back to the allocator
+    pub fn shrink_to_fit(&mut self) {
+        while !self.free.is_null() {
+            unsafe {
+                let node = self.free;
+                self.free = (*node).next;
+                self.release(node);
+            }
+        }
+        self.free_len = 0;
+        self.strict_check();
+    }
+
+    // Frees a node with no element in it: on its own if it was allocated on
+    // its own, or along with its block if it was the block's last node
+    unsafe fn release(&self, node: *mut Node<T>) {
+        let block = ptr::addr_of!((*node).block).read();
+        if block.is_null() {
+            // Freeing it as uninitialized, so we don't drop an element that
+            // isn't there
+            allocator::from_raw_in(node.cast::<MaybeUninit<Node<T>>>(), &self.alloc);
+            return;
+        }
+
+        // Acquiring the other lists' releases too, so they are done with
+        // their nodes by the time we free the block
+        if (*block).live.fetch_sub(1, atomic::Ordering::AcqRel) == 1 {
+            let (layout, _) = Block::layout::<T>((*block).cap);
+            allocator::dealloc_in(block.cast(), layout, &self.alloc);
+        }
+    }
+
+    // Puts a node with no element in it in the pool
+    unsafe fn pool(&mut self, node: *mut Node<T>) {
+        // Not going through a &mut Node, as there's no valid T in it
+        ptr::addr_of_mut!((*node).next).write(self.free);
+        self.free = node;
+        self.free_len += 1;
+    }
+
+    // Moves the element out of a node we already unlinked, and frees the node
+    unsafe fn free_node(&mut self, node: *mut Node<T>) -> T {
+        let elem = ptr::read(ptr::addr_of!((*node).elem));
+        self.release(node);
+        elem
+    }
+
+    // Takes a node from the pool if there's one, and only allocates otherwise
+    fn new_node(&mut self, elem: T, next: Link<T>) -> *mut Node<T> {
+        if self.free.is_null() {
             // We could also allocate memory manually with std::alloc::alloc
-            // But that's a big footgun we generally try to avoid in Rust
-            let new_tail = Box::into_raw(Box::new(Node {
-                elem: elem,
-                next: ptr::null_mut(),
-            }));
+            // But that's a big footgun we generally try to avoid in Rust, so
+            // the allocator helpers stick to boxes whenever they can
+            return allocator::into_raw_in(Node::new(elem, next), &self.alloc);
+        }
+
+        unsafe {
+            let free = self.free;
+            self.free = (*free).next;
+            self.free_len -= 1;
+            // Leaving `block` alone, the node still belongs to it
+            ptr::addr_of_mut!((*free).elem).write(elem);
+            ptr::addr_of_mut!((*free).next).write(next);
+            free
+        }
+    }
+
+    pub fn push(&mut self, elem: T) {
+        let new_tail = self.new_node(elem, ptr::null_mut());
+        self.push_node(new_tail);
+    }
+
+    // Same as `push`, but running out of memory hands the element back to
+    // the caller instead of aborting the process. `Box::new` can't report
+    // allocation failures (and `Box::try_new` is nightly-only), so this is
+    // the one place where we do walk into the footgun of `std::alloc`.
+    pub fn try_push(&mut self, elem: T) -> Result<(), AllocError<T>> {
+        if !self.free.is_null() {
+            self.push(elem);
+            return Ok(());
+        }
+
+        let node = Node::new(elem, ptr::null_mut());
+        match allocator::try_into_raw_in(node, &self.alloc) {
+            Ok(new_tail) => {
+                self.push_node(new_tail);
+                Ok(())
+            }
+            Err(node) => Err(AllocError(node.elem)),
+        }
+    }
 
+    fn push_node(&mut self, new_tail: *mut Node<T>) {
+        unsafe {
             if !self.tail.is_null() {
                 (*self.tail).next = new_tail;
             } else {
@@ -55,7 +357,25 @@ impl<T> List<T> {
             }
 
             self.tail = new_tail;
+            self.len += 1;
+        }
+        self.record_pushes(1);
+        self.strict_check();
+    }
+
+    // Inserting at the head is as cheap as at the tail, which is handy to put
+    // back an element that was just popped without losing its turn.
+    pub fn push_front(&mut self, elem: T) {
+        let new_head = self.new_node(elem, self.head);
+
+        if self.tail.is_null() {
+            self.tail = new_head;
         }
+
+        self.head = new_head;
+        self.len += 1;
+        self.record_pushes(1);
+        self.strict_check();
     }
 
     pub fn pop(&mut self) -> Option<T> {
@@ -63,27 +383,336 @@ impl<T> List<T> {
             if self.head.is_null() {
                 None
             } else {
-                let head = Box::from_raw(self.head);
-                self.head = head.next;
+                let head = self.head;
+                self.head = (*head).next;
 
                 if self.head.is_null() {
                     self.tail = ptr::null_mut();
                 }
 
-                Some(head.elem)
+                self.len -= 1;
+                let elem = self.free_node(head);
+                self.record_pops(1);
+                self.strict_check();
+                Some(elem)
+            }
+        }
+    }
+
+    // Inserts `elem` before the first element that's larger according to
+    // `cmp`, so a queue that's kept sorted stays sorted, and equal elements
+    // keep their insertion order. O(n), as we walk from the head.
+    pub fn insert_sorted<F>(&mut self, elem: T, mut cmp: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        unsafe {
+            // Find the last node that is not larger than `elem`, if any. The
+            // comparisons happen before we allocate or relink anything, so
+            // a panicking `cmp` leaves the queue as it was.
+            let mut prev: Link<T> = ptr::null_mut();
+            let mut cur = self.head;
+            while !cur.is_null() && cmp(&(*cur).elem, &elem) != Ordering::Greater {
+                prev = cur;
+                cur = (*cur).next;
+            }
+
+            if prev.is_null() {
+                return self.push_front(elem);
+            }
+
+            let new = self.new_node(elem, cur);
+            (*prev).next = new;
+            if cur.is_null() {
+                self.tail = new;
+            }
+            self.len += 1;
+        }
+        self.record_pushes(1);
+        self.strict_check();
+    }
+
+    // Moves the head to the back of the queue by relinking its node, which
+    // is what a round-robin loop would otherwise do with a pop and a push
+    // (and a free and an allocation in between).
+    pub fn rotate(&mut self) {
+        // With zero or one elements, the head already is the tail
+        if self.head == self.tail {
+            return;
+        }
+
+        unsafe {
+            let old_head = self.head;
+            self.head = (*old_head).next;
+
+            (*old_head).next = ptr::null_mut();
+            (*self.tail).next = old_head;
+            self.tail = old_head;
+        }
+        self.strict_check();
+    }
+
+    // Links all the nodes of `other` after our tail without reallocating.
+    // Both lists must share the allocator, as `other`'s nodes will be freed
+    // through ours from now on. Only the parallel collect needs it.
+    #[cfg(feature = "rayon")]
+    fn append(&mut self, mut other: List<T, A>) {
+        if other.head.is_null() {
+            return;
+        }
+
+        unsafe {
+            if self.tail.is_null() {
+                self.head = other.head;
+            } else {
+                (*self.tail).next = other.head;
+            }
+        }
+        self.tail = other.tail;
+        self.len += other.len;
+        self.record_pushes(other.len);
+
+        // The nodes are ours now, so `other` must not free them on drop
+        other.head = ptr::null_mut();
+        other.tail = ptr::null_mut();
+        other.len = 0;
+        self.strict_check();
+    }
+
+    // Pushing a slice one element at a time would allocate a node for each of
+    // them. So we reserve them up front, which allocates all the nodes the
+    // pool doesn't have in a single block, and every push below takes one of
+    // those. On a fresh queue that's one allocation for the whole slice.
+    pub fn extend_from_slice(&mut self, elems: &[T])
+    where
+        T: Clone,
+    {
+        self.reserve(elems.len());
+        // If a `clone` panics, the elements pushed so far stay queued, and
+        // the nodes we didn't get to stay pooled
+        for elem in elems {
+            self.push(elem.clone());
+        }
+    }
+
+    // Walks the whole queue checking that head, tail, and len agree with each
+    // other and that there are no cycles, panicking otherwise. Meant to catch
+    // bugs in new unsafe code early, it's O(n).
+    pub fn assert_invariants(&self) {
+        unsafe {
+            // Floyd's cycle detection first, so that the walk below ends
+            let mut slow = self.head;
+            let mut fast = self.head;
+            while !fast.is_null() && !(*fast).next.is_null() {
+                slow = (*slow).next;
+                fast = (*(*fast).next).next;
+                assert!(slow != fast, "the queue has a cycle");
+            }
+
+            let mut count = 0;
+            let mut last = ptr::null_mut();
+            let mut cur = self.head;
+            while !cur.is_null() {
+                count += 1;
+                last = cur;
+                cur = (*cur).next;
+            }
+
+            assert_eq!(count, self.len, "len doesn't match the number of nodes");
+            assert!(last == self.tail, "tail isn't the last node");
+
+            // The pool is only ever pushed to and popped from its front, so
+            // there's no way for it to grow a cycle. Only `reserve` fills it,
+            // so every pooled node lives in a block.
+            let mut pooled = 0;
+            let mut cur = self.free;
+            while !cur.is_null() {
+                pooled += 1;
+                let block = ptr::addr_of!((*cur).block).read();
+                assert!(!block.is_null(), "a pooled node isn't in a block");
+                assert!(
+                    (*block).live.load(atomic::Ordering::Relaxed) > 0,
+                    "a pooled node's block is dead"
+                );
+                cur = (*cur).next;
             }
+            assert_eq!(pooled, self.free_len, "free_len doesn't match the pool");
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    // These are no-ops unless the `metrics` feature is on. Call them once the
+    // length has been updated, so that the high-water mark is right.
+    #[inline]
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    fn record_pushes(&mut self, n: usize) {
+        #[cfg(feature = "metrics")]
+        {
+            self.stats.pushes += n as u64;
+            self.stats.high_water_mark = self.stats.high_water_mark.max(self.len);
+        }
+    }
+
+    #[inline]
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    fn record_pops(&mut self, n: usize) {
+        #[cfg(feature = "metrics")]
+        {
+            self.stats.pops += n as u64;
+        }
+    }
+
+    // With the `strict-checks` feature, debug builds validate the queue after
+    // every operation that relinks nodes.
+    #[inline]
+    fn strict_check(&self) {
+        #[cfg(feature = "strict-checks")]
+        if cfg!(debug_assertions) {
+            self.assert_invariants();
         }
     }
 }
 
-impl<T> Drop for List<T> {
+// The operations below create a second list, which needs an allocator of its
+// own. As with std's collections, we ask for a clonable allocator for that.
+impl<T, A: Allocator + Clone> List<T, A> {
+    // Detaches up to `n` elements from the front as a queue of their own. We
+    // only walk to the n-th node and cut the chain there, nodes are reused.
+    pub fn take_batch(&mut self, n: usize) -> List<T, A> {
+        let n = n.min(self.len);
+        let mut batch = List::new_in(self.alloc.clone());
+        if n == 0 {
+            return batch;
+        }
+
+        unsafe {
+            // Find the last node of the batch, no need to walk for all of them
+            let batch_tail = if n == self.len {
+                self.tail
+            } else {
+                let mut batch_tail = self.head;
+                for _ in 1..n {
+                    batch_tail = (*batch_tail).next;
+                }
+                batch_tail
+            };
+
+            batch.head = self.head;
+            batch.tail = batch_tail;
+            batch.len = n;
+
+            self.head = (*batch_tail).next;
+            if self.head.is_null() {
+                self.tail = ptr::null_mut();
+            }
+            (*batch_tail).next = ptr::null_mut();
+            self.len -= n;
+        }
+
+        self.record_pops(n);
+        batch.record_pushes(n);
+        self.strict_check();
+        batch.strict_check();
+        batch
+    }
+}
+
+// Raw pointers opt us out of Send and Sync, but the queue owns its nodes just
+// like a Box would, so it's safe to opt back in (the channel relies on it)
+unsafe impl<T: Send, A: Allocator + Send> Send for List<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for List<T, A> {}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Raw pointers cost no more than a Box, and the tail pointer and length live
+// in the list itself, so the nodes are as small as second's plus the pointer
+// to their block. Nodes allocated on their own count one by one, but a block
+// only goes once all its nodes do, so it counts as a whole: header, pooled
+// nodes and the nodes already popped from it. A block shared with another
+// list (see `take_batch`) counts in both, as either one keeps it alive.
+impl<T, A: Allocator> MemSize for List<T, A> {
+    fn heap_bytes(&self) -> usize {
+        self.allocation().1
+    }
+
+    fn node_count(&self) -> usize {
+        self.allocation().0
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        size_of::<Node<T>>()
+    }
+}
+
+impl<T, A: Allocator> List<T, A> {
+    // The nodes and bytes allocated for the nodes we hold, queued or pooled
+    fn allocation(&self) -> (usize, usize) {
+        let mut nodes = 0;
+        let mut bytes = 0;
+        let mut blocks = Vec::new();
+        for mut cur in [self.head, self.free] {
+            while !cur.is_null() {
+                unsafe {
+                    // Pooled nodes have no element, so only reading the links
+                    let block = ptr::addr_of!((*cur).block).read();
+                    if block.is_null() {
+                        nodes += 1;
+                        bytes += size_of::<Node<T>>();
+                    } else {
+                        blocks.push(block);
+                    }
+                    cur = ptr::addr_of!((*cur).next).read();
+                }
+            }
+        }
+
+        // Nodes of a block aren't necessarily next to each other in the chain
+        blocks.sort_unstable();
+        blocks.dedup();
+        for block in blocks {
+            let cap = unsafe { (*block).cap };
+            nodes += cap;
+            bytes += Block::layout::<T>(cap).0.size();
+        }
+        (nodes, bytes)
+    }
+}
+
+// The front of a VecDeque is the front of the queue, in both directions
+impl<T> From<VecDeque<T>> for List<T> {
+    fn from(deque: VecDeque<T>) -> Self {
+        let mut list = List::new();
+        for elem in deque {
+            list.push(elem);
+        }
+        list
+    }
+}
+
+impl<T, A: Allocator> From<List<T, A>> for VecDeque<T> {
+    fn from(list: List<T, A>) -> Self {
+        // The exact size of our IntoIter lets `collect` allocate only once
+        list.into_iter().collect()
+    }
+}
+
+impl<T, A: Allocator> Drop for List<T, A> {
     fn drop(&mut self) {
-        // Repeatedly popping
-        while let Some(_) = self.pop() {}
+        // Repeatedly popping, which frees every node, and then the pool
+        while self.pop().is_some() {}
+        self.shrink_to_fit();
     }
 }
 
-pub struct IntoIter<T>(List<T>);
+pub struct IntoIter<T, A: Allocator = Global>(List<T, A>);
 
 pub struct Iter<'a, T> {
     // Given we no longer use safe pointers anywhere in the linked list
@@ -93,21 +722,29 @@ pub struct Iter<'a, T> {
     // However, if we do that then the lifetime is not used. We could use
     // `PhantomData` to work around this... or, instead, we can seettle for
     next: Option<&'a Node<T>>,
+    len: usize,
 }
 
 pub struct IterMut<'a, T> {
     next: Option<&'a mut Node<T>>,
+    len: usize,
 }
 
-impl<T> List<T> {
-    pub fn into_iter(self) -> IntoIter<T> {
+impl<T, A: Allocator> IntoIterator for List<T, A> {
+    type IntoIter = IntoIter<T, A>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
         IntoIter(self)
     }
+}
 
+impl<T, A: Allocator> List<T, A> {
     pub fn iter(&self) -> Iter<'_, T> {
         unsafe {
             Iter {
                 next: self.head.as_ref(),
+                len: self.len,
             }
         }
     }
@@ -121,6 +758,7 @@ impl<T> List<T> {
                 // because it's willing to pretend to be as large as specified
                 // by the caller, even 'static! This is a smell but we push through
                 next: self.head.as_mut(),
+                len: self.len,
             }
         }
     }
@@ -132,42 +770,272 @@ impl<T> List<T> {
     pub fn peek_mut(&mut self) -> Option<&mut T> {
         unsafe { self.head.as_mut().map(|node| &mut node.elem) }
     }
+
+    // Same as `peek_mut`, but the guard borrows the whole list instead of the
+    // head element, so the caller can decide to take the head afterwards.
+    pub fn peek_mut_guard(&mut self) -> Option<PeekMut<'_, T, A>> {
+        if self.head.is_null() {
+            None
+        } else {
+            Some(PeekMut { list: self })
+        }
+    }
+
+    // Lazily removes and yields the elements matching `pred`, front to back,
+    // leaving the rest queued in their original order. Elements that the
+    // iterator didn't get to (e.g. when dropped early) are kept.
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F, A>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        ExtractIf {
+            cur: self.head,
+            prev: ptr::null_mut(),
+            list: self,
+            pred,
+        }
+    }
+}
+
+pub struct ExtractIf<'a, T, F, A: Allocator = Global>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    list: &'a mut List<T, A>,
+    // The next node to look at, and the last node we kept before it. The
+    // latter is the one to relink when `cur` gets extracted.
+    cur: Link<T>,
+    prev: Link<T>,
+    pred: F,
 }
 
-impl<T> Iterator for IntoIter<T> {
+impl<'a, T, F, A: Allocator> Iterator for ExtractIf<'a, T, F, A>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        unsafe {
+            while !self.cur.is_null() {
+                let node = self.cur;
+                self.cur = (*node).next;
+
+                // We haven't touched any links yet, so the list is still
+                // valid if the predicate panics
+                if !(self.pred)(&mut (*node).elem) {
+                    self.prev = node;
+                    continue;
+                }
+
+                if self.prev.is_null() {
+                    self.list.head = self.cur;
+                } else {
+                    (*self.prev).next = self.cur;
+                }
+
+                // Extracting the tail makes the last kept node the new one
+                if self.list.tail == node {
+                    self.list.tail = self.prev;
+                }
+
+                self.list.len -= 1;
+                self.list.record_pops(1);
+                self.list.strict_check();
+                return Some(self.list.free_node(node));
+            }
+
+            None
+        }
+    }
+}
+
+// A guard over a non-empty list that derefs to its head. Holding the list
+// (rather than the node) is what lets `pop` consume the head in place.
+pub struct PeekMut<'a, T, A: Allocator = Global> {
+    list: &'a mut List<T, A>,
+}
+
+impl<'a, T, A: Allocator> PeekMut<'a, T, A> {
+    pub fn pop(self) -> T {
+        // The guard is only ever built over a non-empty list, and it holds
+        // the only borrow of it, so nothing can have popped in the meantime
+        self.list.pop().unwrap()
+    }
+}
+
+impl<'a, T, A: Allocator> Deref for PeekMut<'a, T, A> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &(*self.list.head).elem }
+    }
+}
+
+impl<'a, T, A: Allocator> DerefMut for PeekMut<'a, T, A> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut (*self.list.head).elem }
+    }
+}
+
+impl<T, A: Allocator> Iterator for IntoIter<T, A> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
         self.0.pop()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T, A: Allocator> ExactSizeIterator for IntoIter<T, A> {
+    fn len(&self) -> usize {
+        self.0.len
+    }
 }
 
+impl<T, A: Allocator> FusedIterator for IntoIter<T, A> {}
+
 impl<'a, T> Iterator for Iter<'a, T> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
         unsafe {
             self.next.map(|node| {
-                self.next = node.next.as_ref();
+                self.len -= 1;
+                // A split iterator ends before the list does, so we stop at
+                // len rather than at the null pointer
+                self.next = if self.len == 0 {
+                    None
+                } else {
+                    node.next.as_ref()
+                };
                 &node.elem
             })
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
 }
 
+// Once we've reached the null pointer at the end, we stay there
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
 impl<'a, T> Iterator for IterMut<'a, T> {
     type Item = &'a mut T;
 
     fn next(&mut self) -> Option<Self::Item> {
         unsafe {
             self.next.take().map(|node| {
-                self.next = node.next.as_mut();
+                self.len -= 1;
+                // Not even a reference to the node after our last one, which
+                // may be another iterator's by now
+                self.next = if self.len == 0 {
+                    None
+                } else {
+                    node.next.as_mut()
+                };
                 &mut node.elem
             })
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
 }
 
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
+impl<'a, T> Iter<'a, T> {
+    // Splits the elements left into the next `mid` and the rest
+    pub fn split_at(self, mid: usize) -> (Self, Self) {
+        assert!(
+            mid <= self.len,
+            "split index {mid} out of range for iterator of length {}",
+            self.len
+        );
+        let mut rest = self.next;
+        for _ in 0..mid {
+            rest = rest.and_then(|node| unsafe { node.next.as_ref() });
+        }
+        let left = Iter {
+            next: if mid == 0 { None } else { self.next },
+            len: mid,
+        };
+        let right = Iter {
+            next: if mid == self.len { None } else { rest },
+            len: self.len - mid,
+        };
+        (left, right)
+    }
+}
+
+impl<'a, T> IterMut<'a, T> {
+    // Splits the elements left into the next `mid` and the rest, as two
+    // iterators that can be used at the same time. The halves don't overlap,
+    // so handing out both is fine, like slice::split_at_mut.
+    pub fn split_at(self, mid: usize) -> (Self, Self) {
+        assert!(
+            mid <= self.len,
+            "split index {mid} out of range for iterator of length {}",
+            self.len
+        );
+        if mid == 0 {
+            return (IterMut { next: None, len: 0 }, self);
+        }
+        // There's a node for every element left, and we're taking at least one
+        let first = self.next.unwrap();
+        // We walk through raw pointers, so that the only references we make
+        // are the ones we hand out: the left half's first node, which it
+        // already had, and the right half's
+        let mut last: *const Node<T> = &*first;
+        for _ in 1..mid {
+            last = unsafe { (*last).next };
+        }
+        let rest = if mid == self.len {
+            None
+        } else {
+            unsafe { (*last).next.as_mut() }
+        };
+        let left = IterMut {
+            next: Some(first),
+            len: mid,
+        };
+        let right = IterMut {
+            next: rest,
+            len: self.len - mid,
+        };
+        (left, right)
+    }
+}
+
+// Same as for the queue: the iterators only hand out what they borrow, so
+// they can cross threads whenever a plain reference could
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+#[cfg(feature = "std")]
 mod checks {
+    #[allow(dead_code)]
     fn test_arrays() {
         unsafe {
             let mut data = [0; 10];
@@ -190,9 +1058,87 @@ mod checks {
     }
 }
 
+// Queues are serialized as a sequence from front to back, so pushing the
+// elements back in order on deserialization rebuilds the same queue.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::de::SeqAccess;
+    use serde::de::Visitor;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    use super::List;
+    use crate::allocator::Allocator;
+
+    impl<T: Serialize, A: Allocator> Serialize for List<T, A> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.iter())
+        }
+    }
+
+    impl<'de, T: Deserialize<'de>, A: Allocator + Default> Deserialize<'de> for List<T, A> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_seq(ListVisitor(PhantomData))
+        }
+    }
+
+    struct ListVisitor<T, A>(PhantomData<(T, A)>);
+
+    impl<'de, T: Deserialize<'de>, A: Allocator + Default> Visitor<'de> for ListVisitor<T, A> {
+        type Value = List<T, A>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a sequence")
+        }
+
+        fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+            let mut list = List::new_in(A::default());
+            while let Some(elem) = seq.next_element()? {
+                list.push(elem);
+            }
+            Ok(list)
+        }
+    }
+}
+
+// From front to back. Only for the global allocator, as we can't make up
+// some other one.
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl {
+    use arbitrary::Arbitrary;
+    use arbitrary::Result;
+    use arbitrary::Unstructured;
+
+    use super::List;
+
+    impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for List<T> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut list = List::new();
+            for elem in u.arbitrary_iter()? {
+                list.push(elem?);
+            }
+            Ok(list)
+        }
+
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+            let mut list = List::new();
+            for elem in u.arbitrary_take_rest_iter()? {
+                list.push(elem?);
+            }
+            Ok(list)
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::List;
+    use core::ptr::NonNull;
 
     #[test]
     fn basics() {
@@ -225,42 +1171,663 @@ mod test {
         assert_eq!(list.pop(), Some(7));
         assert_eq!(list.pop(), None);
     }
-}
 
-#[test]
-fn miri_food() {
-    let mut list = List::new();
+    #[test]
+    fn len() {
+        let mut list = List::new();
+        assert!(list.is_empty());
+
+        list.push(1);
+        list.push_front(0);
+        list.extend_from_slice(&[2, 3]);
+        assert_eq!(list.len(), 4);
+
+        list.pop();
+        assert_eq!(list.len(), 3);
+        assert!(!list.is_empty());
+    }
+
+    #[test]
+    fn exact_size_iterators() {
+        let mut list = List::new();
+        list.extend_from_slice(&[1, 2, 3]);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 3);
+        iter.next();
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+        iter.next();
+        iter.next();
+        assert_eq!(iter.len(), 0);
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+
+        let mut iter = list.iter_mut();
+        assert_eq!(iter.len(), 3);
+        iter.next();
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.len(), 3);
+        iter.next();
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+        assert_eq!(iter.collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    #[test]
+    fn rotate() {
+        let mut list = List::new();
+        list.rotate();
+        assert_eq!(list.pop(), None);
+
+        list.push(1);
+        list.rotate();
+        assert_eq!(list.peek(), Some(&1));
+
+        list.push(2);
+        list.push(3);
+        let addr = list.peek().unwrap() as *const i32;
+        list.rotate();
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&2, &3, &1]);
+        // The node was relinked, not reallocated
+        assert_eq!(list.iter().last().unwrap() as *const i32, addr);
+
+        // The tail has to be right for pushes to land after the rotated node
+        list.push(4);
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![2, 3, 1, 4]);
+    }
+
+    #[test]
+    fn take_batch() {
+        let mut list = List::new();
+        list.extend_from_slice(&[1, 2, 3, 4, 5]);
+
+        let batch = list.take_batch(0);
+        assert!(batch.is_empty());
+        assert_eq!(list.len(), 5);
+
+        let mut batch = list.take_batch(2);
+        assert_eq!(batch.len(), 2);
+        assert_eq!(list.len(), 3);
+        // Both halves must have a working tail
+        batch.push(10);
+        list.push(20);
+        assert_eq!(batch.into_iter().collect::<Vec<_>>(), vec![1, 2, 10]);
+
+        let mut batch = list.take_batch(10);
+        assert_eq!(batch.len(), 4);
+        assert!(list.is_empty());
+        list.push(30);
+        batch.push(40);
+        assert_eq!(batch.into_iter().collect::<Vec<_>>(), vec![3, 4, 5, 20, 40]);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![30]);
+
+        // The batch is made of the very nodes that were queued
+        let mut list = List::new();
+        list.push(1);
+        list.push(2);
+        list.push(3);
+        let head = list.raw_head();
+        let second = head.and_then(|head| NonNull::new(unsafe { (*head.as_ptr()).next }));
+        let batch = list.take_batch(2);
+        assert_eq!(batch.raw_head(), head);
+        assert_eq!(batch.raw_tail(), second);
+        assert_eq!(list.len(), 1);
+    }
+
+    // The halves share the block `extend_from_slice` allocated, so popping
+    // them on two threads frees its nodes concurrently
+    #[cfg(feature = "std")]
+    #[test]
+    fn take_batch_across_threads() {
+        for _ in 0..100 {
+            let mut list = List::new();
+            list.extend_from_slice(&[0usize; 8]);
+            let mut batch = list.take_batch(4);
+            std::thread::scope(|scope| {
+                scope.spawn(|| while batch.pop().is_some() {});
+                scope.spawn(|| while list.pop().is_some() {});
+            });
+        }
+    }
+
+    #[test]
+    fn try_push() {
+        let mut list = List::new();
+        assert_eq!(list.try_push(1), Ok(()));
+        list.push(2);
+        assert_eq!(list.try_push(3), Ok(()));
+        assert_eq!(list.len(), 3);
+
+        // Nodes from `try_push` are freed just like the others
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pop(), Some(2));
+        assert_eq!(list.try_push(4), Ok(()));
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![3, 4]);
+    }
+
+    #[cfg(feature = "allocator_api")]
+    #[test]
+    fn custom_allocator() {
+        use std::alloc::Allocator;
+        use std::alloc::Global;
+        use std::alloc::Layout;
+        use std::cell::Cell;
+        use std::ptr::NonNull;
+
+        // Keeps track of live allocations to check every node is given back
+        #[derive(Default)]
+        struct Counting {
+            live: Cell<usize>,
+        }
+
+        unsafe impl Allocator for Counting {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, std::alloc::AllocError> {
+                self.live.set(self.live.get() + 1);
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.live.set(self.live.get() - 1);
+                Global.deallocate(ptr, layout)
+            }
+        }
+
+        let counting = Counting::default();
+        {
+            let mut list = List::new_in(&counting);
+            list.push(1);
+            list.push_front(0);
+            list.try_push(2).unwrap();
+            assert_eq!(counting.live.get(), 3);
+
+            // Both nodes of the slice come from the same block
+            list.extend_from_slice(&[3, 4]);
+            assert_eq!(counting.live.get(), 4);
+
+            // Popping frees the node right away
+            assert_eq!(list.pop(), Some(0));
+            assert_eq!(counting.live.get(), 3);
+
+            let batch = list.take_batch(2);
+            assert_eq!(batch.into_iter().collect::<Vec<_>>(), vec![1, 2]);
+            assert_eq!(counting.live.get(), 1);
+
+            // The block goes with the last of its nodes
+            assert_eq!(list.pop(), Some(3));
+            assert_eq!(counting.live.get(), 1);
+            list.reserve(2);
+            assert_eq!(counting.live.get(), 2);
+            assert_eq!(list.pop(), Some(4));
+            assert_eq!(counting.live.get(), 1);
+        }
+        assert_eq!(counting.live.get(), 0);
+    }
+
+    #[cfg(feature = "allocator_api")]
+    #[test]
+    fn extend_from_slice_allocations() {
+        use std::alloc::Allocator;
+        use std::alloc::Global;
+        use std::alloc::Layout;
+        use std::cell::Cell;
+        use std::ptr::NonNull;
+
+        #[derive(Default)]
+        struct Counting {
+            allocations: Cell<usize>,
+        }
+
+        unsafe impl Allocator for Counting {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, std::alloc::AllocError> {
+                self.allocations.set(self.allocations.get() + 1);
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                Global.deallocate(ptr, layout)
+            }
+        }
+
+        // One allocation for the whole slice on a fresh queue, where pushing
+        // the elements one by one takes one each
+        let counting = Counting::default();
+        let mut list = List::new_in(&counting);
+        list.extend_from_slice(&[0; 100]);
+        assert_eq!(counting.allocations.get(), 1);
+
+        let counting = Counting::default();
+        let mut other = List::new_in(&counting);
+        for elem in list.iter() {
+            other.push(*elem);
+        }
+        assert_eq!(counting.allocations.get(), 100);
+
+        // Splitting a block between two lists, and mixing its nodes with
+        // boxed ones, still frees each of them once
+        list.push(1);
+        let mut batch = list.take_batch(50);
+        batch.extend_from_slice(&[2, 3]);
+        batch.assert_invariants();
+        drop(batch);
+        while list.pop().is_some() {}
+        list.assert_invariants();
+    }
+
+    #[test]
+    fn raw_parts() {
+        use super::Node;
+        use std::ptr;
+        use std::ptr::NonNull;
+
+        let empty = List::<i32>::new();
+        assert_eq!(empty.raw_head(), None);
+        assert_eq!(empty.raw_tail(), None);
+
+        // Round trip through the raw parts
+        let mut list = List::new();
+        list.extend_from_slice(&[1, 2, 3]);
+        let (head, tail, len) = list.into_raw_parts();
+        let mut list = unsafe { List::from_raw_parts(head, tail, len) };
+        list.push(4);
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.pop(), Some(1));
+
+        // Splice a chain we built ourselves after the tail
+        let chain_tail = Box::into_raw(Box::new(Node::new(6, ptr::null_mut())));
+        let chain_head = Box::into_raw(Box::new(Node::new(5, chain_tail)));
+        let (head, tail, len) = list.into_raw_parts();
+        unsafe { (*tail.unwrap().as_ptr()).next = chain_head };
+        let list = unsafe { List::from_raw_parts(head, NonNull::new(chain_tail), len + 2) };
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn extract_if() {
+        let mut list = List::new();
+        list.extend_from_slice(&[1, 2, 3, 4, 5, 6]);
+
+        let evens: Vec<_> = list.extract_if(|x| *x % 2 == 0).collect();
+        assert_eq!(evens, vec![2, 4, 6]);
+        assert_eq!(list.len(), 3);
+
+        // Extracting the last element must fix up the tail
+        list.push(7);
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![&1, &3, &5, &7]);
+
+        // Stopping early leaves the rest untouched
+        assert_eq!(list.extract_if(|_| true).next(), Some(1));
+        assert_eq!(list.len(), 3);
+
+        // Extracting everything leaves a usable empty list
+        assert_eq!(list.extract_if(|_| true).count(), 3);
+        assert!(list.is_empty());
+        list.push(8);
+        assert_eq!(list.pop(), Some(8));
+        assert_eq!(list.pop(), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde::de::value::Error;
+        use serde::de::value::SeqDeserializer;
+        use serde::Deserialize;
+        use serde_test::assert_ser_tokens;
+        use serde_test::Token;
+
+        let mut list = List::new();
+        list.extend_from_slice(&[1, 2, 3]);
+        assert_ser_tokens(
+            &list,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::I32(1),
+                Token::I32(2),
+                Token::I32(3),
+                Token::SeqEnd,
+            ],
+        );
+
+        let seq = SeqDeserializer::<_, Error>::new(vec![1, 2, 3].into_iter());
+        let mut list = List::<i32>::deserialize(seq).unwrap();
+        assert_eq!(list.len(), 3);
+        list.push(4);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn iter_split_at() {
+        let mut list = List::new();
+        list.extend_from_slice(&[1, 2, 3, 4, 5]);
+
+        let (front, back) = list.iter().split_at(2);
+        assert_eq!(front.len(), 2);
+        assert_eq!(front.copied().collect::<Vec<_>>(), &[1, 2]);
+        assert_eq!(back.copied().collect::<Vec<_>>(), &[3, 4, 5]);
+
+        // Both halves at once, each only reaching its own nodes
+        let (front, back) = list.iter_mut().split_at(3);
+        let (mid, back) = back.split_at(1);
+        for (a, b) in front.zip(back) {
+            std::mem::swap(a, b);
+        }
+        mid.for_each(|x| *x *= 10);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), &[5, 2, 3, 40, 1]);
+
+        let (front, back) = list.iter_mut().split_at(0);
+        assert_eq!((front.count(), back.count()), (0, 5));
+        let (front, back) = list.iter().split_at(5);
+        assert_eq!((front.count(), back.count()), (5, 0));
+    }
+
+    #[test]
+    fn assert_invariants() {
+        let mut list = List::new();
+        list.assert_invariants();
+
+        list.extend_from_slice(&[1, 2, 3]);
+        list.rotate();
+        list.push_front(0);
+        list.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "len doesn't match")]
+    fn assert_invariants_len() {
+        let mut list = List::new();
+        list.extend_from_slice(&[1, 2, 3]);
+        list.len = 2;
+        // Leak the broken list, dropping it would panic again with strict checks
+        let list = std::mem::ManuallyDrop::new(list);
+        list.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "tail isn't the last node")]
+    fn assert_invariants_tail() {
+        let mut list = List::new();
+        list.extend_from_slice(&[1, 2, 3]);
+        list.tail = list.head;
+        let list = std::mem::ManuallyDrop::new(list);
+        list.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "cycle")]
+    fn assert_invariants_cycle() {
+        let mut list = List::new();
+        list.extend_from_slice(&[1, 2, 3]);
+        unsafe { (*list.tail).next = list.head };
+        let list = std::mem::ManuallyDrop::new(list);
+        list.assert_invariants();
+    }
+
+    // With strict checks, the next operation catches a broken queue
+    #[cfg(feature = "strict-checks")]
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "len doesn't match")]
+    fn strict_checks() {
+        let mut list = List::new();
+        list.extend_from_slice(&[1, 2, 3]);
+        list.len = 2;
+        let mut list = std::mem::ManuallyDrop::new(list);
+        list.push(4);
+    }
+
+    #[test]
+    fn vec_deque() {
+        use std::collections::VecDeque;
+
+        let deque: VecDeque<_> = (1..=3).collect();
+        let mut list = List::from(deque);
+        assert_eq!(list.len(), 3);
+        list.push(4);
+        assert_eq!(list.pop(), Some(1));
+
+        let deque: VecDeque<_> = list.into();
+        assert_eq!(deque, [2, 3, 4]);
+
+        let list = List::from(VecDeque::<i32>::new());
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn insert_sorted() {
+        let mut list = List::new();
+        for x in [5, 1, 4, 2, 3, 5, 0] {
+            list.insert_sorted(x, |a, b| a.cmp(b));
+        }
+        assert_eq!(list.len(), 7);
+
+        // Inserting at the end moved the tail along
+        list.push(10);
+        assert_eq!(
+            list.iter().copied().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4, 5, 5, 10]
+        );
 
-    list.push(1);
-    list.push(2);
-    list.push(3);
+        // Equal elements stay in insertion order
+        let mut list = List::new();
+        list.insert_sorted((1, 'a'), |a, b| a.0.cmp(&b.0));
+        list.insert_sorted((0, 'b'), |a, b| a.0.cmp(&b.0));
+        list.insert_sorted((1, 'c'), |a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            list.into_iter().collect::<Vec<_>>(),
+            vec![(0, 'b'), (1, 'a'), (1, 'c')]
+        );
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn stats() {
+        use super::Stats;
 
-    assert!(list.pop() == Some(1));
-    list.push(4);
-    assert!(list.pop() == Some(2));
-    list.push(5);
+        let mut list = List::new();
+        assert_eq!(list.stats(), Stats::default());
 
-    assert!(list.peek() == Some(&3));
-    list.push(6);
-    list.peek_mut().map(|x| *x *= 10);
-    assert!(list.peek() == Some(&30));
-    assert!(list.pop() == Some(30));
+        list.push(1);
+        list.push_front(0);
+        list.extend_from_slice(&[2, 3, 4]);
+        assert_eq!(list.pop(), Some(0));
+        list.insert_sorted(5, |a, b| a.cmp(b));
+        assert_eq!(list.extract_if(|x| *x == 3).count(), 1);
+        assert_eq!(
+            list.stats(),
+            Stats {
+                pushes: 6,
+                pops: 2,
+                high_water_mark: 5,
+            }
+        );
 
-    for elem in list.iter_mut() {
-        *elem *= 100;
+        let batch = list.take_batch(2);
+        assert_eq!(list.stats().pops, 4);
+        assert_eq!(
+            batch.stats(),
+            Stats {
+                pushes: 2,
+                pops: 0,
+                high_water_mark: 2,
+            }
+        );
     }
 
-    let mut iter = list.iter();
-    assert_eq!(iter.next(), Some(&400));
-    assert_eq!(iter.next(), Some(&500));
-    assert_eq!(iter.next(), Some(&600));
-    assert_eq!(iter.next(), None);
-    assert_eq!(iter.next(), None);
+    #[test]
+    fn extend_from_slice() {
+        let mut list = List::new();
+        list.extend_from_slice(&[]);
+        assert_eq!(list.pop(), None);
+
+        list.extend_from_slice(&[1, 2, 3]);
+        list.push(4);
+        list.extend_from_slice(&[5, 6]);
+
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.pop(), Some(2));
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), Some(4));
+        assert_eq!(list.pop(), Some(5));
+
+        // The tail must point at the last spliced node
+        list.push(7);
+        assert_eq!(list.pop(), Some(6));
+        assert_eq!(list.pop(), Some(7));
+        assert_eq!(list.pop(), None);
+    }
 
-    assert!(list.pop() == Some(400));
-    list.peek_mut().map(|x| *x *= 10);
-    assert!(list.peek() == Some(&5000));
-    list.push(7);
+    #[test]
+    fn extend_from_slice_reuses_nodes() {
+        let mut list = List::new();
+        list.extend_from_slice(&[1, 2, 3, 4]);
+        assert_eq!(list.capacity(), 4);
 
-    // Drop it on the ground and let the dtor exercise itself
+        // Popping frees nodes instead of pooling them, so draining and
+        // refilling the queue doesn't keep more than it holds
+        for round in 0..3 {
+            while list.pop().is_some() {}
+            assert_eq!(list.capacity(), 0);
+            list.extend_from_slice(&[round, round + 1, round + 2]);
+            assert_eq!(list.capacity(), 3);
+            list.assert_invariants();
+        }
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), &[2, 3, 4]);
+
+        // Only what doesn't fit in the pool gets allocated
+        list.reserve(1);
+        list.extend_from_slice(&[5, 6]);
+        assert_eq!(list.capacity(), 5);
+        assert_eq!(list.len(), 5);
+    }
+
+    #[test]
+    fn capacity() {
+        let mut list = List::new();
+        list.reserve(3);
+        assert_eq!(list.capacity(), 3);
+        assert!(list.is_empty());
+
+        // Every kind of push takes nodes from the pool, popping frees them
+        list.push(2);
+        list.push_front(1);
+        list.insert_sorted(3, Ord::cmp);
+        assert_eq!(list.capacity(), 3);
+        assert_eq!(list.try_push(4), Ok(()));
+        assert_eq!(list.capacity(), 4);
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.extract_if(|elem| *elem == 3).collect::<Vec<_>>(), &[3]);
+        assert_eq!(list.capacity(), 2);
+        list.assert_invariants();
+
+        // reserve counts the pooled nodes only, like sixth's
+        list.reserve(2);
+        assert_eq!(list.capacity(), 4);
+        list.reserve(1);
+        assert_eq!(list.capacity(), 4);
+        list.reserve(3);
+        assert_eq!(list.capacity(), 5);
+        list.shrink_to_fit();
+        assert_eq!(list.capacity(), 2);
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), &[2, 4]);
+
+        // Elements we put in pooled nodes are dropped as usual
+        let mut list = List::new();
+        list.reserve(1);
+        list.push(String::from("a"));
+        assert_eq!(list.pop().as_deref(), Some("a"));
+        list.push(String::from("b"));
+        assert_eq!(list.peek().map(String::as_str), Some("b"));
+    }
+
+    #[test]
+    fn push_front() {
+        let mut list = List::new();
+
+        // On an empty list, pushing at the front also sets the tail
+        list.push_front(1);
+        list.push(2);
+        assert_eq!(list.pop(), Some(1));
+
+        list.push(3);
+        let job = list.pop().unwrap();
+        list.push_front(job);
+        assert_eq!(list.pop(), Some(2));
+        assert_eq!(list.pop(), Some(3));
+        assert_eq!(list.pop(), None);
+
+        list.push_front(4);
+        list.push_front(5);
+        list.push(6);
+        assert_eq!(list.pop(), Some(5));
+        assert_eq!(list.pop(), Some(4));
+        assert_eq!(list.pop(), Some(6));
+        assert_eq!(list.pop(), None);
+    }
+
+    #[test]
+    fn peek_mut_guard() {
+        let mut list = List::new();
+        assert!(list.peek_mut_guard().is_none());
+
+        list.push(1);
+        list.push(2);
+
+        // Change the head without taking it
+        if let Some(mut head) = list.peek_mut_guard() {
+            *head *= 10;
+        }
+        assert_eq!(list.peek(), Some(&10));
+
+        // Decide to take it based on its value
+        let head = list.peek_mut_guard().unwrap();
+        assert_eq!(*head, 10);
+        assert_eq!(head.pop(), 10);
+
+        assert_eq!(list.pop(), Some(2));
+        assert!(list.peek_mut_guard().is_none());
+    }
+
+    #[test]
+    fn miri_food() {
+        let mut list = List::new();
+
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        assert!(list.pop() == Some(1));
+        list.push(4);
+        assert!(list.pop() == Some(2));
+        list.push(5);
+
+        assert!(list.peek() == Some(&3));
+        list.push(6);
+        if let Some(x) = list.peek_mut() {
+            *x *= 10;
+        }
+        assert!(list.peek() == Some(&30));
+        assert!(list.pop() == Some(30));
+
+        for elem in list.iter_mut() {
+            *elem *= 100;
+        }
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&400));
+        assert_eq!(iter.next(), Some(&500));
+        assert_eq!(iter.next(), Some(&600));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+
+        assert!(list.pop() == Some(400));
+        if let Some(x) = list.peek_mut() {
+            *x *= 10;
+        }
+        assert!(list.peek() == Some(&5000));
+        list.push(7);
+
+        // Drop it on the ground and let the dtor exercise itself
+    }
 }
@@ -0,0 +1,258 @@
+// A single-producer single-consumer channel on top of our unsafe queue. The
+// queue itself knows nothing about threads, so all accesses to it go through
+// a mutex, and a condition variable lets the receiver sleep until the sender
+// pushes something (or goes away).
+//
+// Neither half implements Clone: there's exactly one Sender and exactly one
+// Receiver, which is what makes the disconnection logic below so simple.
+
+use std::error::Error;
+use std::fmt;
+
+use super::List;
+use crate::sync::Arc;
+use crate::sync::Condvar;
+use crate::sync::Mutex;
+
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    available: Condvar,
+}
+
+struct State<T> {
+    queue: List<T>,
+    // Once one of these is false it never becomes true again
+    sender_alive: bool,
+    receiver_alive: bool,
+}
+
+// Returned by `send` when the receiver is gone, handing the value back
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SendError<T>(pub T);
+
+// Returned by `recv` when the queue is empty and the sender is gone
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RecvError;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TryRecvError {
+    Empty,
+    Disconnected,
+}
+
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            queue: List::new(),
+            sender_alive: true,
+            receiver_alive: true,
+        }),
+        available: Condvar::new(),
+    });
+
+    (
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared },
+    )
+}
+
+impl<T> Sender<T> {
+    pub fn send(&self, elem: T) -> Result<(), SendError<T>> {
+        let mut state = self.shared.state.lock().unwrap();
+        if !state.receiver_alive {
+            return Err(SendError(elem));
+        }
+
+        state.queue.push(elem);
+        drop(state);
+
+        // There's at most one receiver, so there's at most one thread to wake
+        self.shared.available.notify_one();
+        Ok(())
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.sender_alive = false;
+        drop(state);
+
+        // A receiver blocked in `recv` must wake up to see the disconnection
+        self.shared.available.notify_one();
+    }
+}
+
+impl<T> Receiver<T> {
+    // Blocks until there's an element to return. Elements sent before the
+    // sender was dropped are still delivered, only then do we report an error.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let mut state = self.shared.state.lock().unwrap();
+        loop {
+            if let Some(elem) = state.queue.pop() {
+                return Ok(elem);
+            }
+
+            if !state.sender_alive {
+                return Err(RecvError);
+            }
+
+            // Spurious wakeups are fine, we just check again
+            state = self.shared.available.wait(state).unwrap();
+        }
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut state = self.shared.state.lock().unwrap();
+        match state.queue.pop() {
+            Some(elem) => Ok(elem),
+            None if state.sender_alive => Err(TryRecvError::Empty),
+            None => Err(TryRecvError::Disconnected),
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { receiver: self }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().receiver_alive = false;
+    }
+}
+
+// Blocking iterator over received elements, ending when the sender is gone
+pub struct Iter<'a, T> {
+    receiver: &'a Receiver<T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sending on a channel whose receiver is gone")
+    }
+}
+
+impl<T: fmt::Debug> Error for SendError<T> {}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "receiving on an empty channel whose sender is gone")
+    }
+}
+
+impl Error for RecvError {}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "receiving on an empty channel"),
+            TryRecvError::Disconnected => {
+                write!(f, "receiving on an empty channel whose sender is gone")
+            }
+        }
+    }
+}
+
+impl Error for TryRecvError {}
+
+#[cfg(test)]
+mod test {
+    use super::channel;
+    use super::RecvError;
+    use super::SendError;
+    use super::TryRecvError;
+    use crate::sync::model;
+    use crate::sync::thread;
+
+    #[test]
+    fn basics() {
+        model(|| {
+            let (tx, rx) = channel();
+            assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+            tx.send(1).unwrap();
+            tx.send(2).unwrap();
+            assert_eq!(rx.try_recv(), Ok(1));
+            assert_eq!(rx.recv(), Ok(2));
+            assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+            // Pending elements outlive the sender
+            tx.send(3).unwrap();
+            drop(tx);
+            assert_eq!(rx.recv(), Ok(3));
+            assert_eq!(rx.recv(), Err(RecvError));
+            assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+        });
+    }
+
+    #[test]
+    fn send_after_receiver_is_gone() {
+        model(|| {
+            let (tx, rx) = channel();
+            drop(rx);
+            assert_eq!(tx.send(1), Err(SendError(1)));
+        });
+    }
+
+    // Every order the sends, receives and drops can happen in, which is
+    // only exhaustive under loom
+    #[test]
+    fn interleavings() {
+        model(|| {
+            let (tx, rx) = channel();
+            let producer = thread::spawn(move || {
+                tx.send(1).unwrap();
+                tx.send(2).unwrap();
+            });
+            assert_eq!(rx.iter().collect::<Vec<_>>(), [1, 2]);
+            producer.join().unwrap();
+        });
+    }
+
+    // Far too many interleavings for loom
+    #[cfg(not(loom))]
+    #[test]
+    fn threads() {
+        let (tx, rx) = channel();
+
+        let producer = thread::spawn(move || {
+            for i in 0..1000 {
+                tx.send(i).unwrap();
+            }
+        });
+
+        let received: Vec<i32> = rx.iter().collect();
+        producer.join().unwrap();
+        assert_eq!(received, (0..1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn blocking_recv_wakes_up_on_disconnect() {
+        model(|| {
+            let (tx, rx) = channel::<Box<i32>>();
+
+            let consumer = thread::spawn(move || rx.recv());
+            drop(tx);
+            assert_eq!(consumer.join().unwrap(), Err(RecvError));
+        });
+    }
+}
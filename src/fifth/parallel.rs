@@ -0,0 +1,231 @@
+// Rayon support for the queue, much like for sixth's deque, except that
+// rayon's indexed iterators have to run from both ends, which a singly linked
+// list can't do cheaply. So ours are unindexed: they split in halves rather
+// than at whatever index rayon asks for. Owned queues get cut with
+// `take_batch`, and borrowing iterators find the node at the cut and start a
+// new iterator there. Either way, only the walk to the cut is O(n).
+
+use rayon::iter::plumbing::bridge_unindexed;
+use rayon::iter::plumbing::Folder;
+use rayon::iter::plumbing::UnindexedConsumer;
+use rayon::iter::plumbing::UnindexedProducer;
+use rayon::iter::FromParallelIterator;
+use rayon::iter::IntoParallelIterator;
+use rayon::iter::ParallelIterator;
+
+use super::Iter;
+use super::IterMut;
+use super::List;
+use crate::allocator::Allocator;
+use crate::allocator::Global;
+
+pub struct IntoParIter<T, A: Allocator = Global> {
+    list: List<T, A>,
+}
+
+pub struct ParIter<'a, T> {
+    iter: Iter<'a, T>,
+}
+
+pub struct ParIterMut<'a, T> {
+    iter: IterMut<'a, T>,
+}
+
+impl<T: Send, A: Allocator + Clone + Send> IntoParallelIterator for List<T, A> {
+    type Iter = IntoParIter<T, A>;
+    type Item = T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        IntoParIter { list: self }
+    }
+}
+
+impl<'a, T: Sync, A: Allocator> IntoParallelIterator for &'a List<T, A> {
+    type Iter = ParIter<'a, T>;
+    type Item = &'a T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        ParIter { iter: self.iter() }
+    }
+}
+
+impl<'a, T: Send, A: Allocator> IntoParallelIterator for &'a mut List<T, A> {
+    type Iter = ParIterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        ParIterMut {
+            iter: self.iter_mut(),
+        }
+    }
+}
+
+// Every thread builds a queue of its own, and we link them up in order,
+// which is O(1) per link
+impl<T: Send> FromParallelIterator<T> for List<T> {
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = T>,
+    {
+        par_iter
+            .into_par_iter()
+            .fold(List::new, |mut list, elem| {
+                list.push(elem);
+                list
+            })
+            .reduce(List::new, |mut front, back| {
+                front.append(back);
+                front
+            })
+    }
+}
+
+impl<T: Send, A: Allocator + Clone + Send> ParallelIterator for IntoParIter<T, A> {
+    type Item = T;
+
+    fn drive_unindexed<C: UnindexedConsumer<T>>(self, consumer: C) -> C::Result {
+        bridge_unindexed(self, consumer)
+    }
+}
+
+impl<T: Send, A: Allocator + Clone + Send> UnindexedProducer for IntoParIter<T, A> {
+    type Item = T;
+
+    fn split(mut self) -> (Self, Option<Self>) {
+        if self.list.len < 2 {
+            return (self, None);
+        }
+        let front = self.list.take_batch(self.list.len / 2);
+        (IntoParIter { list: front }, Some(self))
+    }
+
+    fn fold_with<F: Folder<T>>(self, folder: F) -> F {
+        folder.consume_iter(self.list)
+    }
+}
+
+impl<'a, T: Sync> ParallelIterator for ParIter<'a, T> {
+    type Item = &'a T;
+
+    fn drive_unindexed<C: UnindexedConsumer<&'a T>>(self, consumer: C) -> C::Result {
+        bridge_unindexed(self, consumer)
+    }
+}
+
+impl<'a, T: Sync> UnindexedProducer for ParIter<'a, T> {
+    type Item = &'a T;
+
+    fn split(self) -> (Self, Option<Self>) {
+        if self.iter.len < 2 {
+            return (self, None);
+        }
+        let len = self.iter.len;
+        let (left, right) = self.iter.split_at(len / 2);
+        (ParIter { iter: left }, Some(ParIter { iter: right }))
+    }
+
+    fn fold_with<F: Folder<&'a T>>(self, folder: F) -> F {
+        folder.consume_iter(self.iter)
+    }
+}
+
+impl<'a, T: Send> ParallelIterator for ParIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn drive_unindexed<C: UnindexedConsumer<&'a mut T>>(self, consumer: C) -> C::Result {
+        bridge_unindexed(self, consumer)
+    }
+}
+
+impl<'a, T: Send> UnindexedProducer for ParIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn split(self) -> (Self, Option<Self>) {
+        if self.iter.len < 2 {
+            return (self, None);
+        }
+        let len = self.iter.len;
+        let (left, right) = self.iter.split_at(len / 2);
+        (ParIterMut { iter: left }, Some(ParIterMut { iter: right }))
+    }
+
+    fn fold_with<F: Folder<&'a mut T>>(self, folder: F) -> F {
+        folder.consume_iter(self.iter)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rayon::iter::IntoParallelIterator;
+    use rayon::iter::IntoParallelRefIterator;
+    use rayon::iter::IntoParallelRefMutIterator;
+    use rayon::iter::ParallelIterator;
+
+    use super::List;
+
+    fn list(n: u64) -> List<u64> {
+        let mut list = List::new();
+        for i in 0..n {
+            list.push(i);
+        }
+        list
+    }
+
+    #[test]
+    fn into_par_iter() {
+        let doubled: Vec<u64> = list(10_000).into_par_iter().map(|x| x * 2).collect();
+        assert_eq!(doubled, (0..10_000).map(|x| x * 2).collect::<Vec<_>>());
+
+        for len in 0..=8 {
+            let collected: Vec<u64> = list(len).into_par_iter().collect();
+            assert_eq!(collected, (0..len).collect::<Vec<_>>());
+        }
+    }
+
+    // Every half rayon splits off shares the block the elements came from,
+    // and gets consumed on whichever thread picks it up
+    #[test]
+    fn into_par_iter_from_a_block() {
+        let elems: Vec<u64> = (0..1_000).collect();
+        for _ in 0..10 {
+            let mut list = List::new();
+            list.extend_from_slice(&elems);
+            let collected: Vec<u64> = list.into_par_iter().collect();
+            assert_eq!(collected, elems);
+        }
+    }
+
+    #[test]
+    fn par_iter() {
+        let list = list(10_000);
+        assert_eq!(list.par_iter().sum::<u64>(), (0..10_000).sum());
+        assert_eq!(list.par_iter().find_first(|x| **x > 5_000), Some(&5_001));
+        let collected: Vec<&u64> = list.par_iter().collect();
+        assert_eq!(collected, list.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn par_iter_mut() {
+        let mut big = list(10_000);
+        big.par_iter_mut().for_each(|x| *x += 1);
+        assert!(big.iter().cloned().eq(1..10_001));
+
+        // Splits all the way down to single elements
+        for len in 0..=8 {
+            let mut list = list(len);
+            list.par_iter_mut().for_each(|x| *x *= 10);
+            assert!(list.iter().cloned().eq((0..len).map(|x| x * 10)));
+        }
+    }
+
+    #[test]
+    fn from_par_iter() {
+        let list: List<u64> = (0..10_000u64)
+            .into_par_iter()
+            .filter(|x| x % 3 == 0)
+            .collect();
+        assert_eq!(list.len(), 3_334);
+        list.assert_invariants();
+        assert!(list.iter().cloned().eq((0..10_000).filter(|x| x % 3 == 0)));
+    }
+}
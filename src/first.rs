@@ -24,6 +24,11 @@
 // The non-uniform memory layout above (part in the heap, part in the stack) is
 // problematic because it imposes more work on us when we manipulate it. For
 // example, when splitting a list we have to do double work.
+use alloc::boxed::Box;
+use core::mem::size_of;
+
+use crate::traits::MemSize;
+
 pub enum BadList1 {
     Nil,
     Cons(i32, Box<BadList1>),
@@ -64,6 +69,8 @@ pub enum List1 {
 // publically-accessible enum and it'd otherwise refer to a private type `Node1`
 // in its public structure (a permission violation).
 // -- (Note that we marked it as public below so it compiles)
+// Never read, as List1 only exists to show the layout
+#[allow(dead_code)]
 pub struct Node1 {
     elem: i32,
     next: List1,
@@ -99,16 +106,16 @@ impl List {
     }
 
     pub fn push(&mut self, elem: i32) {
-        let next = std::mem::replace(&mut self.head, Link::Empty);
+        let next = core::mem::replace(&mut self.head, Link::Empty);
         let new_node = Node { elem, next };
 
         // Huh, we don't need a replace anymore, this makes sense!
-        // std::mem::replace(&mut self.head, Link::More(Box::new(new_node)));
+        // core::mem::replace(&mut self.head, Link::More(Box::new(new_node)));
         self.head = Link::More(Box::new(new_node));
     }
 
     pub fn pop(&mut self) -> Option<i32> {
-        let curr = std::mem::replace(&mut self.head, Link::Empty);
+        let curr = core::mem::replace(&mut self.head, Link::Empty);
         match curr {
             Link::Empty => Option::None,
             Link::More(node) => {
@@ -119,16 +126,39 @@ impl List {
     }
 }
 
+impl Default for List {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Each element gets a Box of its own, holding the element and the next link
+impl MemSize for List {
+    fn node_count(&self) -> usize {
+        let mut count = 0;
+        let mut link = &self.head;
+        while let Link::More(node) = link {
+            count += 1;
+            link = &node.next;
+        }
+        count
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        size_of::<Node>()
+    }
+}
+
 impl Drop for List {
     fn drop(&mut self) {
-        let mut curr = std::mem::replace(&mut self.head, Link::Empty);
+        let mut curr = core::mem::replace(&mut self.head, Link::Empty);
         // We prefer the use of this pattern rather than reusing pop because pop
         // moves the values from the heap to the stack, and in cases where T can be
         // a large object with a drop implementation that would be inefficient. As
         // this implementation manipulates links (Box<Node>), moving the reference is
         // not copying the data around, and when it goes out of scope we drop the value
         while let Link::More(mut boxed_node) = curr {
-            curr = std::mem::replace(&mut boxed_node.next, Link::Empty);
+            curr = core::mem::replace(&mut boxed_node.next, Link::Empty);
             // After this point, boxed_node goes out of scope and it's freed
             // We replaced the next boxed_node with Link::Empty so no
             // unbounded recursion happens in drop
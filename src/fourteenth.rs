@@ -0,0 +1,1116 @@
+// sixth's deque keeps two links in every node. An XOR list keeps one: the
+// XOR of the two. That's no use on its own, but we never look at a node on
+// its own. We always get to it from one of its neighbours, so we already
+// know one half of the XOR, and XORing it back out leaves the other. Walking
+// forwards from the front gives the next node each time, and walking
+// backwards from the back gives the previous one. A node is just its element
+// and one word, a third less than sixth's for a word-sized element.
+//
+// The classic version XORs addresses, which is where it stops working in
+// Rust: an address rebuilt from an XOR is a plain integer, with no
+// provenance. Nothing says which allocation it points into, so it can't be
+// turned back into a pointer (strict provenance forbids it, and Miri
+// complains). So all our nodes live in one buffer, and we XOR their
+// positions in it instead. Every pointer comes from the buffer's own, and
+// keeps its provenance. Positions count from 1, which leaves 0 meaning "no
+// node": a node at either end XORs its only neighbour with 0, which leaves
+// the neighbour as it is.
+//
+// A buffer also has the usual perks. There's no allocation per node, the
+// buffer grows like a Vec, and moving it around doesn't break any link, as
+// positions don't change. Popped nodes go on a free list, chained through
+// their links (plain positions, not XORs, as they only go one way), and the
+// next push picks them up again. Like sixth's pool, the buffer only shrinks
+// when the list is dropped.
+//
+// A bonus: the links don't say which way is forward. Swapping the front and
+// the back reverses the whole list, in O(1).
+//
+// The cost is that a position alone isn't enough to go anywhere. Cursors
+// and iterators carry the node before them too, and nodes can't be handed
+// out and unlinked later the way sixth's NodeRefs can.
+#![cfg_attr(
+    feature = "strict-provenance",
+    deny(fuzzy_provenance_casts, lossy_provenance_casts)
+)]
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Debug;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::mem;
+use core::mem::size_of;
+use core::mem::MaybeUninit;
+use core::ops::BitXor;
+use core::ops::BitXorAssign;
+
+use crate::traits::MemSize;
+
+pub struct LinkedList<T> {
+    nodes: Vec<Node<T>>,
+    front: Pos,
+    back: Pos,
+    len: usize,
+    // The first of the free nodes. Their elements are uninitialized!
+    free: Pos,
+}
+
+// A position in the buffer, counting from 1, or NONE. The XOR of two of them
+// isn't a position, but we keep it in the same type, as it's only ever
+// XORed with another position to get one back.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Pos(usize);
+
+const NONE: Pos = Pos(0);
+
+struct Node<T> {
+    // The XOR of the positions before and after it, or the next free node
+    link: Pos,
+    elem: MaybeUninit<T>,
+}
+
+impl Pos {
+    fn index(self) -> usize {
+        self.0 - 1
+    }
+}
+
+impl BitXor for Pos {
+    type Output = Pos;
+
+    fn bitxor(self, other: Pos) -> Pos {
+        Pos(self.0 ^ other.0)
+    }
+}
+
+impl BitXorAssign for Pos {
+    fn bitxor_assign(&mut self, other: Pos) {
+        self.0 ^= other.0;
+    }
+}
+
+impl<T> LinkedList<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        LinkedList {
+            nodes: Vec::with_capacity(capacity),
+            front: NONE,
+            back: NONE,
+            len: 0,
+            free: NONE,
+        }
+    }
+
+    // How many elements fit before the buffer has to grow
+    pub fn capacity(&self) -> usize {
+        self.nodes.capacity()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // The nodes stay in the buffer, free for the next pushes
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(self.front)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.front)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.back)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.back)
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        self.link(NONE, self.front, elem);
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        self.link(self.back, NONE, elem);
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let front = self.front;
+        (front != NONE).then(|| self.unlink(NONE, front, self.node(front).link))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let back = self.back;
+        (back != NONE).then(|| self.unlink(self.node(back).link, back, NONE))
+    }
+
+    // Every link reads the same both ways, so this is all it takes
+    pub fn reverse(&mut self) {
+        mem::swap(&mut self.front, &mut self.back);
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            nodes: &self.nodes,
+            front: self.front,
+            before_front: NONE,
+            back: self.back,
+            after_back: NONE,
+            len: self.len,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            nodes: self.nodes.as_mut_ptr(),
+            front: self.front,
+            before_front: NONE,
+            back: self.back,
+            after_back: NONE,
+            len: self.len,
+            _protection: PhantomData,
+        }
+    }
+
+    pub fn cursor_front(&self) -> Cursor<'_, T> {
+        let mut cursor = Cursor {
+            list: self,
+            place: Place::GHOST,
+        };
+        cursor.move_next();
+        cursor
+    }
+
+    pub fn cursor_back(&self) -> Cursor<'_, T> {
+        let mut cursor = Cursor {
+            list: self,
+            place: Place::GHOST,
+        };
+        cursor.move_prev();
+        cursor
+    }
+
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        let mut cursor = CursorMut {
+            list: self,
+            place: Place::GHOST,
+        };
+        cursor.move_next();
+        cursor
+    }
+
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        let mut cursor = CursorMut {
+            list: self,
+            place: Place::GHOST,
+        };
+        cursor.move_prev();
+        cursor
+    }
+
+    fn node(&self, pos: Pos) -> &Node<T> {
+        &self.nodes[pos.index()]
+    }
+
+    fn node_mut(&mut self, pos: Pos) -> &mut Node<T> {
+        &mut self.nodes[pos.index()]
+    }
+
+    // Only the nodes in the list have an element, and we never give out
+    // the position of one that isn't
+    fn get(&self, pos: Pos) -> Option<&T> {
+        (pos != NONE).then(|| unsafe { self.node(pos).elem.assume_init_ref() })
+    }
+
+    fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        (pos != NONE).then(|| unsafe { self.node_mut(pos).elem.assume_init_mut() })
+    }
+
+    // Puts `elem` in a node between `prev` and `next`, which are next to
+    // each other (or NONE, at the ends)
+    fn link(&mut self, prev: Pos, next: Pos, elem: T) -> Pos {
+        let node = Node {
+            link: prev ^ next,
+            elem: MaybeUninit::new(elem),
+        };
+        let pos = if self.free == NONE {
+            self.nodes.push(node);
+            Pos(self.nodes.len())
+        } else {
+            let pos = self.free;
+            self.free = self.node(pos).link;
+            *self.node_mut(pos) = node;
+            pos
+        };
+
+        // Each neighbour swaps the other one out of its link, and us in
+        match prev {
+            NONE => self.front = pos,
+            prev => self.node_mut(prev).link ^= next ^ pos,
+        }
+        match next {
+            NONE => self.back = pos,
+            next => self.node_mut(next).link ^= prev ^ pos,
+        }
+        self.len += 1;
+        self.strict_check();
+        pos
+    }
+
+    // Takes the element out of `pos`, which is between `prev` and `next`
+    fn unlink(&mut self, prev: Pos, pos: Pos, next: Pos) -> T {
+        match prev {
+            NONE => self.front = next,
+            prev => self.node_mut(prev).link ^= pos ^ next,
+        }
+        match next {
+            NONE => self.back = prev,
+            next => self.node_mut(next).link ^= pos ^ prev,
+        }
+        self.len -= 1;
+
+        let free = mem::replace(&mut self.free, pos);
+        let node = self.node_mut(pos);
+        node.link = free;
+        let elem = unsafe { node.elem.assume_init_read() };
+        self.strict_check();
+        elem
+    }
+
+    // Walks the list from the front and from the back, checking that both
+    // walks see the same `len` nodes and end at the other end, and that the
+    // free list has every other node in the buffer, panicking otherwise.
+    pub fn assert_invariants(&self) {
+        assert_eq!(self.front == NONE, self.len == 0, "front doesn't match len");
+        assert_eq!(self.back == NONE, self.len == 0, "back doesn't match len");
+
+        let in_buffer = |pos: Pos| {
+            assert!(
+                (1..=self.nodes.len()).contains(&pos.0),
+                "a link points outside the buffer"
+            );
+        };
+
+        let walk = |from: Pos| {
+            let mut order = Vec::with_capacity(self.len);
+            let (mut prev, mut cur) = (NONE, from);
+            while cur != NONE {
+                assert!(
+                    order.len() < self.len,
+                    "len doesn't match the number of nodes"
+                );
+                in_buffer(cur);
+                order.push(cur);
+                (prev, cur) = (cur, self.node(cur).link ^ prev);
+            }
+            assert_eq!(
+                order.len(),
+                self.len,
+                "len doesn't match the number of nodes"
+            );
+            order
+        };
+
+        let forwards = walk(self.front);
+        assert!(
+            forwards.last().map_or(NONE, |&pos| pos) == self.back,
+            "walking forwards doesn't end at the back"
+        );
+        let mut backwards = walk(self.back);
+        backwards.reverse();
+        assert!(
+            backwards == forwards,
+            "walking backwards doesn't match walking forwards"
+        );
+
+        // Every node is either in the list or free, once. Free links are
+        // plain positions, going one way only.
+        let mut seen = alloc::vec![false; self.nodes.len()];
+        for pos in &forwards {
+            assert!(
+                !mem::replace(&mut seen[pos.index()], true),
+                "a node is in the list twice"
+            );
+        }
+        let mut free = 0;
+        let mut cur = self.free;
+        while cur != NONE {
+            in_buffer(cur);
+            assert!(
+                !mem::replace(&mut seen[cur.index()], true),
+                "a free node is in the list, or free twice"
+            );
+            free += 1;
+            cur = self.node(cur).link;
+        }
+        assert_eq!(
+            free + self.len,
+            self.nodes.len(),
+            "the free list and the list don't add up to the buffer"
+        );
+    }
+
+    // Run after every link and unlink
+    #[inline]
+    fn strict_check(&self) {
+        #[cfg(feature = "strict-checks")]
+        if cfg!(debug_assertions) {
+            self.assert_invariants();
+        }
+    }
+}
+
+impl<T> Drop for LinkedList<T> {
+    // The buffer frees itself, but only we know which elements are there
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for LinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: Debug> Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = LinkedList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+// The whole buffer counts, free nodes and spare capacity included, as that's
+// what we asked the allocator for
+impl<T> MemSize for LinkedList<T> {
+    fn node_count(&self) -> usize {
+        self.nodes.capacity()
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        size_of::<Node<T>>()
+    }
+}
+
+// Each end walks towards the other, knowing the node it came from. The
+// length tells them when they meet.
+pub struct Iter<'a, T> {
+    nodes: &'a [Node<T>],
+    front: Pos,
+    before_front: Pos,
+    back: Pos,
+    after_back: Pos,
+    len: usize,
+}
+
+// Same as Iter, but the elements it has handed out can't be borrowed again,
+// so it goes through a raw pointer to the buffer rather than a slice of it
+pub struct IterMut<'a, T> {
+    nodes: *mut Node<T>,
+    front: Pos,
+    before_front: Pos,
+    back: Pos,
+    after_back: Pos,
+    len: usize,
+    _protection: PhantomData<&'a mut T>,
+}
+
+// Just the list, popping from either end
+pub struct IntoIter<T> {
+    list: LinkedList<T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = &self.nodes[self.front.index()];
+        self.len -= 1;
+        self.before_front = mem::replace(&mut self.front, node.link ^ self.before_front);
+        Some(unsafe { node.elem.assume_init_ref() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = &self.nodes[self.back.index()];
+        self.len -= 1;
+        self.after_back = mem::replace(&mut self.back, node.link ^ self.after_back);
+        Some(unsafe { node.elem.assume_init_ref() })
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+impl<'a, T> Clone for Iter<'a, T> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    // We only borrow the element, never the whole node, as the link may be
+    // read again (by the other end) while the element is out
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        unsafe {
+            let node = self.nodes.add(self.front.index());
+            self.len -= 1;
+            self.before_front = mem::replace(&mut self.front, (*node).link ^ self.before_front);
+            Some((*node).elem.assume_init_mut())
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        unsafe {
+            let node = self.nodes.add(self.back.index());
+            self.len -= 1;
+            self.after_back = mem::replace(&mut self.back, (*node).link ^ self.after_back);
+            Some((*node).elem.assume_init_mut())
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
+// Opting back into Send and Sync, which the raw pointer opts out of. It's a
+// &mut T all the same.
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+// Where a cursor is: on a node, with the one before it (NONE at the front),
+// or on the ghost between the back and the front, like sixth's cursors. The
+// node after it comes out of the link. Both kinds of cursor move the same
+// way, so this is where they do.
+#[derive(Clone, Copy)]
+struct Place {
+    prev: Pos,
+    cur: Pos,
+    index: Option<usize>,
+}
+
+impl Place {
+    const GHOST: Place = Place {
+        prev: NONE,
+        cur: NONE,
+        index: None,
+    };
+
+    // After the ghost is the front of the list
+    fn next<T>(&self, list: &LinkedList<T>) -> Pos {
+        match self.cur {
+            NONE => list.front,
+            cur => list.node(cur).link ^ self.prev,
+        }
+    }
+
+    // And before it is the back
+    fn prev<T>(&self, list: &LinkedList<T>) -> Pos {
+        match self.cur {
+            NONE => list.back,
+            _ => self.prev,
+        }
+    }
+
+    fn move_next<T>(&mut self, list: &LinkedList<T>) {
+        let next = self.next(list);
+        if next == NONE {
+            *self = Place::GHOST;
+        } else {
+            self.index = Some(self.index.map_or(0, |index| index + 1));
+            self.prev = self.cur;
+            self.cur = next;
+        }
+    }
+
+    fn move_prev<T>(&mut self, list: &LinkedList<T>) {
+        let prev = self.prev(list);
+        if prev == NONE {
+            *self = Place::GHOST;
+        } else {
+            self.index = Some(self.index.map_or(list.len - 1, |index| index - 1));
+            // The node before the one we're going to is the other half of
+            // its link
+            self.prev = list.node(prev).link ^ self.cur;
+            self.cur = prev;
+        }
+    }
+}
+
+pub struct Cursor<'a, T> {
+    list: &'a LinkedList<T>,
+    place: Place,
+}
+
+// Deriving these would require T: Clone/Copy, which we don't need
+impl<'a, T> Clone for Cursor<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for Cursor<'a, T> {}
+
+impl<'a, T> Cursor<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.place.index
+    }
+
+    pub fn move_next(&mut self) {
+        self.place.move_next(self.list);
+    }
+
+    pub fn move_prev(&mut self) {
+        self.place.move_prev(self.list);
+    }
+
+    pub fn current(&self) -> Option<&'a T> {
+        self.list.get(self.place.cur)
+    }
+
+    pub fn peek_next(&self) -> Option<&'a T> {
+        self.list.get(self.place.next(self.list))
+    }
+
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        self.list.get(self.place.prev(self.list))
+    }
+}
+
+pub struct CursorMut<'a, T> {
+    list: &'a mut LinkedList<T>,
+    place: Place,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    pub fn index(&self) -> Option<usize> {
+        self.place.index
+    }
+
+    // A read-only view of the cursor, which can't outlive this borrow of it
+    pub fn as_cursor(&self) -> Cursor<'_, T> {
+        Cursor {
+            list: self.list,
+            place: self.place,
+        }
+    }
+
+    pub fn move_next(&mut self) {
+        self.place.move_next(self.list);
+    }
+
+    pub fn move_prev(&mut self) {
+        self.place.move_prev(self.list);
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.list.get_mut(self.place.cur)
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let next = self.place.next(self.list);
+        self.list.get_mut(next)
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        let prev = self.place.prev(self.list);
+        self.list.get_mut(prev)
+    }
+
+    // On the ghost, before means at the back, and after at the front. Our
+    // own link changes either way, but the XOR takes care of that: it's
+    // the node before us we need to keep track of.
+    pub fn insert_before(&mut self, elem: T) {
+        let prev = self.place.prev(self.list);
+        let pos = self.list.link(prev, self.place.cur, elem);
+        if self.place.cur != NONE {
+            self.place.prev = pos;
+            self.place.index = self.place.index.map(|index| index + 1);
+        }
+    }
+
+    pub fn insert_after(&mut self, elem: T) {
+        let next = self.place.next(self.list);
+        self.list.link(self.place.cur, next, elem);
+    }
+
+    // Removes the current element and moves to the next one (which may be
+    // the ghost). Does nothing on the ghost.
+    pub fn remove_current(&mut self) -> Option<T> {
+        if self.place.cur == NONE {
+            return None;
+        }
+        let next = self.place.next(self.list);
+        let elem = self.list.unlink(self.place.prev, self.place.cur, next);
+        // The next element takes our index, unless we fell on the ghost
+        if next == NONE {
+            self.place = Place::GHOST;
+        } else {
+            self.place.cur = next;
+        }
+        Some(elem)
+    }
+}
+
+// A plain sequence, front to back, like sixth's
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::de::SeqAccess;
+    use serde::de::Visitor;
+    use serde::ser::SerializeSeq;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    use super::LinkedList;
+
+    impl<T: Serialize> Serialize for LinkedList<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(Some(self.len()))?;
+            for elem in self.iter() {
+                seq.serialize_element(elem)?;
+            }
+            seq.end()
+        }
+    }
+
+    impl<'de, T: Deserialize<'de>> Deserialize<'de> for LinkedList<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_seq(LinkedListVisitor(PhantomData))
+        }
+    }
+
+    struct LinkedListVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for LinkedListVisitor<T> {
+        type Value = LinkedList<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a sequence")
+        }
+
+        fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+            let mut list = LinkedList::new();
+            while let Some(elem) = seq.next_element()? {
+                list.push_back(elem);
+            }
+            Ok(list)
+        }
+    }
+}
+
+// From front to back
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl {
+    use arbitrary::Arbitrary;
+    use arbitrary::Result;
+    use arbitrary::Unstructured;
+
+    use super::LinkedList;
+
+    impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for LinkedList<T> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_iter()?.collect()
+        }
+
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_take_rest_iter()?.collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+    use std::mem::size_of;
+
+    use rand::rngs::StdRng;
+    use rand::Rng;
+    use rand::SeedableRng;
+
+    use super::LinkedList;
+    use crate::traits::MemSize;
+
+    #[test]
+    fn basics() {
+        let mut list = LinkedList::new();
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+        assert_eq!(list.front(), None);
+
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.front(), Some(&1));
+        assert_eq!(list.back(), Some(&3));
+
+        *list.back_mut().unwrap() = 30;
+        assert_eq!(list.pop_back(), Some(30));
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_front(), Some(2));
+        assert!(list.is_empty());
+        assert_eq!(list.back(), None);
+
+        // Check the buffer survives being emptied
+        list.push_front(4);
+        *list.front_mut().unwrap() += 1;
+        assert_eq!(list.pop_back(), Some(5));
+        assert_eq!(list.pop_back(), None);
+    }
+
+    #[test]
+    fn reverse() {
+        let mut list: LinkedList<_> = (0..5).collect();
+        list.reverse();
+        assert!(list.iter().copied().eq((0..5).rev()));
+        list.push_back(-1);
+        list.push_front(5);
+        assert_eq!(format!("{list:?}"), "[5, 4, 3, 2, 1, 0, -1]");
+    }
+
+    #[test]
+    fn iter() {
+        let list: LinkedList<_> = (0..6).collect();
+        assert!(list.iter().rev().copied().eq((0..6).rev()));
+
+        // Both ends, until they meet
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 6);
+        assert_eq!(iter.next(), Some(&0));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.next_back(), Some(&4));
+        assert_eq!(iter.next(), Some(&1));
+        assert!(iter.clone().copied().eq([2, 3]));
+        assert_eq!(iter.next_back(), Some(&3));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut list: LinkedList<_> = (0..5).collect();
+        let mut iter = list.iter_mut();
+        let first = iter.next().unwrap();
+        let last = iter.next_back().unwrap();
+        for elem in iter {
+            *elem *= 10;
+        }
+        *first = -1;
+        *last = -2;
+        assert!(list.iter().copied().eq([-1, 10, 20, 30, -2]));
+    }
+
+    #[test]
+    fn into_iter() {
+        let list: LinkedList<_> = (0..5).collect();
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next_back(), Some(4));
+        assert!(iter.eq(0..4));
+    }
+
+    #[test]
+    fn cursors() {
+        let mut list: LinkedList<_> = (1..4).collect();
+
+        // Round and round, through the ghost
+        let mut cursor = list.cursor_back();
+        assert_eq!((cursor.index(), cursor.current()), (Some(2), Some(&3)));
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.current()), (None, None));
+        assert_eq!(
+            (cursor.peek_prev(), cursor.peek_next()),
+            (Some(&3), Some(&1))
+        );
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.current()), (Some(1), Some(&2)));
+        assert_eq!(
+            (cursor.peek_prev(), cursor.peek_next()),
+            (Some(&1), Some(&3))
+        );
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!((cursor.index(), cursor.current()), (Some(2), Some(&3)));
+
+        let mut cursor = list.cursor_front_mut();
+        cursor.insert_before(0);
+        cursor.insert_after(15);
+        assert_eq!((cursor.index(), cursor.current()), (Some(1), Some(&mut 1)));
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(15));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(0));
+        assert_eq!((cursor.index(), cursor.current()), (Some(0), Some(&mut 1)));
+        *cursor.peek_next().unwrap() = 20;
+        assert_eq!(cursor.as_cursor().peek_next(), Some(&20));
+
+        // At the back, removing lands on the ghost, where inserting goes at
+        // either end
+        let mut cursor = list.cursor_back_mut();
+        assert_eq!(cursor.remove_current(), Some(3));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.remove_current(), None);
+        cursor.insert_before(4);
+        cursor.insert_after(0);
+        assert_eq!(cursor.peek_prev(), Some(&mut 4));
+        assert!(list.iter().copied().eq([0, 1, 20, 4]));
+
+        let mut empty = LinkedList::<i32>::new();
+        let mut cursor = empty.cursor_front_mut();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        cursor.insert_after(1);
+        assert_eq!(cursor.peek_next(), Some(&mut 1));
+    }
+
+    // Popped nodes are pushed again before the buffer grows
+    #[test]
+    fn reuse() {
+        let mut list = LinkedList::with_capacity(4);
+        list.extend(0..4);
+        for i in 4..100 {
+            list.pop_front();
+            list.push_back(i);
+        }
+        assert_eq!(list.capacity(), 4);
+        list.clear();
+        list.extend(0..4);
+        assert_eq!(list.capacity(), 4);
+        assert!(list.iter().copied().eq(0..4));
+    }
+
+    // One link instead of sixth's two
+    #[test]
+    fn mem_size() {
+        let word = size_of::<usize>();
+        let list: LinkedList<usize> = LinkedList::with_capacity(3);
+        assert_eq!(list.bytes_per_element(), 2 * word);
+        assert_eq!(list.heap_bytes(), 6 * word);
+    }
+
+    // Random pushes, pops, reversals and cursor edits against a VecDeque
+    #[test]
+    fn model() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut list = LinkedList::new();
+        let mut model = VecDeque::new();
+        let steps = if cfg!(miri) { 300 } else { 5000 };
+        for i in 0..steps {
+            match rng.random_range(0..6) {
+                0 => {
+                    list.push_front(i);
+                    model.push_front(i);
+                }
+                1 => {
+                    list.push_back(i);
+                    model.push_back(i);
+                }
+                2 => assert_eq!(list.pop_front(), model.pop_front()),
+                3 => assert_eq!(list.pop_back(), model.pop_back()),
+                4 => {
+                    list.reverse();
+                    model.make_contiguous().reverse();
+                }
+                _ => {
+                    let at = rng.random_range(0..=model.len());
+                    let mut cursor = list.cursor_front_mut();
+                    for _ in 0..at {
+                        cursor.move_next();
+                    }
+                    assert_eq!(cursor.index(), (at < model.len()).then_some(at));
+                    if at < model.len() && rng.random_bool(0.5) {
+                        assert_eq!(cursor.remove_current(), model.remove(at));
+                    } else {
+                        cursor.insert_before(i);
+                        model.insert(at, i);
+                    }
+                }
+            }
+            assert_eq!(list.len(), model.len());
+            assert_eq!(list.front(), model.front());
+            assert_eq!(list.back(), model.back());
+            list.assert_invariants();
+        }
+        assert!(list.iter().eq(model.iter()));
+        assert!(list.iter().rev().eq(model.iter().rev()));
+    }
+
+    // Whatever's left goes with the list, and nothing that was popped goes
+    // twice
+    #[test]
+    fn drop() {
+        let mut list: LinkedList<_> = (0..10).map(Box::new).collect();
+        list.pop_front();
+        list.pop_back();
+        list.push_back(Box::new(10));
+        let mut iter = list.clone().into_iter();
+        iter.next();
+    }
+
+    #[test]
+    #[should_panic(expected = "the free list and the list don't add up to the buffer")]
+    fn assert_invariants_free() {
+        let mut list: LinkedList<_> = (0..4).collect();
+        list.pop_back();
+        list.assert_invariants();
+        // Losing track of the free node
+        list.free = super::NONE;
+        let list = std::mem::ManuallyDrop::new(list);
+        list.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "walking forwards doesn't end at the back")]
+    fn assert_invariants_back() {
+        let mut list: LinkedList<_> = (0..4).collect();
+        list.back = list.front;
+        let list = std::mem::ManuallyDrop::new(list);
+        list.assert_invariants();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde::de::value::Error;
+        use serde::de::value::SeqDeserializer;
+        use serde::Deserialize;
+        use serde_test::assert_ser_tokens;
+        use serde_test::Token;
+
+        let list: LinkedList<_> = [1, 2, 3].into_iter().collect();
+        assert_ser_tokens(
+            &list,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::I32(1),
+                Token::I32(2),
+                Token::I32(3),
+                Token::SeqEnd,
+            ],
+        );
+
+        let seq = SeqDeserializer::<_, Error>::new(vec![1, 2, 3].into_iter());
+        let list = LinkedList::<i32>::deserialize(seq).unwrap();
+        assert_eq!(list.len(), 3);
+        assert!(list.iter().copied().eq([1, 2, 3]));
+    }
+}
@@ -1,4 +1,8 @@
-use std::{borrow::Borrow, cell::Ref, cell::RefCell, cell::RefMut, rc::Rc};
+use alloc::rc::Rc;
+use core::{borrow::Borrow, cell::Ref, cell::RefCell, cell::RefMut};
+
+use crate::traits::counted_size;
+use crate::traits::MemSize;
 
 pub struct List<T> {
     head: Link<T>,
@@ -29,7 +33,7 @@ impl<T> Node<T> {
 // successor, while the nodes on the ends are pointed to by the list itself.
 
 impl<T> List<T> {
-    fn new() -> Self {
+    pub fn new() -> Self {
         List {
             head: None,
             tail: None,
@@ -68,7 +72,7 @@ impl<T> List<T> {
         })
     }
 
-    pub fn peek_front(&self) -> Option<Ref<T>> {
+    pub fn peek_front(&self) -> Option<Ref<'_, T>> {
         self.head.as_ref().map(|n| {
             // The tutorial only uses one borrow, but that fails with the latest Rust!
             // Apparently we have to use double borrow for the same pattern to work?
@@ -108,7 +112,7 @@ impl<T> List<T> {
         })
     }
 
-    pub fn peek_back(&self) -> Option<Ref<T>> {
+    pub fn peek_back(&self) -> Option<Ref<'_, T>> {
         self.tail.as_ref().map(|n| {
             let node_refcell: &RefCell<Node<T>> = n.borrow();
             let node_ref: Ref<Node<T>> = node_refcell.borrow();
@@ -116,19 +120,43 @@ impl<T> List<T> {
         })
     }
 
-    pub fn peek_back_mut(&mut self) -> Option<RefMut<T>> {
+    pub fn peek_back_mut(&mut self) -> Option<RefMut<'_, T>> {
         self.tail
             .as_ref()
             .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
     }
 
-    pub fn peek_front_mut(&mut self) -> Option<RefMut<T>> {
+    pub fn peek_front_mut(&mut self) -> Option<RefMut<'_, T>> {
         self.head
             .as_ref()
             .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
     }
 }
 
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The most expensive nodes in the crate: an Rc's two counts, a RefCell's
+// borrow flag, and two links per element
+impl<T> MemSize for List<T> {
+    fn node_count(&self) -> usize {
+        let mut count = 0;
+        let mut next = self.head.clone();
+        while let Some(node) = next {
+            count += 1;
+            next = RefCell::borrow(&node).next.clone();
+        }
+        count
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        counted_size::<RefCell<Node<T>>>()
+    }
+}
+
 impl<T> Drop for List<T> {
     fn drop(&mut self) {
         while self.pop_front().is_some() {}
@@ -137,8 +165,11 @@ impl<T> Drop for List<T> {
 
 pub struct IntoIter<T>(List<T>);
 
-impl<T> List<T> {
-    pub fn into_iter(self) -> IntoIter<T> {
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
         IntoIter(self)
     }
 }
@@ -156,10 +187,12 @@ impl<T> DoubleEndedIterator for IntoIter<T> {
     }
 }
 
+// Never read, as we couldn't write the Iterator impl below
+#[allow(dead_code)]
 pub struct Iter<'a, T>(Option<Ref<'a, Node<T>>>);
 
 impl<T> List<T> {
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter(self.head.as_ref().map(|n| {
             let node_refcell: &RefCell<Node<T>> = n.borrow();
             let node_ref: Ref<Node<T>> = node_refcell.borrow();
@@ -184,6 +217,99 @@ impl<T> List<T> {
 //     }
 // }
 
+// A sequence from front to back, like sixth's
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::de::SeqAccess;
+    use serde::de::Visitor;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    use serde::ser::SerializeSeq;
+
+    use super::List;
+
+    impl<T: Serialize> Serialize for List<T> {
+        // There's no Iterator to hand to collect_seq (see Iter above), so we
+        // walk the nodes ourselves, borrowing one at a time. Once to count
+        // them, as the other lists give their length up front.
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut len = 0;
+            let mut next = self.head.clone();
+            while let Some(node) = next {
+                len += 1;
+                next = node.borrow().next.clone();
+            }
+
+            let mut seq = serializer.serialize_seq(Some(len))?;
+            let mut next = self.head.clone();
+            while let Some(node) = next {
+                let node = node.borrow();
+                seq.serialize_element(&node.elem)?;
+                next = node.next.clone();
+            }
+            seq.end()
+        }
+    }
+
+    impl<'de, T: Deserialize<'de>> Deserialize<'de> for List<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_seq(ListVisitor(PhantomData))
+        }
+    }
+
+    struct ListVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for ListVisitor<T> {
+        type Value = List<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a sequence")
+        }
+
+        fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+            let mut list = List::new();
+            while let Some(elem) = seq.next_element()? {
+                list.push_back(elem);
+            }
+            Ok(list)
+        }
+    }
+}
+
+// From front to back
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl {
+    use arbitrary::Arbitrary;
+    use arbitrary::Result;
+    use arbitrary::Unstructured;
+
+    use super::List;
+
+    impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for List<T> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let mut list = List::new();
+            for elem in u.arbitrary_iter()? {
+                list.push_back(elem?);
+            }
+            Ok(list)
+        }
+
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+            let mut list = List::new();
+            for elem in u.arbitrary_take_rest_iter()? {
+                list.push_back(elem?);
+            }
+            Ok(list)
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::List;
@@ -242,4 +368,36 @@ mod test {
         assert_eq!(iter.next_back(), None);
         assert_eq!(iter.next(), None);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde::de::value::Error;
+        use serde::de::value::SeqDeserializer;
+        use serde::Deserialize;
+        use serde_test::assert_ser_tokens;
+        use serde_test::Token;
+
+        let mut list = List::new();
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+        assert_ser_tokens(
+            &list,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::I32(1),
+                Token::I32(2),
+                Token::I32(3),
+                Token::SeqEnd,
+            ],
+        );
+
+        let seq = SeqDeserializer::<_, Error>::new(vec![1, 2, 3].into_iter());
+        let mut list = List::<i32>::deserialize(seq).unwrap();
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.pop_back(), None);
+    }
 }
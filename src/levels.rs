@@ -0,0 +1,33 @@
+// How tall the skip lists (twelfth and thirteenth) make their nodes. Each
+// node goes up one more lane with probability 1/2, so a lane has about half
+// the nodes of the one below, and a search skips half of what's left on each
+// lane it goes down.
+//
+// Heights only need to look random to the inputs, not to an attacker, so a
+// xorshift generator with a fixed seed is plenty, and keeps the lists
+// deterministic from one run to the next.
+
+// 2^32 nodes before the lanes run out, which is plenty
+pub(crate) const MAX_HEIGHT: usize = 32;
+
+pub(crate) struct Levels {
+    // The state of the generator
+    seed: u64,
+}
+
+impl Levels {
+    pub(crate) fn new() -> Self {
+        Levels {
+            seed: 0x2545_f491_4f6c_dd1d,
+        }
+    }
+
+    // Between 1 and MAX_HEIGHT, each one half as likely as the one before
+    pub(crate) fn next_height(&mut self) -> usize {
+        // xorshift64
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        (self.seed.trailing_zeros() as usize + 1).min(MAX_HEIGHT)
+    }
+}
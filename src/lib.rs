@@ -1,29 +1,119 @@
+// Each list is behind a feature of its own (all on by default). Without the
+// `std` feature the crate is `no_std`, and all of it works on `alloc` alone,
+// except for the few std-only extras (fifth's channel, the rayon support).
+// Tests always get std, as the test harness needs it anyway.
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+#![cfg_attr(feature = "strict-provenance", feature(strict_provenance_lints))]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(any(feature = "fifth", feature = "sixth"))]
+pub mod allocator;
+#[cfg(feature = "alloc")]
+pub mod conformance;
+mod convert;
+#[cfg(feature = "eighth")]
+pub mod eighth;
+#[cfg(feature = "eleventh")]
+pub mod eleventh;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "fifteenth")]
+pub mod fifteenth;
+#[cfg(feature = "fifth")]
 pub mod fifth;
+#[cfg(feature = "first")]
 pub mod first;
+#[cfg(feature = "fourteenth")]
+pub mod fourteenth;
+#[cfg(feature = "fourth")]
 pub mod fourth;
+#[cfg(any(feature = "twelfth", feature = "thirteenth"))]
+mod levels;
+#[cfg(feature = "alloc")]
+pub mod model;
+#[cfg(feature = "ninth")]
+pub mod ninth;
+#[cfg(feature = "arbitrary")]
+pub mod ops;
+pub mod prelude;
+#[cfg(feature = "alloc")]
+pub mod reclaim;
+#[cfg(feature = "second")]
 pub mod second;
+#[cfg(feature = "seventh")]
+pub mod seventh;
+#[cfg(feature = "sixth")]
 pub mod sixth;
+#[cfg(feature = "stacklist")]
 pub mod stacklist;
+#[cfg(feature = "tenth")]
+pub mod tenth;
+// Not every concurrent list needs every shim
+#[cfg(feature = "alloc")]
+#[allow(unused_imports, dead_code)]
+mod sync;
+#[cfg(feature = "third")]
 pub mod third;
+#[cfg(feature = "thirteenth")]
+pub mod thirteenth;
+pub mod traits;
+#[cfg(feature = "twelfth")]
+pub mod twelfth;
 
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+// These only exist to hold the compile_fail doctests, which check properties
+// of the lists that a regular test can't express.
 
 /// ```compile_fail
 /// use rust_linked_lists::sixth::IterMut;
 ///
 /// fn iter_mut_covariant<'i, 'a, T>(x: IterMut<'i, &'static T>) -> IterMut<'i, &'a T> { x }
 /// ```
+#[cfg(feature = "sixth")]
+#[allow(dead_code)]
 fn iter_mut_invariant() {}
+
+/// ```compile_fail
+/// use std::cell::Cell;
+/// use rust_linked_lists::sixth::Iter;
+///
+/// fn is_send<T: Send>() {}
+/// is_send::<Iter<'static, Cell<i32>>>();
+/// ```
+#[cfg(feature = "sixth")]
+#[allow(dead_code)]
+fn iter_send_needs_sync() {}
+
+/// ```compile_fail
+/// use std::rc::Rc;
+/// use rust_linked_lists::sixth::CursorMut;
+///
+/// fn is_send<T: Send>() {}
+/// is_send::<CursorMut<'static, Rc<i32>>>();
+/// ```
+#[cfg(feature = "sixth")]
+#[allow(dead_code)]
+fn cursor_mut_send_needs_send() {}
+
+/// ```
+/// use rust_linked_lists::stacklist::List;
+///
+/// fn list_covariant<'a>(x: &'a List<'a, &'static str>) -> &'a List<'a, &'a str> { x }
+///
+/// List::push(None, "static", |root| {
+///     let local = String::from("local");
+///     List::push(Some(list_covariant(root)), local.as_str(), |list| assert_eq!(list.len(), 2));
+///     List::push(Some(root), local.as_str(), |list| assert_eq!(list.len(), 2));
+/// });
+/// ```
+///
+/// ```compile_fail
+/// use rust_linked_lists::stacklist::List;
+///
+/// fn list_contravariant<'a>(x: &'a List<'a, &'a str>) -> &'a List<'a, &'static str> { x }
+/// ```
+#[cfg(feature = "stacklist")]
+#[allow(dead_code)]
+fn stacklist_covariant() {}
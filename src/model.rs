@@ -0,0 +1,422 @@
+// Differential testing against a reference model: a script of operations is
+// run on the list under test and on a Vec (for stacks) or VecDeque (for
+// queues and deques), and the first step where the two disagree comes back
+// as a Divergence. Unlike the suites in `conformance`, which pick their own
+// operations and panic, the script is the caller's (hand-written, or from a
+// fuzzer or property test), and what to do with a divergence is up to them:
+//
+//     let script = [StackOp::Push(1), StackOp::Push(2), StackOp::Pop];
+//     if let Err(divergence) = model::stack(&mut MyStack::new(), &script) {
+//         panic!("{divergence}");
+//     }
+//
+// Any StackOps, QueueOps or DequeOps works, so this is just as useful for
+// types in other crates that wrap the lists. The list should start out
+// empty, like the model does.
+//
+// After every step, both sides are compared on what the operation returned
+// (the popped or peeked element, cloned) and on whether they're empty.
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::traits::DequeOps;
+use crate::traits::QueueOps;
+use crate::traits::StackOps;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StackOp<T> {
+    Push(T),
+    Pop,
+    Peek,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum QueueOp<T> {
+    Enqueue(T),
+    Dequeue,
+    Peek,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DequeOp<T> {
+    PushFront(T),
+    PushBack(T),
+    PopFront,
+    PopBack,
+    PeekFront,
+    PeekBack,
+}
+
+// What one side looked like right after an operation
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observed<T> {
+    // What the operation gave back, if anything (pushes never do)
+    pub returned: Option<T>,
+    pub is_empty: bool,
+}
+
+// The first step where the list and the model didn't agree
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence<O, T> {
+    // Index of the op in the script
+    pub step: usize,
+    pub op: O,
+    pub expected: Observed<T>,
+    pub actual: Observed<T>,
+}
+
+impl<O: fmt::Debug, T: fmt::Debug> fmt::Display for Divergence<O, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "step {} ({:?}) diverged from the model: expected {:?}, got {:?}",
+            self.step, self.op, self.expected, self.actual
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<O: fmt::Debug, T: fmt::Debug> Error for Divergence<O, T> {}
+
+impl<T: Clone> StackOp<T> {
+    pub fn apply<S: StackOps<T>>(&self, list: &mut S) -> Observed<T> {
+        let returned = match self {
+            StackOp::Push(elem) => {
+                list.push(elem.clone());
+                None
+            }
+            StackOp::Pop => list.pop(),
+            StackOp::Peek => list.peek().map(|elem| elem.clone()),
+        };
+        Observed {
+            returned,
+            is_empty: list.is_empty(),
+        }
+    }
+}
+
+impl<T: Clone> QueueOp<T> {
+    pub fn apply<Q: QueueOps<T>>(&self, list: &mut Q) -> Observed<T> {
+        let returned = match self {
+            QueueOp::Enqueue(elem) => {
+                list.enqueue(elem.clone());
+                None
+            }
+            QueueOp::Dequeue => list.dequeue(),
+            QueueOp::Peek => list.peek().map(|elem| elem.clone()),
+        };
+        Observed {
+            returned,
+            is_empty: list.is_empty(),
+        }
+    }
+}
+
+impl<T: Clone> DequeOp<T> {
+    pub fn apply<D: DequeOps<T>>(&self, list: &mut D) -> Observed<T> {
+        let returned = match self {
+            DequeOp::PushFront(elem) => {
+                list.push_front(elem.clone());
+                None
+            }
+            DequeOp::PushBack(elem) => {
+                list.push_back(elem.clone());
+                None
+            }
+            DequeOp::PopFront => list.pop_front(),
+            DequeOp::PopBack => list.pop_back(),
+            DequeOp::PeekFront => list.peek_front().map(|elem| elem.clone()),
+            DequeOp::PeekBack => list.peek_back().map(|elem| elem.clone()),
+        };
+        Observed {
+            returned,
+            is_empty: list.is_empty(),
+        }
+    }
+}
+
+// Runs `script` on `list` and on a Vec
+pub fn stack<T, S>(list: &mut S, script: &[StackOp<T>]) -> Result<(), Divergence<StackOp<T>, T>>
+where
+    T: Clone + PartialEq,
+    S: StackOps<T>,
+{
+    let mut model = Vec::new();
+    compare(script, |op| op.apply(list), |op| op.apply(&mut model))
+}
+
+// Runs `script` on `list` and on a VecDeque
+pub fn queue<T, Q>(list: &mut Q, script: &[QueueOp<T>]) -> Result<(), Divergence<QueueOp<T>, T>>
+where
+    T: Clone + PartialEq,
+    Q: QueueOps<T>,
+{
+    let mut model = VecDeque::new();
+    compare(script, |op| op.apply(list), |op| op.apply(&mut model))
+}
+
+// Runs `script` on `list` and on a VecDeque
+pub fn deque<T, D>(list: &mut D, script: &[DequeOp<T>]) -> Result<(), Divergence<DequeOp<T>, T>>
+where
+    T: Clone + PartialEq,
+    D: DequeOps<T>,
+{
+    let mut model = VecDeque::new();
+    compare(script, |op| op.apply(list), |op| op.apply(&mut model))
+}
+
+fn compare<O: Clone, T: PartialEq>(
+    script: &[O],
+    mut list: impl FnMut(&O) -> Observed<T>,
+    mut model: impl FnMut(&O) -> Observed<T>,
+) -> Result<(), Divergence<O, T>> {
+    for (step, op) in script.iter().enumerate() {
+        let expected = model(op);
+        let actual = list(op);
+        if actual != expected {
+            return Err(Divergence {
+                step,
+                op: op.clone(),
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+// The models themselves, which also makes Vec and VecDeque usable wherever
+// the traits are
+
+impl<T> StackOps<T> for Vec<T> {
+    type Ref<'a>
+        = &'a T
+    where
+        T: 'a;
+
+    fn push(&mut self, elem: T) {
+        self.push(elem)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.last()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<T> QueueOps<T> for VecDeque<T> {
+    type Ref<'a>
+        = &'a T
+    where
+        T: 'a;
+
+    fn enqueue(&mut self, elem: T) {
+        self.push_back(elem)
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.front()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+impl<T> DequeOps<T> for VecDeque<T> {
+    type Ref<'a>
+        = &'a T
+    where
+        T: 'a;
+
+    fn push_front(&mut self, elem: T) {
+        self.push_front(elem)
+    }
+
+    fn push_back(&mut self, elem: T) {
+        self.push_back(elem)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        self.pop_back()
+    }
+
+    fn peek_front(&self) -> Option<&T> {
+        self.front()
+    }
+
+    fn peek_back(&self) -> Option<&T> {
+        self.back()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+
+    use super::Divergence;
+    use super::Observed;
+    use super::StackOp;
+    use crate::traits::StackOps;
+
+    // A stack that's really a queue, to have something to diverge
+    struct Broken(VecDeque<i32>);
+
+    impl StackOps<i32> for Broken {
+        type Ref<'a> = &'a i32;
+
+        fn push(&mut self, elem: i32) {
+            self.0.push_back(elem)
+        }
+
+        fn pop(&mut self) -> Option<i32> {
+            self.0.pop_front()
+        }
+
+        fn peek(&self) -> Option<&i32> {
+            self.0.front()
+        }
+    }
+
+    #[cfg(any(feature = "second", feature = "sixth"))]
+    fn stack_script() -> Vec<StackOp<i32>> {
+        let mut script = Vec::new();
+        for i in 0..20 {
+            script.push(StackOp::Push(i));
+            script.push(StackOp::Peek);
+            if i % 3 == 0 {
+                script.push(StackOp::Pop);
+            }
+        }
+        script.extend(vec![StackOp::Pop; 20]);
+        script
+    }
+
+    #[cfg(any(feature = "fourth", feature = "sixth"))]
+    fn deque_script() -> Vec<super::DequeOp<i32>> {
+        use super::DequeOp;
+
+        let mut script = Vec::new();
+        for i in 0..20 {
+            script.push(match i % 4 {
+                0 | 1 => DequeOp::PushFront(i),
+                _ => DequeOp::PushBack(i),
+            });
+            script.push(if i % 2 == 0 {
+                DequeOp::PeekFront
+            } else {
+                DequeOp::PeekBack
+            });
+            if i % 5 == 0 {
+                script.push(DequeOp::PopBack);
+            }
+        }
+        for _ in 0..10 {
+            script.extend([DequeOp::PopFront, DequeOp::PopBack]);
+        }
+        script
+    }
+
+    #[test]
+    fn divergence() {
+        let script = [StackOp::Push(1), StackOp::Push(2), StackOp::Peek];
+        let divergence = super::stack(&mut Broken(VecDeque::new()), &script).unwrap_err();
+        assert_eq!(
+            divergence,
+            Divergence {
+                step: 2,
+                op: StackOp::Peek,
+                expected: Observed {
+                    returned: Some(2),
+                    is_empty: false,
+                },
+                actual: Observed {
+                    returned: Some(1),
+                    is_empty: false,
+                },
+            }
+        );
+        assert_eq!(
+            divergence.to_string(),
+            "step 2 (Peek) diverged from the model: expected \
+             Observed { returned: Some(2), is_empty: false }, \
+             got Observed { returned: Some(1), is_empty: false }"
+        );
+
+        // Steps that return nothing agree until the state shows through
+        assert_eq!(
+            super::stack(&mut Broken(VecDeque::new()), &script[..2]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "second")]
+    fn second() {
+        use crate::second::List;
+
+        assert_eq!(super::stack(&mut List::new(), &stack_script()), Ok(()));
+    }
+
+    #[test]
+    #[cfg(feature = "fourth")]
+    fn fourth() {
+        use crate::fourth::List;
+
+        assert_eq!(super::deque(&mut List::new(), &deque_script()), Ok(()));
+    }
+
+    #[test]
+    #[cfg(feature = "fifth")]
+    fn fifth() {
+        use super::QueueOp;
+        use crate::fifth::List;
+
+        let script: Vec<_> = (0..20)
+            .flat_map(|i| {
+                [
+                    QueueOp::Enqueue(i),
+                    QueueOp::Peek,
+                    QueueOp::Enqueue(-i),
+                    QueueOp::Dequeue,
+                ]
+            })
+            .chain(vec![QueueOp::Dequeue; 25])
+            .collect();
+        assert_eq!(super::queue(&mut List::new(), &script), Ok(()));
+    }
+
+    #[test]
+    #[cfg(feature = "sixth")]
+    fn sixth() {
+        use crate::sixth::LinkedList;
+
+        let mut list = LinkedList::new();
+        assert_eq!(super::stack(&mut list, &stack_script()), Ok(()));
+        assert_eq!(super::deque(&mut list, &deque_script()), Ok(()));
+        assert!(list.is_empty());
+    }
+}
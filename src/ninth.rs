@@ -0,0 +1,346 @@
+// A queue for exactly one producer thread and one consumer thread, with a
+// fixed capacity, for when neither side can afford to wait: an audio callback
+// handing buffers to a UI thread, say. Nothing here allocates after `queue`
+// returns, takes a lock, or retries: every push and pop is a fixed number of
+// steps, whatever the other side is doing (which makes them wait-free).
+//
+// All the nodes are allocated up front and linked in a ring, once and for
+// all. The consumer's end (`head`) points to the oldest element, and the
+// producer's end (`tail`) to the next node to fill, so the elements are the
+// nodes from head up to tail. Pushing fills the tail node and moves the tail
+// on, popping empties the head node and moves the head on, and neither ever
+// relinks anything. Head and tail are equal when the queue is empty, so the
+// ring has one node more than the capacity: otherwise a full queue would look
+// the same.
+//
+// Each end is only ever moved by its own side, and only read by the other.
+// A node is only touched by whichever side owns it: the producer from the
+// tail up to (not including) the head, the consumer from the head up to
+// (not including) the tail. Moving an end with Release and reading the other
+// side's with Acquire hands the nodes back and forth, element included.
+//
+// Both ends would be written from different cores all the time, so each gets
+// a cache line of its own. Otherwise each write would also evict the other
+// side's end from the other core (false sharing).
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use core::mem::size_of;
+use core::mem::MaybeUninit;
+use core::ops::Deref;
+use core::ptr;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use crate::sync::atomic::AtomicPtr;
+use crate::sync::atomic::Ordering;
+use crate::sync::Arc;
+use crate::sync::UnsafeCell;
+use crate::traits::MemSize;
+
+pub struct Producer<T> {
+    ring: Arc<Ring<T>>,
+}
+
+pub struct Consumer<T> {
+    ring: Arc<Ring<T>>,
+}
+
+// Returned by `push` when the queue is full, handing the element back
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Full<T>(pub T);
+
+struct Ring<T> {
+    // The oldest element, moved on by the consumer
+    head: CachePadded<AtomicPtr<Node<T>>>,
+    // The next node to fill, moved on by the producer
+    tail: CachePadded<AtomicPtr<Node<T>>>,
+    // All the nodes, which we only go through as a slice to free them
+    nodes: *mut [Node<T>],
+    capacity: usize,
+}
+
+struct Node<T> {
+    // Initialized from the moment the producer fills the node until the
+    // consumer empties it
+    elem: UnsafeCell<MaybeUninit<T>>,
+    // Set before either side gets the ring, and never again
+    next: *mut Node<T>,
+}
+
+// 128 rather than 64 bytes, as some CPUs fetch cache lines in pairs
+#[repr(align(128))]
+struct CachePadded<T>(T);
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+// Both halves hand elements across threads, hence Send for both
+unsafe impl<T: Send> Send for Ring<T> {}
+unsafe impl<T: Send> Sync for Ring<T> {}
+
+pub fn queue<T>(capacity: usize) -> (Producer<T>, Consumer<T>) {
+    let nodes: Vec<Node<T>> = (0..=capacity)
+        .map(|_| Node {
+            elem: UnsafeCell::new(MaybeUninit::uninit()),
+            next: ptr::null_mut(),
+        })
+        .collect();
+    // Through a raw pointer from here on, so that the pointers between
+    // nodes stay valid
+    let nodes = Box::into_raw(nodes.into_boxed_slice());
+    let first = nodes as *mut Node<T>;
+    for i in 0..=capacity {
+        unsafe { (*first.add(i)).next = first.add((i + 1) % (capacity + 1)) };
+    }
+
+    let ring = Arc::new(Ring {
+        head: CachePadded(AtomicPtr::new(first)),
+        tail: CachePadded(AtomicPtr::new(first)),
+        nodes,
+        capacity,
+    });
+    (Producer { ring: ring.clone() }, Consumer { ring })
+}
+
+impl<T> Producer<T> {
+    pub fn push(&mut self, elem: T) -> Result<(), Full<T>> {
+        // Only we move the tail
+        let tail = self.ring.tail.load(Ordering::Relaxed);
+        let next = unsafe { (*tail).next };
+        // Acquire, so that the consumer is done with whatever it took out
+        // of the nodes it left behind
+        if next == self.ring.head.load(Ordering::Acquire) {
+            return Err(Full(elem));
+        }
+
+        // The tail node is ours until we move the tail past it
+        unsafe { (*tail).elem.with_mut(|slot| (*slot).write(elem)) };
+        // Release, so that the consumer sees the element
+        self.ring.tail.store(next, Ordering::Release);
+        Ok(())
+    }
+
+    pub fn is_full(&self) -> bool {
+        let tail = self.ring.tail.load(Ordering::Relaxed);
+        unsafe { (*tail).next == self.ring.head.load(Ordering::Acquire) }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity
+    }
+}
+
+impl<T> Consumer<T> {
+    pub fn pop(&mut self) -> Option<T> {
+        // Only we move the head
+        let head = self.ring.head.load(Ordering::Relaxed);
+        // Acquire, so that we see the element the producer put in
+        if head == self.ring.tail.load(Ordering::Acquire) {
+            return None;
+        }
+
+        // The head node is ours until we move the head past it
+        let elem = unsafe { (*head).elem.with(|slot| (*slot).assume_init_read()) };
+        // Release, so that the producer doesn't refill the node before
+        // we're done reading it
+        self.ring
+            .head
+            .store(unsafe { (*head).next }, Ordering::Release);
+        Some(elem)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ring.head.load(Ordering::Relaxed) == self.ring.tail.load(Ordering::Acquire)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ring.capacity
+    }
+}
+
+// Either half reports the whole ring, which is allocated up front: one node
+// more than the capacity, whether or not it holds an element
+impl<T> MemSize for Producer<T> {
+    fn node_count(&self) -> usize {
+        self.ring.capacity + 1
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        size_of::<Node<T>>()
+    }
+}
+
+impl<T> MemSize for Consumer<T> {
+    fn node_count(&self) -> usize {
+        self.ring.capacity + 1
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        size_of::<Node<T>>()
+    }
+}
+
+impl<T> Drop for Ring<T> {
+    fn drop(&mut self) {
+        // Both halves are gone, so whatever is left from head to tail is
+        // ours to drop
+        let tail = self.tail.load(Ordering::Relaxed);
+        let mut node = self.head.load(Ordering::Relaxed);
+        while node != tail {
+            unsafe {
+                (*node).elem.with_mut(|slot| (*slot).assume_init_drop());
+                node = (*node).next;
+            }
+        }
+        drop(unsafe { Box::from_raw(self.nodes) });
+    }
+}
+
+impl<T> fmt::Display for Full<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pushing onto a full queue")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: fmt::Debug> Error for Full<T> {}
+
+#[cfg(test)]
+mod test {
+    use super::queue;
+    use super::Full;
+    use crate::sync::model;
+    use crate::sync::thread;
+    use crate::traits::MemSize;
+
+    #[test]
+    fn basics() {
+        model(|| {
+            let (mut producer, mut consumer) = queue(2);
+            assert_eq!(producer.capacity(), 2);
+            assert!(consumer.is_empty());
+            assert_eq!(consumer.pop(), None);
+
+            assert_eq!(producer.push(1), Ok(()));
+            assert_eq!(producer.push(2), Ok(()));
+            assert!(producer.is_full());
+            assert_eq!(producer.push(3), Err(Full(3)));
+
+            assert_eq!(consumer.pop(), Some(1));
+            assert!(!producer.is_full());
+            // Round the ring
+            assert_eq!(producer.push(4), Ok(()));
+            assert_eq!(consumer.pop(), Some(2));
+            assert_eq!(consumer.pop(), Some(4));
+            assert_eq!(consumer.pop(), None);
+            assert!(consumer.is_empty());
+        });
+    }
+
+    // Pushing and popping doesn't change anything, the nodes are all there
+    // from the start
+    #[test]
+    fn mem_size() {
+        model(|| {
+            let (mut producer, consumer) = queue::<usize>(3);
+            // The element and the link (which loom's cells make bigger)
+            let node = producer.bytes_per_element();
+            #[cfg(not(loom))]
+            assert_eq!(node, 2 * std::mem::size_of::<usize>());
+            assert_eq!(producer.node_count(), 4);
+            assert_eq!(consumer.heap_bytes(), 4 * node);
+            producer.push(1).unwrap();
+            assert_eq!(producer.heap_bytes(), consumer.heap_bytes());
+        });
+    }
+
+    // Always full and always empty at once
+    #[test]
+    fn zero_capacity() {
+        model(|| {
+            let (mut producer, mut consumer) = queue(0);
+            assert!(producer.is_full());
+            assert_eq!(producer.push(1), Err(Full(1)));
+            assert_eq!(consumer.pop(), None);
+        });
+    }
+
+    // Whatever's still in the queue goes with the second half to go
+    #[test]
+    fn drop() {
+        model(|| {
+            let (mut producer, mut consumer) = queue(3);
+            for i in 0..3 {
+                producer.push(Box::new(i)).unwrap();
+            }
+            consumer.pop();
+            core::mem::drop(producer);
+            assert_eq!(consumer.pop().map(|x| *x), Some(1));
+        });
+    }
+
+    // Both sides at once on a ring small enough to wrap, in every order loom
+    // can come up with: everything comes out once, in order, and the slots
+    // are never touched by both sides at once (which loom's cells check)
+    #[test]
+    fn interleavings() {
+        model(|| {
+            let (mut producer, mut consumer) = queue(1);
+            let handle = thread::spawn(move || {
+                for i in 0..3 {
+                    while producer.push(Box::new(i)).is_err() {
+                        crate::sync::hint::spin_loop();
+                    }
+                }
+            });
+
+            let mut popped = Vec::new();
+            while popped.len() < 3 {
+                match consumer.pop() {
+                    Some(elem) => popped.push(*elem),
+                    None => crate::sync::hint::spin_loop(),
+                }
+            }
+            handle.join().unwrap();
+            assert_eq!(popped, [0, 1, 2]);
+        });
+    }
+
+    // Far too many interleavings for loom, but plenty of real contention
+    #[cfg(not(loom))]
+    #[test]
+    fn threads() {
+        let count = if cfg!(miri) { 200 } else { 100_000 };
+
+        let (mut producer, mut consumer) = queue(16);
+        let handle = thread::spawn(move || {
+            for i in 0..count {
+                let mut elem = Box::new(i);
+                while let Err(Full(back)) = producer.push(elem) {
+                    elem = back;
+                    std::thread::yield_now();
+                }
+            }
+        });
+
+        let mut next = 0;
+        while next < count {
+            match consumer.pop() {
+                Some(elem) => {
+                    assert_eq!(*elem, next);
+                    next += 1;
+                }
+                None => std::thread::yield_now(),
+            }
+        }
+        handle.join().unwrap();
+        assert!(consumer.is_empty());
+    }
+}
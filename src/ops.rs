@@ -0,0 +1,395 @@
+// Scripts of operations for fuzzers and property tests to generate: each Op
+// is one call on a deque, and a Vec<Op<T>> is a whole run of them. Running
+// the same script on a list and on a VecDeque, and comparing what comes out,
+// checks the list against a known-good deque without writing the sequence of
+// calls by hand.
+//
+// Indices in the script are taken modulo the length at the time they're
+// applied, so every op does something no matter what the fuzzer picked,
+// rather than most inserts and removes falling out of range.
+//
+// A failing script is rarely the shortest one that fails, so `shrink` cuts it
+// down to one where every op matters: it drops runs of ops, then single
+// ops, then trades what's left for simpler ones, for as long as the script
+// keeps failing.
+
+use std::collections::VecDeque;
+
+use arbitrary::Arbitrary;
+use arbitrary::Result;
+use arbitrary::Unstructured;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Op<T> {
+    PushFront(T),
+    PushBack(T),
+    PopFront,
+    PopBack,
+    Insert(usize, T),
+    Remove(usize),
+}
+
+// What a script can run on. Only the basic operations of a deque, so that
+// any list with indexed access can implement it with a few forwarding calls.
+#[allow(clippy::len_without_is_empty)]
+pub trait Target<T> {
+    fn len(&self) -> usize;
+    fn push_front(&mut self, elem: T);
+    fn push_back(&mut self, elem: T);
+    fn pop_front(&mut self) -> Option<T>;
+    fn pop_back(&mut self) -> Option<T>;
+    // Only called with index <= len()
+    fn insert(&mut self, index: usize, elem: T);
+    // Only called with index < len()
+    fn remove(&mut self, index: usize) -> Option<T>;
+}
+
+impl<T> Op<T> {
+    // Runs the op on `target`, returning whatever it took out of it
+    pub fn apply<L: Target<T>>(self, target: &mut L) -> Option<T> {
+        match self {
+            Op::PushFront(elem) => {
+                target.push_front(elem);
+                None
+            }
+            Op::PushBack(elem) => {
+                target.push_back(elem);
+                None
+            }
+            Op::PopFront => target.pop_front(),
+            Op::PopBack => target.pop_back(),
+            Op::Insert(index, elem) => {
+                target.insert(index % (target.len() + 1), elem);
+                None
+            }
+            Op::Remove(index) => match target.len() {
+                0 => None,
+                len => target.remove(index % len),
+            },
+        }
+    }
+}
+
+// Runs the whole script, collecting what each op took out
+pub fn run<T, L: Target<T>>(ops: Vec<Op<T>>, target: &mut L) -> Vec<Option<T>> {
+    ops.into_iter().map(|op| op.apply(target)).collect()
+}
+
+// Cuts `ops` down for as long as `fails` says the result still fails, and
+// returns the smallest failing script it got to. No op can be dropped from
+// it, or made simpler, without it passing.
+pub fn shrink<T: Clone>(
+    mut ops: Vec<Op<T>>,
+    mut fails: impl FnMut(&[Op<T>]) -> bool,
+) -> Vec<Op<T>> {
+    loop {
+        let before = ops.len();
+
+        // Runs of ops first, halving them down to single ops
+        let mut chunk = ops.len() / 2;
+        while chunk > 0 {
+            let mut start = 0;
+            while start < ops.len() {
+                let end = (start + chunk).min(ops.len());
+                let mut candidate = ops[..start].to_vec();
+                candidate.extend_from_slice(&ops[end..]);
+                if fails(&candidate) {
+                    ops = candidate;
+                } else {
+                    start = end;
+                }
+            }
+            chunk /= 2;
+        }
+
+        // Then simpler versions of each op left
+        let mut simplified = false;
+        for i in 0..ops.len() {
+            for simpler in ops[i].simpler() {
+                let mut candidate = ops.clone();
+                candidate[i] = simpler;
+                if fails(&candidate) {
+                    ops = candidate;
+                    simplified = true;
+                    break;
+                }
+            }
+        }
+
+        if ops.len() == before && !simplified {
+            return ops;
+        }
+    }
+}
+
+impl<T: Clone> Op<T> {
+    // What the op could be replaced with, simplest first: an op at an end
+    // rather than at an index, or a smaller index. Indices wrap around the
+    // length, so the index that matters may be any small one, not just one
+    // we'd get to by halving.
+    fn simpler(&self) -> Vec<Op<T>> {
+        let smaller = |index: usize| (0..index.min(8)).chain(Some(index / 2).filter(|&i| i >= 8));
+        match self {
+            Op::Insert(index, elem) => Some(Op::PushBack(elem.clone()))
+                .into_iter()
+                .chain(smaller(*index).map(|i| Op::Insert(i, elem.clone())))
+                .collect(),
+            Op::Remove(index) => Some(Op::PopBack)
+                .into_iter()
+                .chain(smaller(*index).map(Op::Remove))
+                .collect(),
+            Op::PushFront(elem) => vec![Op::PushBack(elem.clone())],
+            Op::PopFront => vec![Op::PopBack],
+            Op::PushBack(_) | Op::PopBack => Vec::new(),
+        }
+    }
+}
+
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for Op<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(match u.int_in_range(0..=5)? {
+            0 => Op::PushFront(u.arbitrary()?),
+            1 => Op::PushBack(u.arbitrary()?),
+            2 => Op::PopFront,
+            3 => Op::PopBack,
+            4 => Op::Insert(u.arbitrary()?, u.arbitrary()?),
+            _ => Op::Remove(u.arbitrary()?),
+        })
+    }
+}
+
+// The reference every other target is compared against
+impl<T> Target<T> for VecDeque<T> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn push_front(&mut self, elem: T) {
+        self.push_front(elem)
+    }
+
+    fn push_back(&mut self, elem: T) {
+        self.push_back(elem)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        self.pop_back()
+    }
+
+    fn insert(&mut self, index: usize, elem: T) {
+        self.insert(index, elem)
+    }
+
+    fn remove(&mut self, index: usize) -> Option<T> {
+        self.remove(index)
+    }
+}
+
+#[cfg(feature = "sixth")]
+impl<T, A: crate::allocator::Allocator> Target<T> for crate::sixth::LinkedList<T, A> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn push_front(&mut self, elem: T) {
+        self.push_front(elem)
+    }
+
+    fn push_back(&mut self, elem: T) {
+        self.push_back(elem)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        self.pop_back()
+    }
+
+    fn insert(&mut self, index: usize, elem: T) {
+        self.insert(index, elem)
+    }
+
+    fn remove(&mut self, index: usize) -> Option<T> {
+        self.remove(index)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+
+    use arbitrary::Arbitrary;
+    use arbitrary::Unstructured;
+
+    use super::Op;
+    use super::Target;
+
+    // Stands in for a fuzzer's input
+    fn bytes(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                (state >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn indices_wrap() {
+        let mut deque = VecDeque::from([1, 2, 3]);
+        assert_eq!(Op::Remove(4).apply(&mut deque), Some(2));
+        assert_eq!(Op::Insert(7, 0).apply(&mut deque), None);
+        assert_eq!(deque, [1, 0, 3]);
+        assert_eq!(Op::<i32>::Remove(0).apply(&mut VecDeque::new()), None);
+    }
+
+    // A deque whose `remove` takes out the element before the one asked
+    // for, which takes two elements and a remove from the second to notice
+    struct OffByOne(VecDeque<u8>);
+
+    impl Target<u8> for OffByOne {
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn push_front(&mut self, elem: u8) {
+            self.0.push_front(elem)
+        }
+
+        fn push_back(&mut self, elem: u8) {
+            self.0.push_back(elem)
+        }
+
+        fn pop_front(&mut self) -> Option<u8> {
+            self.0.pop_front()
+        }
+
+        fn pop_back(&mut self) -> Option<u8> {
+            self.0.pop_back()
+        }
+
+        fn insert(&mut self, index: usize, elem: u8) {
+            self.0.insert(index, elem)
+        }
+
+        fn remove(&mut self, index: usize) -> Option<u8> {
+            self.0.remove(index.saturating_sub(1))
+        }
+    }
+
+    #[test]
+    fn shrink() {
+        let fails = |ops: &[Op<u8>]| {
+            let mut buggy = OffByOne(VecDeque::new());
+            let mut model = VecDeque::new();
+            super::run(ops.to_vec(), &mut buggy) != super::run(ops.to_vec(), &mut model)
+        };
+
+        // Some random script that trips on the bug
+        let ops = (0..)
+            .map(|seed| {
+                let data = bytes(seed, 512);
+                Vec::<Op<u8>>::arbitrary(&mut Unstructured::new(&data)).unwrap()
+            })
+            .find(|ops| fails(ops))
+            .unwrap();
+        assert!(ops.len() > 3);
+
+        // Two different elements, pushed at the back, and the second removed
+        let shrunk = super::shrink(ops, fails);
+        assert_eq!(shrunk.len(), 3, "{shrunk:?}");
+        assert!(
+            matches!(
+                shrunk[..],
+                [Op::PushBack(a), Op::PushBack(b), Op::Remove(1)] if a != b
+            ),
+            "{shrunk:?}"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sixth")]
+    fn sixth_matches_vec_deque() {
+        use crate::sixth::LinkedList;
+
+        for seed in 0..50 {
+            let data = bytes(seed, 2048);
+            let mut u = Unstructured::new(&data);
+            let start = Vec::<u8>::arbitrary(&mut u).unwrap();
+            let ops = Vec::<Op<u8>>::arbitrary(&mut u).unwrap();
+
+            let mut list = LinkedList::from(start.clone());
+            let mut model = VecDeque::from(start);
+            let out = super::run(ops.clone(), &mut list);
+            assert_eq!(out, super::run(ops, &mut model), "seed {seed}");
+            list.assert_invariants();
+            assert!(list.iter().eq(model.iter()), "seed {seed}");
+        }
+    }
+
+    // Every list can be made up from the same bytes, and gets the same
+    // elements in the same order out of them
+    #[test]
+    fn lists() {
+        // Each element comes after a byte saying whether there's another,
+        // so that every list gets several (AsyncStackList needs at least one)
+        let data: Vec<u8> = bytes(7, 32).into_iter().flat_map(|x| [1, x]).collect();
+        let expected: Vec<u8> = data.iter().skip(1).step_by(2).copied().collect();
+        let check = |elems: Vec<u8>| assert_eq!(elems, expected);
+
+        #[cfg(feature = "second")]
+        check(
+            crate::second::List::<u8>::arbitrary_take_rest(Unstructured::new(&data))
+                .unwrap()
+                .into_iter()
+                .collect(),
+        );
+        #[cfg(feature = "third")]
+        check(
+            crate::third::List::<u8>::arbitrary_take_rest(Unstructured::new(&data))
+                .unwrap()
+                .iter()
+                .copied()
+                .collect(),
+        );
+        #[cfg(feature = "fourth")]
+        check(
+            crate::fourth::List::<u8>::arbitrary_take_rest(Unstructured::new(&data))
+                .unwrap()
+                .into_iter()
+                .collect(),
+        );
+        #[cfg(feature = "fifth")]
+        check(
+            crate::fifth::List::<u8>::arbitrary_take_rest(Unstructured::new(&data))
+                .unwrap()
+                .into_iter()
+                .collect(),
+        );
+        #[cfg(feature = "sixth")]
+        check(
+            crate::sixth::LinkedList::<u8>::arbitrary_take_rest(Unstructured::new(&data))
+                .unwrap()
+                .into(),
+        );
+        #[cfg(feature = "stacklist")]
+        check(
+            crate::stacklist::async_list::AsyncStackList::<u8>::arbitrary_take_rest(
+                Unstructured::new(&data),
+            )
+            .unwrap()
+            .iter()
+            .copied()
+            .collect(),
+        );
+    }
+}
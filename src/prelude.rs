@@ -0,0 +1,194 @@
+// The lists are named after the chapter they come from, which is handy while
+// following along but tells you nothing when using them. This gives each one
+// a name that says what it is, so `use rust_linked_lists::prelude::*` is all
+// you need.
+//
+// first is left out, as its list only exists to show what not to do.
+
+#[cfg(feature = "eighth")]
+pub use crate::eighth::Queue as ConcurrentQueue;
+#[cfg(feature = "eleventh")]
+pub use crate::eleventh::Deque as PersistentDeque;
+#[cfg(feature = "fifteenth")]
+pub use crate::fifteenth::LinkedList as SlabList;
+#[cfg(feature = "fifth")]
+pub use crate::fifth::List as UnsafeQueue;
+#[cfg(feature = "fourteenth")]
+pub use crate::fourteenth::LinkedList as XorList;
+#[cfg(feature = "fourth")]
+pub use crate::fourth::List as RefDeque;
+#[cfg(feature = "ninth")]
+pub use crate::ninth::queue as spsc_queue;
+#[cfg(feature = "ninth")]
+pub use crate::ninth::Consumer as SpscConsumer;
+#[cfg(feature = "ninth")]
+pub use crate::ninth::Producer as SpscProducer;
+#[cfg(feature = "second")]
+pub use crate::second::List as Stack;
+#[cfg(feature = "seventh")]
+pub use crate::seventh::Stack as ConcurrentStack;
+#[cfg(feature = "sixth")]
+pub use crate::sixth::LinkedList as Deque;
+#[cfg(feature = "stacklist")]
+pub use crate::stacklist::List as ScopedList;
+#[cfg(feature = "tenth")]
+pub use crate::tenth::Queue as PersistentQueue;
+#[cfg(feature = "third")]
+pub use crate::third::List as PersistentList;
+#[cfg(feature = "thirteenth")]
+pub use crate::thirteenth::IndexedList;
+#[cfg(feature = "twelfth")]
+pub use crate::twelfth::SkipListMap;
+
+#[cfg(test)]
+mod test {
+    #[test]
+    #[cfg(feature = "second")]
+    fn stack() {
+        use super::Stack;
+
+        let mut stack = Stack::new();
+        stack.push(1);
+        assert_eq!(stack.pop(), Some(1));
+    }
+
+    #[test]
+    #[cfg(feature = "third")]
+    fn persistent_list() {
+        use super::PersistentList;
+
+        let list = PersistentList::new().prepend(1);
+        assert_eq!(list.head(), Some(&1));
+    }
+
+    #[test]
+    #[cfg(feature = "tenth")]
+    fn persistent_queue() {
+        use super::PersistentQueue;
+
+        let queue = PersistentQueue::new().snoc(1).snoc(2);
+        assert_eq!(queue.front(), Some(&1));
+    }
+
+    #[test]
+    #[cfg(feature = "eleventh")]
+    fn persistent_deque() {
+        use super::PersistentDeque;
+
+        let deque = PersistentDeque::new().push_back(2).push_front(1);
+        assert_eq!(deque.concat(&deque).get(2), Some(&1));
+    }
+
+    #[test]
+    #[cfg(feature = "fourth")]
+    fn ref_deque() {
+        use super::RefDeque;
+
+        let mut deque = RefDeque::new();
+        deque.push_front(1);
+        assert_eq!(deque.pop_front(), Some(1));
+    }
+
+    #[test]
+    #[cfg(feature = "fifteenth")]
+    fn slab_list() {
+        use super::SlabList;
+
+        let mut list = SlabList::new();
+        let handle = list.push_back(1);
+        assert_eq!(list.remove(handle), Some(1));
+        assert_eq!(list.get(handle), None);
+    }
+
+    #[test]
+    #[cfg(feature = "fourteenth")]
+    fn xor_list() {
+        use super::XorList;
+
+        let mut list = XorList::new();
+        list.push_back(1);
+        list.reverse();
+        assert_eq!(list.pop_front(), Some(1));
+    }
+
+    #[test]
+    #[cfg(feature = "fifth")]
+    fn unsafe_queue() {
+        use super::UnsafeQueue;
+
+        let mut queue = UnsafeQueue::new();
+        queue.push(1);
+        assert_eq!(queue.pop(), Some(1));
+    }
+
+    #[test]
+    #[cfg(feature = "sixth")]
+    fn deque() {
+        use super::Deque;
+
+        let mut deque = Deque::new();
+        deque.push_back(1);
+        assert_eq!(deque.pop_front(), Some(1));
+    }
+
+    #[test]
+    #[cfg(feature = "seventh")]
+    fn concurrent_stack() {
+        use super::ConcurrentStack;
+
+        let stack = ConcurrentStack::new();
+        stack.push(1);
+        assert_eq!(stack.pop(), Some(1));
+    }
+
+    #[test]
+    #[cfg(feature = "eighth")]
+    fn concurrent_queue() {
+        use super::ConcurrentQueue;
+
+        let queue = ConcurrentQueue::new();
+        queue.enqueue(1);
+        assert_eq!(queue.dequeue(), Some(1));
+    }
+
+    #[test]
+    #[cfg(feature = "ninth")]
+    fn spsc_queue() {
+        use super::spsc_queue;
+        use super::SpscConsumer;
+        use super::SpscProducer;
+
+        let (mut producer, mut consumer): (SpscProducer<_>, SpscConsumer<_>) = spsc_queue(1);
+        producer.push(1).unwrap();
+        assert_eq!(consumer.pop(), Some(1));
+    }
+
+    #[test]
+    #[cfg(feature = "twelfth")]
+    fn skip_list_map() {
+        use super::SkipListMap;
+
+        let mut map = SkipListMap::new();
+        map.insert("key", 1);
+        assert_eq!(map.get("key"), Some(&1));
+    }
+
+    #[test]
+    #[cfg(feature = "thirteenth")]
+    fn indexed_list() {
+        use super::IndexedList;
+
+        let mut list = IndexedList::new();
+        list.push_back(2);
+        list.insert(0, 1);
+        assert_eq!(list.get(1), Some(&2));
+    }
+
+    #[test]
+    #[cfg(feature = "stacklist")]
+    fn scoped_list() {
+        use super::ScopedList;
+
+        ScopedList::push(None, 1, |list| assert_eq!(list.data, 1));
+    }
+}
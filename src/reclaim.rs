@@ -0,0 +1,440 @@
+// Freeing memory that other threads may still be reading, for the lock-free
+// lists (and anything else built on them). A node unlinked from a concurrent
+// structure can't be freed right away: another thread may have loaded a
+// pointer to it just before, and still be about to read it. Worse, if its
+// memory got reused for a new node, a compare and swap expecting the old
+// pointer could succeed when it shouldn't (the ABA problem).
+//
+// Each structure owns a Collector, and every operation on the structure
+// holds a Guard from it (`pin`) for as long as it uses pointers it loaded.
+// Unlinked nodes are handed to the guard (`defer_destroy`) instead of being
+// freed. The collector frees them with epoch-based reclamation:
+//
+// - There's a global epoch, a counter that only goes up. Pinning records
+//   the epoch the guard saw in a participant slot of its own, until the
+//   guard is dropped.
+// - The epoch only advances once every pinned guard has seen the current
+//   one. So while a guard is pinned, the epoch moves at most one step past
+//   the one it recorded.
+// - A retired node goes in the garbage bag of the epoch it was retired in.
+//   Two epochs later, every guard that was pinned back then, and so could
+//   have loaded the node before it was unlinked, is gone, and anyone who
+//   pinned since can't reach the node anymore. Whoever advances the epoch
+//   frees that bag.
+//
+// As long as nothing is freed while a guard could hold it, no address can be
+// reused under a guard either, which rules out ABA too. Unlike counting the
+// guards, this frees memory while guards keep overlapping, as long as none
+// of them stays pinned for long: only a guard that's never dropped holds the
+// garbage back. The participant slots are linked from the collector and
+// reused by later guards, so this needs neither std nor thread-locals.
+//
+// The orderings follow crossbeam-epoch's: pinning, retiring and advancing
+// each have a SeqCst fence, so that whichever of them comes first in the
+// single order of those fences is seen by the ones after it. That's what
+// stops the epoch from advancing past a guard that pinned concurrently
+// without noticing it, and a node from being retired in an epoch older than
+// one a guard that can still see it pinned in.
+
+use alloc::boxed::Box;
+use core::ptr;
+
+use crate::sync::atomic::fence;
+use crate::sync::atomic::AtomicBool;
+use crate::sync::atomic::AtomicPtr;
+use crate::sync::atomic::AtomicUsize;
+use crate::sync::atomic::Ordering;
+
+// Bags of garbage, by epoch modulo this. Three is enough: when the epoch
+// goes from `e` to `e + 1`, bag `e - 1` can be freed, bag `e` may still be
+// seen by guards pinned in `e`, and bag `e + 1` starts filling up.
+const BAGS: usize = 3;
+
+pub struct Collector {
+    // The global epoch
+    epoch: AtomicUsize,
+    // Every participant slot that was ever needed, linked through `next`.
+    // Slots are only freed along with the collector.
+    participants: AtomicPtr<Participant>,
+    // Retired objects, by the epoch they were retired in
+    bags: [AtomicPtr<Retired>; BAGS],
+}
+
+// Proof that we're pinned, so that what we load from the structure isn't
+// freed before the guard is gone
+pub struct Guard<'a> {
+    collector: &'a Collector,
+    participant: &'a Participant,
+}
+
+// Where a guard records the epoch it pinned in, while it's pinned
+struct Participant {
+    // The epoch shifted left by one, with the low bit set while pinned
+    state: AtomicUsize,
+    // Whether a guard is using the slot
+    in_use: AtomicBool,
+    next: *mut Participant,
+}
+
+const PINNED: usize = 1;
+
+// A retired object, and how to destroy it without knowing its type
+struct Retired {
+    ptr: *mut (),
+    destroy: unsafe fn(*mut ()),
+    next: *mut Retired,
+}
+
+// The retired objects are destroyed by whichever thread advances the epoch,
+// which is for `defer_destroy`'s callers to allow
+unsafe impl Send for Collector {}
+unsafe impl Sync for Collector {}
+
+impl Collector {
+    pub fn new() -> Self {
+        Collector {
+            epoch: AtomicUsize::new(0),
+            participants: AtomicPtr::new(ptr::null_mut()),
+            bags: [
+                AtomicPtr::new(ptr::null_mut()),
+                AtomicPtr::new(ptr::null_mut()),
+                AtomicPtr::new(ptr::null_mut()),
+            ],
+        }
+    }
+
+    pub fn pin(&self) -> Guard<'_> {
+        let participant = self.participant();
+        // Acquire, so that the store of the epoch we see happens before our
+        // fence: anyone retiring after it in the fence order sees it too
+        let epoch = self.epoch.load(Ordering::Acquire);
+        participant
+            .state
+            .store(epoch << 1 | PINNED, Ordering::Relaxed);
+        // Either whoever tries to advance the epoch next sees us pinned, or
+        // we see everything unlinked before they looked
+        fence(Ordering::SeqCst);
+        Guard {
+            collector: self,
+            participant,
+        }
+    }
+
+    // Claims a free participant slot, or adds one if they're all taken
+    fn participant(&self) -> &Participant {
+        let mut cur = self.participants.load(Ordering::Acquire);
+        while !cur.is_null() {
+            let participant = unsafe { &*cur };
+            if participant
+                .in_use
+                .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                return participant;
+            }
+            cur = participant.next;
+        }
+
+        let new = Box::into_raw(Box::new(Participant {
+            state: AtomicUsize::new(0),
+            in_use: AtomicBool::new(true),
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.participants.load(Ordering::Relaxed);
+        loop {
+            unsafe { (*new).next = head };
+            match self.participants.compare_exchange_weak(
+                head,
+                new,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return unsafe { &*new },
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    // Moves the epoch one step forward if every pinned guard has seen the
+    // current one, and frees the bag that just became safe to free
+    fn try_advance(&self) {
+        let epoch = self.epoch.load(Ordering::Acquire);
+        // Pairs with the fence in `pin`: a guard we don't see pinned below
+        // sees the epoch we loaded, or a later one
+        fence(Ordering::SeqCst);
+
+        let mut cur = self.participants.load(Ordering::Acquire);
+        while !cur.is_null() {
+            let participant = unsafe { &*cur };
+            let state = participant.state.load(Ordering::Relaxed);
+            if state & PINNED != 0 && state >> 1 != epoch {
+                return;
+            }
+            cur = participant.next;
+        }
+        // Whatever the guards that unpinned did happens before we free
+        // anything they may have loaded
+        fence(Ordering::Acquire);
+
+        // Only one of the threads advancing from here frees the bag
+        if self
+            .epoch
+            .compare_exchange(epoch, epoch + 1, Ordering::Release, Ordering::Relaxed)
+            .is_ok()
+        {
+            let expired = &self.bags[(epoch + BAGS - 1) % BAGS];
+            unsafe { destroy_chain(expired.swap(ptr::null_mut(), Ordering::Acquire)) };
+        }
+    }
+}
+
+impl Default for Collector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Collector {
+    fn drop(&mut self) {
+        // No guards can be left, as they borrow the collector
+        for bag in &self.bags {
+            unsafe { destroy_chain(bag.load(Ordering::Relaxed)) };
+        }
+        let mut cur = self.participants.load(Ordering::Relaxed);
+        while !cur.is_null() {
+            let participant = unsafe { Box::from_raw(cur) };
+            cur = participant.next;
+        }
+    }
+}
+
+impl<'a> Guard<'a> {
+    /// Destroys the box behind `ptr` once no guard can see it anymore.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must come from `Box::into_raw`, and be unreachable from the
+    /// structure already, so that guards pinned from now on can't load it.
+    /// It must not be retired twice, and must be fine to drop from any
+    /// thread holding a guard on this collector.
+    pub unsafe fn defer_destroy<T>(&self, ptr: *mut T) {
+        let collector = self.collector;
+        // After unlinking, so that any guard that could still load the node
+        // pinned in the epoch we see, or the one before
+        fence(Ordering::SeqCst);
+        let epoch = collector.epoch.load(Ordering::Acquire);
+
+        let retired = Box::into_raw(Box::new(Retired {
+            ptr: ptr.cast(),
+            destroy: destroy::<T>,
+            next: ptr::null_mut(),
+        }));
+        let bag = &collector.bags[epoch % BAGS];
+        let mut head = bag.load(Ordering::Relaxed);
+        loop {
+            (*retired).next = head;
+            match bag.compare_exchange_weak(head, retired, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => break,
+                Err(current) => head = current,
+            }
+        }
+
+        collector.try_advance();
+    }
+}
+
+impl<'a> Drop for Guard<'a> {
+    fn drop(&mut self) {
+        // Release, so that everything we did while pinned happens before
+        // whoever advances the epoch past us frees what we loaded
+        self.participant.state.store(0, Ordering::Release);
+        self.participant.in_use.store(false, Ordering::Release);
+        self.collector.try_advance();
+    }
+}
+
+unsafe fn destroy<T>(ptr: *mut ()) {
+    drop(Box::from_raw(ptr.cast::<T>()));
+}
+
+unsafe fn destroy_chain(mut retired: *mut Retired) {
+    while !retired.is_null() {
+        let boxed = Box::from_raw(retired);
+        (boxed.destroy)(boxed.ptr);
+        retired = boxed.next;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::mem;
+    use std::sync::atomic;
+
+    use super::Collector;
+    use crate::sync::model;
+    use crate::sync::thread;
+    use crate::sync::Arc;
+
+    // Counts how many of these were dropped
+    struct Flag(std::sync::Arc<atomic::AtomicUsize>);
+
+    impl Drop for Flag {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+    }
+
+    fn flag() -> (*mut Flag, std::sync::Arc<atomic::AtomicUsize>) {
+        let dropped = std::sync::Arc::new(atomic::AtomicUsize::new(0));
+        (Box::into_raw(Box::new(Flag(dropped.clone()))), dropped)
+    }
+
+    // Only for handing a Flag over to another thread
+    struct SendPtr(*mut Flag);
+
+    unsafe impl Send for SendPtr {}
+
+    fn dropped(counter: &atomic::AtomicUsize) -> usize {
+        counter.load(atomic::Ordering::Relaxed)
+    }
+
+    // Retiring advances the epoch once, and unpinning once more, which is
+    // the two epochs the object has to wait
+    #[test]
+    fn alone() {
+        model(|| {
+            let collector = Collector::new();
+            let (ptr, counter) = flag();
+            let guard = collector.pin();
+            unsafe { guard.defer_destroy(ptr) };
+            assert_eq!(dropped(&counter), 0);
+            mem::drop(guard);
+            assert_eq!(dropped(&counter), 1);
+        });
+    }
+
+    #[test]
+    fn waits_for_guards() {
+        model(|| {
+            let collector = Collector::new();
+            let (ptr, counter) = flag();
+
+            let first = collector.pin();
+            let second = collector.pin();
+            unsafe { second.defer_destroy(ptr) };
+            assert_eq!(dropped(&counter), 0);
+
+            // The other guard is still pinned in the epoch the object was
+            // retired in, which holds the epoch back
+            mem::drop(second);
+            assert_eq!(dropped(&counter), 0);
+            mem::drop(first);
+            assert_eq!(dropped(&counter), 1);
+        });
+    }
+
+    // Whatever's still in the bags goes with the collector, even if a guard
+    // was leaked
+    #[test]
+    fn drop() {
+        model(|| {
+            let collector = Collector::new();
+            let (ptr, counter) = flag();
+
+            let first = collector.pin();
+            let second = collector.pin();
+            unsafe { second.defer_destroy(ptr) };
+            mem::drop(second);
+            mem::forget(first);
+            mem::drop(collector);
+            assert_eq!(dropped(&counter), 1);
+        });
+    }
+
+    // Another thread retiring while we're pinned, in every order loom can
+    // come up with: the object is destroyed exactly once, two epochs later
+    // at the latest
+    #[test]
+    fn threads() {
+        model(|| {
+            let collector = Arc::new(Collector::new());
+            let (ptr, counter) = flag();
+            let ptr = SendPtr(ptr);
+
+            let other = collector.clone();
+            let handle = thread::spawn(move || {
+                let guard = other.pin();
+                unsafe { guard.defer_destroy({ ptr }.0) };
+            });
+            let guard = collector.pin();
+            mem::drop(guard);
+            handle.join().unwrap();
+            assert!(dropped(&counter) <= 1);
+
+            // Once nobody's pinned anymore, every guard dropped advances the
+            // epoch, so two are enough
+            mem::drop(collector.pin());
+            mem::drop(collector.pin());
+            assert_eq!(dropped(&counter), 1);
+            mem::drop(collector);
+            assert_eq!(dropped(&counter), 1);
+        });
+    }
+
+    // Threads swapping objects in and out of a shared slot, like pushes and
+    // pops, and always keeping a guard pinned: a new one before dropping the
+    // old one. There's never a moment without guards, yet what they retire
+    // is freed while they keep going. Too big for loom.
+    #[cfg(not(loom))]
+    #[test]
+    fn overlapping_guards() {
+        use std::sync::atomic::AtomicBool;
+        use std::sync::atomic::AtomicPtr;
+        use std::time::Duration;
+        use std::time::Instant;
+
+        const THREADS: usize = 4;
+
+        let collector = Arc::new(Collector::new());
+        let (first, counter) = flag();
+        let slot = Arc::new(AtomicPtr::new(first));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let handles: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let collector = collector.clone();
+                let slot = slot.clone();
+                let stop = stop.clone();
+                let counter = counter.clone();
+                thread::spawn(move || {
+                    let mut created = 0;
+                    let mut guard = collector.pin();
+                    while !stop.load(atomic::Ordering::Relaxed) {
+                        let next = collector.pin();
+                        mem::drop(mem::replace(&mut guard, next));
+                        let new = Box::into_raw(Box::new(Flag(counter.clone())));
+                        let old = slot.swap(new, atomic::Ordering::AcqRel);
+                        unsafe { guard.defer_destroy(old) };
+                        created += 1;
+                    }
+                    created
+                })
+            })
+            .collect();
+
+        // Waiting for a good number of frees, which would never come if
+        // overlapping guards held everything back
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while dropped(&counter) < 10_000 {
+            assert!(Instant::now() < deadline, "nothing was freed");
+            std::thread::yield_now();
+        }
+        stop.store(true, atomic::Ordering::Relaxed);
+        let created: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+
+        // And nothing is freed twice, or left behind
+        unsafe { mem::drop(Box::from_raw(slot.load(atomic::Ordering::Relaxed))) };
+        mem::drop(collector);
+        assert_eq!(dropped(&counter), created + 1);
+    }
+}
@@ -4,6 +4,11 @@
 // A major change in this version is the modeling of the Link as an option of an
 // allocated node which is simpler and more standard. As a result, there's no
 // longer an Empty case.
+use alloc::boxed::Box;
+use core::mem::size_of;
+
+use crate::traits::MemSize;
+
 pub struct List<T> {
     head: Link<T>,
 }
@@ -47,30 +52,67 @@ impl<T> List<T> {
     }
 
     pub fn peek(&self) -> Option<&T> {
-        self.head.as_ref().map(|x| &(*x).elem)
+        self.head.as_ref().map(|x| &x.elem)
     }
 
     pub fn peek_mut(&mut self) -> Option<&mut T> {
         self.head.as_mut().map(|x| &mut x.elem)
     }
 
-    pub fn into_iter(self) -> IntoIter<T> {
-        IntoIter(self)
-    }
-
-    pub fn iter<'a>(&'a self) -> Iter<'a, T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             next: self.head.as_deref(),
         }
     }
 
-    pub fn iter_mut<'a>(&'a mut self) -> IterMut<'a, T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut {
             next: self.head.as_deref_mut(),
         }
     }
 }
 
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Same as first: a Box per element, and nothing else
+impl<T> MemSize for List<T> {
+    fn node_count(&self) -> usize {
+        self.iter().count()
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        size_of::<Node<T>>()
+    }
+}
+
+impl<T> IntoIterator for List<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+// The first element ends up on top, so iterating the list gives the elements
+// back in the order they came in. Pushing them would reverse them, so we
+// append each one after the last instead.
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = List::new();
+        let mut tail = &mut list.head;
+        for elem in iter {
+            let node = tail.insert(Box::new(Node { elem, next: None }));
+            tail = &mut node.next;
+        }
+        list
+    }
+}
+
 impl<T> Iterator for IntoIter<T> {
     type Item = T;
 
@@ -116,6 +158,146 @@ impl<T> Drop for List<T> {
     }
 }
 
+// Stacks are serialized as a sequence from the top down, the order iter()
+// gives, and come back with the first element on top.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use alloc::boxed::Box;
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::de::SeqAccess;
+    use serde::de::Visitor;
+    use serde::ser::SerializeSeq;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    use super::List;
+    use super::Node;
+
+    impl<T: Serialize> Serialize for List<T> {
+        // Our Iter doesn't know its length, which collect_seq would leave
+        // out, so we count the elements first
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(Some(self.iter().count()))?;
+            for elem in self.iter() {
+                seq.serialize_element(elem)?;
+            }
+            seq.end()
+        }
+    }
+
+    impl<'de, T: Deserialize<'de>> Deserialize<'de> for List<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_seq(ListVisitor(PhantomData))
+        }
+    }
+
+    struct ListVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for ListVisitor<T> {
+        type Value = List<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a sequence")
+        }
+
+        // Same as FromIterator, we append after the last node rather than
+        // pushing, which would reverse the elements
+        fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+            let mut list = List::new();
+            let mut tail = &mut list.head;
+            while let Some(elem) = seq.next_element()? {
+                let node = tail.insert(Box::new(Node { elem, next: None }));
+                tail = &mut node.next;
+            }
+            Ok(list)
+        }
+    }
+}
+
+// Lists of any length, with the elements in the order iter() gives, so a
+// fuzzer's input reads the same as the list's serialized form
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl {
+    use arbitrary::Arbitrary;
+    use arbitrary::Result;
+    use arbitrary::Unstructured;
+
+    use super::List;
+
+    impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for List<T> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_iter()?.collect()
+        }
+
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_take_rest_iter()?.collect()
+        }
+    }
+}
+
+// The nodes are Boxes, so the list could cross threads, but there's no way
+// to cut a borrowed chain in two without a reference to every node on
+// the way: each half would need to own the rest of the list. So we make
+// the cuts up front instead, by collecting what we hand out into a Vec,
+// which rayon then splits as it likes. That's one O(n) walk, same as
+// finding a single cut would be.
+#[cfg(feature = "rayon")]
+mod rayon_impl {
+    use alloc::vec::Vec;
+
+    use rayon::iter::FromParallelIterator;
+    use rayon::iter::IntoParallelIterator;
+    use rayon::iter::ParallelIterator;
+    use rayon::vec;
+
+    use super::List;
+
+    impl<T: Send> IntoParallelIterator for List<T> {
+        type Iter = vec::IntoIter<T>;
+        type Item = T;
+
+        fn into_par_iter(self) -> Self::Iter {
+            self.into_iter().collect::<Vec<_>>().into_par_iter()
+        }
+    }
+
+    impl<'a, T: Sync> IntoParallelIterator for &'a List<T> {
+        type Iter = vec::IntoIter<&'a T>;
+        type Item = &'a T;
+
+        fn into_par_iter(self) -> Self::Iter {
+            self.iter().collect::<Vec<_>>().into_par_iter()
+        }
+    }
+
+    impl<'a, T: Send> IntoParallelIterator for &'a mut List<T> {
+        type Iter = vec::IntoIter<&'a mut T>;
+        type Item = &'a mut T;
+
+        fn into_par_iter(self) -> Self::Iter {
+            self.iter_mut().collect::<Vec<_>>().into_par_iter()
+        }
+    }
+
+    // Collected in order, then linked up front to back like FromIterator
+    impl<T: Send> FromParallelIterator<T> for List<T> {
+        fn from_par_iter<I>(par_iter: I) -> Self
+        where
+            I: IntoParallelIterator<Item = T>,
+        {
+            par_iter
+                .into_par_iter()
+                .collect::<Vec<_>>()
+                .into_iter()
+                .collect()
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::List;
@@ -155,7 +337,10 @@ mod test {
         assert_eq!(list.peek(), Some(&3));
         assert_eq!(list.peek_mut(), Some(&mut 3));
 
-        list.peek_mut().map(|value| *value = 4);
+        if let Some(value) = list.peek_mut() {
+            *value = 4;
+        }
+        assert_eq!(list.peek(), Some(&4));
     }
 
     #[test]
@@ -197,4 +382,55 @@ mod test {
         assert_eq!(iter.next(), Some(&mut 2));
         assert_eq!(iter.next(), Some(&mut 1));
     }
+
+    #[test]
+    fn from_iter() {
+        let list: List<_> = (1..=3).collect();
+        assert_eq!(list.iter().collect::<Vec<_>>(), &[&1, &2, &3]);
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), &[1, 2, 3]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde::de::value::Error;
+        use serde::de::value::SeqDeserializer;
+        use serde::Deserialize;
+        use serde_test::assert_ser_tokens;
+        use serde_test::Token;
+
+        let list: List<_> = (1..=3).collect();
+        assert_ser_tokens(
+            &list,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::I32(1),
+                Token::I32(2),
+                Token::I32(3),
+                Token::SeqEnd,
+            ],
+        );
+
+        // The top comes first both ways
+        let seq = SeqDeserializer::<_, Error>::new(vec![1, 2, 3].into_iter());
+        let mut list = List::<i32>::deserialize(seq).unwrap();
+        assert_eq!(list.pop(), Some(1));
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), &[2, 3]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn rayon() {
+        use rayon::iter::IntoParallelIterator;
+        use rayon::iter::IntoParallelRefIterator;
+        use rayon::iter::IntoParallelRefMutIterator;
+        use rayon::iter::ParallelIterator;
+
+        let mut list: List<u64> = (0..10_000u64).into_par_iter().collect();
+        assert!(list.iter().copied().eq(0..10_000));
+        assert_eq!(list.par_iter().sum::<u64>(), (0..10_000).sum());
+        list.par_iter_mut().for_each(|x| *x *= 2);
+        let doubled: Vec<u64> = list.into_par_iter().collect();
+        assert_eq!(doubled, (0..10_000).map(|x| x * 2).collect::<Vec<_>>());
+    }
 }
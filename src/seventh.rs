@@ -0,0 +1,335 @@
+// So far every list has been for one thread at a time (fifth's channel only
+// shares its queue behind a mutex). This one is a stack that any number of
+// threads can push to and pop from at once, without locks: Treiber's stack.
+//
+// The stack is just an atomic pointer to the top node. Pushing links the new
+// node to the current top and swings the pointer to it with a compare and
+// swap, and popping swings it from the top node to the one below, retrying
+// whenever another thread got in first. That's the easy part.
+//
+// The hard part is freeing what we pop. Between loading the top node and
+// swinging the pointer, a popper reads the node's `next`, so another thread
+// that pops the same node in the meantime can't free it right away: the
+// first popper would read freed memory. Worse, if the memory got reused for
+// a new node that ended up on top again, the first popper's compare and swap
+// would succeed with a stale `next` (the ABA problem). Pushers have the same
+// problem in a milder form: their exchange would succeed, and link the new
+// node to the right address, but through a pointer to the freed node, which
+// Rust doesn't let us use even if the address is the same.
+//
+// `reclaim` takes care of all of it: both `push` and `pop` hold a guard from
+// the stack's collector, and popped nodes are handed to it rather than freed.
+
+use alloc::boxed::Box;
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::mem::ManuallyDrop;
+use core::ptr;
+
+use crate::reclaim::Collector;
+use crate::sync::atomic::AtomicPtr;
+use crate::sync::atomic::Ordering;
+use crate::traits::MemSize;
+
+pub struct Stack<T> {
+    head: AtomicPtr<Node<T>>,
+    // Frees popped nodes once no other thread can be looking at them
+    collector: Collector,
+    // We own the elements in the nodes
+    _marker: PhantomData<T>,
+}
+
+struct Node<T> {
+    // Taken out by whoever pops the node, and never dropped in place: the
+    // node itself may be freed much later
+    elem: ManuallyDrop<T>,
+    // Written before the node is pushed, and never again, so that poppers
+    // racing on the node always read the same thing
+    next: *mut Node<T>,
+}
+
+// Sharing the stack lets threads hand elements to each other, hence Send
+// for both, like a Mutex
+unsafe impl<T: Send> Send for Stack<T> {}
+unsafe impl<T: Send> Sync for Stack<T> {}
+
+impl<T> Stack<T> {
+    pub fn new() -> Self {
+        Stack {
+            head: AtomicPtr::new(ptr::null_mut()),
+            collector: Collector::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn push(&self, elem: T) {
+        let node = Box::into_raw(Box::new(Node {
+            elem: ManuallyDrop::new(elem),
+            next: ptr::null_mut(),
+        }));
+
+        // Pinned like a popper, so that the top we link to can't be freed
+        // before we're done
+        let _guard = self.collector.pin();
+
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            // Nobody else can see the node until the exchange succeeds
+            unsafe { (*node).next = head };
+            // Release, so that whoever pops the node sees it (and its
+            // element) fully written
+            match self
+                .head
+                .compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed)
+            {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let guard = self.collector.pin();
+
+        let mut head = self.head.load(Ordering::Acquire);
+        loop {
+            if head.is_null() {
+                return None;
+            }
+
+            // The node can't be freed while we're pinned
+            let next = unsafe { (*head).next };
+            match self
+                .head
+                .compare_exchange_weak(head, next, Ordering::Acquire, Ordering::Acquire)
+            {
+                Ok(_) => break,
+                Err(current) => head = current,
+            }
+        }
+
+        // We unlinked the node, so the element is ours alone, and the node
+        // is ours to retire
+        let elem = unsafe { ManuallyDrop::take(&mut (*head).elem) };
+        unsafe { guard.defer_destroy(head) };
+        Some(elem)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire).is_null()
+    }
+}
+
+impl<T> Default for Stack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Only a snapshot while other threads push and pop, like `is_empty`. Popped
+// nodes the collector hasn't freed yet don't count, as they're no longer
+// part of the stack.
+impl<T> MemSize for Stack<T> {
+    fn node_count(&self) -> usize {
+        // Pinned, so that the nodes we walk can't be freed under us
+        let _guard = self.collector.pin();
+        let mut count = 0;
+        let mut node = self.head.load(Ordering::Acquire);
+        while !node.is_null() {
+            count += 1;
+            node = unsafe { (*node).next };
+        }
+        count
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        size_of::<Node<T>>()
+    }
+}
+
+impl<T> Drop for Stack<T> {
+    fn drop(&mut self) {
+        // Nobody else has the stack anymore, so we can just walk it. The
+        // collector frees whatever popped nodes are still pending.
+        let mut node = self.head.load(Ordering::Relaxed);
+        while !node.is_null() {
+            let mut boxed = unsafe { Box::from_raw(node) };
+            unsafe { ManuallyDrop::drop(&mut boxed.elem) };
+            node = boxed.next;
+        }
+    }
+}
+
+pub struct IntoIter<T>(Stack<T>);
+
+impl<T> IntoIterator for Stack<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
+}
+
+impl<T> FromIterator<T> for Stack<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let stack = Stack::new();
+        for elem in iter {
+            stack.push(elem);
+        }
+        stack
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::mem::size_of;
+
+    use super::Stack;
+    use crate::sync::model;
+    use crate::sync::thread;
+    use crate::sync::Arc;
+    use crate::traits::MemSize;
+
+    #[test]
+    fn basics() {
+        model(|| {
+            let stack = Stack::new();
+            assert!(stack.is_empty());
+            assert_eq!(stack.pop(), None);
+
+            stack.push(1);
+            stack.push(2);
+            assert!(!stack.is_empty());
+            assert_eq!(stack.pop(), Some(2));
+            stack.push(3);
+            assert_eq!(stack.pop(), Some(3));
+            assert_eq!(stack.pop(), Some(1));
+            assert_eq!(stack.pop(), None);
+            assert!(stack.is_empty());
+        });
+    }
+
+    #[test]
+    fn into_iter() {
+        model(|| {
+            let stack: Stack<_> = (0..4).map(Box::new).collect();
+            let elems: Vec<_> = stack.into_iter().map(|x| *x).collect();
+            assert_eq!(elems, [3, 2, 1, 0]);
+        });
+    }
+
+    #[test]
+    fn mem_size() {
+        model(|| {
+            let stack = Stack::new();
+            assert_eq!(stack.heap_bytes(), 0);
+            for i in 0..4 {
+                stack.push(i);
+            }
+            stack.pop();
+            // The element and the link
+            let node = 2 * size_of::<usize>();
+            assert_eq!(stack.node_count(), 3);
+            assert_eq!(stack.heap_bytes(), 3 * node);
+        });
+    }
+
+    // Whatever's left, pushed or waiting to be freed, goes with the stack
+    #[test]
+    fn drop() {
+        model(|| {
+            let stack = Stack::new();
+            for i in 0..4 {
+                stack.push(Box::new(i));
+            }
+            stack.pop();
+        });
+    }
+
+    // Two threads racing on both ends of a pop, which loom runs through
+    // every order: each element comes out exactly once, and the nodes are
+    // freed whichever thread ends up last
+    #[test]
+    fn racing_pops() {
+        model(|| {
+            let stack = Arc::new(Stack::new());
+            stack.push(Box::new(1));
+            stack.push(Box::new(2));
+
+            let other = stack.clone();
+            let handle = thread::spawn(move || other.pop().map(|x| *x));
+            let mine = stack.pop().map(|x| *x);
+            let theirs = handle.join().unwrap();
+
+            let mut popped = [mine.unwrap(), theirs.unwrap()];
+            popped.sort();
+            assert_eq!(popped, [1, 2]);
+            assert!(stack.is_empty());
+        });
+    }
+
+    #[test]
+    fn push_and_pop() {
+        model(|| {
+            let stack = Arc::new(Stack::new());
+            stack.push(Box::new(1));
+
+            let other = stack.clone();
+            let handle = thread::spawn(move || {
+                other.push(Box::new(2));
+                other.pop().map(|x| *x)
+            });
+            let mine = stack.pop().map(|x| *x);
+            let theirs = handle.join().unwrap();
+
+            let mut popped: Vec<_> = [mine, theirs].into_iter().flatten().collect();
+            popped.extend(stack.pop().map(|x| *x));
+            popped.sort();
+            assert_eq!(popped, [1, 2]);
+        });
+    }
+
+    // Far too many interleavings for loom, but plenty of real contention
+    #[cfg(not(loom))]
+    #[test]
+    fn threads() {
+        const THREADS: usize = 4;
+        let per_thread = if cfg!(miri) { 50 } else { 10_000 };
+
+        let stack = Arc::new(Stack::new());
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let stack = stack.clone();
+                thread::spawn(move || {
+                    let mut popped = Vec::new();
+                    for i in 0..per_thread {
+                        stack.push(Box::new(t * per_thread + i));
+                        if i % 2 == 1 {
+                            popped.extend(stack.pop().map(|x| *x));
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect();
+
+        let mut popped: Vec<usize> = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect();
+        while let Some(elem) = stack.pop() {
+            popped.push(*elem);
+        }
+        popped.sort_unstable();
+        assert_eq!(popped, (0..THREADS * per_thread).collect::<Vec<_>>());
+    }
+}
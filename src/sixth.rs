@@ -1,15 +1,68 @@
-use std::cmp::Ordering;
-use std::fmt;
-use std::fmt::Debug;
-use std::hash::Hash;
-use std::hash::Hasher;
-use std::marker::PhantomData;
-use std::ptr::NonNull;
-
-pub struct LinkedList<T> {
+// Everything here only needs `core` and `alloc` (through our allocator
+// helpers), so the deque is available in `no_std` builds of the crate.
+//
+// Links are NonNull all the way, and we never turn pointers into integers
+// or back: comparing nodes compares the pointers, and the pool chains nodes
+// through their own links rather than stashing addresses anywhere. So every
+// pointer keeps the provenance of the allocation it came from, which the
+// `strict-provenance` feature makes the compiler check for us.
+#![cfg_attr(
+    feature = "strict-provenance",
+    deny(fuzzy_provenance_casts, lossy_provenance_casts)
+)]
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::fmt::Debug;
+use core::hash::Hash;
+use core::hash::Hasher;
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::mem::MaybeUninit;
+use core::ops::Bound;
+use core::ops::RangeBounds;
+use core::ptr;
+use core::ptr::NonNull;
+
+use crate::allocator;
+use crate::allocator::AllocError;
+use crate::allocator::Allocator;
+use crate::allocator::Global;
+use crate::traits::MemSize;
+
+#[cfg(feature = "rayon")]
+pub mod parallel;
+
+// Nodes come from `A`, the global allocator unless the user picks another one
+// through `new_in` (with the `allocator_api` feature on nightly), e.g. an arena
+// that can be torn down all at once. Lists only take each other's nodes as
+// they are when their allocators are clones of each other (e.g. after a
+// split), otherwise appending or splicing moves the elements.
+//
+// Unlinked nodes aren't freed right away, but kept in a pool to be reused by
+// the next push, so a list that goes up and down in size (or was reserved
+// upfront) doesn't hit the allocator all the time. Like a Vec's capacity, the
+// pool is only given back on `shrink_to_fit` or drop.
+pub struct LinkedList<T, A: Allocator = Global> {
     front: Link<T>,
     back: Link<T>,
     len: usize,
+    // The pooled nodes, chained through their `back` links. Their elements
+    // are uninitialized!
+    free: Link<T>,
+    free_len: usize,
+    // What NodeRefs check to tell whether their node is still here, see
+    // below. The id is ours alone, and the generation is the one the last
+    // node we filled got.
+    id: usize,
+    generation: u64,
+    // Lists whose allocators are clones of each other's share this, which
+    // tells us when we can take each other's nodes, see `can_adopt`
+    alloc_id: usize,
+    alloc: A,
     /// Adding a phantom data field indicates ownership over values of type T
     /// (behaving as if we stored them) and controls the type variance. It's
     /// even more important to signal this when we use `NonNull` or pointers.
@@ -24,38 +77,157 @@ type Link<T> = Option<NonNull<Node<T>>>;
 struct Node<T> {
     front: Link<T>,
     back: Link<T>,
+    // Every node gets a new generation from its list when it's filled, so we
+    // can tell a NodeRef to a node that got reused apart from a valid one.
+    // Pooled nodes are on generation 0, which no element ever gets, and so
+    // are the ones drain takes away.
+    generation: u64,
     elem: T,
 }
 
-impl<T> Drop for LinkedList<T> {
+impl<T> Node<T> {
+    fn new(elem: T, generation: u64) -> Self {
+        Node {
+            front: None,
+            back: None,
+            generation,
+            elem,
+        }
+    }
+}
+
+// Every list gets an id that no list had before it, so a NodeRef made by
+// another list (or by us, before we forgot about our handles) can't pass for
+// one of ours. We'd rather panic than hand out an id twice.
+fn next_id() -> usize {
+    use core::sync::atomic::AtomicUsize;
+    use core::sync::atomic::Ordering;
+
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    NEXT.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |id| id.checked_add(1))
+        .expect("ran out of list ids")
+}
+
+impl<T, A: Allocator> Drop for LinkedList<T, A> {
     fn drop(&mut self) {
-        while let Some(_) = self.pop_front() {}
+        while self.pop_front().is_some() {}
+        self.shrink_to_fit();
     }
 }
 
 impl<T> LinkedList<T> {
     pub fn new() -> Self {
+        Self::new_in(Global)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+}
+
+impl<T, A: Allocator> LinkedList<T, A> {
+    pub fn new_in(alloc: A) -> Self {
+        // A new allocator is as new as a new list, so one id does for both
+        let id = next_id();
         Self {
             front: None,
             back: None,
             len: 0,
+            free: None,
+            free_len: 0,
+            id,
+            generation: 0,
+            alloc_id: id,
+            alloc,
             _protection: PhantomData,
         }
     }
 
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        let mut list = Self::new_in(alloc);
+        list.reserve(capacity);
+        list
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    // How many elements we can hold without allocating: the ones we have,
+    // plus one per pooled node
+    pub fn capacity(&self) -> usize {
+        self.len + self.free_len
+    }
+
+    // Makes sure the next `additional` pushes don't allocate
+    pub fn reserve(&mut self, additional: usize) {
+        while self.free_len < additional {
+            // A node's worth of memory, without an element in it
+            let node = allocator::into_raw_in(MaybeUninit::<Node<T>>::uninit(), &self.alloc);
+            unsafe { self.pool(NonNull::new_unchecked(node.cast())) };
+        }
+        self.strict_check();
+    }
+
+    // Gives all the pooled nodes back to the allocator. NodeRefs to them
+    // would point at freed memory, so we forget about all our handles.
+    pub fn shrink_to_fit(&mut self) {
+        if self.free.is_some() {
+            self.forget_handles();
+        }
+        while let Some(node) = self.free {
+            unsafe {
+                self.free = (*node.as_ptr()).back;
+                // Freeing it as uninitialized, so we don't drop an element
+                // that isn't there
+                allocator::from_raw_in(node.as_ptr().cast::<MaybeUninit<Node<T>>>(), &self.alloc);
+            }
+        }
+        self.free_len = 0;
+        self.strict_check();
+    }
+
+    // Puts a node with no element in it in the pool
+    unsafe fn pool(&mut self, node: NonNull<Node<T>>) {
+        // Not going through a &mut Node, as there's no valid T in it
+        ptr::addr_of_mut!((*node.as_ptr()).back).write(self.free);
+        ptr::addr_of_mut!((*node.as_ptr()).generation).write(0);
+        self.free = Some(node);
+        self.free_len += 1;
+    }
+
+    // Moves other's pooled nodes to our pool. Only for a list on a clone of
+    // our allocator, see `can_adopt`.
+    unsafe fn adopt_pool(&mut self, other: &mut Self) {
+        while let Some(node) = other.free {
+            other.free = ptr::addr_of!((*node.as_ptr()).back).read();
+            other.free_len -= 1;
+            self.pool(node);
+        }
+        self.strict_check();
+        other.strict_check();
+    }
+
+    // Moves the element out of a node we already unlinked, and pools the node
+    unsafe fn free_node(&mut self, node: NonNull<Node<T>>) -> T {
+        let elem = ptr::read(ptr::addr_of!((*node.as_ptr()).elem));
+        self.pool(node);
+        elem
+    }
+
     pub fn is_empty(&self) -> bool {
         self.len == 0
     }
 
     pub fn clear(&mut self) {
-        while let Some(_) = self.pop_front() {}
+        while self.pop_front().is_some() {}
     }
 
     pub fn front(&self) -> Option<&T> {
         unsafe { self.front.map(|node| &(*node.as_ptr()).elem) }
     }
 
-    pub fn front_mut(&self) -> Option<&mut T> {
+    pub fn front_mut(&mut self) -> Option<&mut T> {
         unsafe { self.front.map(|node| &mut (*node.as_ptr()).elem) }
     }
 
@@ -67,13 +239,31 @@ impl<T> LinkedList<T> {
         unsafe { self.back.map(|node| &mut (*node.as_ptr()).elem) }
     }
 
+    pub fn contains(&self, elem: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|x| x == elem)
+    }
+
+    // The first element matching `pred`, from the front
+    pub fn find<P>(&self, mut pred: P) -> Option<&T>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.iter().find(|x| pred(x))
+    }
+
+    pub fn find_mut<P>(&mut self, mut pred: P) -> Option<&mut T>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.iter_mut().find(|x| pred(x))
+    }
+
     pub fn push_front(&mut self, elem: T) {
         unsafe {
-            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-                front: None,
-                back: None,
-                elem,
-            })));
+            let new = self.new_node(elem);
 
             if let Some(old) = self.front {
                 // Put new front before the old one
@@ -93,16 +283,13 @@ impl<T> LinkedList<T> {
             // so it's safe...
             self.len += 1;
         }
+        self.strict_check();
     }
 
     pub fn push_back(&mut self, elem: T) {
         // SAFETY: it's a linked-list, what do you want?
         unsafe {
-            let new = NonNull::new_unchecked(Box::into_raw(Box::new(Node {
-                back: None,
-                front: None,
-                elem,
-            })));
+            let new = self.new_node(elem);
             if let Some(old) = self.back {
                 // Put the new back before the old one
                 (*old.as_ptr()).back = Some(new);
@@ -116,17 +303,196 @@ impl<T> LinkedList<T> {
             self.back = Some(new);
             self.len += 1;
         }
+        self.strict_check();
+    }
+
+    // Same as push_front/push_back, but running out of memory hands the
+    // element back instead of aborting. Pooled nodes never fail.
+    pub fn try_push_front(&mut self, elem: T) -> Result<(), AllocError<T>> {
+        let node = self.try_new_node(elem)?;
+        unsafe { self.link_front(node) };
+        Ok(())
+    }
+
+    pub fn try_push_back(&mut self, elem: T) -> Result<(), AllocError<T>> {
+        let node = self.try_new_node(elem)?;
+        unsafe { self.link_back(node) };
+        Ok(())
+    }
+
+    // Same as push_front/push_back, but returning a handle to the new node,
+    // see NodeRef below
+    pub fn push_front_ref(&mut self, elem: T) -> NodeRef<T> {
+        self.push_front(elem);
+        NodeRef::new(self.id, self.front.unwrap())
+    }
+
+    pub fn push_back_ref(&mut self, elem: T) -> NodeRef<T> {
+        self.push_back(elem);
+        NodeRef::new(self.id, self.back.unwrap())
+    }
+
+    // Removes the node behind `node` in O(1), returning its element, or None
+    // if the handle doesn't work anymore (see NodeRef below)
+    pub fn remove_node(&mut self, node: NodeRef<T>) -> Option<T> {
+        let node = self.lookup(node)?;
+        unsafe {
+            self.unlink(node);
+            Some(self.free_node(node))
+        }
+    }
+
+    // Moves the node behind `node` to the front in O(1), returning whether
+    // the handle still worked. The element stays where it is in memory, and
+    // `node` stays valid.
+    pub fn move_node_to_front(&mut self, node: NodeRef<T>) -> bool {
+        let Some(node) = self.lookup(node) else {
+            return false;
+        };
+        unsafe {
+            self.unlink(node);
+            self.link_front(node);
+        }
+        true
+    }
+
+    // Same as move_node_to_front, but to the back
+    pub fn move_node_to_back(&mut self, node: NodeRef<T>) -> bool {
+        let Some(node) = self.lookup(node) else {
+            return false;
+        };
+        unsafe {
+            self.unlink(node);
+            self.link_back(node);
+        }
+        true
+    }
+
+    // The node behind a handle, if it's still holding the element the handle
+    // was made for. Nodes only leave us for the pool (through a Drain or not),
+    // which puts them on generation 0, or along with a new id. So once the id
+    // matches, the node is still ours (and allocated), and its generation is
+    // safe to read.
+    fn lookup(&self, node: NodeRef<T>) -> Link<T> {
+        if node.list != self.id {
+            return None;
+        }
+        // Not going through a &Node, as a pooled one has no valid T in it
+        let generation = unsafe { ptr::addr_of!((*node.node.as_ptr()).generation).read() };
+        (generation == node.generation).then_some(node.node)
+    }
+
+    // Whether other's nodes can become ours as they are. We'd free them
+    // through our allocator from then on, so it must be the one they came
+    // from: a clone of other's, which the Allocator contract says is as good
+    // as the original, or any instance of an allocator with no state at all,
+    // like Global, as there's nothing to tell those apart. We can't compare
+    // any other instances, two arenas say, so their elements get moved into
+    // nodes of our own instead.
+    fn can_adopt(&self, other: &Self) -> bool {
+        size_of::<A>() == 0 || self.alloc_id == other.alloc_id
+    }
+
+    // Called whenever nodes of ours may end up freed or in another list, as
+    // NodeRefs to them must stop working. We can't tell which nodes those
+    // are without walking, so all our handles go.
+    fn forget_handles(&mut self) {
+        self.id = next_id();
+    }
+
+    // Makes a detached node, preferably out of the pool
+    fn new_node(&mut self, elem: T) -> NonNull<Node<T>> {
+        self.generation += 1;
+        let node = Node::new(elem, self.generation);
+        match self.free {
+            Some(free) => unsafe {
+                self.free = (*free.as_ptr()).back;
+                self.free_len -= 1;
+                free.as_ptr().write(node);
+                free
+            },
+            None => unsafe { NonNull::new_unchecked(allocator::into_raw_in(node, &self.alloc)) },
+        }
+    }
+
+    // Same as new_node, but fallible
+    fn try_new_node(&mut self, elem: T) -> Result<NonNull<Node<T>>, AllocError<T>> {
+        if self.free.is_some() {
+            return Ok(self.new_node(elem));
+        }
+        self.generation += 1;
+        let node = Node::new(elem, self.generation);
+        match allocator::try_into_raw_in(node, &self.alloc) {
+            Ok(node) => unsafe { Ok(NonNull::new_unchecked(node)) },
+            Err(node) => Err(AllocError(node.elem)),
+        }
+    }
+
+    // Detaches a node of ours from its neighbours, leaving it unlinked but
+    // still allocated
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) {
+        let prev = (*node.as_ptr()).front.take();
+        let next = (*node.as_ptr()).back.take();
+        match prev {
+            Some(prev) => (*prev.as_ptr()).back = next,
+            None => self.front = next,
+        }
+        match next {
+            Some(next) => (*next.as_ptr()).front = prev,
+            None => self.back = prev,
+        }
+        self.len -= 1;
+        self.strict_check();
+    }
+
+    // The other way around, links a detached node at either end
+    unsafe fn link_front(&mut self, node: NonNull<Node<T>>) {
+        match self.front {
+            Some(old) => {
+                (*old.as_ptr()).front = Some(node);
+                (*node.as_ptr()).back = Some(old);
+            }
+            None => self.back = Some(node),
+        }
+        self.front = Some(node);
+        self.len += 1;
+        self.strict_check();
+    }
+
+    // Links a detached node right after `prev`, or at the front if None
+    unsafe fn link_after(&mut self, prev: Link<T>, node: NonNull<Node<T>>) {
+        let Some(prev) = prev else {
+            return self.link_front(node);
+        };
+        let next = (*prev.as_ptr()).back;
+        match next {
+            Some(next) => (*next.as_ptr()).front = Some(node),
+            None => self.back = Some(node),
+        }
+        (*node.as_ptr()).front = Some(prev);
+        (*node.as_ptr()).back = next;
+        (*prev.as_ptr()).back = Some(node);
+        self.len += 1;
+        self.strict_check();
+    }
+
+    unsafe fn link_back(&mut self, node: NonNull<Node<T>>) {
+        match self.back {
+            Some(old) => {
+                (*old.as_ptr()).back = Some(node);
+                (*node.as_ptr()).front = Some(old);
+            }
+            None => self.front = Some(node),
+        }
+        self.back = Some(node);
+        self.len += 1;
+        self.strict_check();
     }
 
     pub fn pop_front(&mut self) -> Option<T> {
         unsafe {
             self.front.map(|front_node| {
-                // This Box instance could potentially panic (out-of-memory
-                // conditions), but it's at the very start so it's relatively safe
-                let boxed_front_node = Box::from_raw(front_node.as_ptr());
-                let result = boxed_front_node.elem;
-
-                self.front = boxed_front_node.back;
+                self.front = (*front_node.as_ptr()).back;
                 if let Some(new) = self.front {
                     // Clean up ref to the popped node
                     (*new.as_ptr()).front = None;
@@ -141,8 +507,10 @@ impl<T> LinkedList<T> {
 
                 // This could panic, but given it's at the end we're good!
                 self.len -= 1;
-                result
-                // Box gets implicitly dropped here, as there's no T
+                // The node goes back to the pool, without its element
+                let elem = self.free_node(front_node);
+                self.strict_check();
+                elem
             })
         }
     }
@@ -151,13 +519,8 @@ impl<T> LinkedList<T> {
         unsafe {
             // Only have to do stuff if there is a back node to pop.
             self.back.map(|node| {
-                // Bring the Box front to life so we can move out its value and
-                // Drop it (Box continues to magically understand this for us).
-                let boxed_node = Box::from_raw(node.as_ptr());
-                let result = boxed_node.elem;
-
                 // Make the next node into the new back.
-                self.back = boxed_node.front;
+                self.back = (*node.as_ptr()).front;
                 if let Some(new) = self.back {
                     // Cleanup its reference to the removed node
                     (*new.as_ptr()).back = None;
@@ -167,8 +530,10 @@ impl<T> LinkedList<T> {
                 }
 
                 self.len -= 1;
-                result
-                // Box gets implicitly freed here, knows there is no T.
+                // Move the element out, and pool the node for the next push
+                let elem = self.free_node(node);
+                self.strict_check();
+                elem
             })
         }
     }
@@ -177,7 +542,195 @@ impl<T> LinkedList<T> {
         self.len
     }
 
-    pub fn iter(&self) -> Iter<T> {
+    // Walks the whole list and panics if its links are broken: every node
+    // must point back to the one before it, the ends must match, and both
+    // the list and the node pool must have as many nodes as they claim. It's
+    // O(n), and meant for tests, e.g. checking that forgetting a Drain or an
+    // IntoIter still leaves a valid list behind.
+    pub fn assert_invariants(&self) {
+        unsafe {
+            // We never walk more than len nodes, so a cycle can't hang us
+            let mut len = 0;
+            let mut last: Link<T> = None;
+            let mut node = self.front;
+            while let Some(cur) = node {
+                assert!(len < self.len, "len doesn't match the number of nodes");
+                assert_eq!((*cur.as_ptr()).front, last, "broken front link");
+                last = node;
+                node = (*cur.as_ptr()).back;
+                len += 1;
+            }
+            assert_eq!(self.len, len, "len doesn't match the number of nodes");
+            assert_eq!(self.back, last, "back isn't the last node");
+
+            let mut len = 0;
+            let mut node = self.back;
+            while let Some(cur) = node {
+                assert!(len < self.len, "len doesn't match the number of nodes");
+                node = (*cur.as_ptr()).front;
+                len += 1;
+            }
+            assert_eq!(self.len, len, "len doesn't match the number of nodes");
+
+            // Pooled nodes have no element, so only their back link is valid
+            let mut free_len = 0;
+            let mut node = self.free;
+            while let Some(cur) = node {
+                assert!(
+                    free_len < self.free_len,
+                    "the pool has more nodes than free_len"
+                );
+                node = ptr::addr_of!((*cur.as_ptr()).back).read();
+                free_len += 1;
+            }
+            assert_eq!(
+                self.free_len, free_len,
+                "the pool has fewer nodes than free_len"
+            );
+        }
+    }
+
+    // With the `strict-checks` feature, debug builds check the invariants
+    // after every operation that relinks nodes, same as fifth does
+    #[inline]
+    fn strict_check(&self) {
+        #[cfg(feature = "strict-checks")]
+        if cfg!(debug_assertions) {
+            self.assert_invariants();
+        }
+    }
+
+    // Drops elements from the back until we have at most `len` of them. Like
+    // clear, this goes one node at a time, so long tails can't overflow the
+    // stack the way a recursive drop would.
+    pub fn truncate(&mut self, len: usize) {
+        while self.len > len {
+            self.pop_back();
+        }
+    }
+
+    // Grows with values from `f` or truncates to exactly `new_len` elements
+    pub fn resize_with<F>(&mut self, new_len: usize, mut f: F)
+    where
+        F: FnMut() -> T,
+    {
+        self.truncate(new_len);
+        while self.len < new_len {
+            self.push_back(f());
+        }
+    }
+
+    // Moves all of other's elements to our back, leaving other empty. This is
+    // the whole point of a linked list: no matter how long either list is, we
+    // only need to fix up the two nodes at the seam. Unless the nodes aren't
+    // ours to take, see `can_adopt`, in which case it's O(other.len).
+    pub fn append(&mut self, other: &mut Self) {
+        if !self.can_adopt(other) {
+            while let Some(elem) = other.pop_front() {
+                self.push_back(elem);
+            }
+            return;
+        }
+
+        if let Some(back) = self.back {
+            if let (Some(other_front), Some(other_back)) = (other.front, other.back) {
+                unsafe {
+                    (*back.as_ptr()).back = Some(other_front);
+                    (*other_front.as_ptr()).front = Some(back);
+                }
+                self.back = Some(other_back);
+                self.len += other.len;
+
+                // other doesn't own its nodes anymore
+                other.front = None;
+                other.back = None;
+                other.len = 0;
+                other.forget_handles();
+            }
+        } else if other.front.is_some() {
+            // We're empty, so we can just take other's nodes. Not swapping
+            // the whole lists, as each of us keeps its own allocator.
+            self.front = other.front.take();
+            self.back = other.back.take();
+            self.len = core::mem::take(&mut other.len);
+            other.forget_handles();
+        }
+        self.strict_check();
+        other.strict_check();
+    }
+
+    // Moves all of other's elements to our front, leaving other empty. Our
+    // nodes don't go anywhere, so unlike other's, our handles keep working.
+    pub fn prepend(&mut self, other: &mut Self) {
+        if !self.can_adopt(other) {
+            while let Some(elem) = other.pop_back() {
+                self.push_front(elem);
+            }
+            return;
+        }
+
+        let Some(front) = self.front else {
+            // We're empty, so it's all the same
+            return self.append(other);
+        };
+        if let (Some(other_front), Some(other_back)) = (other.front, other.back) {
+            unsafe {
+                (*front.as_ptr()).front = Some(other_back);
+                (*other_back.as_ptr()).back = Some(front);
+            }
+            self.front = Some(other_front);
+            self.len += other.len;
+
+            other.front = None;
+            other.back = None;
+            other.len = 0;
+            other.forget_handles();
+        }
+        self.strict_check();
+        other.strict_check();
+    }
+
+    // Moves the first k elements to the back, like VecDeque::rotate_left.
+    // Finding the new front takes min(k, len - k) steps, and the rest is just
+    // relinking: the old ends join up, and the new ones come apart. No node
+    // leaves the list, so the pool and the NodeRefs stay as they were.
+    pub fn rotate_left(&mut self, k: usize) {
+        assert!(
+            k <= self.len,
+            "rotation by {k} out of range for list of length {}",
+            self.len
+        );
+        if k == 0 || k == self.len {
+            return;
+        }
+
+        unsafe {
+            // With 0 < k < len, both halves have nodes
+            let new_front = self.node_at(k);
+            let new_back = (*new_front.as_ptr()).front.take().unwrap();
+            (*new_back.as_ptr()).back = None;
+
+            let (old_front, old_back) = (self.front.unwrap(), self.back.unwrap());
+            (*old_back.as_ptr()).back = Some(old_front);
+            (*old_front.as_ptr()).front = Some(old_back);
+
+            self.front = Some(new_front);
+            self.back = Some(new_back);
+        }
+        self.strict_check();
+    }
+
+    // Moves the last k elements to the front
+    pub fn rotate_right(&mut self, k: usize) {
+        assert!(
+            k <= self.len,
+            "rotation by {k} out of range for list of length {}",
+            self.len
+        );
+        self.rotate_left(self.len - k);
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             front: self.front,
             back: self.back,
@@ -186,7 +739,7 @@ impl<T> LinkedList<T> {
         }
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut {
             front: self.front,
             back: self.back,
@@ -195,767 +748,2726 @@ impl<T> LinkedList<T> {
         }
     }
 
-    pub fn into_iter(self) -> IntoIter<T> {
-        IntoIter { list: self }
+    // Read-only cursors only hold a shared borrow of the list, so any number
+    // of them can be around at the same time to remember positions
+    pub fn cursor_front(&self) -> Cursor<'_, T, A> {
+        Cursor {
+            cur: self.front,
+            index: self.front.map(|_| 0),
+            list: self,
+        }
+    }
+
+    pub fn cursor_back(&self) -> Cursor<'_, T, A> {
+        Cursor {
+            cur: self.back,
+            index: self.back.map(|_| self.len - 1),
+            list: self,
+        }
     }
 
-    pub fn cursor_mut(&mut self) -> CursorMut<T> {
+    // Starts on the "ghost" element, between the back and the front
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T, A> {
         CursorMut {
             list: self,
             cur: None,
             index: None,
         }
     }
-}
 
-pub struct CursorMut<'a, T> {
-    cur: Link<T>,
-    list: &'a mut LinkedList<T>,
-    index: Option<usize>,
-}
+    // Starts on the front element (or the ghost if the list is empty)
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T, A> {
+        CursorMut {
+            cur: self.front,
+            index: self.front.map(|_| 0),
+            list: self,
+        }
+    }
 
-impl<'a, T> CursorMut<'a, T> {
-    pub fn index(&self) -> Option<usize> {
-        self.index
+    // Starts on the back element (or the ghost if the list is empty)
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T, A> {
+        CursorMut {
+            cur: self.back,
+            index: self.back.map(|_| self.len - 1),
+            list: self,
+        }
     }
 
-    pub fn move_next(&mut self) {
-        if let Some(cur) = self.cur {
-            unsafe {
-                self.cur = (*cur.as_ptr()).back;
-                if self.cur.is_some() {
-                    *self.index.as_mut().unwrap() += 1;
-                } else {
-                    self.index = None;
+    // Finds the node at `index`, which must be in bounds. We know the length,
+    // so we can start from whichever end is closer.
+    fn node_at(&self, index: usize) -> NonNull<Node<T>> {
+        debug_assert!(index < self.len);
+        unsafe {
+            if index < self.len / 2 {
+                let mut node = self.front.unwrap();
+                for _ in 0..index {
+                    node = (*node.as_ptr()).back.unwrap();
+                }
+                node
+            } else {
+                let mut node = self.back.unwrap();
+                for _ in index + 1..self.len {
+                    node = (*node.as_ptr()).front.unwrap();
                 }
+                node
             }
-        } else if !self.list.is_empty() {
-            // We're at the ghost, and there is a real front, move to it
-            self.cur = self.list.front;
-            self.index = Some(0);
+        }
+    }
+
+    // The positional operations follow VecDeque: get/get_mut/remove return
+    // None when out of bounds, while inserting past the end panics.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len {
+            unsafe { Some(&(*self.node_at(index).as_ptr()).elem) }
         } else {
-            // We're at the ghost, but this is the last element, so do nothing.
+            None
         }
     }
 
-    pub fn move_prev(&mut self) {
-        if let Some(cur) = self.cur {
-            unsafe {
-                // We're on a real element, go to its previous (front)
-                self.cur = (*cur.as_ptr()).front;
-                if self.cur.is_some() {
-                    *self.index.as_mut().unwrap() -= 1;
-                } else {
-                    // We just walked to the ghost, no more index
-                    self.index = None;
-                }
-            }
-        } else if !self.list.is_empty() {
-            // We're at the ghost, and there is a real back, so move to it!
-            self.cur = self.list.back;
-            self.index = Some(self.list.len - 1)
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.len {
+            unsafe { Some(&mut (*self.node_at(index).as_ptr()).elem) }
         } else {
-            // We're at the ghost, but that's the only element... do nothing.
+            None
         }
     }
 
-    // A very important note when implementing these methods:
-    // They must borrow our cursor by &mut self and the results must be tied to that borrow
-    // When you use lifetime elision (like in current), the default behavior is
-    // that the lifetime of the results is tied to that of the input (!)
+    pub fn insert(&mut self, index: usize, elem: T) {
+        assert!(
+            index <= self.len,
+            "insert index {index} out of range for list of length {}",
+            self.len
+        );
+        if index == self.len {
+            self.push_back(elem);
+        } else {
+            self.cursor_at(index).insert_before(elem);
+        }
+    }
 
-    pub fn current(&mut self) -> Option<&mut T> {
-        unsafe { self.cur.map(|node| &mut (*node.as_ptr()).elem) }
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index < self.len {
+            self.cursor_at(index).remove_current()
+        } else {
+            None
+        }
     }
 
-    pub fn peek_next(&mut self) -> Option<&mut T> {
-        unsafe {
-            self.cur
-                .and_then(|node| (*node.as_ptr()).back)
-                .map(|node| &mut (*node.as_ptr()).elem)
+    // Removes the element at `index`, putting the back element in its place,
+    // like Vec::swap_remove. Finding the node still walks from the closer
+    // end, but the removal itself doesn't shift anything.
+    pub fn remove_unordered(&mut self, index: usize) -> Option<T> {
+        if index < self.len {
+            self.cursor_at(index).remove_current_unordered()
+        } else {
+            None
         }
     }
 
-    pub fn peek_prev(&mut self) -> Option<&mut T> {
+    // Swaps the elements at i and j by swapping their nodes' positions in the
+    // list, so the elements themselves stay put in memory
+    pub fn swap(&mut self, i: usize, j: usize) {
+        assert!(
+            i < self.len,
+            "swap index {i} out of range for list of length {}",
+            self.len
+        );
+        assert!(
+            j < self.len,
+            "swap index {j} out of range for list of length {}",
+            self.len
+        );
+        if i == j {
+            return;
+        }
+
+        let (i, j) = (i.min(j), i.max(j));
+        let a = self.node_at(i);
+        let b = self.node_at(j);
         unsafe {
-            self.cur
-                .and_then(|node| (*node.as_ptr()).front)
-                .map(|node| &mut (*node.as_ptr()).elem)
+            let a_prev = (*a.as_ptr()).front;
+            let b_prev = (*b.as_ptr()).front;
+            if b_prev == Some(a) {
+                // Neighbours, moving b in front of a is enough
+                self.unlink(b);
+                self.link_after(a_prev, b);
+            } else {
+                // b_prev isn't a, so it stays linked while we take both out
+                self.unlink(a);
+                self.unlink(b);
+                self.link_after(b_prev, a);
+                self.link_after(a_prev, b);
+            }
         }
     }
 
-    pub fn split_before(&mut self) -> LinkedList<T> {
-        // We have this:
-        //
-        //     list.front -> A <-> B <-> C <-> D <- list.back
-        //                               ^
-        //                              cur
-        //
-        //
-        // And we want to produce this:
-        //
-        //     list.front -> C <-> D <- list.back
-        //                   ^
-        //                  cur
-        //
-        //
-        //    return.front -> A <-> B <- return.back
-        //
-        if let Some(cur) = self.cur {
-            // We are pointing at a real element, so the list is non-empty.
-            unsafe {
-                // Current state
-                let old_len = self.list.len;
-                let old_idx = self.index.unwrap();
-                let prev = (*cur.as_ptr()).front;
-
-                // What self will become
-                let new_len = old_len - old_idx;
-                let new_front = self.cur;
-                let new_back = self.list.back;
-                let new_idx = Some(0);
+    // A cursor on the element at `index`, which must be in bounds
+    fn cursor_at(&mut self, index: usize) -> CursorMut<'_, T, A> {
+        CursorMut {
+            cur: Some(self.node_at(index)),
+            index: Some(index),
+            list: self,
+        }
+    }
 
-                // What the output will become
-                let output_len = old_len - new_len;
-                let output_front = self.list.front;
-                let output_back = prev;
+    // For a list partitioned by `pred` (all the elements matching it come
+    // first), a cursor on the first element that doesn't match, or on the
+    // ghost if they all do. Like slice::partition_point, but without the
+    // binary search, as we can't jump to the middle of a list.
+    pub fn partition_point<P>(&self, pred: P) -> Cursor<'_, T, A>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let (cur, index) = self.boundary(pred);
+        Cursor {
+            cur,
+            index,
+            list: self,
+        }
+    }
 
-                // Break the links between cur and prev
-                if let Some(prev) = prev {
-                    (*cur.as_ptr()).front = None;
-                    (*prev.as_ptr()).back = None;
-                }
+    // Same as partition_point, with a cursor that can edit the list there
+    pub fn partition_point_mut<P>(&mut self, pred: P) -> CursorMut<'_, T, A>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let (cur, index) = self.boundary(pred);
+        CursorMut {
+            cur,
+            index,
+            list: self,
+        }
+    }
 
-                // Produce the result:
-                self.list.len = new_len;
-                self.list.front = new_front;
-                self.list.back = new_back;
-                self.index = new_idx;
+    // For a list sorted by `cmp`, a cursor where `elem` goes: inserting it
+    // before the cursor keeps the list sorted, after any elements equal to it
+    pub fn find_insert_point<F>(&mut self, elem: &T, mut cmp: F) -> CursorMut<'_, T, A>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        self.partition_point_mut(|x| cmp(x, elem) != Ordering::Greater)
+    }
 
-                LinkedList {
-                    front: output_front,
-                    back: output_back,
-                    len: output_len,
-                    _protection: PhantomData,
+    // Inserts `elem` before the first element that's larger according to
+    // `cmp`, so a list that's kept sorted stays sorted, and equal elements
+    // keep their insertion order
+    pub fn insert_sorted<F>(&mut self, elem: T, cmp: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        // On the ghost, this pushes to the back
+        self.find_insert_point(&elem, cmp).insert_before(elem);
+    }
+
+    // The first node not matching `pred` and its index, if any
+    fn boundary<P>(&self, mut pred: P) -> (Link<T>, Option<usize>)
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let mut cur = self.front;
+        let mut index = 0;
+        while let Some(node) = cur {
+            unsafe {
+                if !pred(&(*node.as_ptr()).elem) {
+                    return (cur, Some(index));
                 }
+                cur = (*node.as_ptr()).back;
             }
-        } else {
-            // We're at the ghost, just replace our list with an empty one.
-            // No other state needs to be changed.
-            std::mem::replace(self.list, LinkedList::new())
+            index += 1;
         }
+        (None, None)
     }
 
-    pub fn split_after(&mut self) -> LinkedList<T> {
-        // We have this:
-        //
-        //     list.front -> A <-> B <-> C <-> D <- list.back
-        //                         ^
-        //                        cur
-        //
-        //
-        // And we want to produce this:
-        //
-        //     list.front -> A <-> B <- list.back
-        //                         ^
-        //                        cur
-        //
-        //
-        //    return.front -> C <-> D <- return.back
-        //
-        if let Some(cur) = self.cur {
-            // We are pointing at a real element, so the list is non-empty.
-            unsafe {
-                // Current state
-                let old_len = self.list.len;
-                let old_idx = self.index.unwrap();
-                let next = (*cur.as_ptr()).back;
+    // Lazily removes and yields the elements matching `pred`, front to back.
+    // Elements the iterator didn't get to (e.g. when dropped early) are kept.
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, T, F, A>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        ExtractIf {
+            cursor: self.cursor_front_mut(),
+            pred,
+        }
+    }
+}
 
-                // What self will become
-                let new_len = old_idx + 1;
-                let new_back = self.cur;
-                let new_front = self.list.front;
-                let new_idx = Some(old_idx);
+// Everything that hands out a new list needs a copy of our allocator for it
+impl<T, A: Allocator + Clone> LinkedList<T, A> {
+    // An empty list on a clone of our allocator, which can take our nodes as
+    // they are and give them back later
+    fn new_sibling(&self) -> Self {
+        let mut list = Self::new_in(self.alloc.clone());
+        list.alloc_id = self.alloc_id;
+        list
+    }
 
-                // What the output will become
-                let output_len = old_len - new_len;
-                let output_front = next;
-                let output_back = self.list.back;
+    // Splits the list in two at `at`, returning everything from there on
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(
+            at <= self.len,
+            "split index {at} out of range for list of length {}",
+            self.len
+        );
+        if at == 0 {
+            return core::mem::replace(self, self.new_sibling());
+        }
 
-                // Break the links between cur and next
-                if let Some(next) = next {
-                    (*cur.as_ptr()).back = None;
-                    (*next.as_ptr()).front = None;
-                }
+        // Park a cursor right before the split point and let it do the work
+        self.cursor_at(at - 1).split_after()
+    }
 
-                // Produce the result:
-                self.list.len = new_len;
-                self.list.front = new_front;
-                self.list.back = new_back;
-                self.index = new_idx;
+    // Removes the elements in `range`, yielding them by value. We unlink the
+    // whole range before handing out the iterator, so the list is already in
+    // its final state: if the Drain is leaked, we leak the drained elements,
+    // but never leave the list pointing at freed nodes. Only the handles to
+    // the drained elements stop working, see NodeRef.
+    pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Drain<'_, T, A> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start
+                .checked_add(1)
+                .expect("drain start out of range for usize"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end
+                .checked_add(1)
+                .expect("drain end out of range for usize"),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len,
+        };
+        assert!(start <= end, "drain starts at {start} but ends at {end}");
+        assert!(
+            end <= self.len,
+            "drain end {end} out of range for list of length {}",
+            self.len
+        );
 
-                LinkedList {
-                    front: output_front,
-                    back: output_back,
-                    len: output_len,
-                    _protection: PhantomData,
+        let mut drained = self.new_sibling();
+        if start < end {
+            unsafe {
+                let first = self.node_at(start);
+                // The drained nodes go on generation 0, same as pooled ones,
+                // so their handles stop working while the rest keep theirs
+                let mut last = first;
+                (*first.as_ptr()).generation = 0;
+                for _ in start + 1..end {
+                    last = (*last.as_ptr()).back.unwrap();
+                    (*last.as_ptr()).generation = 0;
                 }
-            }
-        } else {
-            // We're at the ghost, just replace our list with an empty one.
-            // No other state needs to be changed.
-            std::mem::replace(self.list, LinkedList::new())
-        }
-    }
-
-    pub fn splice_before(&mut self, mut input: LinkedList<T>) {
-        // We have this:
-        //
-        // input.front -> 1 <-> 2 <- input.back
-        //
-        // list.front -> A <-> B <-> C <- list.back
-        //                     ^
-        //                    cur
-        //
-        //
-        // Becoming this:
-        //
-        // list.front -> A <-> 1 <-> 2 <-> B <-> C <- list.back
-        //                                 ^
-        //                                cur
-        //
-        unsafe {
-            // We can either `take` the input's pointers or `mem::forget`
-            // it. Using `take` is more responsible in case we ever do custom
-            // allocators or something that also needs to be cleaned up!
-            if input.is_empty() {
-                // Input is empty, do nothing.
-            } else if let Some(cur) = self.cur {
-                // Both lists are non-empty
-                let in_front = input.front.take().unwrap();
-                let in_back = input.back.take().unwrap();
 
-                if let Some(prev) = (*cur.as_ptr()).front {
-                    // General Case, no boundaries, just internal fixups
-                    (*prev.as_ptr()).back = Some(in_front);
-                    (*in_front.as_ptr()).front = Some(prev);
-                    (*cur.as_ptr()).front = Some(in_back);
-                    (*in_back.as_ptr()).back = Some(cur);
-                } else {
-                    // No prev, we're appending to the front
-                    (*cur.as_ptr()).front = Some(in_back);
-                    (*in_back.as_ptr()).back = Some(cur);
-                    self.list.front = Some(in_front);
+                let prev = (*first.as_ptr()).front.take();
+                let next = (*last.as_ptr()).back.take();
+                match prev {
+                    Some(prev) => (*prev.as_ptr()).back = next,
+                    None => self.front = next,
                 }
-                // Index moves forward by input length
-                *self.index.as_mut().unwrap() += input.len;
-            } else if let Some(back) = self.list.back {
-                // We're on the ghost but non-empty, append to the back
-                let in_front = input.front.take().unwrap();
-                let in_back = input.back.take().unwrap();
+                match next {
+                    Some(next) => (*next.as_ptr()).front = prev,
+                    None => self.back = prev,
+                }
+                self.len -= end - start;
 
-                (*back.as_ptr()).back = Some(in_front);
-                (*in_front.as_ptr()).front = Some(back);
-                self.list.back = Some(in_back);
-            } else {
-                // We're empty, become the input, remain on the ghost
-                std::mem::swap(self.list, &mut input);
+                drained.front = Some(first);
+                drained.back = Some(last);
+                drained.len = end - start;
             }
+        }
+        self.strict_check();
+        drained.strict_check();
 
-            self.list.len += input.len;
-            // Not necessary but Polite To Do
-            input.len = 0;
-
-            // Input dropped here
+        Drain {
+            list: drained,
+            from: self,
         }
     }
 
-    pub fn splice_after(&mut self, mut input: LinkedList<T>) {
-        // We have this:
-        //
-        // input.front -> 1 <-> 2 <- input.back
-        //
-        // list.front -> A <-> B <-> C <- list.back
-        //                     ^
-        //                    cur
-        //
-        //
-        // Becoming this:
-        //
-        // list.front -> A <-> B <-> 1 <-> 2 <-> C <- list.back
-        //                     ^
-        //                    cur
-        //
-        unsafe {
-            // We can either `take` the input's pointers or `mem::forget`
-            // it. Using `take` is more responsible in case we ever do custom
-            // allocators or something that also needs to be cleaned up!
-            if input.is_empty() {
-                // Input is empty, do nothing.
-            } else if let Some(cur) = self.cur {
-                // Both lists are non-empty
-                let in_front = input.front.take().unwrap();
-                let in_back = input.back.take().unwrap();
-
-                if let Some(next) = (*cur.as_ptr()).back {
-                    // General Case, no boundaries, just internal fixups
-                    (*next.as_ptr()).front = Some(in_back);
-                    (*in_back.as_ptr()).back = Some(next);
-                    (*cur.as_ptr()).back = Some(in_front);
-                    (*in_front.as_ptr()).front = Some(cur);
-                } else {
-                    // No next, we're appending to the back
-                    (*cur.as_ptr()).back = Some(in_front);
-                    (*in_front.as_ptr()).front = Some(cur);
-                    self.list.back = Some(in_back);
-                }
-                // Index doesn't change
-            } else if let Some(front) = self.list.front {
-                // We're on the ghost but non-empty, append to the front
-                let in_front = input.front.take().unwrap();
-                let in_back = input.back.take().unwrap();
-
-                (*front.as_ptr()).front = Some(in_back);
-                (*in_back.as_ptr()).back = Some(front);
-                self.list.front = Some(in_front);
+    // Merges two lists sorted by `cmp` into one sorted list, taking from self
+    // first on ties. No nodes get allocated or freed, we only relink them
+    // into self, so this is O(n + m). (If other's allocator isn't a clone of
+    // ours, its elements move into new nodes instead, see `can_adopt`.)
+    pub fn merge<F>(mut self, mut other: Self, mut cmp: F) -> Self
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut cursor = self.cursor_front_mut();
+        while let (Some(cur), Some(next)) = (cursor.current(), other.front()) {
+            if cmp(next, cur) == Ordering::Less {
+                // Detach other's front node, and move it before the cursor
+                let rest = other.split_off(1);
+                cursor.splice_before(core::mem::replace(&mut other, rest));
             } else {
-                // We're empty, become the input, remain on the ghost
-                std::mem::swap(self.list, &mut input);
+                cursor.move_next();
             }
-
-            self.list.len += input.len;
-            // Not necessary but Polite To Do
-            input.len = 0;
-
-            // Input dropped here
         }
+
+        // Either we ran out of elements, and we're on the ghost where this
+        // appends the rest of other, or other is empty and this does nothing
+        cursor.splice_before(other);
+        self
     }
 }
 
-// Opting back into Send and Sync, given NonNull opts out of them by default
-// Note we don't need to implement these, they're auto-derived
-unsafe impl<T: Send> Send for LinkedList<T> {}
-unsafe impl<T: Sync> Sync for LinkedList<T> {}
-
-unsafe impl<'a, T: Send> Send for Iter<'a, T> {}
-unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
-
-unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
-unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+// An opaque handle to a node, for the likes of LRU caches and timer wheels
+// that need to find their entries in the list again in O(1). It doesn't
+// borrow the list (otherwise we couldn't keep pushing), so besides the node's
+// address it carries what the list needs to tell whether the node is still
+// there: the id of the list that made it, and the node's generation.
+//
+// A handle stops working once its element is popped, removed or drained,
+// and also whenever nodes may have left the list for good: when it's appended
+// to another list, split, or shrunk. The list can't tell which nodes went in
+// those cases, so it gets a new id, and all of its handles stop working.
+// Moving nodes around within the list (including with a cursor) is fine.
+pub struct NodeRef<T> {
+    node: NonNull<Node<T>>,
+    list: usize,
+    generation: u64,
+}
 
-impl<T> Default for LinkedList<T> {
-    fn default() -> Self {
-        Self::new()
+impl<T> NodeRef<T> {
+    fn new(list: usize, node: NonNull<Node<T>>) -> Self {
+        NodeRef {
+            node,
+            list,
+            generation: unsafe { (*node.as_ptr()).generation },
+        }
     }
 }
 
-impl<T: Clone> Clone for LinkedList<T> {
+// Deriving these would require T: Clone/Copy/..., which we don't need
+impl<T> Clone for NodeRef<T> {
     fn clone(&self) -> Self {
-        let mut new_list = Self::new();
-        for item in self {
-            new_list.push_back(item.clone());
-        }
-        new_list
+        *self
     }
 }
 
-impl<T> Extend<T> for LinkedList<T> {
-    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
-        for item in iter {
-            self.push_back(item);
-        }
+impl<T> Copy for NodeRef<T> {}
+
+impl<T> PartialEq for NodeRef<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node && self.list == other.list && self.generation == other.generation
     }
 }
 
-impl<T> FromIterator<T> for LinkedList<T> {
-    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let mut list = Self::new();
-        list.extend(iter);
-        list
+impl<T> Eq for NodeRef<T> {}
+
+impl<T> Hash for NodeRef<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.node.hash(state);
+        self.list.hash(state);
+        self.generation.hash(state);
     }
 }
 
-impl<T: Debug> Debug for LinkedList<T> {
+impl<T> Debug for NodeRef<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_list().entries(self).finish()
+        f.debug_struct("NodeRef")
+            .field("node", &self.node)
+            .field("list", &self.list)
+            .field("generation", &self.generation)
+            .finish()
     }
 }
 
-impl<T: PartialEq> PartialEq for LinkedList<T> {
-    fn eq(&self, other: &Self) -> bool {
-        self.len() == other.len() && self.iter().eq(other)
-    }
+// The read-only sibling of CursorMut, see below for how the ghost element and
+// the index work. As we only hand out shared references, they can live as long
+// as the list borrow rather than the cursor borrow.
+pub struct Cursor<'a, T, A: Allocator = Global> {
+    cur: Link<T>,
+    list: &'a LinkedList<T, A>,
+    index: Option<usize>,
+}
 
-    fn ne(&self, other: &Self) -> bool {
-        self.len() != other.len() || self.iter().ne(other)
+// Deriving these would require T: Clone/Copy, which we don't need
+impl<'a, T, A: Allocator> Clone for Cursor<'a, T, A> {
+    fn clone(&self) -> Self {
+        *self
     }
 }
 
-impl<T: Eq> Eq for LinkedList<T> {}
+impl<'a, T, A: Allocator> Copy for Cursor<'a, T, A> {}
 
-impl<T: PartialOrd> PartialOrd for LinkedList<T> {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.iter().partial_cmp(other)
+impl<'a, T, A: Allocator> Cursor<'a, T, A> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.cur = (*cur.as_ptr()).back;
+                if self.cur.is_some() {
+                    *self.index.as_mut().unwrap() += 1;
+                } else {
+                    self.index = None;
+                }
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.front;
+            self.index = Some(0);
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.cur = (*cur.as_ptr()).front;
+                if self.cur.is_some() {
+                    *self.index.as_mut().unwrap() -= 1;
+                } else {
+                    self.index = None;
+                }
+            }
+        } else if !self.list.is_empty() {
+            self.cur = self.list.back;
+            self.index = Some(self.list.len - 1);
+        }
+    }
+
+    pub fn current(&self) -> Option<&'a T> {
+        unsafe { self.cur.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_next(&self) -> Option<&'a T> {
+        unsafe {
+            let next = match self.cur {
+                Some(node) => (*node.as_ptr()).back,
+                None => self.list.front,
+            };
+            next.map(|node| &(*node.as_ptr()).elem)
+        }
+    }
+
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        unsafe {
+            let prev = match self.cur {
+                Some(node) => (*node.as_ptr()).front,
+                None => self.list.back,
+            };
+            prev.map(|node| &(*node.as_ptr()).elem)
+        }
+    }
+}
+
+pub struct CursorMut<'a, T, A: Allocator = Global> {
+    cur: Link<T>,
+    list: &'a mut LinkedList<T, A>,
+    index: Option<usize>,
+}
+
+impl<'a, T, A: Allocator> CursorMut<'a, T, A> {
+    pub fn index(&self) -> Option<usize> {
+        self.index
+    }
+
+    // A read-only view of the cursor, which can't outlive this borrow of it
+    pub fn as_cursor(&self) -> Cursor<'_, T, A> {
+        Cursor {
+            cur: self.cur,
+            list: self.list,
+            index: self.index,
+        }
+    }
+
+    pub fn move_next(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.cur = (*cur.as_ptr()).back;
+                if self.cur.is_some() {
+                    *self.index.as_mut().unwrap() += 1;
+                } else {
+                    self.index = None;
+                }
+            }
+        } else if !self.list.is_empty() {
+            // We're at the ghost, and there is a real front, move to it
+            self.cur = self.list.front;
+            self.index = Some(0);
+        } else {
+            // We're at the ghost, but this is the last element, so do nothing.
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                // We're on a real element, go to its previous (front)
+                self.cur = (*cur.as_ptr()).front;
+                if self.cur.is_some() {
+                    *self.index.as_mut().unwrap() -= 1;
+                } else {
+                    // We just walked to the ghost, no more index
+                    self.index = None;
+                }
+            }
+        } else if !self.list.is_empty() {
+            // We're at the ghost, and there is a real back, so move to it!
+            self.cur = self.list.back;
+            self.index = Some(self.list.len - 1)
+        } else {
+            // We're at the ghost, but that's the only element... do nothing.
+        }
+    }
+
+    // A very important note when implementing these methods:
+    // They must borrow our cursor by &mut self and the results must be tied to that borrow
+    // When you use lifetime elision (like in current), the default behavior is
+    // that the lifetime of the results is tied to that of the input (!)
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        unsafe { self.cur.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        unsafe {
+            // The element after the ghost is the front of the list
+            let next = match self.cur {
+                Some(node) => (*node.as_ptr()).back,
+                None => self.list.front,
+            };
+            next.map(|node| &mut (*node.as_ptr()).elem)
+        }
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        unsafe {
+            // The element before the ghost is the back of the list
+            let prev = match self.cur {
+                Some(node) => (*node.as_ptr()).front,
+                None => self.list.back,
+            };
+            prev.map(|node| &mut (*node.as_ptr()).elem)
+        }
+    }
+
+    // Inserting a single element is just splicing a list of one element, so
+    // we let the splice methods deal with all the boundary cases. On the
+    // ghost, `insert_before` pushes to the back and `insert_after` to the
+    // front, just like the splices.
+    pub fn insert_before(&mut self, elem: T) {
+        let node = self.list.new_node(elem);
+        unsafe {
+            match self.cur {
+                Some(cur) => {
+                    self.list.link_after((*cur.as_ptr()).front, node);
+                    *self.index.as_mut().unwrap() += 1;
+                }
+                // Before the ghost is the back of the list
+                None => self.list.link_back(node),
+            }
+        }
+    }
+
+    pub fn insert_after(&mut self, elem: T) {
+        let node = self.list.new_node(elem);
+        unsafe {
+            match self.cur {
+                Some(cur) => self.list.link_after(Some(cur), node),
+                // After the ghost is the front of the list
+                None => self.list.link_front(node),
+            }
+        }
+    }
+
+    // Relinks the current node at the front of the list (the cursor follows
+    // it), without moving the element in memory. Does nothing on the ghost.
+    pub fn move_to_front(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.list.unlink(cur);
+                self.list.link_front(cur);
+            }
+            self.index = Some(0);
+        }
+    }
+
+    // Same as move_to_front, but to the back
+    pub fn move_to_back(&mut self) {
+        if let Some(cur) = self.cur {
+            unsafe {
+                self.list.unlink(cur);
+                self.list.link_back(cur);
+            }
+            self.index = Some(self.list.len - 1);
+        }
+    }
+
+    // Removes the current element and moves to the next one (which may be
+    // the ghost). Does nothing on the ghost.
+    pub fn remove_current(&mut self) -> Option<T> {
+        // We have this:
+        //
+        //     list.front -> A <-> B <-> C <- list.back
+        //                         ^
+        //                        cur
+        //
+        // And we want to produce this:
+        //
+        //     list.front -> A <-> C <- list.back
+        //                         ^
+        //                        cur
+        //
+        let cur = self.cur?;
+        unsafe {
+            let prev = (*cur.as_ptr()).front;
+            let next = (*cur.as_ptr()).back;
+
+            match prev {
+                Some(prev) => (*prev.as_ptr()).back = next,
+                None => self.list.front = next,
+            }
+            match next {
+                Some(next) => (*next.as_ptr()).front = prev,
+                None => self.list.back = prev,
+            }
+
+            self.list.len -= 1;
+            // The next element takes our index, unless we fell on the ghost
+            self.cur = next;
+            if next.is_none() {
+                self.index = None;
+            }
+
+            let elem = self.list.free_node(cur);
+            self.list.strict_check();
+            Some(elem)
+        }
+    }
+
+    // Like Vec::swap_remove: removes the current element and moves the back
+    // node into its place, where the cursor stays. It's O(1) whatever the
+    // position, but doesn't preserve the order. Removing the back element
+    // moves to the ghost instead.
+    pub fn remove_current_unordered(&mut self) -> Option<T> {
+        let cur = self.cur?;
+        let back = self.list.back?;
+        if cur == back {
+            return self.remove_current();
+        }
+
+        // We relink the back node rather than moving its element, so any
+        // NodeRef to it stays valid
+        unsafe {
+            self.list.unlink(back);
+            self.list.link_after(Some(cur), back);
+            self.list.unlink(cur);
+            self.cur = Some(back);
+            Some(self.list.free_node(cur))
+        }
+    }
+
+    pub fn splice_before(&mut self, mut input: LinkedList<T, A>) {
+        // We have this:
+        //
+        // input.front -> 1 <-> 2 <- input.back
+        //
+        // list.front -> A <-> B <-> C <- list.back
+        //                     ^
+        //                    cur
+        //
+        //
+        // Becoming this:
+        //
+        // list.front -> A <-> 1 <-> 2 <-> B <-> C <- list.back
+        //                                 ^
+        //                                cur
+        //
+        if !self.list.can_adopt(&input) {
+            // The nodes aren't ours to take, so we move the elements over
+            while let Some(elem) = input.pop_front() {
+                self.insert_before(elem);
+            }
+            return;
+        }
+
+        unsafe {
+            // We can either `take` the input's pointers or `mem::forget`
+            // it. Using `take` is more responsible in case we ever do custom
+            // allocators or something that also needs to be cleaned up!
+            if input.is_empty() {
+                // Input is empty, do nothing.
+            } else if let Some(cur) = self.cur {
+                // Both lists are non-empty
+                let in_front = input.front.take().unwrap();
+                let in_back = input.back.take().unwrap();
+
+                if let Some(prev) = (*cur.as_ptr()).front {
+                    // General Case, no boundaries, just internal fixups
+                    (*prev.as_ptr()).back = Some(in_front);
+                    (*in_front.as_ptr()).front = Some(prev);
+                    (*cur.as_ptr()).front = Some(in_back);
+                    (*in_back.as_ptr()).back = Some(cur);
+                } else {
+                    // No prev, we're appending to the front
+                    (*cur.as_ptr()).front = Some(in_back);
+                    (*in_back.as_ptr()).back = Some(cur);
+                    self.list.front = Some(in_front);
+                }
+                // Index moves forward by input length
+                *self.index.as_mut().unwrap() += input.len;
+            } else if let Some(back) = self.list.back {
+                // We're on the ghost but non-empty, append to the back
+                let in_front = input.front.take().unwrap();
+                let in_back = input.back.take().unwrap();
+
+                (*back.as_ptr()).back = Some(in_front);
+                (*in_front.as_ptr()).front = Some(back);
+                self.list.back = Some(in_back);
+            } else {
+                // We're empty, become the input, remain on the ghost
+                self.list.append(&mut input);
+            }
+
+            self.list.len += input.len;
+            // Not necessary but Polite To Do
+            input.len = 0;
+            self.list.strict_check();
+
+            // Input dropped here
+        }
+    }
+
+    pub fn splice_after(&mut self, mut input: LinkedList<T, A>) {
+        // We have this:
+        //
+        // input.front -> 1 <-> 2 <- input.back
+        //
+        // list.front -> A <-> B <-> C <- list.back
+        //                     ^
+        //                    cur
+        //
+        //
+        // Becoming this:
+        //
+        // list.front -> A <-> B <-> 1 <-> 2 <-> C <- list.back
+        //                     ^
+        //                    cur
+        //
+        if !self.list.can_adopt(&input) {
+            // Same as above, but from the back, as every element goes right
+            // after the cursor
+            while let Some(elem) = input.pop_back() {
+                self.insert_after(elem);
+            }
+            return;
+        }
+
+        unsafe {
+            // We can either `take` the input's pointers or `mem::forget`
+            // it. Using `take` is more responsible in case we ever do custom
+            // allocators or something that also needs to be cleaned up!
+            if input.is_empty() {
+                // Input is empty, do nothing.
+            } else if let Some(cur) = self.cur {
+                // Both lists are non-empty
+                let in_front = input.front.take().unwrap();
+                let in_back = input.back.take().unwrap();
+
+                if let Some(next) = (*cur.as_ptr()).back {
+                    // General Case, no boundaries, just internal fixups
+                    (*next.as_ptr()).front = Some(in_back);
+                    (*in_back.as_ptr()).back = Some(next);
+                    (*cur.as_ptr()).back = Some(in_front);
+                    (*in_front.as_ptr()).front = Some(cur);
+                } else {
+                    // No next, we're appending to the back
+                    (*cur.as_ptr()).back = Some(in_front);
+                    (*in_front.as_ptr()).front = Some(cur);
+                    self.list.back = Some(in_back);
+                }
+                // Index doesn't change
+            } else if let Some(front) = self.list.front {
+                // We're on the ghost but non-empty, append to the front
+                let in_front = input.front.take().unwrap();
+                let in_back = input.back.take().unwrap();
+
+                (*front.as_ptr()).front = Some(in_back);
+                (*in_back.as_ptr()).back = Some(front);
+                self.list.front = Some(in_front);
+            } else {
+                // We're empty, become the input, remain on the ghost
+                self.list.append(&mut input);
+            }
+
+            self.list.len += input.len;
+            // Not necessary but Polite To Do
+            input.len = 0;
+            self.list.strict_check();
+
+            // Input dropped here
+        }
+    }
+}
+
+// Same as for lists, splitting needs to clone the allocator
+impl<'a, T, A: Allocator + Clone> CursorMut<'a, T, A> {
+    pub fn split_before(&mut self) -> LinkedList<T, A> {
+        // We have this:
+        //
+        //     list.front -> A <-> B <-> C <-> D <- list.back
+        //                               ^
+        //                              cur
+        //
+        //
+        // And we want to produce this:
+        //
+        //     list.front -> C <-> D <- list.back
+        //                   ^
+        //                  cur
+        //
+        //
+        //    return.front -> A <-> B <- return.back
+        //
+        if let Some(cur) = self.cur {
+            // We are pointing at a real element, so the list is non-empty.
+            unsafe {
+                // Current state
+                let old_len = self.list.len;
+                let old_idx = self.index.unwrap();
+                let prev = (*cur.as_ptr()).front;
+
+                // What self will become
+                let new_len = old_len - old_idx;
+                let new_front = self.cur;
+                let new_back = self.list.back;
+                let new_idx = Some(0);
+
+                // What the output will become. If there's no prev, we're at
+                // the front and the output is empty, so it must not point at
+                // any of our nodes!
+                let output_len = old_len - new_len;
+                let output_front = prev.and(self.list.front);
+                let output_back = prev;
+
+                // Break the links between cur and prev
+                if let Some(prev) = prev {
+                    (*cur.as_ptr()).front = None;
+                    (*prev.as_ptr()).back = None;
+                }
+
+                // Produce the result:
+                self.list.len = new_len;
+                self.list.front = new_front;
+                self.list.back = new_back;
+                self.index = new_idx;
+
+                let output = LinkedList {
+                    front: output_front,
+                    back: output_back,
+                    len: output_len,
+                    free: None,
+                    free_len: 0,
+                    id: next_id(),
+                    generation: 0,
+                    alloc_id: self.list.alloc_id,
+                    alloc: self.list.alloc.clone(),
+                    _protection: PhantomData,
+                };
+                if output_len > 0 {
+                    self.list.forget_handles();
+                }
+                self.list.strict_check();
+                output.strict_check();
+                output
+            }
+        } else {
+            // We're at the ghost, just replace our list with an empty one.
+            // No other state needs to be changed.
+            core::mem::replace(self.list, self.list.new_sibling())
+        }
+    }
+
+    pub fn split_after(&mut self) -> LinkedList<T, A> {
+        // We have this:
+        //
+        //     list.front -> A <-> B <-> C <-> D <- list.back
+        //                         ^
+        //                        cur
+        //
+        //
+        // And we want to produce this:
+        //
+        //     list.front -> A <-> B <- list.back
+        //                         ^
+        //                        cur
+        //
+        //
+        //    return.front -> C <-> D <- return.back
+        //
+        if let Some(cur) = self.cur {
+            // We are pointing at a real element, so the list is non-empty.
+            unsafe {
+                // Current state
+                let old_len = self.list.len;
+                let old_idx = self.index.unwrap();
+                let next = (*cur.as_ptr()).back;
+
+                // What self will become
+                let new_len = old_idx + 1;
+                let new_back = self.cur;
+                let new_front = self.list.front;
+                let new_idx = Some(old_idx);
+
+                // What the output will become. Same as above, if there's no
+                // next the output is empty.
+                let output_len = old_len - new_len;
+                let output_front = next;
+                let output_back = next.and(self.list.back);
+
+                // Break the links between cur and next
+                if let Some(next) = next {
+                    (*cur.as_ptr()).back = None;
+                    (*next.as_ptr()).front = None;
+                }
+
+                // Produce the result:
+                self.list.len = new_len;
+                self.list.front = new_front;
+                self.list.back = new_back;
+                self.index = new_idx;
+
+                let output = LinkedList {
+                    front: output_front,
+                    back: output_back,
+                    len: output_len,
+                    free: None,
+                    free_len: 0,
+                    id: next_id(),
+                    generation: 0,
+                    alloc_id: self.list.alloc_id,
+                    alloc: self.list.alloc.clone(),
+                    _protection: PhantomData,
+                };
+                if output_len > 0 {
+                    self.list.forget_handles();
+                }
+                self.list.strict_check();
+                output.strict_check();
+                output
+            }
+        } else {
+            // We're at the ghost, just replace our list with an empty one.
+            // No other state needs to be changed.
+            core::mem::replace(self.list, self.list.new_sibling())
+        }
+    }
+}
+
+// Opting back into Send and Sync, given NonNull opts out of them by default
+// Note we don't need to implement these, they're auto-derived
+unsafe impl<T: Send, A: Allocator + Send> Send for LinkedList<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for LinkedList<T, A> {}
+
+// The rest follow the references they stand for. Iter and Cursor are a &T, so
+// sending one to another thread means sharing the T, which requires T: Sync.
+// IterMut and CursorMut are a &mut T, which is Send if T is, as only one thread
+// can use it at a time. IntoIter owns a LinkedList, so it gets these for free.
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+unsafe impl<'a, T: Send> Send for IterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for IterMut<'a, T> {}
+
+unsafe impl<'a, T: Sync, A: Allocator + Sync> Send for Cursor<'a, T, A> {}
+unsafe impl<'a, T: Sync, A: Allocator + Sync> Sync for Cursor<'a, T, A> {}
+
+unsafe impl<'a, T: Send, A: Allocator + Send> Send for CursorMut<'a, T, A> {}
+unsafe impl<'a, T: Sync, A: Allocator + Sync> Sync for CursorMut<'a, T, A> {}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Pooled nodes are allocated too, so they count, just like a Vec's spare
+// capacity would
+impl<T, A: Allocator> MemSize for LinkedList<T, A> {
+    fn node_count(&self) -> usize {
+        self.capacity()
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        size_of::<Node<T>>()
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for LinkedList<T, A> {
+    fn clone(&self) -> Self {
+        let mut new_list = self.new_sibling();
+        for item in self {
+            new_list.push_back(item.clone());
+        }
+        new_list
+    }
+
+    // Reuse the nodes we already have instead of freeing them just to
+    // allocate new ones, like std's LinkedList does. Their elements get
+    // replaced, so they get a new generation too, and their handles stop
+    // working as if the nodes had been popped and pushed again.
+    fn clone_from(&mut self, source: &Self) {
+        let mut src = source.iter();
+        let mut node = self.front;
+        while let Some(cur) = node {
+            let Some(elem) = src.next() else {
+                break;
+            };
+            unsafe {
+                self.generation += 1;
+                (*cur.as_ptr()).generation = self.generation;
+                (*cur.as_ptr()).elem.clone_from(elem);
+                node = (*cur.as_ptr()).back;
+            }
+        }
+        while self.len > source.len {
+            self.pop_back();
+        }
+        self.extend(src.cloned());
+    }
+}
+
+// Conversions from and to contiguous collections keep the order, front to back.
+// One pass either way: we link nodes as we move the elements in, and we know
+// exactly how much room the Vec/VecDeque needs on the way out.
+impl<T> From<Vec<T>> for LinkedList<T> {
+    fn from(vec: Vec<T>) -> Self {
+        vec.into_iter().collect()
+    }
+}
+
+impl<T> From<VecDeque<T>> for LinkedList<T> {
+    fn from(deque: VecDeque<T>) -> Self {
+        deque.into_iter().collect()
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for LinkedList<T> {
+    fn from(array: [T; N]) -> Self {
+        array.into_iter().collect()
+    }
+}
+
+impl<T, A: Allocator> From<LinkedList<T, A>> for Vec<T> {
+    fn from(list: LinkedList<T, A>) -> Self {
+        let mut vec = Vec::with_capacity(list.len);
+        vec.extend(list);
+        vec
+    }
+}
+
+impl<T, A: Allocator> From<LinkedList<T, A>> for VecDeque<T> {
+    fn from(list: LinkedList<T, A>) -> Self {
+        let mut deque = VecDeque::with_capacity(list.len);
+        deque.extend(list);
+        deque
+    }
+}
+
+impl<T, A: Allocator> Extend<T> for LinkedList<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item);
+        }
+    }
+}
+
+// Handy for extending from slices and other lists of plain values
+impl<'a, T: Copy + 'a, A: Allocator> Extend<&'a T> for LinkedList<T, A> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug, A: Allocator> Debug for LinkedList<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T: PartialEq, A: Allocator> PartialEq for LinkedList<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq, A: Allocator> Eq for LinkedList<T, A> {}
+
+impl<T: PartialOrd, A: Allocator> PartialOrd for LinkedList<T, A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other)
+    }
+}
+
+impl<T: Ord, A: Allocator> Ord for LinkedList<T, A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other)
+    }
+}
+
+impl<T: Hash, A: Allocator> Hash for LinkedList<T, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.hash(state);
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _protection: PhantomData<&'a T>,
+}
+
+pub struct IterMut<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    len: usize,
+    _protection: PhantomData<&'a mut T>,
+}
+
+// IntoIter is just the list, popping from either end, so it's always a valid
+// list in between calls. Forgetting it leaks the remaining elements and nodes
+// and nothing else.
+pub struct IntoIter<T, A: Allocator = Global> {
+    list: LinkedList<T, A>,
+}
+
+// A cursor already knows how to unlink the node it's on, so we just walk one
+// over the list. It only reaches the ghost once we're done, as we never move
+// past it.
+pub struct ExtractIf<'a, T, F, A: Allocator = Global>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    cursor: CursorMut<'a, T, A>,
+    pred: F,
+}
+
+// Drain owns the nodes it unlinked, it only borrows the list so that no one
+// can observe it while draining, same as std's. As `drain` leaves the list in
+// its final state before handing it out, forgetting a Drain leaks the drained
+// elements, but the rest of the list is still there (std's Vec truncates
+// itself up front, so forgetting its Drain loses the tail too).
+//
+// Once empty, the drained nodes go to the list's pool rather than back to the
+// allocator: the list kept its id, so NodeRefs to them must keep pointing at
+// memory it owns, where they find generation 0 and fail.
+pub struct Drain<'a, T, A: Allocator = Global> {
+    list: LinkedList<T, A>,
+    from: &'a mut LinkedList<T, A>,
+}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a LinkedList<T, A> {
+    type IntoIter = Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // While self.front == self.back is a tempting condition to check here,
+        // it won't do the right for yielding the last element! That sort of
+        // thing only works for arrays because of "one-past-the-end" pointers.
+        if self.len > 0 {
+            // We could unwrap front, but this is safer and easier
+            self.front.map(|node| unsafe {
+                self.len -= 1;
+                self.front = (*node.as_ptr()).back;
+                &(*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|node| unsafe {
+                self.len -= 1;
+                self.back = (*node.as_ptr()).front;
+                &(*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+// Once len hits zero we never look at the links again, so we stay done
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a mut LinkedList<T, A> {
+    type IntoIter = IterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // While self.front == self.back is a tempting condition to check here,
+        // it won't do the right for yielding the last element! That sort of
+        // thing only works for arrays because of "one-past-the-end" pointers.
+        if self.len > 0 {
+            // We could unwrap front, but this is safer and easier
+            self.front.map(|node| unsafe {
+                self.len -= 1;
+                self.front = (*node.as_ptr()).back;
+                &mut (*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len > 0 {
+            self.back.map(|node| unsafe {
+                self.len -= 1;
+                self.back = (*node.as_ptr()).front;
+                &mut (*node.as_ptr()).elem
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, T> FusedIterator for IterMut<'a, T> {}
+
+impl<'a, T> Iter<'a, T> {
+    // Splits the elements left into the next `mid` and the rest
+    pub fn split_at(self, mid: usize) -> (Self, Self) {
+        assert!(
+            mid <= self.len,
+            "split index {mid} out of range for iterator of length {}",
+            self.len
+        );
+        let (left, right) = split_links(self.front, self.back, self.len, mid);
+        let iter = |(front, back), len| Iter {
+            front,
+            back,
+            len,
+            _protection: PhantomData,
+        };
+        (iter(left, mid), iter(right, self.len - mid))
+    }
+}
+
+impl<'a, T> IterMut<'a, T> {
+    // Splits the elements left into the next `mid` and the rest, as two
+    // iterators that can be used at the same time. The halves don't overlap,
+    // so handing out both is fine, like slice::split_at_mut.
+    pub fn split_at(self, mid: usize) -> (Self, Self) {
+        assert!(
+            mid <= self.len,
+            "split index {mid} out of range for iterator of length {}",
+            self.len
+        );
+        let (left, right) = split_links(self.front, self.back, self.len, mid);
+        let iter = |(front, back), len| IterMut {
+            front,
+            back,
+            len,
+            _protection: PhantomData,
+        };
+        (iter(left, mid), iter(right, self.len - mid))
+    }
+}
+
+// Splits the `len` nodes between `front` and `back` into the first `index` and
+// the rest, returning the ends of both halves. Iterators stop after `len`
+// elements, so we don't need to (and can't, as they're borrowed) cut the links.
+type Ends<T> = (Link<T>, Link<T>);
+
+fn split_links<T>(front: Link<T>, back: Link<T>, len: usize, index: usize) -> (Ends<T>, Ends<T>) {
+    debug_assert!(index <= len);
+    if index == 0 {
+        return ((None, None), (front, back));
+    }
+    if index == len {
+        return ((front, back), (None, None));
+    }
+
+    // Find the first node of the back half from whichever end is closer
+    unsafe {
+        let node = if index <= len / 2 {
+            let mut node = front.unwrap();
+            for _ in 0..index {
+                node = (*node.as_ptr()).back.unwrap();
+            }
+            node
+        } else {
+            let mut node = back.unwrap();
+            for _ in index + 1..len {
+                node = (*node.as_ptr()).front.unwrap();
+            }
+            node
+        };
+        let prev = (*node.as_ptr()).front;
+        ((front, prev), (Some(node), back))
+    }
+}
+
+impl<T, A: Allocator> IntoIterator for LinkedList<T, A> {
+    type IntoIter = IntoIter<T, A>;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<T, A: Allocator> Iterator for IntoIter<T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T, A: Allocator> DoubleEndedIterator for IntoIter<T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T, A: Allocator> ExactSizeIterator for IntoIter<T, A> {
+    fn len(&self) -> usize {
+        self.list.len
+    }
+}
+
+impl<T, A: Allocator> FusedIterator for IntoIter<T, A> {}
+
+// Both go through the list, which only holds what's left to yield
+impl<T: Clone, A: Allocator + Clone> Clone for IntoIter<T, A> {
+    fn clone(&self) -> Self {
+        IntoIter {
+            list: self.list.clone(),
+        }
+    }
+}
+
+impl<T: Debug, A: Allocator> Debug for IntoIter<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("IntoIter").field(&self.list).finish()
+    }
+}
+
+impl<'a, T, F, A: Allocator> Iterator for ExtractIf<'a, T, F, A>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(elem) = self.cursor.current() {
+            if (self.pred)(elem) {
+                return self.cursor.remove_current();
+            }
+            self.cursor.move_next();
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // We can't extract more than what's left after the cursor
+        let left = match self.cursor.index {
+            Some(index) => self.cursor.list.len - index,
+            None => 0,
+        };
+        (0, Some(left))
+    }
+}
+
+impl<'a, T, F, A: Allocator> FusedIterator for ExtractIf<'a, T, F, A> where F: FnMut(&mut T) -> bool {}
+
+impl<'a, T, A: Allocator> Iterator for Drain<'a, T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<'a, T, A: Allocator> DoubleEndedIterator for Drain<'a, T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<'a, T, A: Allocator> ExactSizeIterator for Drain<'a, T, A> {
+    fn len(&self) -> usize {
+        self.list.len
+    }
+}
+
+impl<'a, T, A: Allocator> FusedIterator for Drain<'a, T, A> {}
+
+impl<'a, T, A: Allocator> Drop for Drain<'a, T, A> {
+    fn drop(&mut self) {
+        // Runs even if dropping an element panics. The nodes left would then
+        // be freed along with `self.list`, so the handles to them must go.
+        struct Guard<'r, 'a, T, A: Allocator>(&'r mut Drain<'a, T, A>);
+
+        impl<'r, 'a, T, A: Allocator> Drop for Guard<'r, 'a, T, A> {
+            fn drop(&mut self) {
+                let drain = &mut *self.0;
+                if !drain.list.is_empty() {
+                    drain.from.forget_handles();
+                }
+                unsafe { drain.from.adopt_pool(&mut drain.list) };
+            }
+        }
+
+        let guard = Guard(self);
+        guard.0.list.clear();
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::de::SeqAccess;
+    use serde::de::Visitor;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    use super::LinkedList;
+    use crate::allocator::Allocator;
+
+    // A plain sequence, front to back, same as std's LinkedList
+    impl<T: Serialize, A: Allocator> Serialize for LinkedList<T, A> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.iter())
+        }
+    }
+
+    impl<'de, T: Deserialize<'de>, A: Allocator + Default> Deserialize<'de> for LinkedList<T, A> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_seq(LinkedListVisitor(PhantomData))
+        }
+    }
+
+    struct LinkedListVisitor<T, A>(PhantomData<(T, A)>);
+
+    impl<'de, T: Deserialize<'de>, A: Allocator + Default> Visitor<'de> for LinkedListVisitor<T, A> {
+        type Value = LinkedList<T, A>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a sequence")
+        }
+
+        fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+            let mut list = LinkedList::new_in(A::default());
+            while let Some(elem) = seq.next_element()? {
+                list.push_back(elem);
+            }
+            Ok(list)
+        }
+    }
+}
+
+// From front to back, in the global allocator like fifth's
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl {
+    use arbitrary::Arbitrary;
+    use arbitrary::Result;
+    use arbitrary::Unstructured;
+
+    use super::LinkedList;
+
+    impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for LinkedList<T> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_iter()?.collect()
+        }
+
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_take_rest_iter()?.collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sixth::Cursor;
+    use crate::sixth::CursorMut;
+    use crate::sixth::Drain;
+    use crate::sixth::IntoIter;
+    use crate::sixth::Iter;
+    use crate::sixth::IterMut;
+
+    use super::LinkedList;
+
+    // Long enough to overflow the stack if anything recursed over the nodes.
+    // Strict checks make every operation O(n), so we go easier on them.
+    const LONG: usize = if cfg!(feature = "strict-checks") {
+        1_000
+    } else {
+        100_000
+    };
+
+    fn generate_test() -> LinkedList<i32> {
+        list_from(&[0, 1, 2, 3, 4, 5, 6])
+    }
+
+    fn list_from<T: Clone>(v: &[T]) -> LinkedList<T> {
+        v.iter().map(|x| (*x).clone()).collect()
+    }
+
+    #[test]
+    fn test_basic_front() {
+        let mut list = LinkedList::new();
+
+        // Try to break an empty list
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.len(), 0);
+
+        // Try to break a one item list
+        list.push_front(10);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.pop_front(), Some(10));
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.len(), 0);
+
+        // Mess around
+        list.push_front(10);
+        assert_eq!(list.len(), 1);
+        list.push_front(20);
+        assert_eq!(list.len(), 2);
+        list.push_front(30);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_front(), Some(30));
+        assert_eq!(list.len(), 2);
+        list.push_front(40);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.pop_front(), Some(40));
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.pop_front(), Some(20));
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.pop_front(), Some(10));
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.len(), 0);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.len(), 0);
+    }
+
+    #[test]
+    fn test_basic() {
+        let mut m = LinkedList::new();
+        assert_eq!(m.pop_front(), None);
+        assert_eq!(m.pop_back(), None);
+        assert_eq!(m.pop_front(), None);
+        m.push_front(1);
+        assert_eq!(m.pop_front(), Some(1));
+        m.push_back(2);
+        m.push_back(3);
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.pop_front(), Some(2));
+        assert_eq!(m.pop_front(), Some(3));
+        assert_eq!(m.len(), 0);
+        assert_eq!(m.pop_front(), None);
+        m.push_back(1);
+        m.push_back(3);
+        m.push_back(5);
+        m.push_back(7);
+        assert_eq!(m.pop_front(), Some(1));
+
+        let mut n = LinkedList::new();
+        n.push_front(2);
+        n.push_front(3);
+        {
+            assert_eq!(n.front().unwrap(), &3);
+            let x = n.front_mut().unwrap();
+            assert_eq!(*x, 3);
+            *x = 0;
+        }
+        {
+            assert_eq!(n.back().unwrap(), &2);
+            let y = n.back_mut().unwrap();
+            assert_eq!(*y, 2);
+            *y = 1;
+        }
+        assert_eq!(n.pop_front(), Some(0));
+        assert_eq!(n.pop_front(), Some(1));
+    }
+
+    #[test]
+    fn test_iterator() {
+        let m = generate_test();
+        for (i, elt) in m.iter().enumerate() {
+            assert_eq!(i as i32, *elt);
+        }
+        let mut n = LinkedList::new();
+        assert_eq!(n.iter().next(), None);
+        n.push_front(4);
+        let mut it = n.iter();
+        assert_eq!(it.size_hint(), (1, Some(1)));
+        assert_eq!(it.next().unwrap(), &4);
+        assert_eq!(it.size_hint(), (0, Some(0)));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_iterator_double_end() {
+        let mut n = LinkedList::new();
+        assert_eq!(n.iter().next(), None);
+        n.push_front(4);
+        n.push_front(5);
+        n.push_front(6);
+        let mut it = n.iter();
+        assert_eq!(it.size_hint(), (3, Some(3)));
+        assert_eq!(it.next().unwrap(), &6);
+        assert_eq!(it.size_hint(), (2, Some(2)));
+        assert_eq!(it.next_back().unwrap(), &4);
+        assert_eq!(it.size_hint(), (1, Some(1)));
+        assert_eq!(it.next_back().unwrap(), &5);
+        assert_eq!(it.next_back(), None);
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    #[allow(clippy::manual_next_back)]
+    fn test_rev_iter() {
+        let m = generate_test();
+        for (i, elt) in m.iter().rev().enumerate() {
+            assert_eq!(6 - i as i32, *elt);
+        }
+        let mut n = LinkedList::new();
+        assert_eq!(n.iter().rev().next(), None);
+        n.push_front(4);
+        let mut it = n.iter().rev();
+        assert_eq!(it.size_hint(), (1, Some(1)));
+        assert_eq!(it.next().unwrap(), &4);
+        assert_eq!(it.size_hint(), (0, Some(0)));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    fn test_into_iter_double_end() {
+        let mut n = LinkedList::new();
+        assert_eq!(n.clone().into_iter().next_back(), None);
+        n.push_front(4);
+        n.push_front(5);
+        n.push_front(6);
+        let mut it = n.into_iter();
+        assert_eq!(it.size_hint(), (3, Some(3)));
+        assert_eq!(it.next(), Some(6));
+        assert_eq!(it.size_hint(), (2, Some(2)));
+        assert_eq!(it.next_back(), Some(4));
+        assert_eq!(it.size_hint(), (1, Some(1)));
+        assert_eq!(it.next_back(), Some(5));
+        assert_eq!(it.next_back(), None);
+        assert_eq!(it.next(), None);
+
+        let m = generate_test();
+        assert_eq!(
+            m.into_iter().rev().collect::<Vec<_>>(),
+            &[6, 5, 4, 3, 2, 1, 0]
+        );
+    }
+
+    #[test]
+    fn test_exact_size_fused() {
+        fn is_fused<I: std::iter::FusedIterator>(_: &I) {}
+
+        let mut m = generate_test();
+        let mut it = m.iter();
+        is_fused(&it);
+        assert_eq!(it.len(), 7);
+        it.next();
+        it.next_back();
+        assert_eq!(it.len(), 5);
+        assert_eq!(it.by_ref().count(), 5);
+        assert_eq!(it.next(), None);
+        assert_eq!(it.next_back(), None);
+
+        let mut it = m.iter_mut();
+        is_fused(&it);
+        assert_eq!(it.len(), 7);
+        it.nth(5);
+        assert_eq!(it.len(), 1);
+
+        let mut it = m.into_iter();
+        is_fused(&it);
+        it.next_back();
+        assert_eq!(it.len(), 6);
+        assert_eq!(it.by_ref().skip(2).len(), 4);
+        assert_eq!(it.by_ref().skip(2).count(), 4);
+        assert_eq!(it.next(), None);
+        assert_eq!(it.len(), 0);
+
+        // Adapters keep the exact size around
+        let m = generate_test();
+        assert_eq!(m.iter().map(|x| x * 2).rev().len(), 7);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>().capacity(), 7);
+    }
+
+    #[test]
+    fn test_two_pointer() {
+        // The classic use of a double-ended iterator: walk in from both ends
+        fn is_palindrome(list: &LinkedList<char>) -> bool {
+            let mut it = list.iter();
+            while let (Some(front), Some(back)) = (it.next(), it.next_back()) {
+                if front != back {
+                    return false;
+                }
+            }
+            true
+        }
+
+        assert!(is_palindrome(&"racecar".chars().collect()));
+        assert!(is_palindrome(&"abba".chars().collect()));
+        assert!(is_palindrome(&LinkedList::new()));
+        assert!(!is_palindrome(&"linked".chars().collect()));
+    }
+
+    #[test]
+    fn test_mut_iter() {
+        let mut m = generate_test();
+        let mut len = m.len();
+        for (i, elt) in m.iter_mut().enumerate() {
+            assert_eq!(i as i32, *elt);
+            len -= 1;
+        }
+        assert_eq!(len, 0);
+        let mut n = LinkedList::new();
+        assert!(n.iter_mut().next().is_none());
+        n.push_front(4);
+        n.push_back(5);
+        let mut it = n.iter_mut();
+        assert_eq!(it.size_hint(), (2, Some(2)));
+        assert!(it.next().is_some());
+        assert!(it.next().is_some());
+        assert_eq!(it.size_hint(), (0, Some(0)));
+        assert!(it.next().is_none());
+    }
+
+    #[test]
+    fn test_iterator_mut_double_end() {
+        let mut n = LinkedList::new();
+        assert!(n.iter_mut().next_back().is_none());
+        n.push_front(4);
+        n.push_front(5);
+        n.push_front(6);
+        let mut it = n.iter_mut();
+        assert_eq!(it.size_hint(), (3, Some(3)));
+        assert_eq!(*it.next().unwrap(), 6);
+        assert_eq!(it.size_hint(), (2, Some(2)));
+        assert_eq!(*it.next_back().unwrap(), 4);
+        assert_eq!(it.size_hint(), (1, Some(1)));
+        assert_eq!(*it.next_back().unwrap(), 5);
+        assert!(it.next_back().is_none());
+        assert!(it.next().is_none());
+    }
+
+    #[test]
+    fn test_default_collect_extend() {
+        let mut m: LinkedList<u32> = Default::default();
+        check_links(&m);
+        assert!(m.is_empty());
+
+        m.extend([1, 2]);
+        m.extend(&[3, 4]);
+        m.extend(&list_from(&[5]));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 3, 4, 5]);
+
+        let n: LinkedList<u32> = m.iter().map(|x| x * 10).collect();
+        check_links(&n);
+        assert_eq!(n.front(), Some(&10));
+        assert_eq!(n.back(), Some(&50));
+    }
+
+    #[test]
+    fn test_truncate_resize_with() {
+        let mut m: LinkedList<u32> = (0..5).collect();
+        m.truncate(7);
+        assert_eq!(m.len(), 5);
+        m.truncate(2);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1]);
+
+        let mut next = 10;
+        m.resize_with(4, || {
+            next += 1;
+            next
+        });
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 11, 12]);
+        m.resize_with(1, || unreachable!());
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0]);
+        m.truncate(0);
+        check_links(&m);
+        assert!(m.is_empty());
+
+        // Long lists are fine too
+        let mut m = LinkedList::new();
+        m.resize_with(LONG, Default::default);
+        assert_eq!(m.len(), LONG);
+        m.truncate(1);
+        assert_eq!(m.iter().cloned().collect::<Vec<u32>>(), &[0]);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut m = LinkedList::with_capacity(4);
+        assert_eq!(m.capacity(), 4);
+        assert!(m.is_empty());
+
+        // Pushing takes nodes from the pool, popping puts them back
+        m.extend([1, 2, 3]);
+        assert_eq!(m.capacity(), 4);
+        m.push_front(0);
+        m.push_back(4);
+        assert_eq!(m.capacity(), 5);
+        assert_eq!(m.pop_front(), Some(0));
+        assert_eq!(m.pop_back(), Some(4));
+        assert_eq!(m.remove(1), Some(2));
+        assert_eq!(m.capacity(), 5);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 3]);
+
+        // reserve counts the elements we already have, like Vec's
+        m.reserve(2);
+        assert_eq!(m.capacity(), 5);
+        m.reserve(4);
+        assert_eq!(m.capacity(), 6);
+
+        m.clear();
+        assert_eq!(m.capacity(), 6);
+        m.shrink_to_fit();
+        assert_eq!(m.capacity(), 0);
+
+        // Elements we put in pooled nodes are dropped as usual
+        let mut m = LinkedList::with_capacity(2);
+        m.push_back(String::from("a"));
+        m.push_back(String::from("b"));
+        assert_eq!(m.pop_back().as_deref(), Some("b"));
+        m.push_back(String::from("c"));
+        assert_eq!(m.iter().collect::<Vec<_>>(), &["a", "c"]);
+    }
+
+    #[test]
+    fn test_try_push() {
+        let mut m = LinkedList::new();
+        assert_eq!(m.try_push_back(2), Ok(()));
+        assert_eq!(m.try_push_front(1), Ok(()));
+        assert_eq!(m.try_push_back(3), Ok(()));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 3]);
+
+        // Reusing pooled nodes
+        m.clear();
+        assert_eq!(m.try_push_front(4), Ok(()));
+        assert_eq!(m.capacity(), 3);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[4]);
+    }
+
+    #[test]
+    fn test_append_prepend() {
+        let mut m = list_from(&[1, 2]);
+        let mut n = list_from(&[3, 4, 5]);
+        m.append(&mut n);
+        check_links(&m);
+        check_links(&n);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 3, 4, 5]);
+        assert!(n.is_empty());
+
+        // other can still be used afterwards
+        n.push_back(0);
+        m.prepend(&mut n);
+        check_links(&m);
+        check_links(&n);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 2, 3, 4, 5]);
+        assert!(n.is_empty());
+
+        // Empty lists on either side
+        m.append(&mut n);
+        m.prepend(&mut n);
+        assert_eq!(m.len(), 6);
+        n.append(&mut m);
+        check_links(&m);
+        check_links(&n);
+        assert!(m.is_empty());
+        assert_eq!(n.iter().cloned().collect::<Vec<_>>(), &[0, 1, 2, 3, 4, 5]);
+        m.prepend(&mut n);
+        check_links(&m);
+        assert_eq!(m.len(), 6);
+        assert!(n.is_empty());
+    }
+
+    #[test]
+    fn test_queries() {
+        let mut m = list_from(&[3, 1, 4, 1, 5]);
+        assert_eq!(m.front(), Some(&3));
+        assert_eq!(m.back(), Some(&5));
+        *m.front_mut().unwrap() = 2;
+        *m.back_mut().unwrap() = 6;
+
+        assert!(m.contains(&1));
+        assert!(!m.contains(&3));
+        assert_eq!(m.find(|x| *x > 3), Some(&4));
+        assert_eq!(m.find(|x| *x > 6), None);
+        *m.find_mut(|x| *x == 1).unwrap() = 0;
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[2, 0, 4, 1, 6]);
+
+        let e: LinkedList<u32> = LinkedList::new();
+        assert_eq!(e.front(), None);
+        assert_eq!(e.back(), None);
+        assert!(!e.contains(&0));
+        assert_eq!(e.find(|_| true), None);
+    }
+
+    #[test]
+    fn test_node_ref() {
+        // A tiny LRU: the most recently used entry is at the front
+        let mut lru = LinkedList::new();
+        let a = lru.push_front_ref('a');
+        let b = lru.push_front_ref('b');
+        let c = lru.push_back_ref('c');
+        assert_eq!(lru.iter().cloned().collect::<Vec<_>>(), &['b', 'a', 'c']);
+
+        let addr = lru.back().unwrap() as *const char;
+        assert!(lru.move_node_to_front(c));
+        check_links(&lru);
+        assert_eq!(lru.iter().cloned().collect::<Vec<_>>(), &['c', 'b', 'a']);
+        // The element didn't move in memory
+        assert_eq!(lru.front().unwrap() as *const char, addr);
+
+        assert!(lru.move_node_to_back(b));
+        assert!(lru.move_node_to_back(b));
+        check_links(&lru);
+        assert_eq!(lru.iter().cloned().collect::<Vec<_>>(), &['c', 'a', 'b']);
+
+        assert_eq!(lru.remove_node(a), Some('a'));
+        check_links(&lru);
+        assert_eq!(lru.remove_node(c), Some('c'));
+        assert!(lru.move_node_to_front(b));
+        check_links(&lru);
+        assert_eq!(lru.iter().cloned().collect::<Vec<_>>(), &['b']);
+        assert_eq!(lru.remove_node(b), Some('b'));
+        check_links(&lru);
+        assert!(lru.is_empty());
+        assert_ne!(a, b);
+
+        // Removed elements are gone for good
+        assert_eq!(lru.remove_node(a), None);
+        assert!(!lru.move_node_to_front(b));
+        assert!(!lru.move_node_to_back(c));
+    }
+
+    #[test]
+    fn test_node_ref_wrong_list() {
+        let mut m = list_from(&[1]);
+        let mut n = LinkedList::new();
+        let node = n.push_back_ref(2);
+        assert!(!m.move_node_to_front(node));
+        assert_eq!(m.remove_node(node), None);
+        assert_eq!(n.remove_node(node), Some(2));
     }
-}
 
-impl<T: Ord> Ord for LinkedList<T> {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.iter().cmp(other)
+    #[test]
+    fn test_node_ref_reused() {
+        let mut m = LinkedList::new();
+        m.push_back(1);
+        let node = m.push_back_ref(2);
+        assert_eq!(m.pop_back(), Some(2));
+        // This takes the same node out of the pool
+        let other = m.push_back_ref(3);
+        assert_eq!(node.node, other.node);
+        assert_ne!(node, other);
+        assert!(!m.move_node_to_front(node));
+        assert_eq!(m.remove_node(node), None);
+        assert_eq!(m.remove_node(other), Some(3));
     }
-}
 
-impl<T: Hash> Hash for LinkedList<T> {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.len().hash(state);
-        for item in self {
-            item.hash(state);
-        }
+    #[test]
+    fn test_node_ref_drain() {
+        let mut m = LinkedList::new();
+        let before = m.push_back_ref(1);
+        let drained = m.push_back_ref(2);
+        m.push_back(3);
+        let after = m.push_back_ref(4);
+
+        // Only the drained elements' handles stop working, even if the Drain
+        // is leaked
+        core::mem::forget(m.drain(1..3));
+        assert_eq!(m.remove_node(drained), None);
+        assert!(m.move_node_to_front(after));
+        assert!(m.move_node_to_front(before));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 4]);
+
+        // Draining nothing leaves every handle alone
+        assert_eq!(m.drain(1..1).count(), 0);
+        assert_eq!(m.remove_node(after), Some(4));
+        assert_eq!(m.drain(..).collect::<Vec<_>>(), &[1]);
+        assert_eq!(m.remove_node(before), None);
+        // The drained node is back in the pool, only the leaked ones are gone
+        assert_eq!(m.capacity(), 2);
     }
-}
 
-pub struct Iter<'a, T> {
-    front: Link<T>,
-    back: Link<T>,
-    len: usize,
-    _protection: PhantomData<&'a T>,
-}
+    #[test]
+    fn test_node_ref_forgotten() {
+        // Relinking nodes within the list keeps the handles working
+        let mut m = list_from(&[1, 2]);
+        let node = m.push_back_ref(3);
+        m.push_front(0);
+        assert_eq!(m.pop_front(), Some(0));
+        m.cursor_front_mut().remove_current();
+        m.swap(0, 1);
+        assert!(m.move_node_to_front(node));
+
+        // Splitting gives nodes away, so every handle goes, even to the nodes
+        // that stay
+        drop(m.split_off(1));
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[3]);
+        assert!(!m.move_node_to_back(node));
+
+        // Same for the nodes other gives us when appended
+        let node = m.push_back_ref(4);
+        let mut n = list_from(&[5]);
+        let moved = n.push_back_ref(6);
+        m.append(&mut n);
+        assert!(m.move_node_to_back(node));
+        assert_eq!(m.remove_node(moved), None);
+        assert_eq!(n.remove_node(moved), None);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[3, 5, 6, 4]);
+
+        // Prepending doesn't move our nodes either
+        let mut n = list_from(&[1, 2]);
+        m.prepend(&mut n);
+        assert!(n.is_empty());
+        assert!(m.move_node_to_front(node));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[4, 1, 2, 3, 5, 6]);
+
+        // Popping leaves the node in the pool, shrinking frees it
+        let kept = m.push_front_ref(0);
+        let node = m.push_back_ref(7);
+        assert_eq!(m.pop_back(), Some(7));
+        m.shrink_to_fit();
+        assert_eq!(m.remove_node(node), None);
+        assert_eq!(m.remove_node(kept), None);
+        assert_eq!(m.len(), 7);
+    }
 
-pub struct IterMut<'a, T> {
-    front: Link<T>,
-    back: Link<T>,
-    len: usize,
-    _protection: PhantomData<&'a mut T>,
-}
+    // With strict checks, the next operation catches a broken list
+    #[cfg(feature = "strict-checks")]
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "len doesn't match the number of nodes")]
+    fn test_strict_checks() {
+        let mut m = std::mem::ManuallyDrop::new(list_from(&[1, 2, 3]));
+        m.len += 1;
+        m.push_back(4);
+    }
 
-pub struct IntoIter<T> {
-    list: LinkedList<T>,
-}
+    #[test]
+    fn test_get() {
+        let mut m: LinkedList<u32> = (0..7).collect();
+        for i in 0..7 {
+            assert_eq!(m.get(i), Some(&(i as u32)));
+        }
+        assert_eq!(m.get(7), None);
 
-impl<'a, T> IntoIterator for &'a LinkedList<T> {
-    type IntoIter = Iter<'a, T>;
-    type Item = &'a T;
+        // From both halves of the list
+        *m.get_mut(1).unwrap() = 10;
+        *m.get_mut(5).unwrap() = 50;
+        assert_eq!(m.get_mut(7), None);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[0, 10, 2, 3, 4, 50, 6]
+        );
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter()
+        let e: LinkedList<u32> = LinkedList::new();
+        assert_eq!(e.get(0), None);
     }
-}
 
-impl<'a, T> Iterator for Iter<'a, T> {
-    type Item = &'a T;
+    #[test]
+    fn test_insert_remove() {
+        let mut m = LinkedList::new();
+        m.insert(0, 2);
+        m.insert(0, 0);
+        m.insert(2, 4);
+        m.insert(1, 1);
+        m.insert(3, 3);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 2, 3, 4]);
 
-    fn next(&mut self) -> Option<Self::Item> {
-        // While self.front == self.back is a tempting condition to check here,
-        // it won't do the right for yielding the last element! That sort of
-        // thing only works for arrays because of "one-past-the-end" pointers.
-        if self.len > 0 {
-            // We could unwrap front, but this is safer and easier
-            self.front.map(|node| unsafe {
-                self.len -= 1;
-                self.front = (*node.as_ptr()).back;
-                &(*node.as_ptr()).elem
-            })
-        } else {
-            None
-        }
+        assert_eq!(m.remove(5), None);
+        assert_eq!(m.remove(3), Some(3));
+        assert_eq!(m.remove(0), Some(0));
+        assert_eq!(m.remove(2), Some(4));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2]);
+        assert_eq!(m.remove(1), Some(2));
+        assert_eq!(m.remove(0), Some(1));
+        assert_eq!(m.remove(0), None);
+        check_links(&m);
     }
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.len, Some(self.len))
+    #[test]
+    #[should_panic(expected = "insert index 2 out of range")]
+    fn test_insert_out_of_bounds() {
+        list_from(&[1]).insert(2, 0);
     }
-}
 
-impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
-    fn next_back(&mut self) -> Option<Self::Item> {
-        if self.len > 0 {
-            self.back.map(|node| unsafe {
-                self.len -= 1;
-                self.back = (*node.as_ptr()).front;
-                &(*node.as_ptr()).elem
-            })
-        } else {
-            None
+    #[test]
+    fn test_rotate() {
+        let mut m: LinkedList<u32> = (0..5).collect();
+        m.rotate_left(2);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[2, 3, 4, 0, 1]);
+        m.rotate_right(2);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 2, 3, 4]);
+        m.rotate_right(1);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[4, 0, 1, 2, 3]);
+
+        // Full and empty rotations are no-ops
+        m.rotate_left(0);
+        m.rotate_left(5);
+        m.rotate_right(5);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[4, 0, 1, 2, 3]);
+
+        let mut e: LinkedList<u32> = LinkedList::new();
+        e.rotate_left(0);
+        e.rotate_right(0);
+        check_links(&e);
+
+        // The nodes are relinked in place, so the pool and the handles stay
+        let mut m = LinkedList::with_capacity(6);
+        m.extend([0, 1, 2]);
+        let node = m.push_back_ref(3);
+        m.rotate_left(3);
+        m.rotate_right(1);
+        assert_eq!(m.capacity(), 6);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[2, 3, 0, 1]);
+        assert!(m.move_node_to_front(node));
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[3, 2, 0, 1]);
+
+        // Round-robin: the front goes to the back of the line
+        let mut queue = list_from(&['a', 'b', 'c']);
+        let mut served = Vec::new();
+        for _ in 0..4 {
+            served.push(*queue.front().unwrap());
+            queue.rotate_left(1);
         }
+        assert_eq!(served, &['a', 'b', 'c', 'a']);
     }
-}
 
-impl<'a, T> ExactSizeIterator for Iter<'a, T> {
-    fn len(&self) -> usize {
-        self.len
+    #[test]
+    #[should_panic(expected = "rotation by 4 out of range")]
+    fn test_rotate_out_of_bounds() {
+        list_from(&[1, 2, 3]).rotate_right(4);
     }
-}
 
-impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
-    type IntoIter = IterMut<'a, T>;
-    type Item = &'a mut T;
+    #[test]
+    fn test_merge() {
+        let m = list_from(&[1, 3, 5, 7]);
+        let n = list_from(&[0, 2, 3, 8, 9]);
+        let merged = m.merge(n, |a, b| a.cmp(b));
+        check_links(&merged);
+        assert_eq!(
+            merged.iter().cloned().collect::<Vec<_>>(),
+            &[0, 1, 2, 3, 3, 5, 7, 8, 9]
+        );
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter_mut()
-    }
-}
+        // Ties take from self first
+        let m = list_from(&[(1, 'a'), (2, 'a')]);
+        let n = list_from(&[(1, 'b'), (2, 'b')]);
+        let merged = m.merge(n, |a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            merged.iter().cloned().collect::<Vec<_>>(),
+            &[(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')]
+        );
 
-impl<'a, T> Iterator for IterMut<'a, T> {
-    type Item = &'a mut T;
+        let e = LinkedList::new();
+        let merged = e.merge(list_from(&[1, 2]), |a: &i32, b| a.cmp(b));
+        check_links(&merged);
+        assert_eq!(merged.iter().cloned().collect::<Vec<_>>(), &[1, 2]);
+        let merged = merged.merge(LinkedList::new(), |a, b| a.cmp(b));
+        check_links(&merged);
+        assert_eq!(merged.iter().cloned().collect::<Vec<_>>(), &[1, 2]);
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        // While self.front == self.back is a tempting condition to check here,
-        // it won't do the right for yielding the last element! That sort of
-        // thing only works for arrays because of "one-past-the-end" pointers.
-        if self.len > 0 {
-            // We could unwrap front, but this is safer and easier
-            self.front.map(|node| unsafe {
-                self.len -= 1;
-                self.front = (*node.as_ptr()).back;
-                &mut (*node.as_ptr()).elem
-            })
-        } else {
-            None
+    #[test]
+    fn test_insert_sorted() {
+        let mut m = LinkedList::new();
+        for elem in [5, 1, 4, 1, 3, 9, 0] {
+            m.insert_sorted(elem, |a, b| a.cmp(b));
+            check_links(&m);
         }
-    }
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[0, 1, 1, 3, 4, 5, 9]
+        );
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.len, Some(self.len))
+        // Equal elements stay in insertion order
+        let mut m = LinkedList::new();
+        m.insert_sorted((1, 'a'), |a, b| a.0.cmp(&b.0));
+        m.insert_sorted((0, 'a'), |a, b| a.0.cmp(&b.0));
+        m.insert_sorted((1, 'b'), |a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[(0, 'a'), (1, 'a'), (1, 'b')]
+        );
     }
-}
 
-impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
-    fn next_back(&mut self) -> Option<Self::Item> {
-        if self.len > 0 {
-            self.back.map(|node| unsafe {
-                self.len -= 1;
-                self.back = (*node.as_ptr()).front;
-                &mut (*node.as_ptr()).elem
-            })
-        } else {
-            None
-        }
+    #[test]
+    fn test_swap() {
+        let mut m: LinkedList<u32> = (0..6).collect();
+        let addr = m.front().unwrap() as *const u32;
+        m.swap(0, 5);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[5, 1, 2, 3, 4, 0]);
+        // The element moved with its node
+        assert_eq!(m.back().unwrap() as *const u32, addr);
+
+        // Neighbours, in both orders
+        m.swap(2, 1);
+        m.swap(4, 5);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[5, 2, 1, 3, 0, 4]);
+
+        m.swap(1, 4);
+        m.swap(3, 3);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[5, 0, 1, 3, 2, 4]);
+
+        let mut two = list_from(&[1, 2]);
+        two.swap(0, 1);
+        check_links(&two);
+        assert_eq!(two.iter().cloned().collect::<Vec<_>>(), &[2, 1]);
     }
-}
 
-impl<'a, T> ExactSizeIterator for IterMut<'a, T> {
-    fn len(&self) -> usize {
-        self.len
+    #[test]
+    #[should_panic(expected = "swap index 3 out of range")]
+    fn test_swap_out_of_bounds() {
+        list_from(&[1, 2, 3]).swap(0, 3);
     }
-}
 
-impl<T> IntoIterator for LinkedList<T> {
-    type IntoIter = IntoIter<T>;
-    type Item = T;
+    #[test]
+    fn test_partition_point() {
+        let mut m = list_from(&[1, 2, 2, 3, 5, 8]);
+        let cursor = m.partition_point(|x| *x < 3);
+        assert_eq!(cursor.index(), Some(3));
+        assert_eq!(cursor.current(), Some(&3));
+        assert_eq!(cursor.peek_prev(), Some(&2));
+
+        let cursor = m.partition_point(|_| true);
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.peek_prev(), Some(&8));
+        assert_eq!(m.partition_point(|_| false).index(), Some(0));
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.into_iter()
-    }
-}
+        // Inserting at the boundary keeps the list partitioned
+        m.partition_point_mut(|x| x % 2 == 1).insert_before(7);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[1, 7, 2, 2, 3, 5, 8]
+        );
 
-impl<T> Iterator for IntoIter<T> {
-    type Item = T;
+        // Equal elements come before the insert point
+        let mut m = list_from(&[1, 2, 2, 3]);
+        let mut cursor = m.find_insert_point(&2, |a, b| a.cmp(b));
+        assert_eq!(cursor.index(), Some(3));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        let mut cursor = m.find_insert_point(&9, |a, b| a.cmp(b));
+        assert_eq!(cursor.index(), None);
+        cursor.insert_before(9);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 2, 3, 9]);
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.list.pop_front()
+        let e: LinkedList<u32> = LinkedList::new();
+        assert_eq!(e.partition_point(|_| true).index(), None);
     }
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.list.len, Some(self.list.len))
+    #[test]
+    fn test_split_off() {
+        let mut m: LinkedList<u32> = (0..6).collect();
+        let n = m.split_off(4);
+        let o = m.split_off(1);
+        check_links(&m);
+        check_links(&n);
+        check_links(&o);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0]);
+        assert_eq!(o.iter().cloned().collect::<Vec<_>>(), &[1, 2, 3]);
+        assert_eq!(n.iter().cloned().collect::<Vec<_>>(), &[4, 5]);
+
+        let all = m.split_off(0);
+        let none = m.split_off(0);
+        assert!(m.is_empty());
+        assert!(none.is_empty());
+        assert_eq!(all.len(), 1);
     }
-}
 
-impl<T> DoubleEndedIterator for IntoIter<T> {
-    fn next_back(&mut self) -> Option<Self::Item> {
-        self.list.pop_back()
+    #[test]
+    #[should_panic(expected = "split index 4 out of range")]
+    fn test_split_off_out_of_bounds() {
+        list_from(&[1, 2, 3]).split_off(4);
     }
-}
 
-impl<T> ExactSizeIterator for IntoIter<T> {
-    fn len(&self) -> usize {
-        self.list.len
+    #[test]
+    fn test_drain() {
+        let mut m: LinkedList<u32> = (0..10).collect();
+        let mut drain = m.drain(2..5);
+        assert_eq!(drain.len(), 3);
+        assert_eq!(drain.next(), Some(2));
+        assert_eq!(drain.next_back(), Some(4));
+        drop(drain);
+        check_links(&m);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[0, 1, 5, 6, 7, 8, 9]
+        );
+
+        assert_eq!(m.drain(..=1).collect::<Vec<_>>(), &[0, 1]);
+        assert_eq!(m.drain(3..).collect::<Vec<_>>(), &[8, 9]);
+        assert_eq!(m.drain(1..1).count(), 0);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[5, 6, 7]);
+
+        // Leaking the Drain leaks the range, but the rest is still fine
+        std::mem::forget(m.drain(1..2));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[5, 7]);
+
+        assert_eq!(m.drain(..).collect::<Vec<_>>(), &[5, 7]);
+        check_links(&m);
+        assert!(m.is_empty());
     }
-}
 
-#[cfg(test)]
-mod test {
-    use std::io::Cursor;
+    #[test]
+    fn test_remove_unordered() {
+        let mut m = list_from(&[0, 1, 2, 3]);
+        let node = m.push_back_ref(4);
+        assert_eq!(m.remove_unordered(1), Some(1));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 4, 2, 3]);
+        // The back node moved, its handle still works
+        assert!(m.move_node_to_back(node));
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 2, 3, 4]);
+
+        assert_eq!(m.remove_unordered(0), Some(0));
+        assert_eq!(m.remove_unordered(2), Some(3));
+        assert_eq!(m.remove_unordered(2), None);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[4, 2]);
 
-    use crate::sixth::IntoIter;
-    use crate::sixth::Iter;
-    use crate::sixth::IterMut;
+        // Removing the back leaves the cursor on the ghost
+        let mut cursor = m.cursor_back_mut();
+        assert_eq!(cursor.remove_current_unordered(), Some(2));
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.remove_current_unordered(), None);
 
-    use super::LinkedList;
+        // Otherwise it stays at the same index, on the former back
+        let mut m = list_from(&[0, 1, 2]);
+        let mut cursor = m.cursor_front_mut();
+        assert_eq!(cursor.remove_current_unordered(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.remove_current_unordered(), Some(2));
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.remove_current_unordered(), Some(1));
+        assert_eq!(cursor.current(), None);
+        check_links(&m);
+        assert!(m.is_empty());
+    }
 
-    fn generate_test() -> LinkedList<i32> {
-        list_from(&[0, 1, 2, 3, 4, 5, 6])
+    #[test]
+    fn test_iter_mut_split_at() {
+        let mut m = list_from(&[5, 1, 4, 2, 3, 0]);
+        let mut iter = m.iter_mut();
+        iter.next();
+        iter.next_back();
+
+        // Partition what's left around 2, swapping from both ends at once
+        let (mut front, mut back) = iter.split_at(2);
+        assert_eq!(front.len(), 2);
+        assert_eq!(back.len(), 2);
+        for (a, b) in front.by_ref().zip(back.by_ref().rev()) {
+            if *a > 2 && *b <= 2 {
+                std::mem::swap(a, b);
+            }
+        }
+        assert_eq!(front.next(), None);
+        assert_eq!(back.next(), None);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[5, 1, 2, 4, 3, 0]);
+
+        // Splitting at the ends gives an empty half
+        let (front, back) = m.iter_mut().split_at(0);
+        assert_eq!(front.count(), 0);
+        assert_eq!(back.count(), 6);
+        let (mut front, mut back) = m.iter_mut().split_at(6);
+        assert_eq!(front.next_back(), Some(&mut 0));
+        assert_eq!(back.next(), None);
+        assert_eq!(back.next_back(), None);
+
+        let (front, back) = m.iter().split_at(4);
+        assert_eq!(front.cloned().collect::<Vec<_>>(), &[5, 1, 2, 4]);
+        assert_eq!(back.cloned().collect::<Vec<_>>(), &[3, 0]);
     }
 
-    fn list_from<T: Clone>(v: &[T]) -> LinkedList<T> {
-        v.iter().map(|x| (*x).clone()).collect()
+    #[test]
+    #[should_panic(expected = "split index 3 out of range for iterator of length 2")]
+    fn test_iter_mut_split_at_out_of_bounds() {
+        let mut m = list_from(&[1, 2, 3]);
+        let mut iter = m.iter_mut();
+        iter.next();
+        iter.split_at(3);
     }
 
     #[test]
-    fn test_basic_front() {
-        let mut list = LinkedList::new();
+    fn test_forget_drain() {
+        let mut m = list_from(&[0, 1, 2, 3, 4, 5]);
+        let mut drain = m.drain(1..4);
+        assert_eq!(drain.next(), Some(1));
+        assert_eq!(drain.next_back(), Some(3));
+        std::mem::forget(drain);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 4, 5]);
 
-        // Try to break an empty list
-        assert_eq!(list.len(), 0);
-        assert_eq!(list.pop_front(), None);
-        assert_eq!(list.len(), 0);
+        // The list is still fully usable
+        m.push_back(6);
+        m.push_front(-1);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[-1, 0, 4, 5, 6]);
+    }
 
-        // Try to break a one item list
-        list.push_front(10);
-        assert_eq!(list.len(), 1);
-        assert_eq!(list.pop_front(), Some(10));
-        assert_eq!(list.len(), 0);
-        assert_eq!(list.pop_front(), None);
-        assert_eq!(list.len(), 0);
+    #[test]
+    fn test_into_iter_both_ends() {
+        let mut it = generate_test().into_iter();
+        assert_eq!(it.len(), 7);
+        assert_eq!(it.next_back(), Some(6));
+        assert_eq!(it.next(), Some(0));
+        assert_eq!(it.nth_back(1), Some(4));
+        assert_eq!(it.len(), 3);
+        assert_eq!(format!("{it:?}"), "IntoIter([1, 2, 3])");
+
+        // The clone only has what's left, and goes on independently
+        let mut other = it.clone();
+        assert_eq!(it.next(), Some(1));
+        assert_eq!(other.len(), 3);
+        assert_eq!(other.by_ref().rev().collect::<Vec<_>>(), &[3, 2, 1]);
+        assert_eq!(it.collect::<Vec<_>>(), &[2, 3]);
+        assert_eq!(other.next(), None);
+    }
 
-        // Mess around
-        list.push_front(10);
-        assert_eq!(list.len(), 1);
-        list.push_front(20);
-        assert_eq!(list.len(), 2);
-        list.push_front(30);
-        assert_eq!(list.len(), 3);
-        assert_eq!(list.pop_front(), Some(30));
-        assert_eq!(list.len(), 2);
-        list.push_front(40);
-        assert_eq!(list.len(), 3);
-        assert_eq!(list.pop_front(), Some(40));
-        assert_eq!(list.len(), 2);
-        assert_eq!(list.pop_front(), Some(20));
-        assert_eq!(list.len(), 1);
-        assert_eq!(list.pop_front(), Some(10));
-        assert_eq!(list.len(), 0);
-        assert_eq!(list.pop_front(), None);
-        assert_eq!(list.len(), 0);
-        assert_eq!(list.pop_front(), None);
-        assert_eq!(list.len(), 0);
+    #[test]
+    fn test_forget_into_iter() {
+        let mut iter = list_from(&[1, 2, 3, 4]).into_iter();
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next_back(), Some(4));
+        // In between calls, the iterator is a valid list with what's left
+        iter.list.assert_invariants();
+        assert_eq!(iter.list.len(), 2);
+        std::mem::forget(iter);
     }
 
     #[test]
-    fn test_basic() {
-        let mut m = LinkedList::new();
-        assert_eq!(m.pop_front(), None);
-        assert_eq!(m.pop_back(), None);
-        assert_eq!(m.pop_front(), None);
-        m.push_front(1);
-        assert_eq!(m.pop_front(), Some(1));
-        m.push_back(2);
-        m.push_back(3);
-        assert_eq!(m.len(), 2);
-        assert_eq!(m.pop_front(), Some(2));
-        assert_eq!(m.pop_front(), Some(3));
-        assert_eq!(m.len(), 0);
-        assert_eq!(m.pop_front(), None);
-        m.push_back(1);
-        m.push_back(3);
-        m.push_back(5);
-        m.push_back(7);
-        assert_eq!(m.pop_front(), Some(1));
+    #[should_panic(expected = "len doesn't match")]
+    fn test_assert_invariants_len() {
+        let mut m = list_from(&[1, 2, 3]);
+        m.len = 2;
+        // Leak the broken list, dropping it would go wrong
+        let m = std::mem::ManuallyDrop::new(m);
+        m.assert_invariants();
+    }
 
-        let mut n = LinkedList::new();
-        n.push_front(2);
-        n.push_front(3);
-        {
-            assert_eq!(n.front().unwrap(), &3);
-            let x = n.front_mut().unwrap();
-            assert_eq!(*x, 3);
-            *x = 0;
-        }
-        {
-            assert_eq!(n.back().unwrap(), &2);
-            let y = n.back_mut().unwrap();
-            assert_eq!(*y, 2);
-            *y = 1;
-        }
-        assert_eq!(n.pop_front(), Some(0));
-        assert_eq!(n.pop_front(), Some(1));
+    #[test]
+    #[should_panic(expected = "broken front link")]
+    fn test_assert_invariants_front_link() {
+        let m = list_from(&[1, 2, 3]);
+        unsafe { (*m.back.unwrap().as_ptr()).front = m.front };
+        let m = std::mem::ManuallyDrop::new(m);
+        m.assert_invariants();
     }
 
     #[test]
-    fn test_iterator() {
-        let m = generate_test();
-        for (i, elt) in m.iter().enumerate() {
-            assert_eq!(i as i32, *elt);
-        }
-        let mut n = LinkedList::new();
-        assert_eq!(n.iter().next(), None);
-        n.push_front(4);
-        let mut it = n.iter();
-        assert_eq!(it.size_hint(), (1, Some(1)));
-        assert_eq!(it.next().unwrap(), &4);
-        assert_eq!(it.size_hint(), (0, Some(0)));
-        assert_eq!(it.next(), None);
+    #[should_panic(expected = "the pool has fewer nodes than free_len")]
+    fn test_assert_invariants_pool() {
+        let mut m = LinkedList::<i32>::with_capacity(2);
+        m.free_len = 3;
+        let m = std::mem::ManuallyDrop::new(m);
+        m.assert_invariants();
     }
 
     #[test]
-    fn test_iterator_double_end() {
-        let mut n = LinkedList::new();
-        assert_eq!(n.iter().next(), None);
-        n.push_front(4);
-        n.push_front(5);
-        n.push_front(6);
-        let mut it = n.iter();
-        assert_eq!(it.size_hint(), (3, Some(3)));
-        assert_eq!(it.next().unwrap(), &6);
-        assert_eq!(it.size_hint(), (2, Some(2)));
-        assert_eq!(it.next_back().unwrap(), &4);
-        assert_eq!(it.size_hint(), (1, Some(1)));
-        assert_eq!(it.next_back().unwrap(), &5);
-        assert_eq!(it.next_back(), None);
-        assert_eq!(it.next(), None);
+    #[should_panic(expected = "drain end 4 out of range")]
+    fn test_drain_out_of_bounds() {
+        list_from(&[1, 2, 3]).drain(1..4);
     }
 
     #[test]
-    fn test_rev_iter() {
-        let m = generate_test();
-        for (i, elt) in m.iter().rev().enumerate() {
-            assert_eq!(6 - i as i32, *elt);
-        }
-        let mut n = LinkedList::new();
-        assert_eq!(n.iter().rev().next(), None);
-        n.push_front(4);
-        let mut it = n.iter().rev();
-        assert_eq!(it.size_hint(), (1, Some(1)));
-        assert_eq!(it.next().unwrap(), &4);
-        assert_eq!(it.size_hint(), (0, Some(0)));
-        assert_eq!(it.next(), None);
+    #[should_panic(expected = "drain end out of range for usize")]
+    fn test_drain_end_overflow() {
+        list_from(&[1, 2, 3]).drain(..=usize::MAX);
     }
 
     #[test]
-    fn test_mut_iter() {
-        let mut m = generate_test();
-        let mut len = m.len();
-        for (i, elt) in m.iter_mut().enumerate() {
-            assert_eq!(i as i32, *elt);
-            len -= 1;
+    fn test_extract_if() {
+        let mut m: LinkedList<u32> = (0..10).collect();
+        let evens: Vec<_> = m.extract_if(|x| *x % 2 == 0).collect();
+        check_links(&m);
+        assert_eq!(evens, &[0, 2, 4, 6, 8]);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 3, 5, 7, 9]);
+
+        // Stopping early keeps the rest, even if it matches
+        {
+            let mut it = m.extract_if(|x| *x > 2);
+            assert_eq!(it.size_hint(), (0, Some(5)));
+            assert_eq!(it.next(), Some(3));
+            assert_eq!(it.size_hint(), (0, Some(3)));
         }
-        assert_eq!(len, 0);
-        let mut n = LinkedList::new();
-        assert!(n.iter_mut().next().is_none());
-        n.push_front(4);
-        n.push_back(5);
-        let mut it = n.iter_mut();
-        assert_eq!(it.size_hint(), (2, Some(2)));
-        assert!(it.next().is_some());
-        assert!(it.next().is_some());
-        assert_eq!(it.size_hint(), (0, Some(0)));
-        assert!(it.next().is_none());
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 5, 7, 9]);
+
+        // The predicate can update the elements it keeps
+        let expired: Vec<_> = m
+            .extract_if(|ttl| {
+                *ttl -= 1;
+                *ttl < 5
+            })
+            .collect();
+        assert_eq!(expired, &[0, 4]);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[6, 8]);
+
+        {
+            let mut it = m.extract_if(|_| true);
+            assert_eq!(it.by_ref().count(), 2);
+            assert_eq!(it.next(), None);
+        }
+        check_links(&m);
+        assert!(m.is_empty());
+        assert_eq!(m.extract_if(|_| true).next(), None);
     }
 
     #[test]
-    fn test_iterator_mut_double_end() {
-        let mut n = LinkedList::new();
-        assert!(n.iter_mut().next_back().is_none());
-        n.push_front(4);
-        n.push_front(5);
-        n.push_front(6);
-        let mut it = n.iter_mut();
-        assert_eq!(it.size_hint(), (3, Some(3)));
-        assert_eq!(*it.next().unwrap(), 6);
-        assert_eq!(it.size_hint(), (2, Some(2)));
-        assert_eq!(*it.next_back().unwrap(), 4);
-        assert_eq!(it.size_hint(), (1, Some(1)));
-        assert_eq!(*it.next_back().unwrap(), 5);
-        assert!(it.next_back().is_none());
-        assert!(it.next().is_none());
+    fn test_conversions() {
+        use std::collections::VecDeque;
+
+        let m = LinkedList::from(vec![1, 2, 3]);
+        check_links(&m);
+        assert_eq!(Vec::from(m), [1, 2, 3]);
+
+        let mut deque = VecDeque::from([2, 3]);
+        deque.push_front(1);
+        let m = LinkedList::from(deque);
+        check_links(&m);
+        assert_eq!(VecDeque::from(m), [1, 2, 3]);
+
+        let m = LinkedList::from([1, 2, 3]);
+        let v: Vec<_> = m.into();
+        assert_eq!(v.capacity(), 3);
+
+        let e = LinkedList::from(Vec::<i32>::new());
+        check_links(&e);
+        assert!(Vec::from(e).is_empty());
+    }
+
+    #[test]
+    fn test_clone() {
+        let m: LinkedList<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        let mut n = m.clone();
+        check_links(&n);
+        assert_eq!(n, m);
+
+        // The clone is deep, changing it doesn't affect the original
+        n.front_mut().unwrap().push('!');
+        n.push_back("d".to_string());
+        assert_eq!(m.iter().collect::<Vec<_>>(), &["a", "b", "c"]);
+        assert_eq!(n.iter().collect::<Vec<_>>(), &["a!", "b", "c", "d"]);
+
+        // clone_from both shrinks and grows the destination
+        n.clone_from(&m);
+        check_links(&n);
+        assert_eq!(n, m);
+        let mut e: LinkedList<String> = LinkedList::new();
+        e.clone_from(&m);
+        check_links(&e);
+        assert_eq!(e, m);
+        e.clone_from(&LinkedList::new());
+        check_links(&e);
+        assert!(e.is_empty());
+
+        // The nodes clone_from reuses hold other elements now, so their
+        // handles stop working
+        let node = e.push_back_ref("z".to_string());
+        e.clone_from(&m);
+        assert_eq!(e.remove_node(node), None);
+        assert_eq!(e, m);
+
+        // Long lists don't blow the stack
+        let long: LinkedList<usize> = (0..LONG).collect();
+        assert_eq!(long.clone().len(), LONG);
     }
 
     #[test]
@@ -984,8 +3496,9 @@ mod test {
     }
 
     #[test]
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
     fn test_ord_nan() {
-        let nan = 0.0f64 / 0.0;
+        let nan = f64::NAN;
         let n = list_from(&[nan]);
         let m = list_from(&[nan]);
         assert!(!(n < m));
@@ -1020,10 +3533,7 @@ mod test {
         let list: LinkedList<i32> = (0..10).collect();
         assert_eq!(format!("{:?}", list), "[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]");
 
-        let list: LinkedList<&str> = vec!["just", "one", "test", "more"]
-            .iter()
-            .copied()
-            .collect();
+        let list: LinkedList<&str> = ["just", "one", "test", "more"].iter().copied().collect();
         assert_eq!(format!("{:?}", list), r#"["just", "one", "test", "more"]"#);
     }
 
@@ -1049,6 +3559,43 @@ mod test {
         assert!(map.is_empty());
     }
 
+    #[test]
+    fn test_cmp_hash_match_std() {
+        use std::collections::hash_map::RandomState;
+        use std::collections::BTreeSet;
+        use std::hash::BuildHasher;
+
+        // We should order and hash exactly like std's LinkedList does, so
+        // swapping one for the other doesn't reshuffle sets and maps
+        let lists: [&[i32]; 6] = [&[], &[1], &[1, 2], &[1, 3], &[2], &[0, 5, 5]];
+        let hasher = RandomState::new();
+        for a in lists {
+            let ours = list_from(a);
+            let std_list: std::collections::LinkedList<i32> = a.iter().cloned().collect();
+            assert_eq!(hasher.hash_one(&ours), hasher.hash_one(&std_list));
+            for b in lists {
+                let theirs = list_from(b);
+                let std_other: std::collections::LinkedList<i32> = b.iter().cloned().collect();
+                assert_eq!(ours.cmp(&theirs), std_list.cmp(&std_other));
+                assert_eq!(ours == theirs, std_list == std_other);
+            }
+        }
+
+        let set: BTreeSet<LinkedList<i32>> = lists.iter().map(|l| list_from(l)).collect();
+        let sorted: Vec<Vec<i32>> = set.iter().map(|l| l.iter().cloned().collect()).collect();
+        assert_eq!(
+            sorted,
+            [
+                vec![],
+                vec![0, 5, 5],
+                vec![1],
+                vec![1, 2],
+                vec![1, 3],
+                vec![2]
+            ]
+        );
+    }
+
     #[allow(dead_code)]
     fn assert_properties() {
         fn is_send<T: Send>() {}
@@ -1069,6 +3616,18 @@ mod test {
         is_send::<Cursor<i32>>();
         is_sync::<Cursor<i32>>();
 
+        is_send::<CursorMut<i32>>();
+        is_sync::<CursorMut<i32>>();
+
+        is_send::<Drain<i32>>();
+        is_sync::<Drain<i32>>();
+
+        // Shared access needs T: Sync, exclusive access only needs T: Send
+        is_send::<Iter<std::sync::Mutex<i32>>>();
+        is_send::<IterMut<std::cell::Cell<i32>>>();
+        is_send::<CursorMut<std::cell::Cell<i32>>>();
+        is_send::<IntoIter<std::cell::Cell<i32>>>();
+
         fn linked_list_covariant<'a, T>(x: LinkedList<&'static T>) -> LinkedList<&'a T> {
             x
         }
@@ -1080,6 +3639,187 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_threads() {
+        // Move a list into a thread and share one between threads
+        let m: LinkedList<u32> = (0..100).collect();
+        let m = std::thread::spawn(move || m.into_iter().rev().collect::<LinkedList<_>>())
+            .join()
+            .unwrap();
+        std::thread::scope(|s| {
+            let evens = s.spawn(|| m.iter().filter(|x| *x % 2 == 0).count());
+            let odds = s.spawn(|| m.iter().filter(|x| *x % 2 == 1).count());
+            assert_eq!(evens.join().unwrap() + odds.join().unwrap(), 100);
+        });
+        assert_eq!(m.front(), Some(&99));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use serde::de::value::Error;
+        use serde::de::value::SeqDeserializer;
+        use serde::Deserialize;
+        use serde_test::assert_ser_tokens;
+        use serde_test::Token;
+
+        let m = list_from(&[1, 2, 3]);
+        assert_ser_tokens(
+            &m,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::I32(1),
+                Token::I32(2),
+                Token::I32(3),
+                Token::SeqEnd,
+            ],
+        );
+
+        let seq = SeqDeserializer::<_, Error>::new(vec![1, 2, 3].into_iter());
+        let mut m = LinkedList::<i32>::deserialize(seq).unwrap();
+        check_links(&m);
+        assert_eq!(m.len(), 3);
+        m.push_front(0);
+        assert_eq!(m.into_iter().collect::<Vec<_>>(), &[0, 1, 2, 3]);
+    }
+
+    #[cfg(feature = "allocator_api")]
+    #[test]
+    fn test_separate_arenas() {
+        use std::alloc::Allocator;
+        use std::alloc::Global;
+        use std::alloc::Layout;
+        use std::cell::RefCell;
+        use std::ptr::NonNull;
+
+        // Knows which blocks it handed out, and refuses to free any other
+        #[derive(Default)]
+        struct Arena {
+            live: RefCell<Vec<NonNull<u8>>>,
+        }
+
+        unsafe impl Allocator for Arena {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, std::alloc::AllocError> {
+                let block = Global.allocate(layout)?;
+                self.live.borrow_mut().push(block.cast());
+                Ok(block)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                let mut live = self.live.borrow_mut();
+                let index = live.iter().position(|&block| block == ptr);
+                live.swap_remove(index.expect("freeing a block from another arena"));
+                Global.deallocate(ptr, layout)
+            }
+        }
+
+        let (a, b) = (Arena::default(), Arena::default());
+        {
+            let mut m = LinkedList::new_in(&a);
+            let mut n = LinkedList::new_in(&b);
+            m.extend([1, 2]);
+            n.extend([3, 4]);
+
+            // Every element of n ends up in a node from a, the ones from b
+            // stay in n's pool
+            m.append(&mut n);
+            n.extend([0]);
+            m.prepend(&mut n);
+            assert_eq!(a.live.borrow().len(), 5);
+            assert_eq!(b.live.borrow().len(), 2);
+            m.assert_invariants();
+            assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 2, 3, 4]);
+
+            let mut o = LinkedList::new_in(&b);
+            o.extend([5, 6]);
+            m.cursor_at(1).splice_after(o);
+            let mut o = LinkedList::new_in(&b);
+            o.extend([7, 8]);
+            m.cursor_at(1).splice_before(o);
+            let mut o = LinkedList::new_in(&b);
+            o.extend([9]);
+            m = m.merge(o, Ord::cmp);
+            assert_eq!(a.live.borrow().len(), 10);
+            m.assert_invariants();
+            assert_eq!(
+                m.iter().cloned().collect::<Vec<_>>(),
+                &[0, 7, 8, 1, 5, 6, 2, 3, 4, 9]
+            );
+
+            // Lists split from each other share the arena, so they relink
+            let mut back = m.split_off(5);
+            m.append(&mut back);
+            back.prepend(&mut m);
+            assert_eq!(a.live.borrow().len(), 10);
+            assert_eq!(back.len(), 10);
+        }
+        assert!(a.live.borrow().is_empty());
+        assert!(b.live.borrow().is_empty());
+    }
+
+    #[cfg(feature = "allocator_api")]
+    #[test]
+    fn test_custom_allocator() {
+        use std::alloc::Allocator;
+        use std::alloc::Global;
+        use std::alloc::Layout;
+        use std::cell::Cell;
+        use std::ptr::NonNull;
+
+        // Keeps track of live allocations to check every node is given back
+        #[derive(Default)]
+        struct Counting {
+            live: Cell<usize>,
+        }
+
+        unsafe impl Allocator for Counting {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, std::alloc::AllocError> {
+                self.live.set(self.live.get() + 1);
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.live.set(self.live.get() - 1);
+                Global.deallocate(ptr, layout)
+            }
+        }
+
+        let counting = Counting::default();
+        {
+            let mut m = LinkedList::new_in(&counting);
+            m.extend([1, 2, 3]);
+            m.push_front(0);
+            m.insert(2, 10);
+            assert_eq!(counting.live.get(), 5);
+
+            // Moving nodes around doesn't allocate
+            let mut n = m.split_off(3);
+            m.rotate_left(1);
+            n.append(&mut m);
+            m = n.clone();
+            assert_eq!(counting.live.get(), 10);
+
+            // Popped and drained nodes stay in the pool of the list they were
+            // taken from, until it's shrunk or dropped
+            assert_eq!(n.pop_back(), Some(0));
+            assert_eq!(n.drain(1..).collect::<Vec<_>>(), &[3, 1, 10]);
+            assert_eq!(counting.live.get(), 10);
+            assert_eq!(m.into_iter().collect::<Vec<_>>(), &[2, 3, 1, 10, 0]);
+            assert_eq!(counting.live.get(), 5);
+            n.shrink_to_fit();
+            assert_eq!(counting.live.get(), 1);
+
+            // Reserved nodes get used before allocating new ones
+            n.reserve(3);
+            assert_eq!(counting.live.get(), 4);
+            n.extend([4, 5, 6]);
+            assert_eq!(counting.live.get(), 4);
+            n.push_front(7);
+            assert_eq!(counting.live.get(), 5);
+        }
+        assert_eq!(counting.live.get(), 0);
+    }
+
     #[test]
     fn test_cursor_move_peek() {
         let mut m: LinkedList<u32> = LinkedList::new();
@@ -1129,7 +3869,7 @@ mod test {
         cursor.move_next();
         cursor.splice_before(Some(7).into_iter().collect());
         cursor.splice_after(Some(8).into_iter().collect());
-        // check_links(&m);
+        check_links(&m);
         assert_eq!(
             m.iter().cloned().collect::<Vec<_>>(),
             &[7, 1, 8, 2, 3, 4, 5, 6]
@@ -1145,7 +3885,6 @@ mod test {
             &[10, 7, 1, 8, 2, 3, 4, 5, 6, 9]
         );
 
-        /* remove_current not impl'd
         let mut cursor = m.cursor_mut();
         cursor.move_next();
         cursor.move_prev();
@@ -1160,8 +3899,10 @@ mod test {
         cursor.move_next();
         assert_eq!(cursor.remove_current(), Some(10));
         check_links(&m);
-        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 8, 2, 3, 4, 5, 6]);
-        */
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[1, 8, 2, 3, 4, 5, 6]
+        );
 
         let mut cursor = m.cursor_mut();
         cursor.move_next();
@@ -1202,7 +3943,212 @@ mod test {
         );
     }
 
-    fn check_links<T>(_list: &LinkedList<T>) {
-        // would be good to do this!
+    #[test]
+    fn test_cursor_front_back_mut() {
+        let mut m: LinkedList<u32> = LinkedList::new();
+        assert_eq!(m.cursor_front_mut().current(), None);
+        assert_eq!(m.cursor_back_mut().index(), None);
+
+        m.extend([1, 2, 3]);
+        let mut cursor = m.cursor_front_mut();
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.index(), Some(0));
+        cursor.insert_before(0);
+        cursor.insert_after(10);
+        assert_eq!(cursor.index(), Some(1));
+        assert_eq!(cursor.peek_prev(), Some(&mut 0));
+        assert_eq!(cursor.peek_next(), Some(&mut 10));
+
+        let mut cursor = m.cursor_back_mut();
+        assert_eq!(cursor.current(), Some(&mut 3));
+        assert_eq!(cursor.index(), Some(4));
+        assert_eq!(cursor.remove_current(), Some(3));
+        // Removing the back leaves us on the ghost
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.remove_current(), None);
+        // Inserting on the ghost goes to the ends of the list
+        cursor.insert_before(4);
+        cursor.insert_after(-1i32 as u32);
+        check_links(&m);
+        assert_eq!(m.len(), 6);
+        assert_eq!(
+            m.iter().cloned().collect::<Vec<_>>(),
+            &[u32::MAX, 0, 1, 10, 2, 4]
+        );
+    }
+
+    #[test]
+    fn test_cursor_move_to_front_back() {
+        let mut m: LinkedList<u32> = (0..5).collect();
+        let mut cursor = m.cursor_front_mut();
+        cursor.move_next();
+        cursor.move_next();
+        let addr = cursor.current().map(|x| x as *const u32);
+        cursor.move_to_front();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current().map(|x| x as *const u32), addr);
+        assert_eq!(cursor.peek_next(), Some(&mut 0));
+        cursor.move_to_front();
+        assert_eq!(cursor.index(), Some(0));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[2, 0, 1, 3, 4]);
+
+        let mut cursor = m.cursor_front_mut();
+        cursor.move_to_back();
+        assert_eq!(cursor.index(), Some(4));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        assert_eq!(cursor.peek_next(), None);
+        cursor.move_to_back();
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_to_back();
+        assert_eq!(cursor.index(), Some(4));
+        assert_eq!(cursor.current(), Some(&mut 3));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[0, 1, 4, 2, 3]);
+
+        // The ghost stays where it is
+        let mut cursor = m.cursor_mut();
+        cursor.move_to_front();
+        cursor.move_to_back();
+        assert_eq!(cursor.index(), None);
+        check_links(&m);
+        assert_eq!(m.len(), 5);
+    }
+
+    #[test]
+    fn test_cursor() {
+        let m: LinkedList<u32> = LinkedList::new();
+        assert_eq!(m.cursor_front().current(), None);
+        assert_eq!(m.cursor_back().index(), None);
+
+        let m: LinkedList<u32> = (1..=4).collect();
+        let mut front = m.cursor_front();
+        let back = m.cursor_back();
+        assert_eq!(front.current(), Some(&1));
+        assert_eq!(front.index(), Some(0));
+        assert_eq!(back.current(), Some(&4));
+        assert_eq!(back.index(), Some(3));
+
+        // Several cursors over the same list, remembering their positions
+        let saved = front;
+        front.move_next();
+        assert_eq!(front.current(), Some(&2));
+        assert_eq!(front.index(), Some(1));
+        assert_eq!(front.peek_prev(), Some(&1));
+        assert_eq!(front.peek_next(), Some(&3));
+        assert_eq!(saved.index(), Some(0));
+
+        front.move_prev();
+        front.move_prev();
+        assert_eq!(front.current(), None);
+        assert_eq!(front.index(), None);
+        assert_eq!(front.peek_next(), Some(&1));
+        assert_eq!(front.peek_prev(), Some(&4));
+        front.move_prev();
+        assert_eq!(front.current(), Some(&4));
+        assert_eq!(front.index(), Some(3));
+
+        // References outlive the cursor that produced them
+        let elem = {
+            let cursor = m.cursor_back();
+            cursor.current()
+        };
+        assert_eq!(elem, Some(&4));
+
+        let mut m = m;
+        let mut cursor = m.cursor_mut();
+        cursor.move_prev();
+        let view = cursor.as_cursor();
+        assert_eq!(view.current(), Some(&4));
+        assert_eq!(view.index(), Some(3));
+    }
+
+    #[test]
+    fn test_cursor_split() {
+        // Splitting at the boundaries leaves an empty list on one side
+        let mut m: LinkedList<u32> = list_from(&[1, 2, 3]);
+        let mut cursor = m.cursor_front_mut();
+        let mut front = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        check_links(&front);
+        assert!(front.is_empty());
+        // Pushing onto the output must not touch the nodes we kept
+        front.push_back(0);
+        assert_eq!(front.iter().cloned().collect::<Vec<_>>(), &[0]);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 3]);
+
+        let mut cursor = m.cursor_back_mut();
+        let mut back = cursor.split_after();
+        assert_eq!(cursor.index(), Some(2));
+        check_links(&back);
+        assert!(back.is_empty());
+        back.push_front(4);
+        assert_eq!(back.iter().cloned().collect::<Vec<_>>(), &[4]);
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2, 3]);
+
+        // Splitting in the middle
+        let mut cursor = m.cursor_front_mut();
+        cursor.move_next();
+        let before = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        let after = cursor.split_after();
+        assert_eq!(cursor.index(), Some(0));
+        check_links(&before);
+        check_links(&after);
+        check_links(&m);
+        assert_eq!(before.iter().cloned().collect::<Vec<_>>(), &[1]);
+        assert_eq!(after.iter().cloned().collect::<Vec<_>>(), &[3]);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[2]);
+
+        // Splitting at the ghost takes everything
+        let mut cursor = m.cursor_mut();
+        let all = cursor.split_after();
+        assert_eq!(cursor.index(), None);
+        check_links(&all);
+        check_links(&m);
+        assert_eq!(all.iter().cloned().collect::<Vec<_>>(), &[2]);
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_cursor_splice() {
+        // Splicing into an empty list, from the ghost
+        let mut m: LinkedList<i32> = LinkedList::new();
+        let mut cursor = m.cursor_mut();
+        cursor.splice_before(list_from(&[1, 2]));
+        assert_eq!(cursor.index(), None);
+        cursor.splice_after(LinkedList::new());
+        cursor.splice_after(list_from(&[]));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[1, 2]);
+
+        // Splicing at the boundaries of the list
+        let mut cursor = m.cursor_back_mut();
+        cursor.splice_after(list_from(&[3, 4]));
+        assert_eq!(cursor.current(), Some(&mut 2));
+        assert_eq!(cursor.index(), Some(1));
+        cursor.move_prev();
+        cursor.splice_before(list_from(&[-1, 0]));
+        assert_eq!(cursor.current(), Some(&mut 1));
+        assert_eq!(cursor.index(), Some(2));
+        check_links(&m);
+        assert_eq!(m.iter().cloned().collect::<Vec<_>>(), &[-1, 0, 1, 2, 3, 4]);
+
+        // The donor list gives its nodes away without dropping them
+        let mut m: LinkedList<String> = list_from(&["a".to_string(), "d".to_string()]);
+        let mut cursor = m.cursor_back_mut();
+        cursor.splice_before(list_from(&["b".to_string(), "c".to_string()]));
+        assert_eq!(cursor.index(), Some(3));
+        check_links(&m);
+        assert_eq!(m.iter().collect::<Vec<_>>(), &["a", "b", "c", "d"]);
+    }
+
+    fn check_links<T>(list: &LinkedList<T>) {
+        list.assert_invariants();
     }
 }
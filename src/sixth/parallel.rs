@@ -0,0 +1,263 @@
+// Rayon support for the deque. Rayon splits the work by asking "producers" to
+// cut themselves in two at an index, which is something a linked list is good
+// at once it knows where to cut: owned lists get cut with `split_off`, and
+// borrowing iterators just need to find the node at the cut and start a new
+// iterator there. Either way, only the walk to the cut is O(n).
+
+use rayon::iter::plumbing::bridge;
+use rayon::iter::plumbing::Consumer;
+use rayon::iter::plumbing::Producer;
+use rayon::iter::plumbing::ProducerCallback;
+use rayon::iter::plumbing::UnindexedConsumer;
+use rayon::iter::FromParallelIterator;
+use rayon::iter::IndexedParallelIterator;
+use rayon::iter::IntoParallelIterator;
+use rayon::iter::ParallelIterator;
+
+use super::IntoIter;
+use super::Iter;
+use super::IterMut;
+use super::LinkedList;
+use crate::allocator::Allocator;
+use crate::allocator::Global;
+
+pub struct IntoParIter<T, A: Allocator = Global> {
+    list: LinkedList<T, A>,
+}
+
+pub struct ParIter<'a, T> {
+    iter: Iter<'a, T>,
+}
+
+pub struct ParIterMut<'a, T> {
+    iter: IterMut<'a, T>,
+}
+
+impl<T: Send, A: Allocator + Clone + Send> IntoParallelIterator for LinkedList<T, A> {
+    type Iter = IntoParIter<T, A>;
+    type Item = T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        IntoParIter { list: self }
+    }
+}
+
+impl<'a, T: Sync, A: Allocator> IntoParallelIterator for &'a LinkedList<T, A> {
+    type Iter = ParIter<'a, T>;
+    type Item = &'a T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        ParIter { iter: self.iter() }
+    }
+}
+
+impl<'a, T: Send, A: Allocator> IntoParallelIterator for &'a mut LinkedList<T, A> {
+    type Iter = ParIterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        ParIterMut {
+            iter: self.iter_mut(),
+        }
+    }
+}
+
+// Every thread builds a list of its own, and we stitch them together in order,
+// which is O(1) per stitch
+impl<T: Send> FromParallelIterator<T> for LinkedList<T> {
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = T>,
+    {
+        par_iter
+            .into_par_iter()
+            .fold(LinkedList::new, |mut list, elem| {
+                list.push_back(elem);
+                list
+            })
+            .reduce(LinkedList::new, |mut front, mut back| {
+                front.append(&mut back);
+                front
+            })
+    }
+}
+
+impl<T: Send, A: Allocator + Clone + Send> ParallelIterator for IntoParIter<T, A> {
+    type Item = T;
+
+    fn drive_unindexed<C: UnindexedConsumer<T>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.list.len)
+    }
+}
+
+impl<T: Send, A: Allocator + Clone + Send> IndexedParallelIterator for IntoParIter<T, A> {
+    fn len(&self) -> usize {
+        self.list.len
+    }
+
+    fn drive<C: Consumer<T>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn with_producer<CB: ProducerCallback<T>>(self, callback: CB) -> CB::Output {
+        callback.callback(self)
+    }
+}
+
+impl<T: Send, A: Allocator + Clone + Send> Producer for IntoParIter<T, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.list.into_iter()
+    }
+
+    fn split_at(mut self, index: usize) -> (Self, Self) {
+        let back = self.list.split_off(index);
+        (self, IntoParIter { list: back })
+    }
+}
+
+impl<'a, T: Sync> ParallelIterator for ParIter<'a, T> {
+    type Item = &'a T;
+
+    fn drive_unindexed<C: UnindexedConsumer<&'a T>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.iter.len)
+    }
+}
+
+impl<'a, T: Sync> IndexedParallelIterator for ParIter<'a, T> {
+    fn len(&self) -> usize {
+        self.iter.len
+    }
+
+    fn drive<C: Consumer<&'a T>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn with_producer<CB: ProducerCallback<&'a T>>(self, callback: CB) -> CB::Output {
+        callback.callback(self)
+    }
+}
+
+impl<'a, T: Sync> Producer for ParIter<'a, T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter
+    }
+
+    fn split_at(self, index: usize) -> (Self, Self) {
+        let (left, right) = self.iter.split_at(index);
+        (ParIter { iter: left }, ParIter { iter: right })
+    }
+}
+
+impl<'a, T: Send> ParallelIterator for ParIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn drive_unindexed<C: UnindexedConsumer<&'a mut T>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.iter.len)
+    }
+}
+
+impl<'a, T: Send> IndexedParallelIterator for ParIterMut<'a, T> {
+    fn len(&self) -> usize {
+        self.iter.len
+    }
+
+    fn drive<C: Consumer<&'a mut T>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn with_producer<CB: ProducerCallback<&'a mut T>>(self, callback: CB) -> CB::Output {
+        callback.callback(self)
+    }
+}
+
+impl<'a, T: Send> Producer for ParIterMut<'a, T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter
+    }
+
+    fn split_at(self, index: usize) -> (Self, Self) {
+        let (left, right) = self.iter.split_at(index);
+        (ParIterMut { iter: left }, ParIterMut { iter: right })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rayon::iter::IndexedParallelIterator;
+    use rayon::iter::IntoParallelIterator;
+    use rayon::iter::IntoParallelRefIterator;
+    use rayon::iter::IntoParallelRefMutIterator;
+    use rayon::iter::ParallelIterator;
+
+    use super::LinkedList;
+
+    #[test]
+    fn into_par_iter() {
+        let list: LinkedList<u64> = (0..10_000).collect();
+        let doubled: Vec<u64> = list.into_par_iter().map(|x| x * 2).collect();
+        assert_eq!(doubled, (0..10_000).map(|x| x * 2).collect::<Vec<_>>());
+
+        // Splitting at the very ends, and at every point in between
+        let list: LinkedList<u64> = (0..7).collect();
+        let chunks: Vec<Vec<u64>> = list.into_par_iter().with_min_len(1).chunks(3).collect();
+        assert_eq!(chunks, [vec![0, 1, 2], vec![3, 4, 5], vec![6]]);
+
+        let empty: LinkedList<u64> = LinkedList::new();
+        assert_eq!(empty.into_par_iter().count(), 0);
+    }
+
+    #[test]
+    fn par_iter() {
+        let list: LinkedList<u64> = (0..10_000).collect();
+        assert_eq!(list.par_iter().sum::<u64>(), (0..10_000).sum());
+        assert_eq!(list.par_iter().len(), 10_000);
+        assert_eq!(list.par_iter().position_first(|x| *x == 5_000), Some(5_000));
+        for len in 1..=8 {
+            let collected: Vec<&u64> = list.par_iter().take(len).with_max_len(1).collect();
+            assert_eq!(collected, list.iter().take(len).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn par_iter_mut() {
+        let mut list: LinkedList<u64> = (0..10_000).collect();
+        list.par_iter_mut().with_max_len(7).for_each(|x| *x += 1);
+        assert!(list.iter().cloned().eq(1..10_001));
+    }
+
+    #[test]
+    fn from_par_iter() {
+        let list: LinkedList<u64> = (0..10_000u64)
+            .into_par_iter()
+            .filter(|x| x % 3 == 0)
+            .collect();
+        assert_eq!(list.len(), 3_334);
+        assert!(list.iter().cloned().eq((0..10_000).filter(|x| x % 3 == 0)));
+        assert!(list
+            .iter()
+            .rev()
+            .cloned()
+            .eq((0..10_000).filter(|x| x % 3 == 0).rev()));
+    }
+}
@@ -1,25 +1,352 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::borrow::Borrow;
+use core::cell::Cell;
+use core::convert::Infallible;
+use core::fmt;
+use core::iter::Zip;
+use core::ops::Deref;
+#[cfg(feature = "std")]
+use std::error::Error;
+
+#[cfg(feature = "third")]
+use crate::third;
+use crate::traits::MemSize;
+
+#[cfg(feature = "alloc")]
+pub mod arena;
+#[cfg(feature = "alloc")]
+pub mod async_list;
+
 pub struct List<'a, T> {
     pub data: T,
     pub prev: Option<&'a List<'a, T>>,
+    // How many frames there are up to this one. Frames never change their
+    // parent, so we can count once when pushing and keep len() O(1).
+    len: usize,
+}
+
+// Gets told when frames are pushed and popped, e.g. to trace the scopes or
+// time them, without having to touch every place that pushes. Install it with
+// push_hooked (or Frame::hooked), and it's called for that frame and all the
+// frames pushed on top of it with Frame::push.
+//
+// The hook is kept in the Frame, not in the List. A hook takes T's in, so a
+// List holding one couldn't be covariant in T anymore, and a chain of
+// `&'static str`s couldn't take a frame with a shorter-lived `&str` on top.
+// The flip side is that frames pushed with List::push (or in an arena) only
+// get to see a List, so they don't know about the hook and aren't reported.
+pub trait FrameHook<T> {
+    // Called once the frame is pushed, before the callback runs
+    fn enter(&self, _frame: &List<'_, T>) {}
+
+    // Called when the frame is popped: after the callback returns (or
+    // panics), or when a Frame goes out of scope
+    fn exit(&self, _frame: &List<'_, T>) {}
 }
 
 pub struct Iter<'a, T> {
     next: Option<&'a List<'a, T>>,
 }
 
+// Returned by try_push when the chain is already as long as it may get,
+// handing the element back
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DepthExceeded<T>(pub T);
+
+impl<T> fmt::Display for DepthExceeded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "maximum stack depth exceeded")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: fmt::Debug> Error for DepthExceeded<T> {}
+
+// Returned by display_path, so the path can be formatted without building a
+// String first
+pub struct DisplayPath<'a, 's, T> {
+    list: &'a List<'a, T>,
+    separator: &'s str,
+}
+
+// Frames are only ever shared, so to change what's in them we need interior
+// mutability. For Copy values, Cells are enough, and these are the helpers to
+// go with them.
+pub type CellList<'a, T> = List<'a, Cell<T>>;
+
+pub struct Values<'a, T> {
+    iter: Iter<'a, Cell<T>>,
+}
+
+pub struct IterWithDepth<'a, T> {
+    next: Option<&'a List<'a, T>>,
+}
+
+// Frames only know their parent, so to go the other way we first collect the
+// elements on the way out, and then yield them backwards
+#[cfg(feature = "alloc")]
+pub struct IterFromRoot<'a, T> {
+    elems: alloc::vec::IntoIter<&'a T>,
+}
+
 impl<'a, T> List<'a, T> {
     pub fn push<U>(
         prev: Option<&'a List<'a, T>>,
         data: T,
         callback: impl FnOnce(&List<'a, T>) -> U,
     ) -> U {
-        let list = List { data, prev };
-        callback(&list)
+        callback(&List::new(prev, data))
+    }
+
+    // Same as push, with `hook` watching this frame. The callback gets the
+    // Frame, so that the frames it pushes on top with Frame::push are watched
+    // too. The frame calls the exit hook when dropped, even if we unwind.
+    pub fn push_hooked<U>(
+        prev: Option<&'a List<'a, T>>,
+        data: T,
+        hook: &'a dyn FrameHook<T>,
+        callback: impl FnOnce(&Frame<'a, T>) -> U,
+    ) -> U {
+        callback(&Frame::hooked(prev, data, hook))
+    }
+
+    // Same as push, unless the chain would end up with more than `max_len`
+    // frames, which makes for a cheap recursion limit
+    pub fn try_push<U>(
+        prev: Option<&'a List<'a, T>>,
+        data: T,
+        max_len: usize,
+        callback: impl FnOnce(&List<'a, T>) -> U,
+    ) -> Result<U, DepthExceeded<T>> {
+        if prev.map_or(0, List::len) >= max_len {
+            return Err(DepthExceeded(data));
+        }
+        Ok(List::push(prev, data, callback))
+    }
+
+    // Pushes a frame per item, in order, and calls `callback` with the last
+    // one. The callback gets the innermost list the same way `prev` is given,
+    // as the chain is still `prev` if `iter` is empty (and there's no chain
+    // at all if `prev` was None too).
+    pub fn push_all<I, U>(
+        prev: Option<&'a List<'a, T>>,
+        iter: I,
+        callback: impl FnOnce(Option<&List<'_, T>>) -> U,
+    ) -> U
+    where
+        I: IntoIterator<Item = T>,
+    {
+        // We still need a closure per frame, we just write it once
+        fn go<T, I, U, F>(prev: Option<&List<'_, T>>, mut iter: I, callback: F) -> U
+        where
+            I: Iterator<Item = T>,
+            F: FnOnce(Option<&List<'_, T>>) -> U,
+        {
+            match iter.next() {
+                Some(data) => List::push(prev, data, |list| go(Some(list), iter, callback)),
+                None => callback(prev),
+            }
+        }
+
+        go(prev, iter.into_iter(), callback)
+    }
+
+    fn new(prev: Option<&'a List<'a, T>>, data: T) -> Self {
+        List {
+            data,
+            prev,
+            len: prev.map_or(1, |prev| prev.len + 1),
+        }
     }
 
-    pub fn iter(&'a self) -> Iter<'a, T> {
+    // The number of frames in the chain, this one included. There's no
+    // is_empty, as there's no such thing as an empty chain.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    // How far we are from the root, which is at depth 0
+    pub fn depth(&self) -> usize {
+        self.len - 1
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter { next: Some(self) }
     }
+
+    // Same as iter, along with each element's depth. Depths count from the
+    // root like depth() does, so the root is 0 and they go down as we walk
+    // out, which is what you want to indent a dump of the scopes.
+    pub fn iter_with_depth(&self) -> IterWithDepth<'_, T> {
+        IterWithDepth { next: Some(self) }
+    }
+
+    // The elements from the root to this frame, the reverse of iter. Takes
+    // a Vec of len() references.
+    #[cfg(feature = "alloc")]
+    pub fn iter_from_root(&self) -> IterFromRoot<'_, T> {
+        let mut elems = Vec::with_capacity(self.len);
+        elems.extend(self.iter());
+        IterFromRoot {
+            elems: elems.into_iter(),
+        }
+    }
+
+    // The deepest frame that both chains go through, if they share any.
+    // Frames are compared by address, not by value, so two chains that just
+    // happen to hold equal elements don't share anything. As we know how deep
+    // both are, we can line them up first and then walk them in lockstep.
+    pub fn common_ancestor<'r>(&'r self, other: &'r List<'r, T>) -> Option<&'r List<'r, T>> {
+        let mut a: &'r List<'r, T> = self;
+        let mut b = other;
+        while a.len > b.len {
+            a = a.prev?;
+        }
+        while b.len > a.len {
+            b = b.prev?;
+        }
+        while !core::ptr::eq(a, b) {
+            a = a.prev?;
+            b = b.prev?;
+        }
+        Some(a)
+    }
+
+    // Formats the chain from the root to this frame with `separator` in
+    // between, e.g. `main/parse/expr`
+    pub fn display_path<'s>(&self, separator: &'s str) -> DisplayPath<'_, 's, T> {
+        DisplayPath {
+            list: self,
+            separator,
+        }
+    }
+
+    // Copies the chain out of the stack, from the root to this frame, so it
+    // can outlive the callbacks that pushed it
+    #[cfg(feature = "alloc")]
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.iter_from_root().cloned().collect()
+    }
+
+    // Same, but as a persistent list that keeps our order: this frame is its
+    // head, and its parents follow. Snapshots of chains that grow from the
+    // same root don't share their tails, though, as each freeze starts anew.
+    #[cfg(feature = "third")]
+    pub fn freeze(&self) -> third::List<T>
+    where
+        T: Clone,
+    {
+        self.iter_from_root()
+            .fold(third::List::new(), |list, x| list.prepend(x.clone()))
+    }
+
+    // Whether `other`'s chain goes through this very frame, i.e. whether
+    // we're a prefix of it. A frame counts as its own ancestor, like a path
+    // starts with itself. Only walks up as far as our depth.
+    pub fn is_ancestor_of(&self, other: &List<'_, T>) -> bool {
+        let mut other = other;
+        while other.len > self.len {
+            match other.prev {
+                Some(prev) => other = prev,
+                None => return false,
+            }
+        }
+        core::ptr::eq(self, other)
+    }
+
+    // The other way around, whether `prefix` is one of our frames
+    pub fn starts_with(&self, prefix: &List<'_, T>) -> bool {
+        prefix.is_ancestor_of(self)
+    }
+
+    // Folds the chain from this frame out to the root
+    pub fn fold_from_tip<B>(&self, init: B, f: impl FnMut(B, &T) -> B) -> B {
+        self.iter().fold(init, f)
+    }
+
+    // Folds the chain from the root in to this frame, without collecting the
+    // frames like iter_from_root does (so it works without `alloc` too)
+    pub fn fold_from_root<B>(&self, init: B, mut f: impl FnMut(B, &T) -> B) -> B {
+        match self.try_fold_from_root(init, |acc, x| Ok::<_, Infallible>(f(acc, x))) {
+            Ok(acc) => acc,
+            Err(never) => match never {},
+        }
+    }
+
+    // Same, stopping at the first error. Frames only know their parent, and
+    // recursing all the way to the root would go as deep as the chain, which
+    // can be far deeper than our stack when the frames are in an arena. So
+    // we halve the chain instead: we fold the half closer to the root, then
+    // the half closer to us, each the same way. That's only log(len) calls
+    // deep, and each level walks the chain once to find where its halves
+    // meet, so O(len log len) steps in all.
+    fn try_fold_from_root<B, E>(
+        &self,
+        init: B,
+        mut f: impl FnMut(B, &T) -> Result<B, E>,
+    ) -> Result<B, E> {
+        // Folds the `count` frames from `tip` out, so `count` is at least 1
+        fn go<T, B, E>(
+            tip: &List<'_, T>,
+            count: usize,
+            acc: B,
+            f: &mut impl FnMut(B, &T) -> Result<B, E>,
+        ) -> Result<B, E> {
+            if count == 1 {
+                return f(acc, &tip.data);
+            }
+            let upper = count / 2;
+            let mut lower = tip;
+            for _ in 0..upper {
+                lower = lower.prev.unwrap();
+            }
+            let acc = go(lower, count - upper, acc, f)?;
+            go(tip, upper, acc, f)
+        }
+
+        go(self, self.len, init, &mut f)
+    }
+
+    // Pairs up the elements of two chains pushed in lockstep (e.g. names and
+    // where they were defined), from the tips out until either one ends
+    pub fn zip<'r, U>(&'r self, other: &'r List<'_, U>) -> Zip<Iter<'r, T>, Iter<'r, U>> {
+        self.iter().zip(other.iter())
+    }
+
+    // The frame `n` levels up, this one being 0, like the frame a de Bruijn
+    // index points to. We know right away if there aren't that many.
+    pub fn nth_parent(&self, n: usize) -> Option<&List<'_, T>> {
+        if n >= self.len {
+            return None;
+        }
+        let mut frame: &List<'_, T> = self;
+        for _ in 0..n {
+            frame = frame.prev?;
+        }
+        Some(frame)
+    }
+
+    // The innermost element matching `pred`, searching from this frame out
+    // to the root, like resolving a name in the enclosing scopes
+    pub fn find<P>(&self, mut pred: P) -> Option<&T>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.iter().find(|x| pred(x))
+    }
+
+    pub fn contains(&self, x: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|y| y == x)
+    }
 }
 
 impl<'a, T> Iterator for Iter<'a, T> {
@@ -27,11 +354,302 @@ impl<'a, T> Iterator for Iter<'a, T> {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.next.map(|node| {
-            // self.next = node.prev.as_ref().map(|prev| &**prev)
-            self.next = node.prev.as_deref();
+            // Unlike the Box-based lists, prev is already a plain reference,
+            // so there's no need for as_deref (or as_ref().map(|prev| &**prev))
+            self.next = node.prev;
             &node.data
         })
     }
+
+    // Every frame knows how many are left from there
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.next.map_or(0, List::len);
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a List<'a, T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// `for x in &frame` doesn't go through Deref
+impl<'a, T> IntoIterator for &'a Frame<'a, T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> Iterator for IterWithDepth<'a, T> {
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.prev;
+            (node.depth(), &node.data)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.next.map_or(0, List::len);
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterWithDepth<'a, T> {}
+
+#[cfg(feature = "alloc")]
+impl<'a, T> Iterator for IterFromRoot<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.elems.next_back()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.elems.size_hint()
+    }
+}
+
+impl<'a, 's, T: fmt::Display> fmt::Display for DisplayPath<'a, 's, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The parents go first, so we write from the root, without recursing
+        // as deep as the chain (see try_fold_from_root)
+        self.list.try_fold_from_root(true, |first, x| {
+            if !first {
+                f.write_str(self.separator)?;
+            }
+            write!(f, "{}", x)?;
+            Ok(false)
+        })?;
+        Ok(())
+    }
+}
+
+// The frames live on the call stack, so the chain takes no heap at all,
+// however long it gets. Frames in an arena are the arena's to count.
+impl<'a, T> MemSize for List<'a, T> {
+    fn node_count(&self) -> usize {
+        self.len
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        0
+    }
+}
+
+// The chain from this frame out, e.g. `[tip <- mid <- root]`, rather than
+// the nested structs we'd get from deriving it
+impl<'a, T: fmt::Debug> fmt::Debug for List<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, x) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, " <- ")?;
+            }
+            // Going through Debug again, so that {:#?} reaches the elements
+            fmt::Debug::fmt(x, f)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for Frame<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.list, f)
+    }
+}
+
+// Paths separated by slashes
+impl<'a, T: fmt::Display> fmt::Display for List<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display_path("/"))
+    }
+}
+
+impl<'a, T: Copy> CellList<'a, T> {
+    // The current values, from this frame out
+    pub fn values(&self) -> Values<'_, T> {
+        Values { iter: self.iter() }
+    }
+
+    // Replaces every value in the chain with `f` of it
+    pub fn update_all(&self, mut f: impl FnMut(T) -> T) {
+        for cell in self.iter() {
+            cell.set(f(cell.get()));
+        }
+    }
+}
+
+impl<'a, T: Copy> Iterator for Values<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(Cell::get)
+    }
+}
+
+// The callback API nests a closure per frame, which drifts to the right fast
+// and makes early returns awkward. A Frame is the same node, owned by the
+// caller's stack frame instead of living inside `push`, so frames can be
+// pushed in straight-line code. It's popped when it goes out of scope, and
+// the borrow checker still makes sure that its children are gone by then.
+pub struct Frame<'a, T> {
+    list: List<'a, T>,
+    // Passed on to the frames pushed on top of this one, see FrameHook
+    hook: Option<&'a dyn FrameHook<T>>,
+}
+
+impl<'a, T> Frame<'a, T> {
+    pub fn new(parent: Option<&'a List<'a, T>>, data: T) -> Self {
+        Self::with_hook(List::new(parent, data), None)
+    }
+
+    // Same as new, with `hook` watching this frame and the ones on top of it
+    pub fn hooked(parent: Option<&'a List<'a, T>>, data: T, hook: &'a dyn FrameHook<T>) -> Self {
+        Self::with_hook(List::new(parent, data), Some(hook))
+    }
+
+    fn with_hook(list: List<'a, T>, hook: Option<&'a dyn FrameHook<T>>) -> Self {
+        if let Some(hook) = hook {
+            hook.enter(&list);
+        }
+        Frame { list, hook }
+    }
+
+    // A new frame on top of this one, watched by our hook if we have one
+    pub fn push(&'a self, data: T) -> Frame<'a, T> {
+        Self::with_hook(List::new(Some(&self.list), data), self.hook)
+    }
+}
+
+impl<'a, T> Drop for Frame<'a, T> {
+    fn drop(&mut self) {
+        if let Some(hook) = self.hook {
+            hook.exit(&self.list);
+        }
+    }
+}
+
+// A frame is a list, so it can be iterated or handed to the callback API
+impl<'a, T> Deref for Frame<'a, T> {
+    type Target = List<'a, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.list
+    }
+}
+
+// A lexical environment: each scope is a frame with its own bindings, and
+// looking a key up walks from the innermost scope out, so inner bindings
+// shadow outer ones. Scopes tend to be small, so the bindings are just a Vec
+// we scan, and nothing needs to be hashable. New bindings can only go in the
+// innermost scope, as a child scope borrows its parent.
+#[cfg(feature = "alloc")]
+pub struct ScopeMap<'a, K, V> {
+    frame: Frame<'a, Vec<(K, V)>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, K, V> ScopeMap<'a, K, V> {
+    // The outermost scope, e.g. the globals
+    pub fn new() -> Self {
+        ScopeMap {
+            frame: Frame::new(None, Vec::new()),
+        }
+    }
+
+    // A new, empty scope nested in this one
+    pub fn scope(&'a self) -> ScopeMap<'a, K, V> {
+        ScopeMap {
+            frame: self.frame.push(Vec::new()),
+        }
+    }
+
+    // Binds `key` in this scope, returning the value it had in this very
+    // scope, if any. Bindings in the enclosing scopes are only shadowed.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V>
+    where
+        K: Eq,
+    {
+        let bindings = &mut self.frame.list.data;
+        match bindings.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => Some(core::mem::replace(v, value)),
+            None => {
+                bindings.push((key, value));
+                None
+            }
+        }
+    }
+
+    // The value of the innermost binding of `key`
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.frame
+            .iter()
+            .flat_map(|bindings| bindings.iter())
+            .find(|(k, _)| k.borrow() == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    // How many scopes deep we are, the outermost one being at depth 0
+    pub fn depth(&self) -> usize {
+        self.frame.depth()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, K, V> Default for ScopeMap<'a, K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Writes the nested pushes for us, so that
+//
+//     stack_list!([3, 5, 13] => |list| body)
+//
+// is the same as
+//
+//     List::push(None, 3, |list| {
+//         List::push(Some(list), 5, |list| List::push(Some(list), 13, |list| body))
+//     })
+//
+// To stack on top of an existing list, pass it first, as in
+// `stack_list!(Some(list), [7, 8] => |list| body)`.
+#[macro_export]
+macro_rules! stack_list {
+    ([$($data:expr),+ $(,)?] => |$list:ident| $body:expr) => {
+        $crate::stack_list!(None, [$($data),+] => |$list| $body)
+    };
+    ($prev:expr, [$data:expr $(,)?] => |$list:ident| $body:expr) => {
+        $crate::stacklist::List::push($prev, $data, |$list| $body)
+    };
+    ($prev:expr, [$data:expr, $($rest:expr),+ $(,)?] => |$list:ident| $body:expr) => {
+        $crate::stacklist::List::push($prev, $data, |$list| {
+            $crate::stack_list!(Some($list), [$($rest),+] => |$list| $body)
+        })
+    };
 }
 
 // Example of usage:
@@ -44,10 +662,51 @@ impl<'a, T> Iterator for Iter<'a, T> {
 //         })
 //     })
 // })
+//
+// Or, with the macro:
+// stack_list!([3, 5, 13] => |list| {
+//     println!("{:?}", list.iter().collect::<Vec<_>>());
+// })
+//
+// Or, with frames:
+// let list = Frame::new(None, 3);
+// let list = list.push(5);
+// let list = list.push(13);
+// println!("{:?}", list.iter().collect::<Vec<_>>());
+
+// A chain is serialized as a sequence from this frame out to the root, the
+// order iter() gives, like the other lists. There's no deserializing one, as
+// the frames have to live on someone's stack (AsyncStackList can, though).
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use serde::Serialize;
+    use serde::Serializer;
+
+    use super::Frame;
+    use super::List;
+
+    impl<'a, T: Serialize> Serialize for List<'a, T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.iter())
+        }
+    }
+
+    impl<'a, T: Serialize> Serialize for Frame<'a, T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.list.serialize(serializer)
+        }
+    }
+}
 
 #[cfg(test)]
 mod test {
+    use super::CellList;
+    use super::DepthExceeded;
+    use super::Frame;
+    use super::FrameHook;
     use super::List;
+    #[cfg(feature = "alloc")]
+    use super::ScopeMap;
 
     #[test]
     fn elegance() {
@@ -62,6 +721,414 @@ mod test {
         })
     }
 
+    #[test]
+    fn frames() {
+        let root = Frame::new(None, 3);
+        let mid = root.push(5);
+        let tip = Frame::new(Some(&mid), 13);
+        assert_eq!(tip.iter().copied().sum::<i32>(), 13 + 5 + 3);
+        assert_eq!(mid.iter().copied().sum::<i32>(), 5 + 3);
+        assert_eq!(tip.data, 13);
+
+        // Frames and callbacks mix freely
+        List::push(Some(&tip), 1, |list| {
+            let top = Frame::new(Some(list), 2);
+            assert_eq!(top.iter().copied().collect::<Vec<_>>(), &[2, 1, 13, 5, 3]);
+        });
+    }
+
+    #[test]
+    fn frames_early_return() {
+        // Pushes n, n - 1, ... until a multiple of 3, and sums the chain
+        fn sum_down(list: &List<'_, u32>, n: u32) -> u32 {
+            let frame = Frame::new(Some(list), n);
+            if n % 3 == 0 {
+                return frame.iter().sum();
+            }
+            sum_down(&frame, n - 1)
+        }
+
+        let root = Frame::new(None, 100);
+        assert_eq!(sum_down(&root, 8), 100 + 8 + 7 + 6);
+        assert_eq!(sum_down(&root, 3), 100 + 3);
+    }
+
+    #[test]
+    fn len() {
+        List::push(None, 'a', |list| {
+            assert_eq!(list.len(), 1);
+            assert_eq!(list.depth(), 0);
+            List::push(Some(list), 'b', |list| {
+                let frame = Frame::new(Some(list), 'c');
+                assert_eq!(frame.len(), 3);
+                assert_eq!(frame.depth(), 2);
+                assert_eq!(frame.len(), frame.iter().count());
+                // Branching off a parent counts from there
+                let sibling = Frame::new(list.prev, 'd');
+                assert_eq!(sibling.len(), 2);
+            })
+        })
+    }
+
+    #[test]
+    fn find() {
+        let globals = Frame::new(None, ("x", 1));
+        let outer = globals.push(("y", 2));
+        let inner = outer.push(("x", 3));
+
+        // The innermost binding shadows the outer ones
+        fn lookup<'l>(list: &'l List<'_, (&str, i32)>, name: &str) -> Option<&'l (&'l str, i32)> {
+            list.find(|&(n, _)| n == name)
+        }
+        assert_eq!(lookup(&inner, "x"), Some(&("x", 3)));
+        assert_eq!(lookup(&outer, "x"), Some(&("x", 1)));
+        assert_eq!(lookup(&inner, "y"), Some(&("y", 2)));
+        assert_eq!(lookup(&globals, "y"), None);
+
+        assert!(inner.contains(&("x", 1)));
+        assert!(!outer.contains(&("x", 3)));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn scope_map() {
+        let mut globals = ScopeMap::new();
+        globals.insert("x".to_string(), 1);
+        globals.insert("y".to_string(), 2);
+
+        let mut function = globals.scope();
+        assert_eq!(function.get("x"), Some(&1));
+        assert_eq!(function.insert("x".to_string(), 10), None);
+        assert_eq!(function.depth(), 1);
+        {
+            let mut block = function.scope();
+            assert_eq!(block.insert("z".to_string(), 3), None);
+            // Rebinding in the same scope replaces the binding
+            assert_eq!(block.insert("z".to_string(), 4), Some(3));
+            assert_eq!(block.get("x"), Some(&10));
+            assert_eq!(block.get("y"), Some(&2));
+            assert_eq!(block.get("z"), Some(&4));
+            assert_eq!(block.depth(), 2);
+        }
+
+        // The block's bindings are gone with it, and the shadowed x is back
+        // once we leave the function
+        assert!(!function.contains_key("z"));
+        assert_eq!(function.get("x"), Some(&10));
+        drop(function);
+        assert_eq!(globals.get("x"), Some(&1));
+        assert_eq!(globals.get("w"), None);
+    }
+
+    #[test]
+    fn push_all() {
+        List::push(None, 0, |list| {
+            let sum = List::push_all(Some(list), 1..=3, |list| {
+                let list = list.unwrap();
+                assert_eq!(list.iter().copied().collect::<Vec<_>>(), &[3, 2, 1, 0]);
+                assert_eq!(list.len(), 4);
+                list.iter().sum::<i32>()
+            });
+            assert_eq!(sum, 6);
+
+            // Nothing to push leaves us where we were
+            List::push_all(Some(list), None, |tip| {
+                assert!(core::ptr::eq(tip.unwrap(), list));
+            });
+        });
+        List::push_all(None, Vec::<i32>::new(), |list| assert!(list.is_none()));
+    }
+
+    #[test]
+    fn stack_list_macro() {
+        let sum = crate::stack_list!([3, 5, 13] => |list| {
+            assert_eq!(list.len(), 3);
+            list.iter().copied().sum::<i32>()
+        });
+        assert_eq!(sum, 13 + 5 + 3);
+
+        crate::stack_list!(["a"] => |root| {
+            crate::stack_list!(Some(root), ["b", "c",] => |list| {
+                assert_eq!(list.iter().copied().collect::<Vec<_>>(), &["c", "b", "a"]);
+            })
+        });
+    }
+
+    #[test]
+    fn iter_with_depth() {
+        crate::stack_list!(["main", "parse", "expr"] => |list| {
+            let dump = list
+                .iter_with_depth()
+                .map(|(depth, name)| format!("{}{name}", "  ".repeat(depth)))
+                .collect::<Vec<_>>();
+            assert_eq!(dump, &["    expr", "  parse", "main"]);
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn iter_from_root() {
+        crate::stack_list!(["A", "B", "C"] => |list| {
+            let path = list.iter_from_root().copied().collect::<Vec<_>>();
+            assert_eq!(path.join(" > "), "A > B > C");
+            assert_eq!(list.iter_from_root().size_hint(), (3, Some(3)));
+            assert_eq!(list.prev.unwrap().iter_from_root().count(), 2);
+        });
+    }
+
+    #[test]
+    fn common_ancestor() {
+        let root = Frame::new(None, "root");
+        let shared = root.push("shared");
+        let left = shared.push("left");
+        let left = left.push("left2");
+        let right = shared.push("right");
+
+        let ancestor = left.common_ancestor(&right).unwrap();
+        assert!(core::ptr::eq(ancestor, &*shared));
+        assert_eq!(ancestor.data, "shared");
+        assert!(core::ptr::eq(
+            right.common_ancestor(&left).unwrap(),
+            &*shared
+        ));
+
+        // A chain's own frames count too
+        assert!(core::ptr::eq(
+            left.common_ancestor(&shared).unwrap(),
+            &*shared
+        ));
+        assert!(core::ptr::eq(left.common_ancestor(&left).unwrap(), &*left));
+
+        // Equal elements in different frames aren't shared
+        let other = Frame::new(None, "root");
+        let other = other.push("shared");
+        assert!(left.common_ancestor(&other).is_none());
+    }
+
+    #[test]
+    fn display_path() {
+        crate::stack_list!(["main", "parse", "expr"] => |list| {
+            assert_eq!(list.to_string(), "main/parse/expr");
+            assert_eq!(list.display_path(" > ").to_string(), "main > parse > expr");
+            assert_eq!(list.prev.unwrap().display_path("::").to_string(), "main::parse");
+        });
+        assert_eq!(Frame::new(None, 42).to_string(), "42");
+    }
+
+    #[test]
+    fn cell_list() {
+        use std::cell::Cell;
+
+        crate::stack_list!([Cell::new(3), Cell::new(5), Cell::new(13)] => |list| {
+            let list: &CellList<'_, i32> = list;
+            list.update_all(|x| x * 10);
+            assert_eq!(list.values().collect::<Vec<_>>(), &[130, 50, 30]);
+            assert_eq!(list.values().sum::<i32>(), 210);
+
+            // Only this frame and its parents are updated
+            let parent = list.prev.unwrap();
+            parent.update_all(|x| x + 1);
+            assert_eq!(list.values().collect::<Vec<_>>(), &[130, 51, 31]);
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "third")]
+    fn snapshots() {
+        let (vec, frozen) = crate::stack_list!(["main", "parse", "expr"] => |list| {
+            (list.to_vec(), list.freeze())
+        });
+        assert_eq!(vec, &["main", "parse", "expr"]);
+        assert_eq!(frozen.head(), Some(&"expr"));
+        assert_eq!(
+            frozen.iter().copied().collect::<Vec<_>>(),
+            &["expr", "parse", "main"]
+        );
+    }
+
+    #[test]
+    fn is_ancestor_of() {
+        let root = Frame::new(None, 1);
+        let mid = root.push(2);
+        let tip = mid.push(3);
+        let branch = root.push(2);
+
+        assert!(root.is_ancestor_of(&tip));
+        assert!(mid.is_ancestor_of(&tip));
+        assert!(tip.is_ancestor_of(&tip));
+        assert!(!tip.is_ancestor_of(&mid));
+        // Same elements at the same depth, but not the same frame
+        assert!(!branch.is_ancestor_of(&tip));
+
+        assert!(tip.starts_with(&root));
+        assert!(!tip.starts_with(&branch));
+        assert!(!root.starts_with(&Frame::new(None, 1)));
+    }
+
+    #[test]
+    fn into_iter() {
+        crate::stack_list!([1, 2, 3] => |list| {
+            let mut sum = 0;
+            for x in list {
+                sum += x;
+            }
+            assert_eq!(sum, 6);
+
+            let mut iter = list.iter();
+            assert_eq!(iter.len(), 3);
+            iter.next();
+            assert_eq!(iter.size_hint(), (2, Some(2)));
+            assert_eq!(iter.by_ref().count(), 2);
+            assert_eq!(iter.len(), 0);
+            assert_eq!(list.iter_with_depth().len(), 3);
+        });
+
+        let frame = Frame::new(None, 'a');
+        let frame = frame.push('b');
+        let mut names = String::new();
+        for c in &frame {
+            names.push(*c);
+        }
+        assert_eq!(names, "ba");
+    }
+
+    #[test]
+    fn try_push() {
+        // Counts down with a frame per call, as long as the limit allows
+        fn countdown(prev: Option<&List<'_, u32>>, n: u32) -> Result<usize, DepthExceeded<u32>> {
+            List::try_push(prev, n, 5, |list| match n {
+                0 => Ok(list.len()),
+                _ => countdown(Some(list), n - 1),
+            })?
+        }
+
+        assert_eq!(countdown(None, 4), Ok(5));
+        assert_eq!(countdown(None, 5), Err(DepthExceeded(0)));
+        assert_eq!(List::try_push(None, 1, 0, |_| ()), Err(DepthExceeded(1)));
+        assert_eq!(DepthExceeded(1).to_string(), "maximum stack depth exceeded");
+    }
+
+    #[test]
+    fn folds() {
+        crate::stack_list!(["a", "b", "c"] => |list| {
+            let from_tip = list.fold_from_tip(String::new(), |acc, x| acc + x);
+            let from_root = list.fold_from_root(String::new(), |acc, x| acc + x);
+            assert_eq!(from_tip, "cba");
+            assert_eq!(from_root, "abc");
+        });
+
+        // E.g. permissions, where inner scopes can only take some away
+        crate::stack_list!([0b111, 0b101, 0b100] => |list| {
+            assert_eq!(list.fold_from_root(u8::MAX, |acc, mask| acc & mask), 0b100);
+            assert_eq!(list.prev.unwrap().fold_from_tip(u8::MAX, |acc, mask| acc & mask), 0b101);
+        });
+    }
+
+    #[test]
+    fn hooks() {
+        use std::cell::RefCell;
+
+        #[derive(Default)]
+        struct Trace(RefCell<Vec<String>>);
+
+        impl FrameHook<&str> for Trace {
+            fn enter(&self, frame: &List<'_, &str>) {
+                self.0.borrow_mut().push(format!("enter {frame}"));
+            }
+
+            fn exit(&self, frame: &List<'_, &str>) {
+                self.0.borrow_mut().push(format!("exit {frame}"));
+            }
+        }
+
+        let trace = Trace::default();
+        List::push(None, "untraced", |root| {
+            List::push_hooked(Some(root), "main", &trace, |main| {
+                // Frames pushed through the Frame pass the hook on
+                let a = main.push("a");
+                {
+                    let _b = a.push("b");
+                }
+                // Plain lists don't know about it
+                List::push(Some(&a), "untraced", |_| ());
+                let _ = List::try_push(Some(main), "untraced", 10, |_| ());
+                let _ = Frame::new(Some(main), "untraced");
+            });
+        });
+        assert_eq!(
+            trace.0.into_inner(),
+            &[
+                "enter untraced/main",
+                "enter untraced/main/a",
+                "enter untraced/main/a/b",
+                "exit untraced/main/a/b",
+                "exit untraced/main/a",
+                "exit untraced/main",
+            ]
+        );
+    }
+
+    #[test]
+    fn hooks_unwind() {
+        use std::cell::Cell;
+        use std::panic::AssertUnwindSafe;
+
+        struct Depth(Cell<i32>);
+
+        impl FrameHook<i32> for Depth {
+            fn enter(&self, _frame: &List<'_, i32>) {
+                self.0.set(self.0.get() + 1);
+            }
+
+            fn exit(&self, _frame: &List<'_, i32>) {
+                self.0.set(self.0.get() - 1);
+            }
+        }
+
+        let depth = Depth(Cell::new(0));
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            List::push_hooked(None, 1, &depth, |list| {
+                List::push(Some(list), 2, |_| panic!("boom"));
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(depth.0.get(), 0);
+    }
+
+    #[test]
+    fn zip() {
+        crate::stack_list!(["f", "x", "y"] => |names| {
+            crate::stack_list!([1, 4] => |lines| {
+                let pairs = names.zip(lines).map(|(n, l)| (*n, *l)).collect::<Vec<_>>();
+                assert_eq!(pairs, &[("y", 4), ("x", 1)]);
+                assert_eq!(lines.zip(names).len(), 2);
+            })
+        });
+    }
+
+    #[test]
+    fn nth_parent() {
+        // λx. λy. λz. x, with the binders pushed as we go into the lambdas
+        crate::stack_list!(["x", "y", "z"] => |env| {
+            assert_eq!(env.nth_parent(0).map(|f| f.data), Some("z"));
+            assert_eq!(env.nth_parent(2).map(|f| f.data), Some("x"));
+            assert!(core::ptr::eq(env.nth_parent(1).unwrap(), env.prev.unwrap()));
+            assert!(env.nth_parent(3).is_none());
+            assert!(env.nth_parent(usize::MAX).is_none());
+        });
+    }
+
+    #[test]
+    fn debug() {
+        crate::stack_list!(["root", "mid", "tip"] => |list| {
+            assert_eq!(format!("{list:?}"), r#"["tip" <- "mid" <- "root"]"#);
+        });
+        let frame = Frame::new(None, 1);
+        assert_eq!(format!("{frame:?}"), "[1]");
+        assert_eq!(format!("{:?}", frame.push(2)), "[2 <- 1]");
+    }
+
+    #[test]
     fn example1() {
         let mut x = Box::new(42);
         let r = &x;
@@ -77,6 +1144,8 @@ mod test {
         }
     }
 
+    #[test]
+    #[allow(clippy::replace_box)]
     fn example2() {
         let mut x = Box::new(42);
         let mut z = &x;
@@ -92,6 +1161,7 @@ mod test {
         }
     }
 
+    #[test]
     fn example3() {
         struct MutStr<'a, 'b> {
             s: &'a mut &'b str,
@@ -101,6 +1171,26 @@ mod test {
         *MutStr { s: &mut s }.s = "world";
         println!("{}", s);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde_test::assert_ser_tokens;
+        use serde_test::Token;
+
+        let tokens = [
+            Token::Seq { len: Some(3) },
+            Token::I32(1),
+            Token::I32(2),
+            Token::I32(3),
+            Token::SeqEnd,
+        ];
+        let root = Frame::new(None, 3);
+        let mid = root.push(2);
+        let tip = mid.push(1);
+        assert_ser_tokens(&tip, &tokens);
+        assert_ser_tokens(&*tip, &tokens);
+    }
 }
 
 #[test]
@@ -0,0 +1,224 @@
+// Frames pushed with the callback API (or as Frames) live on the stack, so
+// the chain can't leave the function that built it. Here they live in an
+// arena instead: a frame is borrowed from the arena rather than from a stack
+// frame, so a chain can be returned and kept as long as the arena is around.
+// The frames are regular stacklist::Lists, so everything that works on those
+// works on these too, and frames on the stack can be pushed on top of them.
+//
+// Nothing is freed until the arena goes away, as frames can't be popped
+// (there's no knowing who still points at them). Hooks live in Frames, so
+// they don't hear about arena frames either.
+
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::mem::size_of;
+
+use super::List;
+use crate::traits::MemSize;
+
+pub struct ArenaStackList<'a, T> {
+    // The frames go in chunks that we never grow past their capacity, so
+    // pushing to them never moves the frames already handed out. When a chunk
+    // is full we start a bigger one, like typed arenas do.
+    chunks: RefCell<Vec<Vec<List<'a, T>>>>,
+}
+
+impl<'a, T> ArenaStackList<'a, T> {
+    pub fn new() -> Self {
+        ArenaStackList {
+            chunks: RefCell::new(Vec::new()),
+        }
+    }
+
+    // Pushes a frame on `prev` (or a root, if None), which lasts as long as
+    // the arena. `prev` can be any frame, not only one from this arena.
+    pub fn push(&'a self, prev: Option<&'a List<'a, T>>, data: T) -> &'a List<'a, T> {
+        let frame = List::new(prev, data);
+
+        let mut chunks = self.chunks.borrow_mut();
+        let chunk = match chunks.last_mut() {
+            Some(chunk) if chunk.len() < chunk.capacity() => chunk,
+            _ => {
+                let capacity = chunks.last().map_or(8, |chunk| chunk.capacity() * 2);
+                chunks.push(Vec::with_capacity(capacity));
+                chunks.last_mut().unwrap()
+            }
+        };
+        chunk.push(frame);
+        let frame: *const List<'a, T> = chunk.last().unwrap();
+        drop(chunks);
+        self.strict_check();
+        // SAFETY: the chunk has room, so the push didn't move its frames, and
+        // we never remove or hand out mutable references to them. They're
+        // only dropped along with the arena, which we borrow for 'a.
+        unsafe { &*frame }
+    }
+
+    // The number of frames in the arena, across all its chains
+    pub fn frames(&self) -> usize {
+        self.chunks.borrow().iter().map(Vec::len).sum()
+    }
+
+    // Panics if the chunks aren't laid out the way push relies on: every
+    // chunk but the last one full (a chunk that grew would have moved its
+    // frames), and every frame one longer than its parent. It's O(n), and
+    // meant for tests.
+    pub fn assert_invariants(&self) {
+        let chunks = self.chunks.borrow();
+        if let Some((_, full)) = chunks.split_last() {
+            for chunk in full {
+                assert_eq!(chunk.len(), chunk.capacity(), "a chunk was left with room");
+            }
+        }
+        for frame in chunks.iter().flatten() {
+            let len = frame.prev.map_or(0, List::len) + 1;
+            assert_eq!(frame.len, len, "a frame's len doesn't follow its parent's");
+        }
+    }
+
+    // With the `strict-checks` feature, debug builds check the invariants
+    // after every push
+    #[inline]
+    fn strict_check(&self) {
+        #[cfg(feature = "strict-checks")]
+        if cfg!(debug_assertions) {
+            self.assert_invariants();
+        }
+    }
+}
+
+impl<'a, T> Default for ArenaStackList<'a, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Every chain in the arena together, as that's what the memory is shared
+// between. The chunks are allocated in full upfront, so their spare room
+// counts too, along with the Vec that keeps track of them.
+impl<'a, T> MemSize for ArenaStackList<'a, T> {
+    fn heap_bytes(&self) -> usize {
+        let chunks = self.chunks.borrow();
+        let frames: usize = chunks.iter().map(Vec::capacity).sum();
+        frames * self.bytes_per_element() + chunks.capacity() * size_of::<Vec<List<'a, T>>>()
+    }
+
+    fn node_count(&self) -> usize {
+        self.frames()
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        size_of::<List<'a, T>>()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ArenaStackList;
+    use crate::stacklist::List;
+
+    // The point of the arena: building a chain in a function and returning it
+    fn scopes<'a>(arena: &'a ArenaStackList<'a, String>, path: &str) -> &'a List<'a, String> {
+        let mut list = None;
+        for name in path.split('/') {
+            list = Some(arena.push(list, name.to_string()));
+        }
+        list.unwrap()
+    }
+
+    #[test]
+    fn basics() {
+        let arena = ArenaStackList::new();
+        let list = scopes(&arena, "main/parse/expr");
+        assert_eq!(list.to_string(), "main/parse/expr");
+        assert_eq!(list.len(), 3);
+
+        // Chains can branch off any arena frame
+        let other = arena.push(list.prev, "stmt".to_string());
+        assert_eq!(format!("{other:?}"), r#"["stmt" <- "parse" <- "main"]"#);
+        assert_eq!(other.common_ancestor(list).unwrap().data, "parse");
+        assert_eq!(arena.frames(), 4);
+
+        // And stack frames can go on top of them
+        List::push(Some(other), "tmp".to_string(), |tmp| {
+            assert_eq!(tmp.depth(), 3);
+        });
+    }
+
+    #[test]
+    fn many_frames() {
+        let arena = ArenaStackList::new();
+        let mut list = arena.push(None, 0);
+        for i in 1..1000 {
+            list = arena.push(Some(list), i);
+        }
+        // All the frames are still where they were when we pushed them
+        assert_eq!(list.iter().copied().sum::<i32>(), 999 * 1000 / 2);
+        assert_eq!(list.iter().len(), 1000);
+        assert_eq!(arena.frames(), 1000);
+    }
+
+    // Chains in an arena can get much deeper than the stack, so nothing that
+    // walks them may recurse once per frame
+    #[test]
+    fn deep_chain() {
+        let depth = if cfg!(miri) || cfg!(feature = "strict-checks") {
+            1_000
+        } else {
+            1_000_000
+        };
+        let arena = ArenaStackList::new();
+        let mut list = arena.push(None, 0);
+        for i in 1..depth {
+            list = arena.push(Some(list), i);
+        }
+
+        let count = list.fold_from_root(0, |count, &x| {
+            assert_eq!(x, count);
+            count + 1
+        });
+        assert_eq!(count, depth);
+
+        // Display goes root first too
+        let path = list.to_string();
+        assert!(path.starts_with("0/1/2/"));
+        assert!(path.ends_with(&format!("/{}", depth - 1)));
+        assert_eq!(path.split('/').count(), depth);
+    }
+
+    #[test]
+    fn mem_size() {
+        use std::mem::size_of;
+
+        use crate::traits::MemSize;
+
+        let arena = ArenaStackList::new();
+        assert_eq!(arena.heap_bytes(), 0);
+
+        // Chunks of 8 and 16 frames, the second only partly used
+        let mut list = arena.push(None, 0);
+        for i in 1..10 {
+            list = arena.push(Some(list), i);
+        }
+        assert_eq!(arena.node_count(), 10);
+        let frames = 24 * size_of::<List<'_, i32>>();
+        assert!(arena.heap_bytes() > frames);
+
+        // The frames themselves are the arena's, not the chain's
+        assert_eq!(list.node_count(), 10);
+        assert_eq!(list.heap_bytes(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "a chunk was left with room")]
+    fn assert_invariants() {
+        let arena = ArenaStackList::new();
+        let root = arena.push(None, 0);
+        arena.push(Some(root), 1);
+        arena.assert_invariants();
+
+        // A new chunk while the last one still has room, as if it had grown
+        arena.chunks.borrow_mut().push(Vec::with_capacity(16));
+        arena.assert_invariants();
+    }
+}
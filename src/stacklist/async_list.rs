@@ -0,0 +1,406 @@
+// The borrowed frames of a stacklist can't be held across an .await: the
+// future would have to borrow from itself. Here each frame owns its parent
+// through an Arc instead, like third's persistent list but thread-safe, so
+// a frame is a plain value that can move into a future (and the future to
+// another thread). Pushing is still cheap, it only bumps the parent's count,
+// and a frame is popped when the last handle to it goes away.
+
+use alloc::sync::Arc;
+use core::fmt;
+use core::future::Future;
+
+use crate::traits::counted_size;
+use crate::traits::MemSize;
+
+pub struct AsyncStackList<T> {
+    frame: Arc<Frame<T>>,
+}
+
+struct Frame<T> {
+    data: T,
+    prev: Option<Arc<Frame<T>>>,
+    // Same as stacklist::List, the number of frames up to this one
+    len: usize,
+}
+
+pub struct Iter<'a, T> {
+    next: Option<&'a Frame<T>>,
+}
+
+impl<T> AsyncStackList<T> {
+    // The outermost frame
+    pub fn root(data: T) -> Self {
+        AsyncStackList {
+            frame: Arc::new(Frame {
+                data,
+                prev: None,
+                len: 1,
+            }),
+        }
+    }
+
+    // A new frame on top of this one. It's the guard for the new scope: the
+    // frame lives as long as it (or a clone of it) does.
+    pub fn push(&self, data: T) -> Self {
+        AsyncStackList {
+            frame: Arc::new(Frame {
+                data,
+                prev: Some(self.frame.clone()),
+                len: self.frame.len + 1,
+            }),
+        }
+    }
+
+    // The async take on stacklist::List::push: pushes a frame on `prev` (or
+    // a root, if None) and awaits `callback` with it
+    pub async fn push_scoped<U, F, Fut>(prev: Option<&Self>, data: T, callback: F) -> U
+    where
+        F: FnOnce(Self) -> Fut,
+        Fut: Future<Output = U>,
+    {
+        let frame = match prev {
+            Some(prev) => prev.push(data),
+            None => Self::root(data),
+        };
+        callback(frame).await
+    }
+
+    pub fn data(&self) -> &T {
+        &self.frame.data
+    }
+
+    // A handle to the parent frame, if we aren't the root
+    pub fn parent(&self) -> Option<Self> {
+        self.frame
+            .prev
+            .clone()
+            .map(|frame| AsyncStackList { frame })
+    }
+
+    // The number of frames in the chain, this one included
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.frame.len
+    }
+
+    // How far we are from the root, which is at depth 0
+    pub fn depth(&self) -> usize {
+        self.frame.len - 1
+    }
+
+    // From this frame out to the root, same as stacklist::List::iter
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: Some(&self.frame),
+        }
+    }
+}
+
+// Cloning a handle doesn't clone the data, so T doesn't need to be Clone
+impl<T> Clone for AsyncStackList<T> {
+    fn clone(&self) -> Self {
+        AsyncStackList {
+            frame: self.frame.clone(),
+        }
+    }
+}
+
+// Each frame is an Arc, counted once per chain it's in, like third's nodes
+impl<T> MemSize for AsyncStackList<T> {
+    fn node_count(&self) -> usize {
+        self.frame.len
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        counted_size::<Frame<T>>()
+    }
+}
+
+// Same format as stacklist::List's, from this frame out
+impl<T: fmt::Debug> fmt::Debug for AsyncStackList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        for (i, x) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, " <- ")?;
+            }
+            fmt::Debug::fmt(x, f)?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|frame| {
+            self.next = frame.prev.as_deref();
+            &frame.data
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.next.map_or(0, |frame| frame.len);
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a AsyncStackList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// Same as third's, we unwind the chain ourselves so that dropping a long one
+// doesn't recurse. Arc::into_inner (rather than try_unwrap) guarantees that
+// if two threads drop the last two handles at once, one of them gets it.
+impl<T> Drop for Frame<T> {
+    fn drop(&mut self) {
+        let mut prev = self.prev.take();
+        while let Some(frame) = prev {
+            match Arc::into_inner(frame) {
+                Some(mut frame) => prev = frame.prev.take(),
+                None => break,
+            }
+        }
+    }
+}
+
+// Same encoding as stacklist::List, from this frame out to the root
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use alloc::vec::Vec;
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::de::Error;
+    use serde::de::SeqAccess;
+    use serde::de::Visitor;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    use super::AsyncStackList;
+
+    impl<T: Serialize> Serialize for AsyncStackList<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.iter())
+        }
+    }
+
+    impl<'de, T: Deserialize<'de>> Deserialize<'de> for AsyncStackList<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_seq(AsyncStackListVisitor(PhantomData))
+        }
+    }
+
+    struct AsyncStackListVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for AsyncStackListVisitor<T> {
+        type Value = AsyncStackList<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a non-empty sequence")
+        }
+
+        // The root comes last, and it has to be pushed first
+        fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+            let mut elems = Vec::new();
+            while let Some(elem) = seq.next_element()? {
+                elems.push(elem);
+            }
+            let mut elems = elems.into_iter().rev();
+            let root = elems
+                .next()
+                .ok_or_else(|| S::Error::invalid_length(0, &self))?;
+            Ok(elems.fold(AsyncStackList::root(root), |list, elem| list.push(elem)))
+        }
+    }
+}
+
+// From this frame out to the root, which always has to be there
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl {
+    use arbitrary::Arbitrary;
+    use arbitrary::Result;
+    use arbitrary::Unstructured;
+
+    use super::AsyncStackList;
+
+    impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for AsyncStackList<T> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let elems = u.arbitrary_iter()?.collect::<Result<Vec<T>>>()?;
+            let mut elems = elems.into_iter().rev();
+            let root = elems.next().ok_or(arbitrary::Error::NotEnoughData)?;
+            Ok(elems.fold(AsyncStackList::root(root), |list, elem| list.push(elem)))
+        }
+
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+            let elems = u.arbitrary_take_rest_iter()?.collect::<Result<Vec<T>>>()?;
+            let mut elems = elems.into_iter().rev();
+            let root = elems.next().ok_or(arbitrary::Error::NotEnoughData)?;
+            Ok(elems.fold(AsyncStackList::root(root), |list, elem| list.push(elem)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::future::Future;
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::task::Context;
+    use std::task::Poll;
+    use std::task::Wake;
+    use std::task::Waker;
+
+    use super::AsyncStackList;
+
+    // Just enough of an executor for futures that never wait on anything
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        struct Noop;
+
+        impl Wake for Noop {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        let waker = Waker::from(Arc::new(Noop));
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = pin!(fut);
+        loop {
+            if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+                return out;
+            }
+        }
+    }
+
+    // Gives up control once, so that there's a real await point
+    async fn yield_now() {
+        let mut yielded = false;
+        std::future::poll_fn(|cx| {
+            if yielded {
+                Poll::Ready(())
+            } else {
+                yielded = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    async fn path(
+        mut list: AsyncStackList<&'static str>,
+        names: &[&'static str],
+    ) -> Vec<&'static str> {
+        for name in names {
+            // The frame is held across the await, which borrowed ones can't
+            list = list.push(name);
+            yield_now().await;
+        }
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn basics() {
+        let root = AsyncStackList::root(1);
+        let mid = root.push(2);
+        let tip = mid.push(3);
+        assert_eq!(tip.iter().copied().collect::<Vec<_>>(), &[3, 2, 1]);
+        assert_eq!(tip.iter().len(), 3);
+        assert_eq!(format!("{tip:?}"), "[3 <- 2 <- 1]");
+        assert_eq!((&tip).into_iter().sum::<i32>(), 6);
+        assert_eq!(*tip.data(), 3);
+        assert_eq!(tip.len(), 3);
+        assert_eq!(tip.depth(), 2);
+        assert_eq!(tip.parent().map(|p| *p.data()), Some(2));
+        assert!(root.parent().is_none());
+
+        // Frames outlive the handles they were pushed from
+        drop(root);
+        drop(mid);
+        assert_eq!(tip.iter().copied().sum::<i32>(), 6);
+    }
+
+    #[test]
+    fn across_await() {
+        let root = AsyncStackList::root("main");
+        let names = block_on(path(root.clone(), &["parse", "expr"]));
+        assert_eq!(names, &["expr", "parse", "main"]);
+
+        let len = block_on(AsyncStackList::push_scoped(
+            Some(&root),
+            "task",
+            |list| async move {
+                yield_now().await;
+                list.len()
+            },
+        ));
+        assert_eq!(len, 2);
+    }
+
+    #[test]
+    fn send() {
+        fn is_send<T: Send>(_: &T) {}
+
+        let fut = AsyncStackList::push_scoped(None, 0, |list| async move {
+            yield_now().await;
+            *list.data()
+        });
+        is_send(&fut);
+
+        // And the frames themselves can go to another thread
+        let list = AsyncStackList::root(1).push(2);
+        let sum = std::thread::spawn(move || list.iter().sum::<i32>());
+        assert_eq!(sum.join().unwrap(), 3);
+    }
+
+    #[test]
+    fn long_chain() {
+        let mut list = AsyncStackList::root(0);
+        for i in 1..100_000 {
+            list = list.push(i);
+        }
+        assert_eq!(list.len(), 100_000);
+        // Dropping it mustn't overflow the stack
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde::de::value::Error;
+        use serde::de::value::SeqDeserializer;
+        use serde::Deserialize;
+        use serde_test::assert_ser_tokens;
+        use serde_test::Token;
+
+        let list = AsyncStackList::root(3).push(2).push(1);
+        assert_ser_tokens(
+            &list,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::I32(1),
+                Token::I32(2),
+                Token::I32(3),
+                Token::SeqEnd,
+            ],
+        );
+
+        let seq = SeqDeserializer::<_, Error>::new(vec![1, 2, 3].into_iter());
+        let list = AsyncStackList::<i32>::deserialize(seq).unwrap();
+        assert_eq!(*list.data(), 1);
+        assert_eq!(list.depth(), 2);
+        assert_eq!(format!("{list:?}"), "[1 <- 2 <- 3]");
+
+        // There's no chain without a root
+        let seq = SeqDeserializer::<_, Error>::new(Vec::<i32>::new().into_iter());
+        assert!(AsyncStackList::<i32>::deserialize(seq).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+// Everything the thread-safe code in the crate synchronizes with, in one
+// place. Normally these are just core's, alloc's and std's, but building
+// with RUSTFLAGS="--cfg loom" swaps in loom's, whose model checker then sees
+// every atomic access and lock, and runs each test under every interleaving
+// of its threads that could make a difference:
+//
+//     RUSTFLAGS="--cfg loom" cargo test --release --lib
+//
+// So concurrent code goes through here rather than std::sync, and its tests
+// wrap their body in `model` and spawn their threads with `thread::spawn`
+// from here (anything touching the shims panics under loom outside of a
+// model). Tests that are too big for loom to explore are left out of loom
+// builds with #[cfg(not(loom))].
+
+#[cfg(not(loom))]
+pub(crate) use alloc::sync::Arc;
+#[cfg(loom)]
+pub(crate) use loom::sync::Arc;
+#[cfg(loom)]
+pub(crate) use loom::sync::Condvar;
+#[cfg(loom)]
+pub(crate) use loom::sync::Mutex;
+#[cfg(all(not(loom), feature = "std"))]
+pub(crate) use std::sync::Condvar;
+#[cfg(all(not(loom), feature = "std"))]
+pub(crate) use std::sync::Mutex;
+
+pub(crate) mod atomic {
+    #[cfg(not(loom))]
+    pub(crate) use core::sync::atomic::fence;
+    #[cfg(not(loom))]
+    pub(crate) use core::sync::atomic::AtomicBool;
+    #[cfg(not(loom))]
+    pub(crate) use core::sync::atomic::AtomicPtr;
+    #[cfg(not(loom))]
+    pub(crate) use core::sync::atomic::AtomicUsize;
+    #[cfg(not(loom))]
+    pub(crate) use core::sync::atomic::Ordering;
+    #[cfg(loom)]
+    pub(crate) use loom::sync::atomic::fence;
+    #[cfg(loom)]
+    pub(crate) use loom::sync::atomic::AtomicBool;
+    #[cfg(loom)]
+    pub(crate) use loom::sync::atomic::AtomicPtr;
+    #[cfg(loom)]
+    pub(crate) use loom::sync::atomic::AtomicUsize;
+    #[cfg(loom)]
+    pub(crate) use loom::sync::atomic::Ordering;
+}
+
+pub(crate) mod hint {
+    #[cfg(not(loom))]
+    pub(crate) use core::hint::spin_loop;
+    // Lets loom switch to another thread, so spinning can't loop forever
+    #[cfg(loom)]
+    pub(crate) use loom::hint::spin_loop;
+}
+
+#[cfg(test)]
+pub(crate) mod thread {
+    #[cfg(loom)]
+    pub(crate) use loom::thread::spawn;
+    #[cfg(not(loom))]
+    pub(crate) use std::thread::spawn;
+}
+
+// Memory that threads share without atomics, like a ring buffer's slots.
+// loom can't see plain reads and writes through a raw pointer, so they go
+// through `with` and `with_mut`, which loom checks for data races: two
+// threads in `with_mut` at once, or one in `with_mut` and one in `with`,
+// without a happens-before between them fails the test.
+#[cfg(loom)]
+pub(crate) use loom::cell::UnsafeCell;
+
+#[cfg(not(loom))]
+#[derive(Debug)]
+pub(crate) struct UnsafeCell<T>(core::cell::UnsafeCell<T>);
+
+#[cfg(not(loom))]
+impl<T> UnsafeCell<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        UnsafeCell(core::cell::UnsafeCell::new(value))
+    }
+
+    pub(crate) fn with<R>(&self, f: impl FnOnce(*const T) -> R) -> R {
+        f(self.0.get())
+    }
+
+    pub(crate) fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+        f(self.0.get())
+    }
+}
+
+// Runs `f` under every interleaving loom can tell apart, or just once
+// without loom, so that the same test is a regular one too
+#[cfg(test)]
+pub(crate) fn model(f: impl Fn() + Sync + Send + 'static) {
+    #[cfg(loom)]
+    loom::model(f);
+    #[cfg(not(loom))]
+    f();
+}
+
+#[cfg(test)]
+mod test {
+    use super::atomic::AtomicUsize;
+    use super::atomic::Ordering;
+    use super::thread;
+    use super::Arc;
+    use super::UnsafeCell;
+
+    #[test]
+    fn counter() {
+        super::model(|| {
+            let counter = Arc::new(AtomicUsize::new(0));
+            let other = counter.clone();
+            let handle = thread::spawn(move || {
+                other.fetch_add(1, Ordering::AcqRel);
+            });
+            counter.fetch_add(1, Ordering::AcqRel);
+            handle.join().unwrap();
+            assert_eq!(counter.load(Ordering::Acquire), 2);
+        });
+    }
+
+    #[test]
+    fn cell() {
+        super::model(|| {
+            let cell = UnsafeCell::new(1);
+            cell.with_mut(|value| unsafe { *value += 1 });
+            assert_eq!(cell.with(|value| unsafe { *value }), 2);
+        });
+    }
+
+    // The harness itself: two threads bumping a counter with a load and a
+    // store can lose an update, and loom has to find the interleaving that
+    // does (which plain threads would hardly ever hit)
+    #[cfg(loom)]
+    #[test]
+    #[should_panic(expected = "left == right")]
+    fn lost_update() {
+        loom::model(|| {
+            let counter = Arc::new(AtomicUsize::new(0));
+            let other = counter.clone();
+            let handle = thread::spawn(move || {
+                let seen = other.load(Ordering::Acquire);
+                other.store(seen + 1, Ordering::Release);
+            });
+            let seen = counter.load(Ordering::Acquire);
+            counter.store(seen + 1, Ordering::Release);
+            handle.join().unwrap();
+            assert_eq!(counter.load(Ordering::Acquire), 2);
+        });
+    }
+}
@@ -0,0 +1,497 @@
+// third gave us a persistent stack: every version of the list stays usable,
+// and new ones share the nodes of the old. This is the queue to go with it,
+// after Okasaki's banker's queue, built from two of third's lists.
+//
+// The front list holds the oldest elements, oldest first, so taking one off
+// is just third's `tail`. The rear list holds the newest, newest first, so
+// adding one is just `prepend`. Whenever the rear would get longer than the
+// front, we rotate: the front becomes the front followed by the rear
+// reversed, and the rear starts over empty. That's the one O(n) step, but
+// after a rotation of n elements there are n more snocs before the next one,
+// so it's O(1) amortized.
+//
+// Done eagerly, that bound falls apart with persistence: an old version
+// right before a rotation rotates again every time it's used. So, like
+// Okasaki, we make the rotation lazy. The new front is a suspension, only
+// computed once it's needed and then remembered, so every version sharing
+// it pays for it once. It isn't needed until the elements before it are
+// gone: we keep a prefix of the front that's already computed, for `front`
+// and `tail` to use, and only force the suspension when the prefix runs out,
+// n tails after the rotation. Taking the tail of a suspended front is lazy
+// too. Okasaki's banker's queue suspends each node of the front on its own;
+// suspending the whole front with a computed prefix in front of it (his
+// physicist's queue) gets the same bounds out of third's plain lists.
+//
+// As the nodes are shared, rotating can't move the elements into the new
+// list, only clone them, hence T: Clone on everything that might rotate.
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::OnceCell;
+use core::cell::RefCell;
+
+use crate::third;
+use crate::traits::MemSize;
+
+pub struct Queue<T> {
+    // The first elements of the front, already computed
+    prefix: third::List<T>,
+    front: Rc<Lazy<T>>,
+    front_len: usize,
+    rear: third::List<T>,
+    rear_len: usize,
+}
+
+// A front that's computed the first time it's forced, and then remembered
+struct Lazy<T> {
+    value: OnceCell<third::List<T>>,
+    // How to compute it, until it is
+    pending: RefCell<Option<Pending<T>>>,
+}
+
+enum Pending<T> {
+    // The front followed by the rear reversed
+    Rotate(third::List<T>, third::List<T>),
+    // Another front without its first element
+    Tail(Rc<Lazy<T>>),
+}
+
+impl<T> Lazy<T> {
+    fn ready(value: third::List<T>) -> Rc<Self> {
+        Rc::new(Lazy {
+            value: OnceCell::from(value),
+            pending: RefCell::new(None),
+        })
+    }
+
+    fn pending(pending: Pending<T>) -> Rc<Self> {
+        Rc::new(Lazy {
+            value: OnceCell::new(),
+            pending: RefCell::new(Some(pending)),
+        })
+    }
+}
+
+impl<T: Clone> Lazy<T> {
+    fn force(&self) -> &third::List<T> {
+        if let Some(value) = self.value.get() {
+            return value;
+        }
+
+        // Tails of tails chain up as long as the front, so rather than
+        // recursing, we go down to the first one that's computed or
+        // rotates, and compute our way back up from there
+        let mut chain = Vec::new();
+        let mut next = self.inner();
+        while let Some(lazy) = next {
+            if lazy.value.get().is_some() {
+                break;
+            }
+            next = lazy.inner();
+            chain.push(lazy);
+        }
+        for lazy in chain.iter().rev() {
+            lazy.compute();
+        }
+        drop(chain);
+        self.compute();
+        self.value.get().unwrap()
+    }
+
+    // The front this one is the tail of, if it is one
+    fn inner(&self) -> Option<Rc<Lazy<T>>> {
+        match &*self.pending.borrow() {
+            Some(Pending::Tail(inner)) => Some(inner.clone()),
+            _ => None,
+        }
+    }
+
+    // Computes the value, once whatever it comes from is computed
+    fn compute(&self) {
+        let value = match self.pending.borrow_mut().take() {
+            Some(Pending::Rotate(front, rear)) => rotate(&front, &rear),
+            Some(Pending::Tail(inner)) => inner.value.get().unwrap().tail(),
+            None => return,
+        };
+        let _ = self.value.set(value);
+    }
+}
+
+// A chain of tails is dropped one by one, rather than recursively
+impl<T> Drop for Lazy<T> {
+    fn drop(&mut self) {
+        let mut pending = self.pending.get_mut().take();
+        while let Some(Pending::Tail(inner)) = pending {
+            pending = match Rc::try_unwrap(inner) {
+                Ok(mut lazy) => lazy.pending.get_mut().take(),
+                Err(_) => None,
+            };
+        }
+    }
+}
+
+// The new list is built from its end: the rear from the newest on, and then
+// the front from its last element back to its head
+fn rotate<T: Clone>(front: &third::List<T>, rear: &third::List<T>) -> third::List<T> {
+    let mut rotated = third::List::new();
+    for elem in rear.iter() {
+        rotated = rotated.prepend(elem.clone());
+    }
+    let front: Vec<&T> = front.iter().collect();
+    for elem in front.into_iter().rev() {
+        rotated = rotated.prepend(elem.clone());
+    }
+    rotated
+}
+
+impl<T> Queue<T> {
+    pub fn new() -> Self {
+        Queue {
+            prefix: third::List::new(),
+            front: Lazy::ready(third::List::new()),
+            front_len: 0,
+            rear: third::List::new(),
+            rear_len: 0,
+        }
+    }
+
+    // The rear is never longer than the front, and the prefix is only empty
+    // along with the front, so the oldest element is always at its head
+    pub fn front(&self) -> Option<&T> {
+        self.prefix.head()
+    }
+
+    pub fn len(&self) -> usize {
+        self.front_len + self.rear_len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.front_len == 0
+    }
+}
+
+impl<T: Clone> Queue<T> {
+    pub fn snoc(&self, elem: T) -> Self {
+        Queue::balanced(
+            self.prefix.clone(),
+            self.front.clone(),
+            self.front_len,
+            self.rear.prepend(elem),
+            self.rear_len + 1,
+        )
+    }
+
+    pub fn tail(&self) -> Self {
+        if self.is_empty() {
+            return Queue::new();
+        }
+        Queue::balanced(
+            self.prefix.tail(),
+            Lazy::pending(Pending::Tail(self.front.clone())),
+            self.front_len - 1,
+            self.rear.clone(),
+            self.rear_len,
+        )
+    }
+
+    pub fn uncons(&self) -> Option<(&T, Self)> {
+        self.front().map(|elem| (elem, self.tail()))
+    }
+
+    // Puts the lists together, suspending a rotation if the rear got too
+    // long, and computing the front if the prefix ran out
+    fn balanced(
+        prefix: third::List<T>,
+        front: Rc<Lazy<T>>,
+        front_len: usize,
+        rear: third::List<T>,
+        rear_len: usize,
+    ) -> Self {
+        let (prefix, front, front_len, rear, rear_len) = if rear_len <= front_len {
+            (prefix, front, front_len, rear, rear_len)
+        } else {
+            // The front this rotation starts from is computed by now, by a
+            // rotation that was paid for by the elements that came since
+            let forced = front.force().clone();
+            let rotated = Lazy::pending(Pending::Rotate(forced.clone(), rear));
+            (forced, rotated, front_len + rear_len, third::List::new(), 0)
+        };
+
+        let prefix = match prefix.head() {
+            None => front.force().clone(),
+            Some(_) => prefix,
+        };
+        Queue {
+            prefix,
+            front,
+            front_len,
+            rear,
+            rear_len,
+        }
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Versions share nodes the same way third's lists do, so this counts them
+// the same way too
+impl<T> MemSize for Queue<T> {
+    fn node_count(&self) -> usize {
+        self.len()
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        self.rear.bytes_per_element()
+    }
+}
+
+impl<T: Clone> FromIterator<T> for Queue<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(Queue::new(), |queue, elem| queue.snoc(elem))
+    }
+}
+
+// Oldest to newest: the front as it is, then the rear backwards
+pub struct Iter<'a, T> {
+    front: third::Iter<'a, T>,
+    rear: Vec<&'a T>,
+}
+
+// Iterating needs the whole front, so it forces it
+impl<T: Clone> Queue<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.front.force().iter(),
+            rear: self.rear.iter().collect(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.rear.pop())
+    }
+}
+
+// Same encoding as the other lists: a sequence, oldest first. Versions that
+// share nodes serialize them once each, and don't share them anymore once
+// they're deserialized.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::de::SeqAccess;
+    use serde::de::Visitor;
+    use serde::ser::SerializeSeq;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    use super::Queue;
+
+    impl<T: Serialize + Clone> Serialize for Queue<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(Some(self.len()))?;
+            for elem in self.iter() {
+                seq.serialize_element(elem)?;
+            }
+            seq.end()
+        }
+    }
+
+    impl<'de, T: Deserialize<'de> + Clone> Deserialize<'de> for Queue<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_seq(QueueVisitor(PhantomData))
+        }
+    }
+
+    struct QueueVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de> + Clone> Visitor<'de> for QueueVisitor<T> {
+        type Value = Queue<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a sequence")
+        }
+
+        fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+            let mut queue = Queue::new();
+            while let Some(elem) = seq.next_element()? {
+                queue = queue.snoc(elem);
+            }
+            Ok(queue)
+        }
+    }
+}
+
+// Oldest first
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl {
+    use arbitrary::Arbitrary;
+    use arbitrary::Result;
+    use arbitrary::Unstructured;
+
+    use super::Queue;
+
+    impl<'a, T: Arbitrary<'a> + Clone> Arbitrary<'a> for Queue<T> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_iter()?.collect()
+        }
+
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_take_rest_iter()?.collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Queue;
+
+    #[test]
+    fn basics() {
+        let queue = Queue::new();
+        assert!(queue.is_empty());
+        assert_eq!(queue.front(), None);
+        assert!(queue.uncons().is_none());
+
+        let queue = queue.snoc(1).snoc(2).snoc(3);
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.front(), Some(&1));
+
+        let (elem, queue) = queue.uncons().unwrap();
+        assert_eq!(*elem, 1);
+        let queue = queue.snoc(4);
+        assert_eq!(queue.front(), Some(&2));
+
+        let queue = queue.tail().tail();
+        assert_eq!(queue.front(), Some(&4));
+        let queue = queue.tail();
+        assert!(queue.is_empty());
+
+        // Make sure empty tail works
+        assert!(queue.tail().is_empty());
+    }
+
+    // Every version stays as it was, whatever is done with the others
+    #[test]
+    fn persistence() {
+        let one: Queue<_> = (0..5).collect();
+        let two = one.snoc(5);
+        let three = one.tail().snoc(6);
+
+        assert!(one.iter().copied().eq(0..5));
+        assert!(two.iter().copied().eq(0..6));
+        assert!(three.iter().copied().eq([1, 2, 3, 4, 6]));
+    }
+
+    // Enough snocs and tails in a row to go through plenty of rotations
+    #[test]
+    fn fifo() {
+        let mut queue = Queue::new();
+        let mut next = 0;
+        for i in 0..1000 {
+            queue = queue.snoc(i);
+            if i % 3 == 0 {
+                let (elem, rest) = queue.uncons().unwrap();
+                assert_eq!(*elem, next);
+                next += 1;
+                queue = rest;
+            }
+        }
+        assert_eq!(queue.len(), 1000 - next);
+        assert!(queue.iter().copied().eq(next..1000));
+    }
+
+    // Popping from the same old version over and over, right where its
+    // front has to be computed: the rotation is paid for the first time,
+    // and every later pop reuses it
+    #[test]
+    fn rotation_is_shared() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        // Counts its clones, which is all rotating costs
+        struct Counted(Rc<Cell<usize>>);
+
+        impl Clone for Counted {
+            fn clone(&self) -> Self {
+                self.0.set(self.0.get() + 1);
+                Counted(self.0.clone())
+            }
+        }
+
+        // 1023 snocs end with a rotation of all of them, behind a prefix of
+        // the 511 that came before it
+        let clones = Rc::new(Cell::new(0));
+        let mut old = Queue::new();
+        for _ in 0..1023 {
+            old = old.snoc(Counted(clones.clone()));
+        }
+        for _ in 0..510 {
+            old = old.tail();
+        }
+        assert_eq!(old.len(), 513);
+
+        let before = clones.get();
+        assert_eq!(old.tail().len(), 512);
+        let rotation = clones.get() - before;
+        assert_eq!(rotation, 1023);
+        for _ in 0..100 {
+            assert_eq!(old.tail().len(), 512);
+        }
+        assert_eq!(clones.get(), before + rotation);
+    }
+
+    // third's Drop is iterative, and so is everything that walks the lists,
+    // forces a chain of tails, or drops one
+    #[test]
+    fn long() {
+        let queue: Queue<_> = (0..100_000).collect();
+        assert_eq!(queue.iter().count(), 100_000);
+
+        let mut rest = queue.tail();
+        for _ in 0..60_000 {
+            rest = rest.tail();
+        }
+        assert!(rest.iter().copied().eq(60_001..100_000));
+        drop(queue);
+        drop(rest);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde::de::value::Error;
+        use serde::de::value::SeqDeserializer;
+        use serde::Deserialize;
+        use serde_test::assert_ser_tokens;
+        use serde_test::Token;
+
+        let queue: Queue<_> = [1, 2, 3].into_iter().collect();
+        assert_ser_tokens(
+            &queue,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::I32(1),
+                Token::I32(2),
+                Token::I32(3),
+                Token::SeqEnd,
+            ],
+        );
+
+        let seq = SeqDeserializer::<_, Error>::new(vec![1, 2, 3].into_iter());
+        let queue = Queue::<i32>::deserialize(seq).unwrap();
+        assert_eq!(queue.len(), 3);
+        assert!(queue.iter().copied().eq([1, 2, 3]));
+    }
+}
@@ -9,7 +9,10 @@
 // access to the same list and we want that list to exist
 // until the last reference goes away
 
-use std::{rc::Rc, unimplemented};
+use alloc::rc::Rc;
+
+use crate::traits::counted_size;
+use crate::traits::MemSize;
 
 pub struct List<T> {
     head: Link<T>,
@@ -50,6 +53,34 @@ impl<T> List<T> {
     }
 }
 
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Another handle to the same nodes, no T: Clone needed. Nothing can change
+// them, so sharing is as good as copying.
+impl<T> Clone for List<T> {
+    fn clone(&self) -> Self {
+        List {
+            head: self.head.clone(),
+        }
+    }
+}
+
+// Every node is an Rc, so it carries two counts on top of the element and the
+// link. Lists sharing a tail each count it, even though it's only there once.
+impl<T> MemSize for List<T> {
+    fn node_count(&self) -> usize {
+        self.iter().count()
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        counted_size::<Node<T>>()
+    }
+}
+
 pub struct Iter<'a, T> {
     next: Option<&'a Node<T>>,
 }
@@ -86,6 +117,137 @@ impl<T> Drop for List<T> {
     }
 }
 
+// Same encoding as the other lists: a sequence, from the head on. Lists
+// that share a tail serialize it once each, and don't share it anymore once
+// they're deserialized.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::de::SeqAccess;
+    use serde::de::Visitor;
+    use serde::ser::SerializeSeq;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    use alloc::vec::Vec;
+
+    use super::List;
+
+    impl<T: Serialize> Serialize for List<T> {
+        // Our Iter doesn't know its length, which collect_seq would leave
+        // out, so we count the elements first
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(Some(self.iter().count()))?;
+            for elem in self.iter() {
+                seq.serialize_element(elem)?;
+            }
+            seq.end()
+        }
+    }
+
+    impl<'de, T: Deserialize<'de>> Deserialize<'de> for List<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_seq(ListVisitor(PhantomData))
+        }
+    }
+
+    struct ListVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for ListVisitor<T> {
+        type Value = List<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a sequence")
+        }
+
+        // We can only prepend, so the elements have to wait in a Vec until
+        // we get to the last one
+        fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+            let mut elems = Vec::new();
+            while let Some(elem) = seq.next_element()? {
+                elems.push(elem);
+            }
+            Ok(elems
+                .into_iter()
+                .rev()
+                .fold(List::new(), |list, elem| list.prepend(elem)))
+        }
+    }
+}
+
+// Same order as iter(), from the head on
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl {
+    use arbitrary::Arbitrary;
+    use arbitrary::Result;
+    use arbitrary::Unstructured;
+
+    use super::List;
+
+    impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for List<T> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let elems = u.arbitrary_iter()?.collect::<Result<Vec<T>>>()?;
+            Ok(elems
+                .into_iter()
+                .rev()
+                .fold(List::new(), |list, elem| list.prepend(elem)))
+        }
+
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+            let elems = u.arbitrary_take_rest_iter()?.collect::<Result<Vec<T>>>()?;
+            Ok(elems
+                .into_iter()
+                .rev()
+                .fold(List::new(), |list, elem| list.prepend(elem)))
+        }
+    }
+}
+
+// Rc's counts aren't atomic, so the nodes can't cross threads, but the
+// elements can: we collect references to them into a Vec for rayon to
+// split. Only the borrowed list can be iterated in parallel, as the nodes of
+// an owned one may be shared with other lists.
+#[cfg(feature = "rayon")]
+mod rayon_impl {
+    use alloc::vec::Vec;
+
+    use rayon::iter::FromParallelIterator;
+    use rayon::iter::IntoParallelIterator;
+    use rayon::iter::ParallelIterator;
+    use rayon::vec;
+
+    use super::List;
+
+    impl<'a, T: Sync> IntoParallelIterator for &'a List<T> {
+        type Iter = vec::IntoIter<&'a T>;
+        type Item = &'a T;
+
+        fn into_par_iter(self) -> Self::Iter {
+            self.iter().collect::<Vec<_>>().into_par_iter()
+        }
+    }
+
+    // We can only prepend, so the nodes are made on one thread, from the
+    // last element back
+    impl<T: Send> FromParallelIterator<T> for List<T> {
+        fn from_par_iter<I>(par_iter: I) -> Self
+        where
+            I: IntoParallelIterator<Item = T>,
+        {
+            par_iter
+                .into_par_iter()
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev()
+                .fold(List::new(), |list, elem| list.prepend(elem))
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::List;
@@ -121,4 +283,48 @@ mod test {
         assert_eq!(iter.next(), Some(&2));
         assert_eq!(iter.next(), Some(&1));
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde::de::value::Error;
+        use serde::de::value::SeqDeserializer;
+        use serde::Deserialize;
+        use serde_test::assert_ser_tokens;
+        use serde_test::Token;
+
+        let list = List::new().prepend(3).prepend(2).prepend(1);
+        assert_ser_tokens(
+            &list,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::I32(1),
+                Token::I32(2),
+                Token::I32(3),
+                Token::SeqEnd,
+            ],
+        );
+
+        let seq = SeqDeserializer::<_, Error>::new(vec![1, 2, 3].into_iter());
+        let list = List::<i32>::deserialize(seq).unwrap();
+        assert_eq!(list.head(), Some(&1));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), &[1, 2, 3]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn rayon() {
+        use rayon::iter::IndexedParallelIterator;
+        use rayon::iter::IntoParallelIterator;
+        use rayon::iter::IntoParallelRefIterator;
+        use rayon::iter::ParallelIterator;
+
+        let list: List<u64> = (0..10_000u64).into_par_iter().collect();
+        assert_eq!(list.head(), Some(&0));
+        assert!(list.iter().copied().eq(0..10_000));
+        let tail = list.tail();
+        assert_eq!(tail.par_iter().sum::<u64>(), (1..10_000).sum());
+        let found = list.par_iter().position_first(|x| *x == 5_000);
+        assert_eq!(found, Some(5_000));
+    }
 }
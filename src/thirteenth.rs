@@ -0,0 +1,658 @@
+// twelfth's skip list, for positions rather than keys: a sequence where
+// getting, inserting and removing at an index all take O(log n), instead of
+// walking the list up to it.
+//
+// The lanes are the same as twelfth's, but every link also knows how far it
+// goes: its width, the number of positions between the two nodes it joins.
+// Counting the head as position 0 and the node at index i as position i + 1,
+// a search for a position runs along a lane adding up widths for as long as
+// that doesn't overshoot, then drops a lane, exactly like twelfth's search
+// does with keys.
+//
+// The last link on each lane goes nowhere, but gets a width anyway: as if
+// there was one more node at the end, at position len + 1. That way
+// inserting or removing a node shifts every link going over it by one, the
+// last ones included, with no special case for them.
+//
+// Heights are random the same way as twelfth's, and as little random.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Debug;
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::ptr;
+use core::ptr::NonNull;
+
+use crate::levels::Levels;
+use crate::levels::MAX_HEIGHT;
+use crate::traits::MemSize;
+
+pub struct IndexedList<T> {
+    // The links out of the head, one per lane, lane 0 being the plain list.
+    // There are as many lanes as the tallest node is tall (or more, never
+    // less).
+    head: Vec<Lane<T>>,
+    len: usize,
+    // Where the heights of new nodes come from
+    levels: Levels,
+    // We own the nodes
+    _marker: PhantomData<Box<Node<T>>>,
+}
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Lane<T> {
+    next: Link<T>,
+    // How many positions ahead `next` is (or the end, if there's no next)
+    width: usize,
+}
+
+struct Node<T> {
+    elem: T,
+    // `height` of them, behind a raw pointer like twelfth's
+    lanes: *mut Lane<T>,
+    height: usize,
+}
+
+// Where a search stopped on one lane: the link it would follow next, and
+// the position of the node (or head) it's out of
+struct Stop<T> {
+    link: *mut Lane<T>,
+    position: usize,
+}
+
+pub struct Iter<'a, T> {
+    next: Link<T>,
+    len: usize,
+    _marker: PhantomData<&'a T>,
+}
+
+impl<T> IndexedList<T> {
+    pub fn new() -> Self {
+        IndexedList {
+            head: Vec::new(),
+            len: 0,
+            levels: Levels::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.node(index)
+            .map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.node(index)
+            .map(|node| unsafe { &mut (*node.as_ptr()).elem })
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        self.insert(self.len, elem);
+    }
+
+    // Panics if `index` is past the end, like Vec::insert
+    pub fn insert(&mut self, index: usize, elem: T) {
+        assert!(
+            index <= self.len,
+            "insertion index (is {index}) should be <= len (is {})",
+            self.len
+        );
+
+        // The head has to be tall enough before we take pointers into it.
+        // New lanes start out empty, so they go all the way to the end.
+        let height = self.levels.next_height();
+        while self.head.len() < height {
+            self.head.push(Lane {
+                next: None,
+                width: self.len + 1,
+            });
+        }
+
+        let lanes: Box<[Lane<T>]> = (0..height).map(|_| Lane::empty()).collect();
+        let node = Box::into_raw(Box::new(Node {
+            elem,
+            lanes: Box::into_raw(lanes) as *mut Lane<T>,
+            height,
+        }));
+        let position = index + 1;
+
+        for (lane, stop) in self.stops(index).into_iter().enumerate() {
+            unsafe {
+                if lane < height {
+                    // We split the link in two, around the node
+                    let before = position - stop.position;
+                    let ours = (*node).lanes.add(lane);
+                    (*ours).next = (*stop.link).next;
+                    (*ours).width = (*stop.link).width - before + 1;
+                    (*stop.link).next = Some(NonNull::new_unchecked(node));
+                    (*stop.link).width = before;
+                } else {
+                    // The link goes over the node
+                    (*stop.link).width += 1;
+                }
+            }
+        }
+        self.len += 1;
+        self.strict_check();
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+
+        let stops = self.stops(index);
+        let node = unsafe { (*stops[0].link).next.unwrap_unchecked() };
+        for (lane, stop) in stops.into_iter().enumerate() {
+            unsafe {
+                if lane < (*node.as_ptr()).height {
+                    // The link takes over the node's own
+                    let ours = (*node.as_ptr()).lanes.add(lane);
+                    (*stop.link).next = (*ours).next;
+                    (*stop.link).width += (*ours).width - 1;
+                } else {
+                    (*stop.link).width -= 1;
+                }
+            }
+        }
+        self.trim();
+        self.len -= 1;
+        self.strict_check();
+        Some(unsafe { Box::from_raw(node.as_ptr()) }.into_elem())
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.first().and_then(|lane| lane.next),
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    // The node at `index`, if there is one
+    fn node(&self, index: usize) -> Option<NonNull<Node<T>>> {
+        if index >= self.len {
+            return None;
+        }
+
+        // The last stop before the node's position, on lane 0, is right
+        // before it
+        let target = index + 1;
+        let mut lanes = self.head.as_ptr();
+        let mut position = 0;
+        for lane in (0..self.head.len()).rev() {
+            unsafe {
+                let mut link = lanes.add(lane);
+                while (*link).next.is_some() && position + (*link).width < target {
+                    position += (*link).width;
+                    lanes = (*(*link).next.unwrap_unchecked().as_ptr()).lanes;
+                    link = lanes.add(lane);
+                }
+            }
+        }
+        unsafe { (*lanes).next }
+    }
+
+    // Where a search for the node right before `index` (or the head) stops
+    // on each lane, lane 0 first: the links that go to or over `index`, which
+    // are the ones inserting or removing there changes
+    fn stops(&mut self, index: usize) -> Vec<Stop<T>> {
+        let mut stops = Vec::with_capacity(self.head.len());
+        let mut lanes = self.head.as_mut_ptr();
+        let mut position = 0;
+        for lane in (0..self.head.len()).rev() {
+            unsafe {
+                let mut link = lanes.add(lane);
+                while (*link).next.is_some() && position + (*link).width <= index {
+                    position += (*link).width;
+                    lanes = (*(*link).next.unwrap_unchecked().as_ptr()).lanes;
+                    link = lanes.add(lane);
+                }
+                stops.push(Stop { link, position });
+            }
+        }
+        stops.reverse();
+        stops
+    }
+
+    // Drops the lanes nobody's on anymore
+    fn trim(&mut self) {
+        while let Some(Lane { next: None, .. }) = self.head.last() {
+            self.head.pop();
+        }
+    }
+
+    // Walks every lane checking that it has exactly the nodes of lane 0 that
+    // are tall enough for it, in the same order, that every link's width is
+    // how far it really goes (so each lane's add up to len + 1), and that the
+    // head is as tall as the tallest node, panicking otherwise.
+    pub fn assert_invariants(&self) {
+        assert!(self.head.len() <= MAX_HEIGHT, "the head is too tall");
+        assert!(
+            self.head.last().map_or(true, |lane| lane.next.is_some()),
+            "the head has an empty top lane"
+        );
+
+        // Lane 0 has every node
+        let mut nodes: Vec<NonNull<Node<T>>> = Vec::new();
+        let mut next = self.head.first().and_then(|lane| lane.next);
+        while let Some(node) = next {
+            assert!(
+                nodes.len() < self.len,
+                "len doesn't match the number of nodes"
+            );
+            let current = unsafe { node.as_ref() };
+            assert!(
+                (1..=self.head.len()).contains(&current.height),
+                "a node's height doesn't fit in the head"
+            );
+            nodes.push(node);
+            next = unsafe { (*current.lanes).next };
+        }
+        assert_eq!(
+            nodes.len(),
+            self.len,
+            "len doesn't match the number of nodes"
+        );
+
+        for lane in 0..self.head.len() {
+            let mut link: *const Lane<T> = &self.head[lane];
+            let mut position = 0;
+            let mut total = 0;
+            for (at, &node) in nodes.iter().enumerate() {
+                let current = unsafe { node.as_ref() };
+                if current.height <= lane {
+                    continue;
+                }
+                let Lane { next, width } = unsafe { &*link };
+                assert!(*next == Some(node), "lane {lane} doesn't match lane 0");
+                assert_eq!(
+                    *width,
+                    at + 1 - position,
+                    "a link on lane {lane} has the wrong width"
+                );
+                total += width;
+                position = at + 1;
+                link = unsafe { current.lanes.add(lane) };
+            }
+            let last = unsafe { &*link };
+            assert!(last.next.is_none(), "lane {lane} doesn't match lane 0");
+            assert_eq!(
+                total + last.width,
+                self.len + 1,
+                "the widths on lane {lane} don't add up to len + 1"
+            );
+        }
+    }
+
+    // Run after every insert and remove
+    #[inline]
+    fn strict_check(&self) {
+        #[cfg(feature = "strict-checks")]
+        if cfg!(debug_assertions) {
+            self.assert_invariants();
+        }
+    }
+}
+
+impl<T> Lane<T> {
+    fn empty() -> Self {
+        Lane {
+            next: None,
+            width: 0,
+        }
+    }
+}
+
+impl<T> Node<T> {
+    // Frees the lanes, keeping the element
+    fn into_elem(self) -> T {
+        unsafe {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+                self.lanes,
+                self.height,
+            )))
+        };
+        self.elem
+    }
+}
+
+impl<T> Drop for IndexedList<T> {
+    fn drop(&mut self) {
+        let mut next = self.head.first().and_then(|lane| lane.next);
+        while let Some(node) = next {
+            let node = *unsafe { Box::from_raw(node.as_ptr()) };
+            next = unsafe { (*node.lanes).next };
+            node.into_elem();
+        }
+    }
+}
+
+// Two allocations per node: the node, and its lanes with a width each.
+// bytes_per_element charges an element for two lanes, the average height,
+// while heap_bytes adds up the lanes each node really has, plus the head's.
+impl<T> MemSize for IndexedList<T> {
+    fn heap_bytes(&self) -> usize {
+        let mut lanes = self.head.capacity();
+        let mut next = self.head.first().and_then(|lane| lane.next);
+        while let Some(node) = next {
+            let node = unsafe { &*node.as_ptr() };
+            lanes += node.height;
+            next = unsafe { (*node.lanes).next };
+        }
+        self.len * size_of::<Node<T>>() + lanes * size_of::<Lane<T>>()
+    }
+
+    fn node_count(&self) -> usize {
+        self.len
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        size_of::<Node<T>>() + 2 * size_of::<Lane<T>>()
+    }
+}
+
+impl<T> Default for IndexedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            let node = unsafe { &*node.as_ptr() };
+            self.next = unsafe { (*node.lanes).next };
+            self.len -= 1;
+            &node.elem
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> IntoIterator for &'a IndexedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Extend<T> for IndexedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for IndexedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = IndexedList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Debug> Debug for IndexedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+// Opting back into Send and Sync, which NonNull opts out of, the same way a
+// Vec<T> would. Iter is a &T, so it needs T: Sync.
+unsafe impl<T: Send> Send for IndexedList<T> {}
+unsafe impl<T: Sync> Sync for IndexedList<T> {}
+
+unsafe impl<'a, T: Sync> Send for Iter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for Iter<'a, T> {}
+
+// A plain sequence, front to back, like the other lists. The widths and
+// heights aren't part of it: they're rebuilt as the elements are pushed.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::de::SeqAccess;
+    use serde::de::Visitor;
+    use serde::ser::SerializeSeq;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    use super::IndexedList;
+
+    impl<T: Serialize> Serialize for IndexedList<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(Some(self.len()))?;
+            for elem in self.iter() {
+                seq.serialize_element(elem)?;
+            }
+            seq.end()
+        }
+    }
+
+    impl<'de, T: Deserialize<'de>> Deserialize<'de> for IndexedList<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_seq(IndexedListVisitor(PhantomData))
+        }
+    }
+
+    struct IndexedListVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for IndexedListVisitor<T> {
+        type Value = IndexedList<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a sequence")
+        }
+
+        fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+            let mut list = IndexedList::new();
+            while let Some(elem) = seq.next_element()? {
+                list.push_back(elem);
+            }
+            Ok(list)
+        }
+    }
+}
+
+// From front to back
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl {
+    use arbitrary::Arbitrary;
+    use arbitrary::Result;
+    use arbitrary::Unstructured;
+
+    use super::IndexedList;
+
+    impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for IndexedList<T> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_iter()?.collect()
+        }
+
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_take_rest_iter()?.collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::StdRng;
+    use rand::Rng;
+    use rand::SeedableRng;
+
+    use super::IndexedList;
+
+    #[test]
+    fn basics() {
+        let mut list = IndexedList::new();
+        assert!(list.is_empty());
+        assert_eq!(list.get(0), None);
+        assert_eq!(list.remove(0), None);
+
+        list.push_back(1);
+        list.push_back(3);
+        list.insert(1, 2);
+        list.insert(0, 0);
+        assert_eq!(list.len(), 4);
+        assert!(list.iter().copied().eq(0..4));
+        list.assert_invariants();
+
+        *list.get_mut(2).unwrap() = 20;
+        assert_eq!(list.get(2), Some(&20));
+        assert_eq!(list.remove(2), Some(20));
+        assert_eq!(list.remove(0), Some(0));
+        assert_eq!(format!("{list:?}"), "[1, 3]");
+        list.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "insertion index (is 2) should be <= len (is 1)")]
+    fn insert_past_the_end() {
+        let mut list = IndexedList::new();
+        list.push_back(1);
+        list.insert(2, 2);
+    }
+
+    #[test]
+    fn get() {
+        let list: IndexedList<_> = (0..1000).collect();
+        for i in 0..1000 {
+            assert_eq!(list.get(i), Some(&i));
+        }
+        assert_eq!(list.get(1000), None);
+        assert_eq!(list.iter().len(), 1000);
+    }
+
+    // Random inserts and removes against a Vec
+    #[test]
+    fn model() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut list = IndexedList::new();
+        let mut model = Vec::new();
+        let steps = if cfg!(miri) { 200 } else { 3000 };
+        for i in 0..steps {
+            if model.is_empty() || rng.random_bool(0.6) {
+                let at = rng.random_range(0..=model.len());
+                list.insert(at, i);
+                model.insert(at, i);
+            } else {
+                let at = rng.random_range(0..model.len());
+                assert_eq!(list.remove(at), Some(model.remove(at)));
+            }
+            if i % 50 == 0 {
+                list.assert_invariants();
+            }
+            if let Some(at) = (!model.is_empty()).then(|| rng.random_range(0..model.len())) {
+                assert_eq!(list.get(at), model.get(at));
+            }
+        }
+        list.assert_invariants();
+        assert!(list.iter().eq(model.iter()));
+    }
+
+    // Whatever's left goes with the list, lanes and all
+    #[test]
+    fn drop() {
+        let mut list: IndexedList<_> = (0..100).map(Box::new).collect();
+        for i in 0..50 {
+            list.remove(i);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the widths on lane 0 don't add up to len + 1")]
+    fn assert_invariants_widths() {
+        let mut list: IndexedList<_> = (0..10).collect();
+        list.assert_invariants();
+        // The last link on lane 0, which goes to the end
+        let mut link = &mut list.head[0];
+        while let Some(node) = link.next {
+            link = unsafe { &mut *(*node.as_ptr()).lanes };
+        }
+        link.width += 1;
+        list.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "len doesn't match the number of nodes")]
+    fn assert_invariants_len() {
+        let mut list: IndexedList<_> = (0..10).collect();
+        list.len -= 1;
+        list.assert_invariants();
+    }
+
+    #[test]
+    fn mem_size() {
+        use std::mem::size_of;
+
+        use super::Lane;
+        use super::Node;
+        use crate::traits::MemSize;
+
+        let mut list = IndexedList::new();
+        assert_eq!(list.heap_bytes(), 0);
+
+        let lane = size_of::<Lane<u8>>();
+        let node = size_of::<Node<u8>>();
+        list.push_back(1u8);
+        let height = list.head.len();
+        assert_eq!(list.node_count(), 1);
+        assert_eq!(list.bytes_per_element(), node + 2 * lane);
+        assert_eq!(
+            list.heap_bytes(),
+            node + (height + list.head.capacity()) * lane
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde::de::value::Error;
+        use serde::de::value::SeqDeserializer;
+        use serde::Deserialize;
+        use serde_test::assert_ser_tokens;
+        use serde_test::Token;
+
+        let list: IndexedList<_> = [1, 2, 3].into_iter().collect();
+        assert_ser_tokens(
+            &list,
+            &[
+                Token::Seq { len: Some(3) },
+                Token::I32(1),
+                Token::I32(2),
+                Token::I32(3),
+                Token::SeqEnd,
+            ],
+        );
+
+        let seq = SeqDeserializer::<_, Error>::new(vec![1, 2, 3].into_iter());
+        let list = IndexedList::<i32>::deserialize(seq).unwrap();
+        assert_eq!(list.len(), 3);
+        assert!(list.iter().copied().eq([1, 2, 3]));
+    }
+}
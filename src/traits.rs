@@ -0,0 +1,921 @@
+// Each chapter's list has its own API, which is fine for learning but means
+// that code comparing them (tests, benchmarks) has to be written once per
+// list. These traits capture what they have in common, so that code can be
+// written once against any list that fits.
+//
+// Peeking gives back whatever the list can lend out: a plain reference for
+// most lists, but fourth can only hand out `Ref`s to its RefCells, hence the
+// `Ref` associated types. For the same reason fourth can't be Iterable.
+
+#[cfg(any(
+    feature = "third",
+    feature = "fourth",
+    feature = "eleventh",
+    all(feature = "stacklist", feature = "alloc")
+))]
+use core::alloc::Layout;
+#[cfg(feature = "fourth")]
+use core::cell::Ref;
+use core::ops::Deref;
+
+#[cfg(any(feature = "fifth", feature = "sixth"))]
+use crate::allocator::Allocator;
+#[cfg(feature = "eleventh")]
+use crate::eleventh;
+#[cfg(feature = "fifteenth")]
+use crate::fifteenth;
+#[cfg(feature = "fifth")]
+use crate::fifth;
+#[cfg(feature = "fourteenth")]
+use crate::fourteenth;
+#[cfg(feature = "fourth")]
+use crate::fourth;
+#[cfg(feature = "second")]
+use crate::second;
+#[cfg(feature = "sixth")]
+use crate::sixth;
+#[cfg(feature = "tenth")]
+use crate::tenth;
+#[cfg(feature = "third")]
+use crate::third;
+
+// Lists whose elements can be borrowed one after the other, front to back
+pub trait Iterable<T> {
+    type Iter<'a>: Iterator<Item = &'a T>
+    where
+        Self: 'a,
+        T: 'a;
+
+    fn iter(&self) -> Self::Iter<'_>;
+}
+
+// Last in, first out
+pub trait StackOps<T> {
+    type Ref<'a>: Deref<Target = T>
+    where
+        Self: 'a;
+
+    fn push(&mut self, elem: T);
+    fn pop(&mut self) -> Option<T>;
+    // The element pop would return
+    fn peek(&self) -> Option<Self::Ref<'_>>;
+
+    fn is_empty(&self) -> bool {
+        self.peek().is_none()
+    }
+}
+
+// First in, first out
+pub trait QueueOps<T> {
+    type Ref<'a>: Deref<Target = T>
+    where
+        Self: 'a;
+
+    fn enqueue(&mut self, elem: T);
+    fn dequeue(&mut self) -> Option<T>;
+    // The element dequeue would return
+    fn peek(&self) -> Option<Self::Ref<'_>>;
+
+    fn is_empty(&self) -> bool {
+        self.peek().is_none()
+    }
+}
+
+// Pushes and pops at both ends
+pub trait DequeOps<T> {
+    type Ref<'a>: Deref<Target = T>
+    where
+        Self: 'a;
+
+    fn push_front(&mut self, elem: T);
+    fn push_back(&mut self, elem: T);
+    fn pop_front(&mut self) -> Option<T>;
+    fn pop_back(&mut self) -> Option<T>;
+    fn peek_front(&self) -> Option<Self::Ref<'_>>;
+    fn peek_back(&self) -> Option<Self::Ref<'_>>;
+
+    fn is_empty(&self) -> bool {
+        self.peek_front().is_none()
+    }
+}
+
+// Lists that can hand out cursors, to walk them back and forth and (with a
+// mutable one) change them in place wherever the cursor is
+pub trait CursorOps<T> {
+    type Cursor<'a>: Cursor<T>
+    where
+        Self: 'a;
+    type CursorMut<'a>: CursorMut<T>
+    where
+        Self: 'a;
+
+    fn cursor_front(&self) -> Self::Cursor<'_>;
+    fn cursor_back(&self) -> Self::Cursor<'_>;
+    fn cursor_front_mut(&mut self) -> Self::CursorMut<'_>;
+    fn cursor_back_mut(&mut self) -> Self::CursorMut<'_>;
+}
+
+// A position in a list, which is either on an element or on the "ghost"
+// between the back and the front. Moving past either end lands on the ghost,
+// and moving off the ghost goes to the other end, so a cursor can go round
+// and round.
+pub trait Cursor<T> {
+    // Where the cursor is, None on the ghost
+    fn index(&self) -> Option<usize>;
+    fn move_next(&mut self);
+    fn move_prev(&mut self);
+    fn current(&self) -> Option<&T>;
+    fn peek_next(&self) -> Option<&T>;
+    fn peek_prev(&self) -> Option<&T>;
+}
+
+// Same as Cursor, plus changing the list where the cursor is
+pub trait CursorMut<T> {
+    fn index(&self) -> Option<usize>;
+    fn move_next(&mut self);
+    fn move_prev(&mut self);
+    fn current(&mut self) -> Option<&mut T>;
+    fn peek_next(&mut self) -> Option<&mut T>;
+    fn peek_prev(&mut self) -> Option<&mut T>;
+    // On the ghost, before means at the back and after at the front
+    fn insert_before(&mut self, elem: T);
+    fn insert_after(&mut self, elem: T);
+    // Moves to the next element (or the ghost). Does nothing on the ghost.
+    fn remove_current(&mut self) -> Option<T>;
+}
+
+// What a list costs in heap memory, to compare how much each kind of node
+// (Box, Rc, raw pointers) adds on top of the elements themselves. Only the
+// list's own allocations count: anything the elements own on the heap is
+// theirs. Sizes are the ones we ask the allocator for, which may round up.
+//
+// Lists implement it next to their node types, which it needs to see.
+pub trait MemSize {
+    // Bytes of heap memory the list has allocated
+    fn heap_bytes(&self) -> usize {
+        self.node_count() * self.bytes_per_element()
+    }
+
+    // Nodes the list has allocated, whether or not they hold an element
+    fn node_count(&self) -> usize;
+
+    // Bytes each element takes on the heap: its node, with the element, the
+    // links and whatever else the list keeps next to it
+    fn bytes_per_element(&self) -> usize;
+}
+
+// The size of what Rc::new and Arc::new allocate for a `T`: the value, after
+// the strong and weak counts
+#[cfg(any(
+    feature = "third",
+    feature = "fourth",
+    feature = "eleventh",
+    all(feature = "stacklist", feature = "alloc")
+))]
+pub(crate) fn counted_size<T>() -> usize {
+    let counts = Layout::new::<[usize; 2]>();
+    let (layout, _) = counts.extend(Layout::new::<T>()).unwrap();
+    layout.pad_to_align().size()
+}
+
+// The implementations only forward to each list's own methods. The ones with
+// the same name as the trait method still call the inherent method, as
+// those take precedence.
+
+#[cfg(feature = "second")]
+impl<T> Iterable<T> for second::List<T> {
+    type Iter<'a>
+        = second::Iter<'a, T>
+    where
+        T: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "second")]
+impl<T> StackOps<T> for second::List<T> {
+    type Ref<'a>
+        = &'a T
+    where
+        T: 'a;
+
+    fn push(&mut self, elem: T) {
+        self.push(elem)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.peek()
+    }
+}
+
+// Persistent lists can't be changed in place, so they're only Iterable
+#[cfg(feature = "third")]
+impl<T> Iterable<T> for third::List<T> {
+    type Iter<'a>
+        = third::Iter<'a, T>
+    where
+        T: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "eleventh")]
+impl<T> Iterable<T> for eleventh::Deque<T> {
+    type Iter<'a>
+        = eleventh::Iter<'a, T>
+    where
+        T: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "tenth")]
+impl<T: Clone> Iterable<T> for tenth::Queue<T> {
+    type Iter<'a>
+        = tenth::Iter<'a, T>
+    where
+        T: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "fourth")]
+impl<T> DequeOps<T> for fourth::List<T> {
+    type Ref<'a>
+        = Ref<'a, T>
+    where
+        T: 'a;
+
+    fn push_front(&mut self, elem: T) {
+        self.push_front(elem)
+    }
+
+    fn push_back(&mut self, elem: T) {
+        self.push_back(elem)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        self.pop_back()
+    }
+
+    fn peek_front(&self) -> Option<Ref<'_, T>> {
+        self.peek_front()
+    }
+
+    fn peek_back(&self) -> Option<Ref<'_, T>> {
+        self.peek_back()
+    }
+}
+
+#[cfg(feature = "fifth")]
+impl<T, A: Allocator> Iterable<T> for fifth::List<T, A> {
+    type Iter<'a>
+        = fifth::Iter<'a, T>
+    where
+        Self: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "fifth")]
+impl<T, A: Allocator> QueueOps<T> for fifth::List<T, A> {
+    type Ref<'a>
+        = &'a T
+    where
+        Self: 'a;
+
+    fn enqueue(&mut self, elem: T) {
+        self.push(elem)
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.peek()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+#[cfg(feature = "sixth")]
+impl<T, A: Allocator> Iterable<T> for sixth::LinkedList<T, A> {
+    type Iter<'a>
+        = sixth::Iter<'a, T>
+    where
+        Self: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+// A deque works as a stack at its back, and as a queue from back to front
+#[cfg(feature = "sixth")]
+impl<T, A: Allocator> StackOps<T> for sixth::LinkedList<T, A> {
+    type Ref<'a>
+        = &'a T
+    where
+        Self: 'a;
+
+    fn push(&mut self, elem: T) {
+        self.push_back(elem)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.pop_back()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.back()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+#[cfg(feature = "sixth")]
+impl<T, A: Allocator> QueueOps<T> for sixth::LinkedList<T, A> {
+    type Ref<'a>
+        = &'a T
+    where
+        Self: 'a;
+
+    fn enqueue(&mut self, elem: T) {
+        self.push_back(elem)
+    }
+
+    fn dequeue(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+
+    fn peek(&self) -> Option<&T> {
+        self.front()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+#[cfg(feature = "sixth")]
+impl<T, A: Allocator> DequeOps<T> for sixth::LinkedList<T, A> {
+    type Ref<'a>
+        = &'a T
+    where
+        Self: 'a;
+
+    fn push_front(&mut self, elem: T) {
+        self.push_front(elem)
+    }
+
+    fn push_back(&mut self, elem: T) {
+        self.push_back(elem)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        self.pop_back()
+    }
+
+    fn peek_front(&self) -> Option<&T> {
+        self.front()
+    }
+
+    fn peek_back(&self) -> Option<&T> {
+        self.back()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+#[cfg(feature = "sixth")]
+impl<T, A: Allocator> CursorOps<T> for sixth::LinkedList<T, A> {
+    type Cursor<'a>
+        = sixth::Cursor<'a, T, A>
+    where
+        Self: 'a;
+    type CursorMut<'a>
+        = sixth::CursorMut<'a, T, A>
+    where
+        Self: 'a;
+
+    fn cursor_front(&self) -> Self::Cursor<'_> {
+        self.cursor_front()
+    }
+
+    fn cursor_back(&self) -> Self::Cursor<'_> {
+        self.cursor_back()
+    }
+
+    fn cursor_front_mut(&mut self) -> Self::CursorMut<'_> {
+        self.cursor_front_mut()
+    }
+
+    fn cursor_back_mut(&mut self) -> Self::CursorMut<'_> {
+        self.cursor_back_mut()
+    }
+}
+
+#[cfg(feature = "sixth")]
+impl<'a, T, A: Allocator> Cursor<T> for sixth::Cursor<'a, T, A> {
+    fn index(&self) -> Option<usize> {
+        self.index()
+    }
+
+    fn move_next(&mut self) {
+        self.move_next()
+    }
+
+    fn move_prev(&mut self) {
+        self.move_prev()
+    }
+
+    fn current(&self) -> Option<&T> {
+        self.current()
+    }
+
+    fn peek_next(&self) -> Option<&T> {
+        self.peek_next()
+    }
+
+    fn peek_prev(&self) -> Option<&T> {
+        self.peek_prev()
+    }
+}
+
+#[cfg(feature = "sixth")]
+impl<'a, T, A: Allocator> CursorMut<T> for sixth::CursorMut<'a, T, A> {
+    fn index(&self) -> Option<usize> {
+        self.index()
+    }
+
+    fn move_next(&mut self) {
+        self.move_next()
+    }
+
+    fn move_prev(&mut self) {
+        self.move_prev()
+    }
+
+    fn current(&mut self) -> Option<&mut T> {
+        self.current()
+    }
+
+    fn peek_next(&mut self) -> Option<&mut T> {
+        self.peek_next()
+    }
+
+    fn peek_prev(&mut self) -> Option<&mut T> {
+        self.peek_prev()
+    }
+
+    fn insert_before(&mut self, elem: T) {
+        self.insert_before(elem)
+    }
+
+    fn insert_after(&mut self, elem: T) {
+        self.insert_after(elem)
+    }
+
+    fn remove_current(&mut self) -> Option<T> {
+        self.remove_current()
+    }
+}
+
+#[cfg(feature = "fourteenth")]
+impl<T> Iterable<T> for fourteenth::LinkedList<T> {
+    type Iter<'a>
+        = fourteenth::Iter<'a, T>
+    where
+        T: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "fourteenth")]
+impl<T> DequeOps<T> for fourteenth::LinkedList<T> {
+    type Ref<'a>
+        = &'a T
+    where
+        T: 'a;
+
+    fn push_front(&mut self, elem: T) {
+        self.push_front(elem)
+    }
+
+    fn push_back(&mut self, elem: T) {
+        self.push_back(elem)
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        self.pop_back()
+    }
+
+    fn peek_front(&self) -> Option<&T> {
+        self.front()
+    }
+
+    fn peek_back(&self) -> Option<&T> {
+        self.back()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+#[cfg(feature = "fourteenth")]
+impl<T> CursorOps<T> for fourteenth::LinkedList<T> {
+    type Cursor<'a>
+        = fourteenth::Cursor<'a, T>
+    where
+        T: 'a;
+    type CursorMut<'a>
+        = fourteenth::CursorMut<'a, T>
+    where
+        T: 'a;
+
+    fn cursor_front(&self) -> Self::Cursor<'_> {
+        self.cursor_front()
+    }
+
+    fn cursor_back(&self) -> Self::Cursor<'_> {
+        self.cursor_back()
+    }
+
+    fn cursor_front_mut(&mut self) -> Self::CursorMut<'_> {
+        self.cursor_front_mut()
+    }
+
+    fn cursor_back_mut(&mut self) -> Self::CursorMut<'_> {
+        self.cursor_back_mut()
+    }
+}
+
+#[cfg(feature = "fourteenth")]
+impl<'a, T> Cursor<T> for fourteenth::Cursor<'a, T> {
+    fn index(&self) -> Option<usize> {
+        self.index()
+    }
+
+    fn move_next(&mut self) {
+        self.move_next()
+    }
+
+    fn move_prev(&mut self) {
+        self.move_prev()
+    }
+
+    fn current(&self) -> Option<&T> {
+        self.current()
+    }
+
+    fn peek_next(&self) -> Option<&T> {
+        self.peek_next()
+    }
+
+    fn peek_prev(&self) -> Option<&T> {
+        self.peek_prev()
+    }
+}
+
+#[cfg(feature = "fourteenth")]
+impl<'a, T> CursorMut<T> for fourteenth::CursorMut<'a, T> {
+    fn index(&self) -> Option<usize> {
+        self.index()
+    }
+
+    fn move_next(&mut self) {
+        self.move_next()
+    }
+
+    fn move_prev(&mut self) {
+        self.move_prev()
+    }
+
+    fn current(&mut self) -> Option<&mut T> {
+        self.current()
+    }
+
+    fn peek_next(&mut self) -> Option<&mut T> {
+        self.peek_next()
+    }
+
+    fn peek_prev(&mut self) -> Option<&mut T> {
+        self.peek_prev()
+    }
+
+    fn insert_before(&mut self, elem: T) {
+        self.insert_before(elem)
+    }
+
+    fn insert_after(&mut self, elem: T) {
+        self.insert_after(elem)
+    }
+
+    fn remove_current(&mut self) -> Option<T> {
+        self.remove_current()
+    }
+}
+
+#[cfg(feature = "fifteenth")]
+impl<T> Iterable<T> for fifteenth::LinkedList<T> {
+    type Iter<'a>
+        = fifteenth::Iter<'a, T>
+    where
+        T: 'a;
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+}
+
+// The handles the pushes return are dropped, so this is only a plain deque
+#[cfg(feature = "fifteenth")]
+impl<T> DequeOps<T> for fifteenth::LinkedList<T> {
+    type Ref<'a>
+        = &'a T
+    where
+        T: 'a;
+
+    fn push_front(&mut self, elem: T) {
+        self.push_front(elem);
+    }
+
+    fn push_back(&mut self, elem: T) {
+        self.push_back(elem);
+    }
+
+    fn pop_front(&mut self) -> Option<T> {
+        self.pop_front()
+    }
+
+    fn pop_back(&mut self) -> Option<T> {
+        self.pop_back()
+    }
+
+    fn peek_front(&self) -> Option<&T> {
+        self.front()
+    }
+
+    fn peek_back(&self) -> Option<&T> {
+        self.back()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+// Exercises every list, so it's only built when they all are
+#[cfg(all(
+    test,
+    feature = "second",
+    feature = "third",
+    feature = "fourth",
+    feature = "fifth",
+    feature = "sixth"
+))]
+mod test {
+    use super::CursorMut;
+    use super::CursorOps;
+    use super::DequeOps;
+    use super::Iterable;
+    use super::MemSize;
+    use super::QueueOps;
+    use super::StackOps;
+    use crate::fifth;
+    use crate::fourth;
+    use crate::second;
+    use crate::sixth;
+    use crate::third;
+
+    // Written once, run against every list that fits
+    fn stack(mut stack: impl StackOps<i32>) {
+        assert!(stack.is_empty());
+        stack.push(1);
+        stack.push(2);
+        assert_eq!(stack.peek().as_deref(), Some(&2));
+        assert_eq!(stack.pop(), Some(2));
+        stack.push(3);
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+        assert!(stack.is_empty());
+    }
+
+    fn queue(mut queue: impl QueueOps<i32>) {
+        assert!(queue.is_empty());
+        queue.enqueue(1);
+        queue.enqueue(2);
+        assert_eq!(queue.peek().as_deref(), Some(&1));
+        assert_eq!(queue.dequeue(), Some(1));
+        queue.enqueue(3);
+        assert_eq!(queue.dequeue(), Some(2));
+        assert_eq!(queue.dequeue(), Some(3));
+        assert_eq!(queue.dequeue(), None);
+        assert!(queue.is_empty());
+    }
+
+    fn deque(mut deque: impl DequeOps<i32>) {
+        assert!(deque.is_empty());
+        deque.push_back(2);
+        deque.push_front(1);
+        deque.push_back(3);
+        assert_eq!(deque.peek_front().as_deref(), Some(&1));
+        assert_eq!(deque.peek_back().as_deref(), Some(&3));
+        assert_eq!(deque.pop_back(), Some(3));
+        assert_eq!(deque.pop_front(), Some(1));
+        assert_eq!(deque.pop_front(), Some(2));
+        assert_eq!(deque.pop_back(), None);
+        assert!(deque.is_empty());
+    }
+
+    fn sum<L: Iterable<i32>>(list: &L) -> i32 {
+        list.iter().sum()
+    }
+
+    // Removes the elements matching `pred` among the `radius` ones on either
+    // side of the cursor (and the one under it), ending up past them
+    fn remove_near<C: CursorMut<i32>>(cursor: &mut C, radius: usize, pred: impl Fn(i32) -> bool) {
+        let mut before = 0;
+        while before < radius && cursor.peek_prev().is_some() {
+            cursor.move_prev();
+            before += 1;
+        }
+        for _ in 0..before + 1 + radius {
+            match cursor.current() {
+                Some(elem) if pred(*elem) => {
+                    cursor.remove_current();
+                }
+                Some(_) => cursor.move_next(),
+                None => break,
+            }
+        }
+    }
+
+    // Written once, against any list with cursors
+    fn cursors<L: CursorOps<i32>>(list: &mut L) {
+        use super::Cursor;
+
+        {
+            let mut cursor = list.cursor_front_mut();
+            for _ in 0..4 {
+                cursor.move_next();
+            }
+            remove_near(&mut cursor, 2, |x| x % 2 == 0);
+            assert_eq!(cursor.current(), Some(&mut 7));
+            cursor.insert_before(6);
+        }
+
+        let mut cursor = list.cursor_back();
+        assert_eq!(cursor.current(), Some(&9));
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.peek_next()), (None, Some(&0)));
+    }
+
+    #[test]
+    fn stacks() {
+        stack(second::List::new());
+        stack(sixth::LinkedList::new());
+    }
+
+    #[test]
+    fn queues() {
+        queue(fifth::List::new());
+        queue(sixth::LinkedList::new());
+    }
+
+    #[test]
+    fn deques() {
+        deque(fourth::List::new());
+        deque(sixth::LinkedList::new());
+        #[cfg(feature = "fourteenth")]
+        deque(crate::fourteenth::LinkedList::new());
+        #[cfg(feature = "fifteenth")]
+        deque(crate::fifteenth::LinkedList::new());
+    }
+
+    #[test]
+    fn cursor_lists() {
+        let mut list: sixth::LinkedList<_> = (0..10).collect();
+        cursors(&mut list);
+        assert!(list.iter().copied().eq([0, 1, 3, 5, 6, 7, 8, 9]));
+
+        #[cfg(feature = "fourteenth")]
+        {
+            let mut list: crate::fourteenth::LinkedList<_> = (0..10).collect();
+            cursors(&mut list);
+            assert!(list.iter().copied().eq([0, 1, 3, 5, 6, 7, 8, 9]));
+        }
+    }
+
+    #[test]
+    fn iterables() {
+        let mut second = second::List::new();
+        second.push(1);
+        second.push(2);
+        assert_eq!(sum(&second), 3);
+
+        let third = third::List::new().prepend(1).prepend(2);
+        assert_eq!(sum(&third), 3);
+
+        let mut fifth = fifth::List::new();
+        fifth.push(1);
+        fifth.push(2);
+        assert_eq!(sum(&fifth), 3);
+
+        let sixth: sixth::LinkedList<_> = [1, 2].into();
+        assert_eq!(sum(&sixth), 3);
+    }
+
+    #[test]
+    fn mem_size() {
+        use std::mem::size_of;
+
+        // Elements of a word each, so node sizes come out in whole words
+        let word = size_of::<usize>();
+        let elems = [1usize, 2, 3];
+
+        let second: second::List<_> = elems.into_iter().collect();
+        assert_eq!(second.node_count(), 3);
+        assert_eq!(second.bytes_per_element(), 2 * word);
+        assert_eq!(second.heap_bytes(), 6 * word);
+
+        // The counts, the element and the link
+        let third = third::List::new().prepend(3usize).prepend(2).prepend(1);
+        assert_eq!(third.bytes_per_element(), 4 * word);
+        assert_eq!(third.tail().node_count(), 2);
+
+        // The counts, the borrow flag, the element and two links
+        let mut fourth = fourth::List::new();
+        for elem in elems {
+            fourth.push_back(elem);
+        }
+        assert_eq!(fourth.node_count(), 3);
+        assert_eq!(fourth.bytes_per_element(), 6 * word);
+
+        // Like second's, plus the pointer to the block the nodes came from.
+        // The block has a header of two words, and stays whole until the
+        // last of its nodes goes.
+        let mut fifth = fifth::List::new();
+        fifth.extend_from_slice(&elems);
+        assert_eq!(fifth.bytes_per_element(), 3 * word);
+        assert_eq!(fifth.node_count(), 3);
+        assert_eq!(fifth.heap_bytes(), 11 * word);
+        fifth.pop();
+        fifth.shrink_to_fit();
+        assert_eq!(fifth.heap_bytes(), 11 * word);
+        fifth.push(4);
+        assert_eq!(fifth.node_count(), 4);
+        assert_eq!(fifth.heap_bytes(), 14 * word);
+
+        // The element, two links and the generation. Pooled nodes are there
+        // all the same.
+        let node = 3 * word + size_of::<u64>();
+        let mut sixth = sixth::LinkedList::from(elems);
+        assert_eq!(sixth.bytes_per_element(), node);
+        sixth.pop_back();
+        sixth.reserve(4);
+        assert_eq!(sixth.node_count(), 6);
+        sixth.shrink_to_fit();
+        assert_eq!(sixth.heap_bytes(), 2 * node);
+
+        let empty = second::List::<usize>::new();
+        assert_eq!(empty.heap_bytes(), 0);
+    }
+}
@@ -0,0 +1,734 @@
+// An ordered map as a linked list: a skip list. The entries are kept sorted
+// on a plain singly linked list, which alone would take O(n) to search. On
+// top of it, every node also links ahead on a random number of express
+// lanes: half the nodes on lane 1, a quarter on lane 2, and so on. Searching
+// runs along the highest lane until the next node would overshoot, then
+// drops a lane and carries on, which skips over most of the list and takes
+// O(log n) steps on average. Inserting and removing are a search, plus
+// relinking the node on each of its lanes.
+//
+// The nodes are as unsafe as fifth's and sixth's: raw pointers all the way,
+// owned by the map, and freed by hand.
+//
+// A node's height (how many lanes it's on) is all the randomness there is,
+// and it has nothing to do with the key. So there's no input that makes the
+// map degenerate more often than any other, and heights don't have to be
+// unpredictable, only spread out. A small generator per map does that
+// without needing std, and makes the shape of the map the same from run to
+// run.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+use core::fmt::Debug;
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::ops::Bound;
+use core::ops::RangeBounds;
+use core::ptr;
+use core::ptr::NonNull;
+
+use crate::levels::Levels;
+use crate::levels::MAX_HEIGHT;
+use crate::traits::MemSize;
+
+pub struct SkipListMap<K, V> {
+    // The first node on each lane, lane 0 being the plain list. There are as
+    // many lanes as the tallest node is tall (or more, never less).
+    head: Vec<Link<K, V>>,
+    len: usize,
+    // Where the heights of new nodes come from
+    levels: Levels,
+    // We own the nodes
+    _marker: PhantomData<Box<Node<K, V>>>,
+}
+
+type Link<K, V> = Option<NonNull<Node<K, V>>>;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // The next node on each of the node's lanes, `height` of them. A raw
+    // pointer rather than a Box, so that following them around never makes
+    // a reference that could invalidate the pointers we got before.
+    next: *mut Link<K, V>,
+    height: usize,
+}
+
+// Front to back, in key order
+pub struct Iter<'a, K, V> {
+    next: Link<K, V>,
+    // The first node not to yield, if the iteration stops before the end
+    end: Link<K, V>,
+    _marker: PhantomData<&'a (K, V)>,
+}
+
+impl<K, V> SkipListMap<K, V> {
+    pub fn new() -> Self {
+        SkipListMap {
+            head: Vec::new(),
+            len: 0,
+            levels: Levels::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            next: self.head.first().copied().flatten(),
+            end: None,
+            _marker: PhantomData,
+        }
+    }
+
+    // The first node whose key `before` is false for, `before` being true
+    // for a prefix of the list and false for the rest: the node a search
+    // lands on
+    fn find(&self, before: impl Fn(&K) -> bool) -> Link<K, V> {
+        let mut lanes = self.head.as_ptr();
+        let mut found = None;
+        for lane in (0..self.head.len()).rev() {
+            unsafe {
+                while let Some(node) = *lanes.add(lane) {
+                    if !before(&(*node.as_ptr()).key) {
+                        break;
+                    }
+                    lanes = (*node.as_ptr()).next;
+                }
+                found = *lanes.add(lane);
+            }
+        }
+        found
+    }
+
+    // Same as `find`, but gives back the link to the node found on every
+    // lane, as those are the ones to change to insert or remove a node there
+    fn links_to(&mut self, before: impl Fn(&K) -> bool) -> Vec<*mut Link<K, V>> {
+        let mut links = Vec::with_capacity(self.head.len());
+        let mut lanes = self.head.as_mut_ptr();
+        for lane in (0..self.head.len()).rev() {
+            unsafe {
+                while let Some(node) = *lanes.add(lane) {
+                    if !before(&(*node.as_ptr()).key) {
+                        break;
+                    }
+                    lanes = (*node.as_ptr()).next;
+                }
+                links.push(lanes.add(lane));
+            }
+        }
+        // Lane 0 first
+        links.reverse();
+        links
+    }
+
+    // Drops the lanes nobody's on anymore
+    fn trim(&mut self) {
+        while let Some(None) = self.head.last() {
+            self.head.pop();
+        }
+    }
+}
+
+impl<K: Ord, V> SkipListMap<K, V> {
+    // Gives back the old value if the key was there already, in which case
+    // the key stays the one from before
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        // The head has to be tall enough before we take pointers into it
+        let height = self.levels.next_height();
+        while self.head.len() < height {
+            self.head.push(None);
+        }
+
+        let links = self.links_to(|k| *k < key);
+        if let Some(node) = unsafe { *links[0] } {
+            let node = unsafe { &mut *node.as_ptr() };
+            if node.key == key {
+                self.trim();
+                self.strict_check();
+                return Some(core::mem::replace(&mut node.value, value));
+            }
+        }
+
+        let next: Box<[Link<K, V>]> = (0..height).map(|_| None).collect();
+        let node = Box::into_raw(Box::new(Node {
+            key,
+            value,
+            next: Box::into_raw(next) as *mut Link<K, V>,
+            height,
+        }));
+        // Now the lanes we made room for exist, and we hold no references
+        // into the head, only raw pointers
+        for (lane, link) in links.into_iter().take(height).enumerate() {
+            unsafe {
+                *(*node).next.add(lane) = *link;
+                *link = Some(NonNull::new_unchecked(node));
+            }
+        }
+        self.len += 1;
+        self.strict_check();
+        None
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(|k| k.borrow() < key)
+            .map(|node| unsafe { &*node.as_ptr() })
+            .filter(|node| node.key.borrow() == key)
+            .map(|node| &node.value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.find(|k| k.borrow() < key)
+            .map(|node| unsafe { &mut *node.as_ptr() })
+            .filter(|node| node.key.borrow() == key)
+            .map(|node| &mut node.value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let links = self.links_to(|k| k.borrow() < key);
+        // No lanes at all if the map is empty
+        let node = unsafe { **links.first()? }?;
+        if unsafe { (*node.as_ptr()).key.borrow() } != key {
+            return None;
+        }
+
+        // The node is found on all of its lanes, as nothing before it on any
+        // of them has a key that isn't smaller
+        let node = *unsafe { Box::from_raw(node.as_ptr()) };
+        for (lane, link) in links.into_iter().take(node.height).enumerate() {
+            unsafe { *link = *node.next.add(lane) };
+        }
+        self.trim();
+        self.len -= 1;
+        self.strict_check();
+        Some(node.into_entry())
+    }
+
+    // The entries whose keys are in `range`, in order. Like BTreeMap::range,
+    // panics if the range starts after it ends.
+    pub fn range<Q, R>(&self, range: R) -> Iter<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        match (range.start_bound(), range.end_bound()) {
+            (Bound::Excluded(start), Bound::Excluded(end)) if start == end => {
+                panic!("range start and end are equal and excluded")
+            }
+            (
+                Bound::Included(start) | Bound::Excluded(start),
+                Bound::Included(end) | Bound::Excluded(end),
+            ) if start > end => {
+                panic!("range start is greater than range end")
+            }
+            _ => {}
+        }
+
+        // Both ends are found from the head, so the iterator doesn't have to
+        // compare keys as it goes: the end is simply the first node past it
+        let next = match range.start_bound() {
+            Bound::Included(start) => self.find(|k| k.borrow() < start),
+            Bound::Excluded(start) => self.find(|k| k.borrow() <= start),
+            Bound::Unbounded => self.head.first().copied().flatten(),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.find(|k| k.borrow() <= end),
+            Bound::Excluded(end) => self.find(|k| k.borrow() < end),
+            Bound::Unbounded => None,
+        };
+        Iter {
+            next,
+            end,
+            _marker: PhantomData,
+        }
+    }
+
+    // Walks every lane checking that lane 0 is sorted and has `len` nodes,
+    // that every other lane has exactly the nodes of lane 0 that are tall
+    // enough for it, in the same order, and that the head is as tall as the
+    // tallest node, panicking otherwise.
+    pub fn assert_invariants(&self) {
+        assert!(self.head.len() <= MAX_HEIGHT, "the head is too tall");
+        assert!(
+            self.head.last() != Some(&None),
+            "the head has an empty top lane"
+        );
+
+        // Lane 0 has every node
+        let mut nodes: Vec<NonNull<Node<K, V>>> = Vec::new();
+        let mut next = self.head.first().copied().flatten();
+        while let Some(node) = next {
+            assert!(
+                nodes.len() < self.len,
+                "len doesn't match the number of nodes"
+            );
+            let current = unsafe { node.as_ref() };
+            assert!(
+                (1..=self.head.len()).contains(&current.height),
+                "a node's height doesn't fit in the head"
+            );
+            if let Some(last) = nodes.last() {
+                let last = unsafe { last.as_ref() };
+                assert!(last.key < current.key, "lane 0 isn't sorted");
+            }
+            nodes.push(node);
+            next = unsafe { *current.next };
+        }
+        assert_eq!(
+            nodes.len(),
+            self.len,
+            "len doesn't match the number of nodes"
+        );
+
+        for lane in 1..self.head.len() {
+            let mut next = self.head[lane];
+            for node in nodes.iter().map(|node| unsafe { node.as_ref() }) {
+                if node.height <= lane {
+                    continue;
+                }
+                assert!(
+                    next == Some(node.into()),
+                    "lane {lane} doesn't match lane 0"
+                );
+                next = unsafe { *node.next.add(lane) };
+            }
+            assert!(next.is_none(), "lane {lane} doesn't match lane 0");
+        }
+    }
+
+    // Run after every insert and remove
+    #[inline]
+    fn strict_check(&self) {
+        #[cfg(feature = "strict-checks")]
+        if cfg!(debug_assertions) {
+            self.assert_invariants();
+        }
+    }
+}
+
+impl<K, V> Node<K, V> {
+    // Frees the lanes, keeping the rest
+    fn into_entry(self) -> (K, V) {
+        unsafe {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+                self.next,
+                self.height,
+            )))
+        };
+        (self.key, self.value)
+    }
+}
+
+impl<K, V> Drop for SkipListMap<K, V> {
+    fn drop(&mut self) {
+        let mut next = self.head.first().copied().flatten();
+        while let Some(node) = next {
+            let node = *unsafe { Box::from_raw(node.as_ptr()) };
+            next = unsafe { *node.next };
+            node.into_entry();
+        }
+    }
+}
+
+// Each node is two allocations: the node itself, and its lanes. Nodes are on
+// two lanes on average, so that's what an entry costs, although heap_bytes
+// counts the lanes each node really has (and the head's).
+impl<K, V> MemSize for SkipListMap<K, V> {
+    fn heap_bytes(&self) -> usize {
+        let mut lanes = self.head.capacity();
+        let mut next = self.head.first().copied().flatten();
+        while let Some(node) = next {
+            let node = unsafe { &*node.as_ptr() };
+            lanes += node.height;
+            next = unsafe { *node.next };
+        }
+        self.len * size_of::<Node<K, V>>() + lanes * size_of::<Link<K, V>>()
+    }
+
+    fn node_count(&self) -> usize {
+        self.len
+    }
+
+    fn bytes_per_element(&self) -> usize {
+        size_of::<Node<K, V>>() + 2 * size_of::<Link<K, V>>()
+    }
+}
+
+impl<K, V> Default for SkipListMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next.filter(|&node| Some(node) != self.end)?;
+        let node = unsafe { &*node.as_ptr() };
+        self.next = unsafe { *node.next };
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SkipListMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for SkipListMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SkipListMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = SkipListMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Debug, V: Debug> Debug for SkipListMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self).finish()
+    }
+}
+
+// Opting back into Send and Sync, which NonNull opts out of, the same way a
+// Box<(K, V)> would. Iter is a &(K, V), so it needs both to be Sync.
+unsafe impl<K: Send, V: Send> Send for SkipListMap<K, V> {}
+unsafe impl<K: Sync, V: Sync> Sync for SkipListMap<K, V> {}
+
+unsafe impl<'a, K: Sync, V: Sync> Send for Iter<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for Iter<'a, K, V> {}
+
+// A map in key order, like std's BTreeMap. A key that comes up twice keeps
+// the last value, as `insert` would.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::de::MapAccess;
+    use serde::de::Visitor;
+    use serde::ser::SerializeMap;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    use super::SkipListMap;
+
+    impl<K: Serialize, V: Serialize> Serialize for SkipListMap<K, V> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(self.len))?;
+            for (key, value) in self.iter() {
+                map.serialize_entry(key, value)?;
+            }
+            map.end()
+        }
+    }
+
+    impl<'de, K: Deserialize<'de> + Ord, V: Deserialize<'de>> Deserialize<'de> for SkipListMap<K, V> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_map(SkipListMapVisitor(PhantomData))
+        }
+    }
+
+    struct SkipListMapVisitor<K, V>(PhantomData<(K, V)>);
+
+    impl<'de, K: Deserialize<'de> + Ord, V: Deserialize<'de>> Visitor<'de>
+        for SkipListMapVisitor<K, V>
+    {
+        type Value = SkipListMap<K, V>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a map")
+        }
+
+        fn visit_map<M: MapAccess<'de>>(self, mut access: M) -> Result<Self::Value, M::Error> {
+            let mut map = SkipListMap::new();
+            while let Some((key, value)) = access.next_entry()? {
+                map.insert(key, value);
+            }
+            Ok(map)
+        }
+    }
+}
+
+// Entries in whatever order they come, sorted by inserting them
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl {
+    use arbitrary::Arbitrary;
+    use arbitrary::Result;
+    use arbitrary::Unstructured;
+
+    use super::SkipListMap;
+
+    impl<'a, K: Arbitrary<'a> + Ord, V: Arbitrary<'a>> Arbitrary<'a> for SkipListMap<K, V> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_iter()?.collect()
+        }
+
+        fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+            u.arbitrary_take_rest_iter()?.collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::ops::Bound;
+
+    use rand::rngs::StdRng;
+    use rand::Rng;
+    use rand::SeedableRng;
+
+    use super::SkipListMap;
+
+    #[test]
+    fn basics() {
+        let mut map = SkipListMap::new();
+        assert!(map.is_empty());
+        assert_eq!(map.get(&1), None);
+        assert_eq!(map.remove(&1), None);
+
+        assert_eq!(map.insert(2, "two"), None);
+        assert_eq!(map.insert(1, "one"), None);
+        assert_eq!(map.insert(3, "three"), None);
+        assert_eq!(map.insert(2, "deux"), Some("two"));
+        assert_eq!(map.len(), 3);
+
+        assert_eq!(map.get(&2), Some(&"deux"));
+        *map.get_mut(&3).unwrap() = "trois";
+        assert!(map.iter().eq([(&1, &"one"), (&2, &"deux"), (&3, &"trois")]));
+
+        assert_eq!(map.remove(&2), Some("deux"));
+        assert_eq!(map.remove(&2), None);
+        assert!(!map.contains_key(&2));
+        assert_eq!(map.len(), 2);
+        assert_eq!(format!("{map:?}"), r#"{1: "one", 3: "trois"}"#);
+    }
+
+    // Looking up String keys with a &str, like the std maps
+    #[test]
+    fn borrow() {
+        let mut map: SkipListMap<String, usize> = ["b", "a", "c"]
+            .iter()
+            .map(|s| s.to_string())
+            .zip(0..)
+            .collect();
+        assert_eq!(map.get("a"), Some(&1));
+        assert_eq!(map.remove_entry("c"), Some(("c".to_string(), 2)));
+        let range = (Bound::Included("a"), Bound::Excluded("b"));
+        assert!(map.range::<str, _>(range).eq([(&"a".to_string(), &1)]));
+    }
+
+    #[test]
+    fn range() {
+        let map: SkipListMap<_, _> = (0..100).map(|i| (i * 2, i)).collect();
+        let keys = |iter: super::Iter<'_, i32, i32>| iter.map(|(k, _)| *k).collect::<Vec<_>>();
+
+        assert_eq!(keys(map.range(10..16)), [10, 12, 14]);
+        assert_eq!(keys(map.range(9..=16)), [10, 12, 14, 16]);
+        assert_eq!(keys(map.range(195..)), [196, 198]);
+        assert_eq!(keys(map.range(..3)), [0, 2]);
+        assert_eq!(keys(map.range(11..12)), []);
+        assert_eq!(keys(map.range(12..12)), []);
+        assert_eq!(map.range(..).count(), 100);
+        assert_eq!(map.range(500..).count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "range start is greater than range end")]
+    fn backwards_range() {
+        let map: SkipListMap<i32, i32> = SkipListMap::new();
+        #[allow(clippy::reversed_empty_ranges)]
+        map.range(3..1);
+    }
+
+    // Whatever's left goes with the map, lanes and all
+    #[test]
+    fn drop() {
+        let mut map = SkipListMap::new();
+        for i in 0..100 {
+            map.insert(Box::new(i), Box::new(i));
+        }
+        for i in 0..50 {
+            map.remove(&Box::new(i * 2));
+        }
+    }
+
+    // Random inserts and removes against a BTreeMap
+    #[test]
+    fn model() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut map = SkipListMap::new();
+        let mut model = BTreeMap::new();
+        let steps = if cfg!(miri) { 300 } else { 5000 };
+        for i in 0..steps {
+            let key = rng.random_range(0..200);
+            if rng.random_bool(0.6) {
+                assert_eq!(map.insert(key, i), model.insert(key, i));
+            } else {
+                assert_eq!(map.remove(&key), model.remove(&key));
+            }
+            assert_eq!(map.len(), model.len());
+            map.assert_invariants();
+        }
+
+        assert!(map.iter().eq(model.iter()));
+        for key in 0..200 {
+            assert_eq!(map.get(&key), model.get(&key));
+        }
+        assert!(map.range(50..150).eq(model.range(50..150)));
+    }
+
+    // Every lane in order, and lane 0 with everything on it
+    #[test]
+    fn lanes() {
+        let map: SkipListMap<_, _> = (0..1000).rev().map(|i| (i, ())).collect();
+        let mut tallest = 0;
+        for lane in 0..map.head.len() {
+            let mut keys = Vec::new();
+            let mut next = map.head[lane];
+            while let Some(node) = next {
+                let node = unsafe { &*node.as_ptr() };
+                assert!(node.height > lane);
+                tallest = tallest.max(node.height);
+                keys.push(node.key);
+                next = unsafe { *node.next.add(lane) };
+            }
+            assert!(keys.windows(2).all(|w| w[0] < w[1]));
+            if lane == 0 {
+                assert_eq!(keys.len(), 1000);
+            }
+        }
+        assert_eq!(tallest, map.head.len());
+    }
+
+    #[test]
+    #[should_panic(expected = "the head has an empty top lane")]
+    fn assert_invariants_head() {
+        let mut map: SkipListMap<_, _> = (0..10).map(|i| (i, i)).collect();
+        map.assert_invariants();
+        map.head.push(None);
+        map.assert_invariants();
+    }
+
+    #[test]
+    #[should_panic(expected = "lane 1 doesn't match lane 0")]
+    fn assert_invariants_lanes() {
+        let mut map: SkipListMap<_, _> = (0..10).map(|i| (i, i)).collect();
+        // Skipping a short node on lane 0 from lane 1
+        let mut next = map.head[0];
+        while let Some(node) = next {
+            if unsafe { (*node.as_ptr()).height } == 1 {
+                break;
+            }
+            next = unsafe { *(*node.as_ptr()).next };
+        }
+        map.head[1] = next;
+        map.assert_invariants();
+    }
+
+    #[test]
+    fn mem_size() {
+        use std::mem::size_of;
+
+        use super::Link;
+        use super::Node;
+        use crate::traits::MemSize;
+
+        let mut map = SkipListMap::new();
+        assert_eq!(map.heap_bytes(), 0);
+
+        let link = size_of::<Link<u8, u8>>();
+        let node = size_of::<Node<u8, u8>>();
+        map.insert(1u8, 1u8);
+        let height = map.head.len();
+        assert_eq!(map.node_count(), 1);
+        assert_eq!(map.bytes_per_element(), node + 2 * link);
+        assert_eq!(
+            map.heap_bytes(),
+            node + (height + map.head.capacity()) * link
+        );
+    }
+
+    // Duplicate keys keep the last value, like `insert`
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde::de::value::Error;
+        use serde::de::value::MapDeserializer;
+        use serde::Deserialize;
+        use serde_test::assert_ser_tokens;
+        use serde_test::Token;
+
+        let map: SkipListMap<_, _> = [(2, 'b'), (1, 'a')].into_iter().collect();
+        assert_ser_tokens(
+            &map,
+            &[
+                Token::Map { len: Some(2) },
+                Token::I32(1),
+                Token::Char('a'),
+                Token::I32(2),
+                Token::Char('b'),
+                Token::MapEnd,
+            ],
+        );
+
+        let entries = vec![(2, 'b'), (1, 'a'), (2, 'c')];
+        let map = MapDeserializer::<_, Error>::new(entries.into_iter());
+        let map = SkipListMap::<i32, char>::deserialize(map).unwrap();
+        assert_eq!(map.len(), 2);
+        assert!(map.iter().eq([(&1, &'a'), (&2, &'c')]));
+    }
+}